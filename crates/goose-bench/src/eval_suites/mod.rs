@@ -2,6 +2,7 @@ mod core;
 mod evaluation;
 mod factory;
 mod metrics;
+mod tool_routing;
 mod utils;
 mod vibes;
 
@@ -0,0 +1,34 @@
+// Route the benchmark queries with BM25 keyword index
+
+use super::run_tool_routing_benchmark;
+use crate::bench_session::BenchAgent;
+use crate::bench_work_dir::BenchmarkWorkDir;
+use crate::eval_suites::{EvalMetricValue, Evaluation};
+use crate::register_evaluation;
+use async_trait::async_trait;
+use goose::agents::router_tool_selector::RouterToolSelectionStrategy;
+
+pub struct ToolRoutingBm25 {}
+
+impl ToolRoutingBm25 {
+    pub fn new() -> Self {
+        ToolRoutingBm25 {}
+    }
+}
+
+#[async_trait]
+impl Evaluation for ToolRoutingBm25 {
+    async fn run(
+        &self,
+        _agent: &mut BenchAgent,
+        _run_loc: &mut BenchmarkWorkDir,
+    ) -> anyhow::Result<Vec<(String, EvalMetricValue)>> {
+        run_tool_routing_benchmark(RouterToolSelectionStrategy::Bm25).await
+    }
+
+    fn name(&self) -> &str {
+        "tool_routing_bm25"
+    }
+}
+
+register_evaluation!(ToolRoutingBm25);
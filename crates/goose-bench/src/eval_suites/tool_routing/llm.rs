@@ -0,0 +1,34 @@
+// Route the benchmark queries with provider ranking tools
+
+use super::run_tool_routing_benchmark;
+use crate::bench_session::BenchAgent;
+use crate::bench_work_dir::BenchmarkWorkDir;
+use crate::eval_suites::{EvalMetricValue, Evaluation};
+use crate::register_evaluation;
+use async_trait::async_trait;
+use goose::agents::router_tool_selector::RouterToolSelectionStrategy;
+
+pub struct ToolRoutingLlm {}
+
+impl ToolRoutingLlm {
+    pub fn new() -> Self {
+        ToolRoutingLlm {}
+    }
+}
+
+#[async_trait]
+impl Evaluation for ToolRoutingLlm {
+    async fn run(
+        &self,
+        _agent: &mut BenchAgent,
+        _run_loc: &mut BenchmarkWorkDir,
+    ) -> anyhow::Result<Vec<(String, EvalMetricValue)>> {
+        run_tool_routing_benchmark(RouterToolSelectionStrategy::Llm).await
+    }
+
+    fn name(&self) -> &str {
+        "tool_routing_llm"
+    }
+}
+
+register_evaluation!(ToolRoutingLlm);
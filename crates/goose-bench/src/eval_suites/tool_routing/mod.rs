@@ -0,0 +1,256 @@
+// Compare tool router strategies on the same catalog of tools and queries
+
+mod bm25;
+mod llm;
+mod vector;
+
+use crate::eval_suites::EvalMetricValue;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use goose::agents::router_tool_selector::{
+    create_tool_selector, RouterToolSelectionStrategy, RouterToolSelector,
+};
+use goose::config::Config;
+use goose::model::ModelConfig;
+use goose::providers;
+use mcp_core::tool::Tool;
+use mcp_core::Content;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+/// How many tools each query asks the router for
+const K: usize = 3;
+
+/// Tools modelled on common extensions, with a few near neighbours to tell apart
+const CATALOG: &[(&str, &str)] = &[
+    (
+        "developer__shell",
+        "Execute a command in the shell and return its output",
+    ),
+    (
+        "developer__text_editor",
+        "View, create and edit files, replacing text or writing whole files",
+    ),
+    (
+        "developer__list_windows",
+        "List the titles of all open windows",
+    ),
+    (
+        "developer__screen_capture",
+        "Capture a screenshot of a display or window",
+    ),
+    (
+        "developer__image_processor",
+        "Resize and convert an image file",
+    ),
+    (
+        "computercontroller__web_scrape",
+        "Fetch a web page and save its content to a file",
+    ),
+    (
+        "computercontroller__pdf_tool",
+        "Extract text and images from a PDF document",
+    ),
+    (
+        "computercontroller__docx_tool",
+        "Read and write Microsoft Word documents",
+    ),
+    (
+        "computercontroller__xlsx_tool",
+        "Read cells and sheets from an Excel spreadsheet",
+    ),
+    (
+        "computercontroller__automation_script",
+        "Run an AppleScript or PowerShell automation script",
+    ),
+    (
+        "memory__remember_memory",
+        "Save a fact or preference to memory for later sessions",
+    ),
+    (
+        "memory__retrieve_memories",
+        "Look up facts saved to memory by category",
+    ),
+    (
+        "memory__remove_memory_category",
+        "Delete every memory in a category",
+    ),
+    ("slack__send_message", "Send a message to a Slack channel"),
+    ("slack__read_thread", "Read the replies in a Slack thread"),
+    ("slack__create_channel", "Create a new Slack channel"),
+    (
+        "github__create_issue",
+        "Open a new issue in a GitHub repository",
+    ),
+    (
+        "github__create_pull_request",
+        "Open a pull request from a branch in a GitHub repository",
+    ),
+    (
+        "github__list_commits",
+        "List the recent commits on a branch of a GitHub repository",
+    ),
+    (
+        "google_drive__search",
+        "Search Google Drive for files by name or content",
+    ),
+    (
+        "google_drive__read_file",
+        "Read the content of a file stored in Google Drive",
+    ),
+    (
+        "calendar__create_event",
+        "Schedule a meeting or event on the calendar",
+    ),
+    (
+        "calendar__list_events",
+        "List upcoming events and meetings on the calendar",
+    ),
+    ("weather__forecast", "Get the weather forecast for a city"),
+];
+
+/// Requests a user might make and the tool each should be routed to
+const QUERIES: &[(&str, &str)] = &[
+    ("run ls -l in the terminal", "developer__shell"),
+    (
+        "edit the config file and change the port",
+        "developer__text_editor",
+    ),
+    (
+        "take a screenshot of my screen",
+        "developer__screen_capture",
+    ),
+    (
+        "extract the text from this pdf",
+        "computercontroller__pdf_tool",
+    ),
+    (
+        "read the numbers in the excel spreadsheet",
+        "computercontroller__xlsx_tool",
+    ),
+    (
+        "remember that I prefer tabs over spaces",
+        "memory__remember_memory",
+    ),
+    (
+        "post an update to the team slack channel",
+        "slack__send_message",
+    ),
+    (
+        "open a pull request for my branch",
+        "github__create_pull_request",
+    ),
+    (
+        "file a bug on github about the crash",
+        "github__create_issue",
+    ),
+    (
+        "find the budget file in google drive",
+        "google_drive__search",
+    ),
+    (
+        "book a meeting with Alex tomorrow at 3pm",
+        "calendar__create_event",
+    ),
+    ("will it rain in Tokyo this weekend", "weather__forecast"),
+];
+
+/// Index the catalog with the given strategy, route every query and report how often
+/// the expected tool was selected and how long indexing and selection took
+pub async fn run_tool_routing_benchmark(
+    strategy: RouterToolSelectionStrategy,
+) -> Result<Vec<(String, EvalMetricValue)>> {
+    let config = Config::global();
+    let provider_name: String = config
+        .get_param("GOOSE_PROVIDER")
+        .context("No provider configured")?;
+    let model_name: String = config
+        .get_param("GOOSE_MODEL")
+        .context("No model configured")?;
+    let provider = providers::create(&provider_name, ModelConfig::new(model_name))?;
+
+    let table_name = format!("tool_routing_bench_{}", Utc::now().timestamp_millis());
+    let selector = create_tool_selector(Some(strategy), provider, table_name).await?;
+
+    // Every run indexes into a fresh table, so don't leave it behind whatever the outcome
+    let metrics = route_queries(selector.as_ref()).await;
+    if let Err(e) = selector.drop_index().await {
+        tracing::warn!("Failed to drop the benchmark's tool index: {}", e);
+    }
+    metrics
+}
+
+/// Index the catalog and route every query, measuring accuracy and timing
+async fn route_queries(
+    selector: &dyn RouterToolSelector,
+) -> Result<Vec<(String, EvalMetricValue)>> {
+    let tools: Vec<Tool> = CATALOG
+        .iter()
+        .map(|(name, description)| Tool::new(*name, *description, json!({"type": "object"}), None))
+        .collect();
+    let start_time = Instant::now();
+    selector
+        .index_tools(&tools)
+        .await
+        .map_err(|e| anyhow!("Failed to index tools: {}", e))?;
+    let index_time = start_time.elapsed();
+
+    let mut top_1_hits = 0;
+    let mut recall_hits = 0;
+    let mut selection_errors = 0;
+    let mut selection_time = Duration::ZERO;
+    for (query, expected) in QUERIES {
+        let start_time = Instant::now();
+        let result = selector.select_tools(json!({"query": query, "k": K})).await;
+        selection_time += start_time.elapsed();
+
+        match result {
+            Ok(contents) => {
+                let selected = selected_tool_names(&contents);
+                if selected.first() == Some(expected) {
+                    top_1_hits += 1;
+                }
+                if selected.contains(expected) {
+                    recall_hits += 1;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Tool selection failed for query '{}': {}", query, e);
+                selection_errors += 1;
+            }
+        }
+    }
+
+    let query_count = QUERIES.len() as f64;
+    Ok(vec![
+        (
+            "index_time_ms".to_string(),
+            EvalMetricValue::Float(index_time.as_secs_f64() * 1000.0),
+        ),
+        (
+            "avg_selection_time_ms".to_string(),
+            EvalMetricValue::Float(selection_time.as_secs_f64() * 1000.0 / query_count),
+        ),
+        (
+            "top_1_accuracy".to_string(),
+            EvalMetricValue::Float(top_1_hits as f64 / query_count),
+        ),
+        (
+            format!("recall_at_{}", K),
+            EvalMetricValue::Float(recall_hits as f64 / query_count),
+        ),
+        (
+            "selection_errors".to_string(),
+            EvalMetricValue::Integer(selection_errors),
+        ),
+    ])
+}
+
+/// Tool names from the router's results, which start with a `Tool: <name>` line
+fn selected_tool_names(contents: &[Content]) -> Vec<&str> {
+    contents
+        .iter()
+        .filter_map(|content| content.as_text())
+        .filter_map(|text| text.lines().next()?.strip_prefix("Tool: "))
+        .collect()
+}
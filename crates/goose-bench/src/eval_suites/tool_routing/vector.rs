@@ -0,0 +1,34 @@
+// Route the benchmark queries with embedding similarity search
+
+use super::run_tool_routing_benchmark;
+use crate::bench_session::BenchAgent;
+use crate::bench_work_dir::BenchmarkWorkDir;
+use crate::eval_suites::{EvalMetricValue, Evaluation};
+use crate::register_evaluation;
+use async_trait::async_trait;
+use goose::agents::router_tool_selector::RouterToolSelectionStrategy;
+
+pub struct ToolRoutingVector {}
+
+impl ToolRoutingVector {
+    pub fn new() -> Self {
+        ToolRoutingVector {}
+    }
+}
+
+#[async_trait]
+impl Evaluation for ToolRoutingVector {
+    async fn run(
+        &self,
+        _agent: &mut BenchAgent,
+        _run_loc: &mut BenchmarkWorkDir,
+    ) -> anyhow::Result<Vec<(String, EvalMetricValue)>> {
+        run_tool_routing_benchmark(RouterToolSelectionStrategy::Vector).await
+    }
+
+    fn name(&self) -> &str {
+        "tool_routing_vector"
+    }
+}
+
+register_evaluation!(ToolRoutingVector);
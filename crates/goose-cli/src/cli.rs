@@ -2,21 +2,31 @@ use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
 
 use goose::config::{Config, ExtensionConfig};
+use goose::recipe::sub_recipe::resolve_sub_recipe_paths;
+use goose::recipe::{Response, Settings, SubRecipe, RECIPE_DIR_PARAM};
 
 use crate::commands::bench::agent_generator;
 use crate::commands::configure::handle_configure;
 use crate::commands::info::handle_info;
 use crate::commands::mcp::run_server;
+use crate::commands::permissions::handle_permissions_test;
 use crate::commands::project::{handle_project_default, handle_projects_interactive};
-use crate::commands::recipe::{handle_deeplink, handle_validate};
+use crate::commands::recipe::{
+    handle_deeplink, handle_install, handle_list, handle_remove, handle_test, handle_update,
+    handle_validate,
+};
 // Import the new handlers from commands::schedule
 use crate::commands::schedule::{
     handle_schedule_add, handle_schedule_list, handle_schedule_remove, handle_schedule_run_now,
     handle_schedule_sessions,
 };
-use crate::commands::session::{handle_session_list, handle_session_remove};
+use crate::commands::session::{
+    handle_session_fork, handle_session_list, handle_session_remove, handle_session_search,
+};
 use crate::logging::setup_logging;
-use crate::recipes::recipe::{explain_recipe_with_parameters, load_recipe_as_template};
+use crate::recipes::recipe::{
+    explain_recipe_with_parameters, print_loaded_recipe, render_recipe_template,
+};
 use crate::session;
 use crate::session::{build_session, SessionBuilderConfig};
 use goose_bench::bench_config::BenchRunConfig;
@@ -115,6 +125,40 @@ enum SessionCommand {
         )]
         output: Option<PathBuf>,
     },
+    #[command(about = "Search the messages of all sessions")]
+    Search {
+        #[arg(help = "Words to search for; messages must contain all of them")]
+        query: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Maximum number of matching messages to show",
+            default_value = "20"
+        )]
+        limit: usize,
+
+        #[arg(
+            short,
+            long,
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+    },
+    #[command(about = "Fork a session into a new session")]
+    Fork {
+        #[command(flatten)]
+        identifier: Option<Identifier>,
+
+        #[arg(
+            long = "at",
+            value_name = "N",
+            help = "Keep only the first N messages (default: all)",
+            long_help = "Fork the session at message N, keeping the first N messages in the new session. Defaults to all messages."
+        )]
+        at_message: Option<usize>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -211,6 +255,25 @@ pub enum BenchCommand {
     },
 }
 
+#[derive(Subcommand)]
+enum PermissionsCommand {
+    /// Show which permission rule applies to a tool call
+    #[command(about = "Dry-run a tool call against the permission rules")]
+    Test {
+        /// Full name of the tool, e.g. developer__shell
+        #[arg(help = "Full name of the tool, e.g. developer__shell")]
+        tool: String,
+
+        /// Tool arguments as JSON
+        #[arg(
+            long,
+            default_value = "{}",
+            help = "Tool arguments as JSON, e.g. '{\"command\": \"git push\"}'"
+        )]
+        args: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum RecipeCommand {
     /// Validate a recipe file
@@ -230,6 +293,78 @@ enum RecipeCommand {
         )]
         recipe_name: String,
     },
+
+    /// Test a recipe against a fixture of scripted model responses
+    #[command(about = "Test a recipe against scripted model responses")]
+    Test {
+        /// Recipe name to get recipe file to test
+        #[arg(help = "recipe name to get recipe file or full path to the recipe file to test")]
+        recipe_name: String,
+
+        /// Fixture with the responses and expectations
+        #[arg(
+            help = "Path to the fixture with the model's responses, tool stubs and expectations"
+        )]
+        fixture: PathBuf,
+
+        /// Directory to run the recipe in
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Directory to run the recipe in, instead of a new temporary directory"
+        )]
+        workdir: Option<PathBuf>,
+    },
+
+    /// Install a recipe into the local registry
+    #[command(about = "Install a recipe from a git repository, directory or tarball")]
+    Install {
+        /// Where to install the recipe from
+        #[arg(
+            help = "Git remote, local directory or tarball URL to install the recipe from",
+            long_help = "Git remote (https, ssh, a path ending in .git, or any location prefixed with git+), local directory, or URL or path of a .tar.gz, .tgz or .tar archive. The recipe.yaml or recipe.json at its root, or in --path, is installed."
+        )]
+        source: String,
+
+        /// Name to install the recipe as
+        #[arg(
+            long,
+            help = "Name to install the recipe as, instead of the name of the source"
+        )]
+        name: Option<String>,
+
+        /// Git tag, branch or commit to install
+        #[arg(
+            long,
+            value_name = "REF",
+            help = "Git tag, branch or commit to install, followed again by update"
+        )]
+        version: Option<String>,
+
+        /// Directory of the recipe within the source
+        #[arg(long, help = "Directory of the recipe within the source")]
+        path: Option<String>,
+    },
+
+    /// List the recipes installed in the local registry
+    #[command(about = "List installed recipes")]
+    List {},
+
+    /// Update installed recipes from their sources
+    #[command(about = "Update installed recipes from their sources")]
+    Update {
+        /// Recipe to update, all of them if not given
+        #[arg(help = "Name of the recipe to update, all recipes are updated if not given")]
+        name: Option<String>,
+    },
+
+    /// Remove a recipe from the local registry
+    #[command(about = "Remove an installed recipe")]
+    Remove {
+        /// Recipe to remove
+        #[arg(help = "Name of the recipe to remove, or name@version to remove one version")]
+        recipe: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -316,6 +451,16 @@ enum Command {
         )]
         remote_extensions: Vec<String>,
 
+        /// Add streamable HTTP extensions with a URL
+        #[arg(
+            long = "with-streamable-http-extension",
+            value_name = "URL",
+            help = "Add streamable HTTP extensions (can be specified multiple times)",
+            long_help = "Add remote extensions that use the MCP streamable HTTP transport from a URL. Can be specified multiple times. Format: 'url...'",
+            action = clap::ArgAction::Append
+        )]
+        streamable_http_extensions: Vec<String>,
+
         /// Add builtin extensions by name
         #[arg(
             long = "with-builtin",
@@ -416,6 +561,70 @@ enum Command {
         )]
         max_tool_repetitions: Option<u32>,
 
+        /// Maximum number of agent turns before the run stops
+        #[arg(
+            long = "max-turns",
+            value_name = "NUMBER",
+            help = "Maximum number of agent turns before the run stops",
+            long_help = "Stop the run gracefully once the agent has taken this many turns (model calls). Overrides max_turns from a recipe."
+        )]
+        max_turns: Option<u32>,
+
+        /// Maximum number of accumulated tokens before the run stops
+        #[arg(
+            long = "max-tokens",
+            value_name = "NUMBER",
+            help = "Maximum number of accumulated tokens before the run stops",
+            long_help = "Stop the run gracefully once the session has used this many tokens in total. Overrides max_tokens from a recipe."
+        )]
+        max_tokens: Option<i32>,
+
+        /// Maximum wall-clock time before the run stops
+        #[arg(
+            long = "max-duration",
+            value_name = "DURATION",
+            value_parser = humantime::parse_duration,
+            help = "Maximum wall-clock time before the run stops, such as 30m or 1h30m",
+            long_help = "Stop the run gracefully once this much time has passed since it started; the agent finishes its current turn first. Accepts durations such as 90s, 30m or 1h30m."
+        )]
+        max_duration: Option<std::time::Duration>,
+
+        /// JSON Schema the final output must match
+        #[arg(
+            long = "output-schema",
+            value_name = "FILE or JSON",
+            help = "JSON Schema the final output must match, as a file or inline JSON",
+            long_help = "Require the agent to finish by providing output that matches this JSON Schema, given as a JSON or YAML file or as inline JSON. The validated output is printed to stdout as the result of the run, and the run fails if the agent never provides it. Overrides the response schema from a recipe."
+        )]
+        output_schema: Option<String>,
+
+        /// File to write the final output to
+        #[arg(
+            long = "output-file",
+            value_name = "FILE",
+            help = "Write the structured final output to this file instead of stdout",
+            long_help = "Write the validated output of a run with an output schema to this file instead of stdout. The conversation is then rendered as usual."
+        )]
+        output_file: Option<PathBuf>,
+
+        /// Provider to run with
+        #[arg(
+            long = "provider",
+            value_name = "PROVIDER",
+            help = "Provider to run with instead of the configured one",
+            long_help = "Run with this provider instead of GOOSE_PROVIDER. Overrides the provider from a recipe's settings."
+        )]
+        provider: Option<String>,
+
+        /// Model to run with
+        #[arg(
+            long = "model",
+            value_name = "MODEL",
+            help = "Model to run with instead of the configured one",
+            long_help = "Run with this model instead of GOOSE_MODEL. Overrides the model from a recipe's settings."
+        )]
+        model: Option<String>,
+
         /// Identifier for this run session
         #[command(flatten)]
         identifier: Option<Identifier>,
@@ -458,6 +667,16 @@ enum Command {
         )]
         remote_extensions: Vec<String>,
 
+        /// Add streamable HTTP extensions with a URL
+        #[arg(
+            long = "with-streamable-http-extension",
+            value_name = "URL",
+            help = "Add streamable HTTP extensions (can be specified multiple times)",
+            long_help = "Add remote extensions that use the MCP streamable HTTP transport from a URL. Can be specified multiple times. Format: 'url...'",
+            action = clap::ArgAction::Append
+        )]
+        streamable_http_extensions: Vec<String>,
+
         /// Add builtin extensions by name
         #[arg(
             long = "with-builtin",
@@ -469,13 +688,22 @@ enum Command {
         builtins: Vec<String>,
     },
 
-    /// Recipe utilities for validation and deeplinking
-    #[command(about = "Recipe utilities for validation and deeplinking")]
+    /// Recipe utilities for validation, testing, deeplinking and installing recipes
+    #[command(
+        about = "Recipe utilities for validation, testing, deeplinking and installing recipes"
+    )]
     Recipe {
         #[command(subcommand)]
         command: RecipeCommand,
     },
 
+    /// Tool permission utilities
+    #[command(about = "Tool permission utilities")]
+    Permissions {
+        #[command(subcommand)]
+        command: PermissionsCommand,
+    },
+
     /// Manage scheduled jobs
     #[command(about = "Manage scheduled jobs", visible_alias = "sched")]
     Schedule {
@@ -542,6 +770,40 @@ struct InputConfig {
     contents: Option<String>,
     extensions_override: Option<Vec<ExtensionConfig>>,
     additional_system_prompt: Option<String>,
+    max_turns: Option<u32>,
+    max_tokens: Option<i32>,
+    response: Option<Response>,
+    sub_recipes: Option<Vec<SubRecipe>>,
+    settings: Option<Settings>,
+}
+
+/// Read an output schema given as inline JSON or as the path of a JSON or YAML file
+fn load_output_schema(schema: &str) -> Result<serde_json::Value> {
+    if schema.trim_start().starts_with('{') {
+        return serde_json::from_str(schema)
+            .map_err(|e| anyhow::anyhow!("Invalid inline output schema: {}", e));
+    }
+    let contents = std::fs::read_to_string(schema)
+        .map_err(|e| anyhow::anyhow!("Failed to read output schema {}: {}", schema, e))?;
+    // YAML is a superset of JSON, so this reads both
+    serde_yaml::from_str(&contents)
+        .map_err(|e| anyhow::anyhow!("Invalid output schema in {}: {}", schema, e))
+}
+
+/// Print the validated output of a structured run, or write it to `output_file`
+async fn write_final_output(session: &crate::Session, output_file: Option<PathBuf>) -> Result<()> {
+    let output = session.final_output().await.ok_or_else(|| {
+        anyhow::anyhow!("The agent finished without providing output that matches the schema")
+    })?;
+    let json = serde_json::to_string_pretty(&output)?;
+    match output_file {
+        Some(path) => std::fs::write(&path, format!("{}\n", json))
+            .map_err(|e| anyhow::anyhow!("Failed to write output to {}: {}", path.display(), e)),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
 }
 
 pub async fn cli() -> Result<()> {
@@ -573,6 +835,7 @@ pub async fn cli() -> Result<()> {
             max_tool_repetitions,
             extensions,
             remote_extensions,
+            streamable_http_extensions,
             builtins,
         }) => {
             return match command {
@@ -593,7 +856,9 @@ pub async fn cli() -> Result<()> {
                         extract_identifier(id)
                     } else {
                         // If no identifier is provided, prompt for interactive selection
-                        match crate::commands::session::prompt_interactive_session_selection() {
+                        match crate::commands::session::prompt_interactive_session_selection(
+                            "Select a session to export:",
+                        ) {
                             Ok(id) => id,
                             Err(e) => {
                                 eprintln!("Error: {}", e);
@@ -605,6 +870,35 @@ pub async fn cli() -> Result<()> {
                     crate::commands::session::handle_session_export(session_identifier, output)?;
                     Ok(())
                 }
+                Some(SessionCommand::Search {
+                    query,
+                    limit,
+                    format,
+                }) => {
+                    handle_session_search(&query, limit, format)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Fork {
+                    identifier,
+                    at_message,
+                }) => {
+                    let session_identifier = if let Some(id) = identifier {
+                        extract_identifier(id)
+                    } else {
+                        match crate::commands::session::prompt_interactive_session_selection(
+                            "Select a session to fork:",
+                        ) {
+                            Ok(id) => id,
+                            Err(e) => {
+                                eprintln!("Error: {}", e);
+                                return Ok(());
+                            }
+                        }
+                    };
+
+                    handle_session_fork(session_identifier, at_message)?;
+                    Ok(())
+                }
                 None => {
                     // Run session command by default
                     let mut session: crate::Session = build_session(SessionBuilderConfig {
@@ -613,11 +907,21 @@ pub async fn cli() -> Result<()> {
                         no_session: false,
                         extensions,
                         remote_extensions,
+                        streamable_http_extensions,
                         builtins,
                         extensions_override: None,
                         additional_system_prompt: None,
                         debug,
                         max_tool_repetitions,
+                        max_turns: None,
+                        max_tokens: None,
+                        max_duration: None,
+                        final_output_response: None,
+                        sub_recipes: None,
+                        quiet: false,
+                        settings: None,
+                        provider: None,
+                        model: None,
                     })
                     .await;
                     setup_logging(
@@ -655,8 +959,16 @@ pub async fn cli() -> Result<()> {
             no_session,
             debug,
             max_tool_repetitions,
+            max_turns,
+            max_tokens,
+            max_duration,
+            output_schema,
+            output_file,
+            provider,
+            model,
             extensions,
             remote_extensions,
+            streamable_http_extensions,
             builtins,
             params,
             explain,
@@ -672,6 +984,11 @@ pub async fn cli() -> Result<()> {
                         contents: Some(input),
                         extensions_override: None,
                         additional_system_prompt: None,
+                        max_turns: None,
+                        max_tokens: None,
+                        response: None,
+                        sub_recipes: None,
+                        settings: None,
                     }
                 }
                 (Some(file), _, _, _) => {
@@ -686,27 +1003,60 @@ pub async fn cli() -> Result<()> {
                         contents: Some(contents),
                         extensions_override: None,
                         additional_system_prompt: None,
+                        max_turns: None,
+                        max_tokens: None,
+                        response: None,
+                        sub_recipes: None,
+                        settings: None,
                     }
                 }
                 (_, Some(text), _, _) => InputConfig {
                     contents: Some(text),
                     extensions_override: None,
                     additional_system_prompt: None,
+                    max_turns: None,
+                    max_tokens: None,
+                    response: None,
+                    sub_recipes: None,
+                    settings: None,
                 },
                 (_, _, Some(recipe_name), explain) => {
                     if explain {
                         explain_recipe_with_parameters(&recipe_name, params)?;
                         return Ok(());
                     }
-                    let recipe =
-                        load_recipe_as_template(&recipe_name, params).unwrap_or_else(|err| {
+                    let (recipe, params_for_template) =
+                        render_recipe_template(&recipe_name, params).unwrap_or_else(|err| {
                             eprintln!("{}: {}", console::style("Error").red().bold(), err);
                             std::process::exit(1);
                         });
+                    // A structured result on stdout is all a structured run prints there
+                    let structured_stdout = !interactive
+                        && output_file.is_none()
+                        && (output_schema.is_some()
+                            || recipe
+                                .response
+                                .as_ref()
+                                .is_some_and(|response| response.json_schema.is_some()));
+                    // Sub-recipe paths are relative to the recipe's directory
+                    let recipe_dir = params_for_template
+                        .get(RECIPE_DIR_PARAM)
+                        .map(PathBuf::from)
+                        .unwrap_or_default();
+                    if !structured_stdout {
+                        print_loaded_recipe(&recipe, params_for_template);
+                    }
                     InputConfig {
                         contents: recipe.prompt,
                         extensions_override: recipe.extensions,
                         additional_system_prompt: recipe.instructions,
+                        max_turns: recipe.max_turns,
+                        max_tokens: recipe.max_tokens,
+                        response: recipe.response,
+                        sub_recipes: recipe
+                            .sub_recipes
+                            .map(|sub_recipes| resolve_sub_recipe_paths(sub_recipes, &recipe_dir)),
+                        settings: recipe.settings,
                     }
                 }
                 (None, None, None, _) => {
@@ -715,17 +1065,43 @@ pub async fn cli() -> Result<()> {
                 }
             };
 
+            let response = match output_schema {
+                Some(schema) => match load_output_schema(&schema) {
+                    Ok(json_schema) => Some(Response {
+                        json_schema: Some(json_schema),
+                    }),
+                    Err(e) => {
+                        eprintln!("{}: {}", console::style("Error").red().bold(), e);
+                        std::process::exit(1);
+                    }
+                },
+                None => input_config
+                    .response
+                    .filter(|response| response.json_schema.is_some()),
+            };
+            let quiet = response.is_some() && !interactive && output_file.is_none();
+
             let mut session = build_session(SessionBuilderConfig {
                 identifier: identifier.map(extract_identifier),
                 resume,
                 no_session,
                 extensions,
                 remote_extensions,
+                streamable_http_extensions,
                 builtins,
                 extensions_override: input_config.extensions_override,
                 additional_system_prompt: input_config.additional_system_prompt,
                 debug,
                 max_tool_repetitions,
+                max_turns: max_turns.or(input_config.max_turns),
+                max_tokens: max_tokens.or(input_config.max_tokens),
+                max_duration,
+                final_output_response: response.clone(),
+                sub_recipes: input_config.sub_recipes,
+                quiet,
+                settings: input_config.settings,
+                provider,
+                model,
             })
             .await;
 
@@ -738,6 +1114,12 @@ pub async fn cli() -> Result<()> {
                 let _ = session.interactive(input_config.contents).await;
             } else if let Some(contents) = input_config.contents {
                 let _ = session.headless(contents).await;
+                if response.is_some() {
+                    if let Err(e) = write_final_output(&session, output_file).await {
+                        eprintln!("{}: {}", console::style("Error").red().bold(), e);
+                        std::process::exit(1);
+                    }
+                }
             } else {
                 eprintln!("Error: no text provided for prompt in headless mode");
                 std::process::exit(1);
@@ -807,6 +1189,38 @@ pub async fn cli() -> Result<()> {
                 RecipeCommand::Deeplink { recipe_name } => {
                     handle_deeplink(&recipe_name)?;
                 }
+                RecipeCommand::Test {
+                    recipe_name,
+                    fixture,
+                    workdir,
+                } => {
+                    handle_test(&recipe_name, &fixture, workdir).await?;
+                }
+                RecipeCommand::Install {
+                    source,
+                    name,
+                    version,
+                    path,
+                } => {
+                    handle_install(&source, name, version, path).await?;
+                }
+                RecipeCommand::List {} => {
+                    handle_list()?;
+                }
+                RecipeCommand::Update { name } => {
+                    handle_update(name.as_deref()).await?;
+                }
+                RecipeCommand::Remove { recipe } => {
+                    handle_remove(&recipe)?;
+                }
+            }
+            return Ok(());
+        }
+        Some(Command::Permissions { command }) => {
+            match command {
+                PermissionsCommand::Test { tool, args } => {
+                    handle_permissions_test(&tool, &args).await?;
+                }
             }
             return Ok(());
         }
@@ -826,11 +1240,21 @@ pub async fn cli() -> Result<()> {
                     no_session: false,
                     extensions: Vec::new(),
                     remote_extensions: Vec::new(),
+                    streamable_http_extensions: Vec::new(),
                     builtins: Vec::new(),
                     extensions_override: None,
                     additional_system_prompt: None,
                     debug: false,
                     max_tool_repetitions: None,
+                    max_turns: None,
+                    max_tokens: None,
+                    max_duration: None,
+                    final_output_response: None,
+                    sub_recipes: None,
+                    quiet: false,
+                    settings: None,
+                    provider: None,
+                    model: None,
                 })
                 .await;
                 setup_logging(
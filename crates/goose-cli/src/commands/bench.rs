@@ -37,11 +37,21 @@ pub async fn agent_generator(
         no_session: false,
         extensions: requirements.external,
         remote_extensions: requirements.remote,
+        streamable_http_extensions: Vec::new(),
         builtins: requirements.builtin,
         extensions_override: None,
         additional_system_prompt: None,
         debug: false,
         max_tool_repetitions: None,
+        max_turns: None,
+        max_tokens: None,
+        max_duration: None,
+        final_output_response: None,
+        sub_recipes: None,
+        quiet: false,
+        settings: None,
+        provider: None,
+        model: None,
     })
     .await;
 
@@ -2,6 +2,7 @@ pub mod bench;
 pub mod configure;
 pub mod info;
 pub mod mcp;
+pub mod permissions;
 pub mod project;
 pub mod recipe;
 pub mod schedule;
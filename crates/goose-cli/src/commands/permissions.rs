@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use console::style;
+use goose::agents::Agent;
+use goose::config::permission::{PermissionLevel, PermissionRule};
+use goose::config::{Config, ExtensionConfigManager, PermissionManager};
+use goose::permission::{decide_tool_permission, ToolPermissionDecision};
+use serde_json::Value;
+
+fn describe_level(level: &PermissionLevel) -> &'static str {
+    match level {
+        PermissionLevel::AlwaysAllow => "runs without asking",
+        PermissionLevel::AskBefore => "asks before running",
+        PermissionLevel::NeverAllow => "is denied",
+    }
+}
+
+fn describe_rule(rule: &PermissionRule) -> String {
+    serde_json::to_string(rule).unwrap_or_else(|_| rule.tool.clone())
+}
+
+/// The tools of the enabled extensions with and without a read-only annotation
+async fn annotated_tools() -> Result<(HashSet<String>, HashSet<String>)> {
+    let agent = Agent::new();
+    for extension in ExtensionConfigManager::get_all()? {
+        if extension.enabled {
+            if let Err(e) = agent.add_extension(extension.config.clone()).await {
+                eprintln!(
+                    "Warning: Failed to load extension {}: {}",
+                    extension.config.name(),
+                    e
+                );
+            }
+        }
+    }
+    let tools = agent.list_tools(None).await;
+    Ok(Agent::categorize_tools_by_annotation(&tools))
+}
+
+/// Show which permission would apply to a tool call, without running it
+pub async fn handle_permissions_test(tool: &str, args: &str) -> Result<()> {
+    let arguments: Value =
+        serde_json::from_str(args).context("--args must be the tool arguments as JSON")?;
+    let manager = PermissionManager::default();
+    let mode: String = Config::global()
+        .get_param("GOOSE_MODE")
+        .unwrap_or("auto".to_string());
+
+    // Only smart approve looks at annotations, so extensions are started for it alone
+    let (readonly_tools, unannotated_tools) = if mode == "smart_approve" {
+        annotated_tools().await?
+    } else {
+        (HashSet::new(), HashSet::new())
+    };
+
+    println!("{} {}", style("Tool:").cyan().bold(), tool);
+    println!("{} {}", style("Arguments:").cyan().bold(), arguments);
+    println!("{} {}", style("Mode:").cyan().bold(), mode);
+    println!();
+
+    let decision = decide_tool_permission(
+        tool,
+        &arguments,
+        &mode,
+        &readonly_tools,
+        &unannotated_tools,
+        &manager,
+    );
+    match &decision {
+        ToolPermissionDecision::ChatMode => {
+            println!("No tools are run in chat mode.");
+            return Ok(());
+        }
+        ToolPermissionDecision::Rule { index, rule } => {
+            println!("Rule #{} matched: {}", index + 1, describe_rule(rule));
+            println!("The call {}.", describe_level(&rule.permission));
+            return Ok(());
+        }
+        _ => println!("No rule matched."),
+    }
+
+    match decision {
+        ToolPermissionDecision::AutoMode => {
+            println!("The call runs without asking, as auto mode does for every tool.")
+        }
+        ToolPermissionDecision::UserPermission(level) => println!(
+            "The call {}, following the permission set for {}.",
+            describe_level(&level),
+            tool
+        ),
+        ToolPermissionDecision::SmartApprove(level) => println!(
+            "The call {}, following smart approve's earlier decision for {}.",
+            describe_level(&level),
+            tool
+        ),
+        ToolPermissionDecision::ReadOnlyAnnotation => println!(
+            "The call runs without asking, {} is annotated as read-only.",
+            tool
+        ),
+        ToolPermissionDecision::DetectReadOnly => println!(
+            "Smart approve asks the model: the call runs without asking if it is read-only, \
+             and asks first otherwise."
+        ),
+        ToolPermissionDecision::Ask if mode == "smart_approve" => println!(
+            "The call asks before running, no enabled extension has a tool named {}.",
+            tool
+        ),
+        _ => println!("The call asks before running."),
+    }
+    Ok(())
+}
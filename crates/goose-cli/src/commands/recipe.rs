@@ -1,8 +1,13 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use base64::Engine;
 use console::style;
+use goose::recipe::sub_recipe::resolve_sub_recipe_paths;
+use goose::recipe::RECIPE_DIR_PARAM;
+use std::path::{Path, PathBuf};
 
-use crate::recipes::recipe::load_recipe;
+use crate::recipes::recipe::{load_recipe, render_recipe_for_test};
+use crate::recipes::recipe_test::{check_expectations, run_recipe_test, RecipeTestFixture};
+use crate::recipes::registry::{InstallOptions, InstalledRecipe, RecipeRegistry};
 
 /// Validates a recipe file
 ///
@@ -57,3 +62,206 @@ pub fn handle_deeplink(recipe_name: &str) -> Result<()> {
         }
     }
 }
+
+/// Tests a recipe against a fixture of scripted model responses
+///
+/// # Arguments
+///
+/// * `recipe_name` - Name or path of the recipe to test
+/// * `fixture_path` - Path to the fixture with the responses, tool stubs and expectations
+/// * `workdir` - Directory to run the recipe in, a new temporary directory if `None`
+///
+/// # Returns
+///
+/// Result indicating whether the recipe did what the fixture expects
+pub async fn handle_test(
+    recipe_name: &str,
+    fixture_path: &Path,
+    workdir: Option<PathBuf>,
+) -> Result<()> {
+    let fixture = RecipeTestFixture::from_file(fixture_path)?;
+    let (mut recipe, params_for_template) = render_recipe_for_test(recipe_name, fixture.params())?;
+    let recipe_dir = params_for_template
+        .get(RECIPE_DIR_PARAM)
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    recipe.sub_recipes = recipe
+        .sub_recipes
+        .map(|sub_recipes| resolve_sub_recipe_paths(sub_recipes, &recipe_dir));
+
+    // Extensions work in the current directory, so the recipe runs from the working directory
+    let temp_dir;
+    let workdir = match workdir {
+        Some(workdir) => {
+            std::fs::create_dir_all(&workdir)?;
+            workdir.canonicalize()?
+        }
+        None => {
+            temp_dir = tempfile::tempdir()?;
+            temp_dir.path().to_path_buf()
+        }
+    };
+    std::env::set_current_dir(&workdir)?;
+
+    let title = recipe.title.clone();
+    let run = run_recipe_test(recipe, &fixture, &workdir).await?;
+    for call in &run.tool_calls {
+        let status = if call.is_error { " (error)" } else { "" };
+        println!(
+            "  {} {}{}",
+            style("→").dim(),
+            call.name,
+            style(status).dim()
+        );
+    }
+
+    let failures = check_expectations(&run, &fixture.expect, &workdir);
+    if failures.is_empty() {
+        println!(
+            "{} {} passed with {} tool calls",
+            style("✓").green().bold(),
+            title,
+            run.tool_calls.len()
+        );
+        return Ok(());
+    }
+    for failure in &failures {
+        println!("{} {}", style("✗").red().bold(), failure);
+    }
+    Err(anyhow!(
+        "{} failed {} of the fixture's expectations",
+        title,
+        failures.len()
+    ))
+}
+
+/// Installs a recipe into the local registry
+///
+/// # Arguments
+///
+/// * `source` - Git remote, local directory or tarball to install the recipe from
+/// * `name` - Name to install the recipe as
+/// * `version` - Git tag, branch or commit to install
+/// * `path` - Directory of the recipe within the source
+///
+/// # Returns
+///
+/// Result indicating success or failure
+pub async fn handle_install(
+    source: &str,
+    name: Option<String>,
+    version: Option<String>,
+    path: Option<String>,
+) -> Result<()> {
+    let registry = RecipeRegistry::from_config()?;
+    let options = InstallOptions {
+        name,
+        version,
+        path,
+    };
+    match registry.install(source, options).await {
+        Ok(recipe) => {
+            println!(
+                "{} Installed {}@{} ({})",
+                style("✓").green().bold(),
+                recipe.name,
+                recipe.version,
+                short_revision(&recipe)
+            );
+            println!("  Run it with: goose run --recipe {}", recipe.name);
+            Ok(())
+        }
+        Err(err) => {
+            println!("{} {}", style("✗").red().bold(), err);
+            Err(err)
+        }
+    }
+}
+
+/// Lists the recipes installed in the local registry
+pub fn handle_list() -> Result<()> {
+    let recipes = RecipeRegistry::from_config()?.list()?;
+    if recipes.is_empty() {
+        println!("No recipes installed, add one with goose recipe install <source>");
+        return Ok(());
+    }
+    for recipe in recipes {
+        println!(
+            "{}@{} {} {}",
+            style(&recipe.name).bold(),
+            recipe.version,
+            style(short_revision(&recipe)).dim(),
+            style(&recipe.source).dim()
+        );
+    }
+    Ok(())
+}
+
+/// Updates installed recipes from their sources
+///
+/// # Arguments
+///
+/// * `name` - Recipe to update, all recipes if `None`
+///
+/// # Returns
+///
+/// Result indicating success or failure
+pub async fn handle_update(name: Option<&str>) -> Result<()> {
+    let registry = RecipeRegistry::from_config()?;
+    match registry.update(name).await {
+        Ok(updates) => {
+            if updates.is_empty() {
+                println!("No recipes installed");
+            }
+            for update in updates {
+                let previous = &update.previous;
+                match update.updated {
+                    Some(recipe) => println!(
+                        "{} Updated {} from {} to {} ({})",
+                        style("✓").green().bold(),
+                        recipe.name,
+                        previous.version,
+                        recipe.version,
+                        short_revision(&recipe)
+                    ),
+                    None => println!(
+                        "{} {}@{} is up to date",
+                        style("✓").green().bold(),
+                        previous.name,
+                        previous.version
+                    ),
+                }
+            }
+            Ok(())
+        }
+        Err(err) => {
+            println!("{} {}", style("✗").red().bold(), err);
+            Err(err)
+        }
+    }
+}
+
+/// Removes an installed recipe, all its versions or the one given as name@version
+pub fn handle_remove(recipe: &str) -> Result<()> {
+    match RecipeRegistry::from_config()?.remove(recipe) {
+        Ok(removed) => {
+            for recipe in removed {
+                println!(
+                    "{} Removed {}@{}",
+                    style("✓").green().bold(),
+                    recipe.name,
+                    recipe.version
+                );
+            }
+            Ok(())
+        }
+        Err(err) => {
+            println!("{} {}", style("✗").red().bold(), err);
+            Err(err)
+        }
+    }
+}
+
+fn short_revision(recipe: &InstalledRecipe) -> &str {
+    &recipe.revision[..recipe.revision.len().min(12)]
+}
@@ -4,6 +4,7 @@ use cliclack::{confirm, multiselect, select};
 use goose::session::info::{get_session_info, SessionInfo, SortOrder};
 use goose::session::{self, Identifier};
 use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -21,8 +22,8 @@ pub fn remove_sessions(sessions: Vec<SessionInfo>) -> Result<()> {
 
     if should_delete {
         for session in sessions {
-            fs::remove_file(session.path.clone())
-                .with_context(|| format!("Failed to remove session file '{}'", session.path))?;
+            session::remove_session(Path::new(&session.path))
+                .with_context(|| format!("Failed to remove session '{}'", session.path))?;
             println!("Session `{}` removed.", session.id);
         }
     } else {
@@ -42,7 +43,7 @@ fn prompt_interactive_session_removal(sessions: &[SessionInfo]) -> Result<Vec<Se
         "Select sessions to delete (use spacebar, Enter to confirm, Ctrl+C to cancel):",
     );
 
-    let display_map: std::collections::HashMap<String, SessionInfo> = sessions
+    let display_map: HashMap<String, SessionInfo> = sessions
         .iter()
         .map(|s| {
             let desc = if s.metadata.description.is_empty() {
@@ -141,34 +142,121 @@ pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Re
             if sessions.is_empty() {
                 println!("No sessions found");
                 return Ok(());
-            } else {
-                println!("Available sessions:");
-                for SessionInfo {
-                    id,
-                    path,
-                    metadata,
-                    modified,
-                } in sessions
-                {
-                    let description = if metadata.description.is_empty() {
-                        "(none)"
-                    } else {
-                        &metadata.description
-                    };
-                    let output = format!("{} - {} - {}", id, description, modified);
-                    if verbose {
-                        println!("  {}", output);
-                        println!("    Path: {}", path);
-                    } else {
-                        println!("{}", output);
+            }
+
+            // Forks are listed under the session they were forked from, in the same order
+            let ids: HashSet<&str> = sessions.iter().map(|s| s.id.as_str()).collect();
+            let mut forks: HashMap<&str, Vec<&SessionInfo>> = HashMap::new();
+            let mut roots = Vec::new();
+            for session in &sessions {
+                match session.metadata.parent_session_id.as_deref() {
+                    Some(parent) if ids.contains(parent) => {
+                        forks.entry(parent).or_default().push(session)
                     }
+                    _ => roots.push(session),
                 }
             }
+
+            println!("Available sessions:");
+            for session in roots {
+                print_session_tree(session, &forks, 0, verbose);
+            }
         }
     }
     Ok(())
 }
 
+fn print_session_tree(
+    session: &SessionInfo,
+    forks: &HashMap<&str, Vec<&SessionInfo>>,
+    depth: usize,
+    verbose: bool,
+) {
+    let SessionInfo {
+        id,
+        path,
+        metadata,
+        modified,
+    } = session;
+    let description = if metadata.description.is_empty() {
+        "(none)"
+    } else {
+        &metadata.description
+    };
+    let mut output = format!("{} - {} - {}", id, description, modified);
+    if let Some(cost) = metadata.accumulated_cost {
+        output.push_str(&format!(" - {}", format_cost(cost)));
+    }
+    if let Some(fork_point) = metadata.fork_point {
+        output.push_str(&format!(" (forked at message {})", fork_point));
+    }
+
+    let indent = "   ".repeat(depth.saturating_sub(1));
+    let branch = if depth > 0 { "└─ " } else { "" };
+    if verbose {
+        println!("  {}{}{}", indent, branch, output);
+        let detail_indent = "   ".repeat(depth);
+        println!("    {}Path: {}", detail_indent, path);
+        if let Some(costs) = &metadata.accumulated_cost_by_model {
+            let mut costs: Vec<_> = costs.iter().collect();
+            costs.sort_by(|a, b| a.0.cmp(b.0));
+            for (model, cost) in costs {
+                println!(
+                    "    {}Cost ({}): {}",
+                    detail_indent,
+                    model,
+                    format_cost(*cost)
+                );
+            }
+        }
+    } else {
+        println!("{}{}{}", indent, branch, output);
+    }
+
+    for fork in forks.get(id.as_str()).into_iter().flatten() {
+        print_session_tree(fork, forks, depth + 1, verbose);
+    }
+}
+
+/// Search the messages of all sessions, from the session store's full-text index when it
+/// has one
+pub fn handle_session_search(query: &str, limit: usize, format: String) -> Result<()> {
+    let hits = session::search_sessions(query, limit)?;
+
+    match format.as_str() {
+        "json" => {
+            println!("{}", serde_json::to_string(&hits)?);
+        }
+        _ => {
+            if hits.is_empty() {
+                println!("No messages found matching '{}'", query);
+                return Ok(());
+            }
+            for hit in hits {
+                println!(
+                    "{} #{} ({}): {}",
+                    hit.session_id, hit.message_index, hit.role, hit.snippet
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fork a session into a new session with its first `at_message` messages, or all of them
+pub fn handle_session_fork(identifier: Identifier, at_message: Option<usize>) -> Result<()> {
+    let session_file_path = session::get_path(identifier);
+    let fork_id = session::fork_session(&session_file_path, at_message)?;
+
+    println!("Forked session `{}`.", fork_id);
+    println!("Resume it with: goose session --resume --name {}", fork_id);
+    Ok(())
+}
+
+fn format_cost(cost: f64) -> String {
+    format!("${:.4}", cost)
+}
+
 /// Export a session to Markdown without creating a full Session object
 ///
 /// This function directly reads messages from the session file and converts them to Markdown
@@ -177,7 +265,7 @@ pub fn handle_session_export(identifier: Identifier, output_path: Option<PathBuf
     // Get the session file path
     let session_file_path = goose::session::get_path(identifier.clone());
 
-    if !session_file_path.exists() {
+    if !session::session_exists(&session_file_path) {
         return Err(anyhow::anyhow!(
             "Session file not found (expected path: {})",
             session_file_path.display()
@@ -286,8 +374,8 @@ fn export_session_to_markdown(
 
 /// Prompt the user to interactively select a session
 ///
-/// Shows a list of available sessions and lets the user select one
-pub fn prompt_interactive_session_selection() -> Result<session::Identifier> {
+/// Shows a list of available sessions under `prompt` and lets the user select one
+pub fn prompt_interactive_session_selection(prompt: &str) -> Result<session::Identifier> {
     // Get sessions sorted by modification date (newest first)
     let sessions = match get_session_info(SortOrder::Descending) {
         Ok(sessions) => sessions,
@@ -302,10 +390,10 @@ pub fn prompt_interactive_session_selection() -> Result<session::Identifier> {
     }
 
     // Build the selection prompt
-    let mut selector = select("Select a session to export:");
+    let mut selector = select(prompt);
 
     // Map to display text
-    let display_map: std::collections::HashMap<String, SessionInfo> = sessions
+    let display_map: HashMap<String, SessionInfo> = sessions
         .iter()
         .map(|s| {
             let desc = if s.metadata.description.is_empty() {
@@ -454,6 +454,9 @@ async fn process_message_streaming(
         id: session::Identifier::Path(session_file.clone()),
         working_dir: std::env::current_dir()?,
         schedule_id: None,
+        max_turns: None,
+        max_tokens: None,
+        deadline: None,
     };
 
     // Get response from agent
@@ -589,6 +592,24 @@ async fn process_message_streaming(
                         // For now, we'll just log them
                         tracing::info!("Received MCP notification in web interface");
                     }
+                    Ok(AgentEvent::MessageDelta(_)) => {
+                        // The complete message follows, which is what the web interface renders
+                    }
+                    Ok(AgentEvent::ExtensionRestarted { name, reason }) => {
+                        tracing::warn!("Extension {} was restarted because {}", name, reason);
+                    }
+                    Ok(AgentEvent::ContextCompacted {
+                        messages: compacted,
+                        ..
+                    }) => {
+                        // The agent compacted the conversation itself, keep the session in sync
+                        {
+                            let mut session_msgs = session_messages.lock().await;
+                            *session_msgs = compacted.clone();
+                        }
+                        session::persist_messages(&session_file, &compacted, None).await?;
+                        messages = compacted;
+                    }
                     Err(e) => {
                         error!("Error in message stream: {}", e);
                         let mut sender = sender.lock().await;
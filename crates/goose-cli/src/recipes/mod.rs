@@ -1,4 +1,6 @@
 pub mod github_recipe;
 pub mod print_recipe;
 pub mod recipe;
+pub mod recipe_test;
+pub mod registry;
 pub mod search_recipe;
@@ -1,9 +1,7 @@
 use std::collections::HashMap;
 
 use console::style;
-use goose::recipe::Recipe;
-
-use crate::recipes::recipe::BUILT_IN_RECIPE_DIR_PARAM;
+use goose::recipe::{Recipe, RecipeParameter, RecipeParameterInputType, RECIPE_DIR_PARAM};
 
 pub fn print_recipe_explanation(recipe: &Recipe) {
     println!(
@@ -21,12 +19,17 @@ pub fn print_recipe_explanation(recipe: &Recipe) {
                     Some(val) => format!(" (default: {})", val),
                     None => String::new(),
                 };
+                let options_display = match &param.options {
+                    Some(options) => format!(" [{}]", options.join(", ")),
+                    None => String::new(),
+                };
 
                 println!(
-                    "   - {} ({}, {}){}: {}",
+                    "   - {} ({}, {}){}{}: {}",
                     style(&param.key).cyan(),
                     param.input_type,
                     param.requirement,
+                    options_display,
                     default_display,
                     param.description
                 );
@@ -35,13 +38,25 @@ pub fn print_recipe_explanation(recipe: &Recipe) {
     }
 }
 
-pub fn print_parameters_with_values(params: HashMap<String, String>) {
+/// Print parameter values, hiding those of secret parameters
+pub fn print_parameters_with_values(
+    params: HashMap<String, String>,
+    recipe_parameters: &[RecipeParameter],
+) {
     for (key, value) in params {
-        let label = if key == BUILT_IN_RECIPE_DIR_PARAM {
+        let label = if key == RECIPE_DIR_PARAM {
             " (built-in)"
         } else {
             ""
         };
+        let is_secret = recipe_parameters
+            .iter()
+            .any(|param| param.key == key && param.input_type == RecipeParameterInputType::Secret);
+        let value = if is_secret {
+            "********".to_string()
+        } else {
+            value
+        };
         println!("   {}{}: {}", key, label, value);
     }
 }
@@ -49,13 +64,14 @@ pub fn print_parameters_with_values(params: HashMap<String, String>) {
 pub fn print_required_parameters_for_template(
     params_for_template: HashMap<String, String>,
     missing_params: Vec<String>,
+    recipe_parameters: &[RecipeParameter],
 ) {
     if !params_for_template.is_empty() {
         println!(
             "{}",
             style("📥 Parameters used to load this recipe:").bold()
         );
-        print_parameters_with_values(params_for_template)
+        print_parameters_with_values(params_for_template, recipe_parameters)
     }
     if !missing_params.is_empty() {
         println!(
@@ -6,15 +6,30 @@ use crate::recipes::print_recipe::{
     print_required_parameters_for_template,
 };
 use crate::recipes::search_recipe::retrieve_recipe_file;
-use goose::recipe::{Recipe, RecipeParameter, RecipeParameterRequirement};
+use goose::recipe::parameters::{
+    parameter_value, render_parameter_values, secret_value, validate_parameter_definitions,
+};
+use goose::recipe::sub_recipe::validate_sub_recipes;
+use goose::recipe::{
+    parse_recipe_content, Recipe, RecipeParameter, RecipeParameterInputType,
+    RecipeParameterRequirement, RECIPE_DIR_PARAM,
+};
 use minijinja::{Environment, Error, Template, UndefinedBehavior};
-use serde_json::Value as JsonValue;
-use serde_yaml::Value as YamlValue;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
-pub const BUILT_IN_RECIPE_DIR_PARAM: &str = "recipe_dir";
 pub const RECIPE_FILE_EXTENSIONS: &[&str] = &["yaml", "json"];
+
+/// Where the values of parameters that weren't given come from, besides their defaults
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ParameterSources {
+    /// The keyring for secrets, and asking the user for `user_prompt` parameters
+    KeyringAndUser,
+    Keyring,
+    /// Nothing else, so the result doesn't depend on who runs it
+    DefaultsOnly,
+}
+
 /// Loads, validates a recipe from a YAML or JSON file, and renders it with the given parameters
 ///
 /// # Arguments
@@ -32,24 +47,65 @@ pub const RECIPE_FILE_EXTENSIONS: &[&str] = &["yaml", "json"];
 /// - Recipe is not valid
 /// - The required fields are missing
 pub fn load_recipe_as_template(recipe_name: &str, params: Vec<(String, String)>) -> Result<Recipe> {
+    let (recipe, params_for_template) = render_recipe_template(recipe_name, params)?;
+    print_loaded_recipe(&recipe, params_for_template);
+    Ok(recipe)
+}
+
+/// Loads and renders a recipe like [`load_recipe_as_template`] without displaying it,
+/// returning the parameter values it was rendered with as they were given, so file
+/// parameters are their paths
+pub fn render_recipe_template(
+    recipe_name: &str,
+    params: Vec<(String, String)>,
+) -> Result<(Recipe, HashMap<String, String>)> {
+    render_recipe_template_from(recipe_name, params, ParameterSources::KeyringAndUser)
+}
+
+/// Renders a recipe like [`render_recipe_template`] with only the given values and the
+/// parameters' defaults, never prompting or reading secrets from the keyring, as a test
+/// must run the same for everyone
+pub fn render_recipe_for_test(
+    recipe_name: &str,
+    params: Vec<(String, String)>,
+) -> Result<(Recipe, HashMap<String, String>)> {
+    render_recipe_template_from(recipe_name, params, ParameterSources::DefaultsOnly)
+}
+
+fn render_recipe_template_from(
+    recipe_name: &str,
+    params: Vec<(String, String)>,
+    sources: ParameterSources,
+) -> Result<(Recipe, HashMap<String, String>)> {
     let (recipe_file_content, recipe_parent_dir) = retrieve_recipe_file(recipe_name)?;
 
     let recipe = validate_recipe_file_parameters(&recipe_file_content)?;
+    let recipe_parameters = recipe.parameters.unwrap_or_default();
 
     let (params_for_template, missing_params) =
-        apply_values_to_parameters(&params, recipe.parameters, recipe_parent_dir, true)?;
+        apply_values_to_parameters(&params, &recipe_parameters, recipe_parent_dir, sources)?;
     if !missing_params.is_empty() {
-        return Err(anyhow::anyhow!(
-            "Please provide the following parameters in the command line: {}",
-            missing_parameters_command_line(missing_params)
-        ));
+        return Err(match sources {
+            ParameterSources::DefaultsOnly => anyhow::anyhow!(
+                "Please provide the following parameters in the fixture's params: {}",
+                missing_params.join(", ")
+            ),
+            _ => anyhow::anyhow!(
+                "Please provide the following parameters in the command line: {}",
+                missing_parameters_command_line(missing_params)
+            ),
+        });
     }
 
-    let rendered_content = render_content_with_params(&recipe_file_content, &params_for_template)?;
+    let rendered_params = render_parameter_values(&recipe_parameters, &params_for_template)?;
+    let rendered_content = render_content_with_params(&recipe_file_content, &rendered_params)?;
 
     let recipe = parse_recipe_content(&rendered_content)?;
+    Ok((recipe, params_for_template))
+}
 
-    // Display information about the loaded recipe
+/// Display information about a loaded recipe
+pub fn print_loaded_recipe(recipe: &Recipe, params_for_template: HashMap<String, String>) {
     println!(
         "{} {}",
         style("Loading recipe:").green().bold(),
@@ -59,10 +115,12 @@ pub fn load_recipe_as_template(recipe_name: &str, params: Vec<(String, String)>)
 
     if !params_for_template.is_empty() {
         println!("{}", style("Parameters used to load this recipe:").bold());
-        print_parameters_with_values(params_for_template);
+        print_parameters_with_values(
+            params_for_template,
+            recipe.parameters.as_deref().unwrap_or_default(),
+        );
     }
     println!();
-    Ok(recipe)
 }
 
 /// Loads and validates a recipe from a YAML or JSON file
@@ -83,10 +141,18 @@ pub fn load_recipe_as_template(recipe_name: &str, params: Vec<(String, String)>)
 /// - The file can't be read
 /// - The YAML/JSON is invalid
 /// - The parameter definition does not match the template variables in the recipe file
+/// - A parameter's options or default don't fit its input type
+/// - A sub-recipe can't be loaded, or sub-recipes run each other in a cycle
+/// - The settings name an unknown goose_mode
 pub fn load_recipe(recipe_name: &str) -> Result<Recipe> {
-    let (recipe_file_content, _) = retrieve_recipe_file(recipe_name)?;
+    let (recipe_file_content, recipe_parent_dir) = retrieve_recipe_file(recipe_name)?;
 
-    validate_recipe_file_parameters(&recipe_file_content)
+    let recipe = validate_recipe_file_parameters(&recipe_file_content)?;
+    validate_sub_recipes(&recipe, &recipe_parent_dir)?;
+    if let Some(settings) = &recipe.settings {
+        settings.goose_mode()?;
+    }
+    Ok(recipe)
 }
 
 pub fn explain_recipe_with_parameters(
@@ -97,10 +163,14 @@ pub fn explain_recipe_with_parameters(
 
     let raw_recipe = validate_recipe_file_parameters(&recipe_file_content)?;
     print_recipe_explanation(&raw_recipe);
-    let recipe_parameters = raw_recipe.parameters;
-    let (params_for_template, missing_params) =
-        apply_values_to_parameters(&params, recipe_parameters, recipe_parent_dir, false)?;
-    print_required_parameters_for_template(params_for_template, missing_params);
+    let recipe_parameters = raw_recipe.parameters.unwrap_or_default();
+    let (params_for_template, missing_params) = apply_values_to_parameters(
+        &params,
+        &recipe_parameters,
+        recipe_parent_dir,
+        ParameterSources::Keyring,
+    )?;
+    print_required_parameters_for_template(params_for_template, missing_params, &recipe_parameters);
 
     Ok(())
 }
@@ -108,6 +178,12 @@ pub fn explain_recipe_with_parameters(
 fn validate_recipe_file_parameters(recipe_file_content: &str) -> Result<Recipe> {
     let recipe_from_recipe_file: Recipe = parse_recipe_content(recipe_file_content)?;
     validate_optional_parameters(&recipe_from_recipe_file)?;
+    validate_parameter_definitions(
+        recipe_from_recipe_file
+            .parameters
+            .as_deref()
+            .unwrap_or_default(),
+    )?;
     validate_parameters_in_template(&recipe_from_recipe_file.parameters, recipe_file_content)?;
     Ok(recipe_from_recipe_file)
 }
@@ -117,7 +193,7 @@ fn validate_parameters_in_template(
     recipe_file_content: &str,
 ) -> Result<()> {
     let mut template_variables = extract_template_variables(recipe_file_content)?;
-    template_variables.remove(BUILT_IN_RECIPE_DIR_PARAM);
+    template_variables.remove(RECIPE_DIR_PARAM);
 
     let param_keys: HashSet<String> = recipe_parameters
         .as_ref()
@@ -183,18 +259,6 @@ fn validate_optional_parameters(recipe: &Recipe) -> Result<()> {
     }
 }
 
-fn parse_recipe_content(content: &str) -> Result<Recipe> {
-    if serde_json::from_str::<JsonValue>(content).is_ok() {
-        Ok(serde_json::from_str(content)?)
-    } else if serde_yaml::from_str::<YamlValue>(content).is_ok() {
-        Ok(serde_yaml::from_str(content)?)
-    } else {
-        Err(anyhow::anyhow!(
-            "Unsupported file format for recipe file. Expected .yaml or .json"
-        ))
-    }
-}
-
 fn extract_template_variables(template_str: &str) -> Result<HashSet<String>> {
     let mut env = Environment::new();
     env.set_undefined_behavior(UndefinedBehavior::Strict);
@@ -208,29 +272,34 @@ fn extract_template_variables(template_str: &str) -> Result<HashSet<String>> {
 
 fn apply_values_to_parameters(
     user_params: &[(String, String)],
-    recipe_parameters: Option<Vec<RecipeParameter>>,
+    recipe_parameters: &[RecipeParameter],
     recipe_parent_dir: PathBuf,
-    enable_user_prompt: bool,
+    sources: ParameterSources,
 ) -> Result<(HashMap<String, String>, Vec<String>)> {
     let mut param_map: HashMap<String, String> = user_params.iter().cloned().collect();
     let recipe_parent_dir_str = recipe_parent_dir
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Invalid UTF-8 in recipe_dir"))?;
     param_map.insert(
-        BUILT_IN_RECIPE_DIR_PARAM.to_string(),
+        RECIPE_DIR_PARAM.to_string(),
         recipe_parent_dir_str.to_string(),
     );
     let mut missing_params: Vec<String> = Vec::new();
-    for param in recipe_parameters.unwrap_or_default() {
+    for param in recipe_parameters {
         if !param_map.contains_key(&param.key) {
-            match (&param.default, &param.requirement) {
-                (Some(default), _) => param_map.insert(param.key.clone(), default.clone()),
-                (None, RecipeParameterRequirement::UserPrompt) if enable_user_prompt => {
-                    let input_value = cliclack::input(format!(
-                        "Please enter {} ({})",
-                        param.key, param.description
-                    ))
-                    .interact()?;
+            let secret = match param.input_type {
+                RecipeParameterInputType::Secret if sources != ParameterSources::DefaultsOnly => {
+                    secret_value(param)
+                }
+                _ => None,
+            };
+            match (secret, &param.default, &param.requirement) {
+                (Some(secret), _, _) => param_map.insert(param.key.clone(), secret),
+                (None, Some(default), _) => param_map.insert(param.key.clone(), default.clone()),
+                (None, None, RecipeParameterRequirement::UserPrompt)
+                    if sources == ParameterSources::KeyringAndUser =>
+                {
+                    let input_value = prompt_for_parameter(param)?;
                     param_map.insert(param.key.clone(), input_value)
                 }
                 _ => {
@@ -243,6 +312,37 @@ fn apply_values_to_parameters(
     Ok((param_map, missing_params))
 }
 
+/// Ask for the value of a parameter with an input that fits its type
+fn prompt_for_parameter(param: &RecipeParameter) -> Result<String> {
+    let prompt = format!("Please enter {} ({})", param.key, param.description);
+    let value = match param.input_type {
+        RecipeParameterInputType::Boolean => {
+            cliclack::confirm(format!("{} ({})", param.key, param.description))
+                .interact()?
+                .to_string()
+        }
+        RecipeParameterInputType::Select => {
+            let mut select = cliclack::select(prompt);
+            for option in param.options.iter().flatten() {
+                select = select.item(option.clone(), option, "");
+            }
+            select.interact()?
+        }
+        RecipeParameterInputType::Secret => cliclack::password(prompt).mask('▪').interact()?,
+        _ => {
+            let param = param.clone();
+            cliclack::input(prompt)
+                .validate(move |input: &String| {
+                    parameter_value(&param, input)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .interact()?
+        }
+    };
+    Ok(value)
+}
+
 fn render_content_with_params(content: &str, params: &HashMap<String, String>) -> Result<String> {
     // Create a minijinja environment and context
     let mut env = minijinja::Environment::new();
@@ -518,6 +618,60 @@ mod tests {
             .contains("unknown variant `some_invalid_type`"));
     }
 
+    #[test]
+    fn test_load_recipe_as_template_checks_parameter_types() {
+        let instructions_and_parameters = r#"
+            "instructions": "Review {{ count }} files at {{ level }} depth, dry run: {{ dry_run }}",
+            "parameters": [
+                {
+                    "key": "count",
+                    "input_type": "number",
+                    "requirement": "required",
+                    "description": "How many files"
+                },
+                {
+                    "key": "level",
+                    "input_type": "select",
+                    "requirement": "optional",
+                    "default": "shallow",
+                    "options": ["shallow", "deep"],
+                    "description": "How deep to review"
+                },
+                {
+                    "key": "dry_run",
+                    "input_type": "boolean",
+                    "requirement": "required",
+                    "description": "Only report"
+                }
+            ]"#;
+        let (_temp_dir, recipe_path) = setup_recipe_file(instructions_and_parameters);
+
+        let params = vec![
+            ("count".to_string(), "abc".to_string()),
+            ("dry_run".to_string(), "yes".to_string()),
+        ];
+        let err = load_recipe_as_template(recipe_path.to_str().unwrap(), params).unwrap_err();
+        assert!(err.to_string().contains("'count' must be a number"));
+
+        let params = vec![
+            ("count".to_string(), "3".to_string()),
+            ("dry_run".to_string(), "yes".to_string()),
+        ];
+        let recipe = load_recipe_as_template(recipe_path.to_str().unwrap(), params).unwrap();
+        assert_eq!(
+            recipe.instructions.unwrap(),
+            "Review 3 files at shallow depth, dry run: true"
+        );
+
+        let params = vec![
+            ("count".to_string(), "3".to_string()),
+            ("dry_run".to_string(), "yes".to_string()),
+            ("level".to_string(), "medium".to_string()),
+        ];
+        let err = load_recipe_as_template(recipe_path.to_str().unwrap(), params).unwrap_err();
+        assert!(err.to_string().contains("one of shallow, deep"));
+    }
+
     #[test]
     fn test_load_recipe_as_template_success_without_parameters() {
         let instructions_and_parameters = r#"
@@ -529,4 +683,41 @@ mod tests {
         assert_eq!(recipe.instructions.unwrap(), "Test instructions");
         assert!(recipe.parameters.is_none());
     }
+
+    #[test]
+    fn test_render_recipe_for_test_only_uses_given_values() {
+        let instructions_and_parameters = r#"
+            "instructions": "Review {{ target }} with {{ token }}",
+            "parameters": [
+                {
+                    "key": "target",
+                    "input_type": "string",
+                    "requirement": "user_prompt",
+                    "description": "What to review"
+                },
+                {
+                    "key": "token",
+                    "input_type": "secret",
+                    "requirement": "user_prompt",
+                    "description": "API token"
+                }
+            ]"#;
+        let (_temp_dir, recipe_path) = setup_recipe_file(instructions_and_parameters);
+        let recipe_path = recipe_path.to_str().unwrap();
+
+        temp_env::with_var("RECIPE_SECRET_TOKEN", Some("from-the-environment"), || {
+            let err = render_recipe_for_test(recipe_path, Vec::new()).unwrap_err();
+            assert!(err.to_string().contains("target, token"));
+
+            let params = vec![
+                ("target".to_string(), "main.rs".to_string()),
+                ("token".to_string(), "fixture-token".to_string()),
+            ];
+            let (recipe, _) = render_recipe_for_test(recipe_path, params).unwrap();
+            assert_eq!(
+                recipe.instructions.unwrap(),
+                "Review main.rs with fixture-token"
+            );
+        });
+    }
 }
@@ -0,0 +1,461 @@
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use goose::agents::{Agent, AgentEvent};
+use goose::message::{Message, MessageContent};
+use goose::permission::permission_confirmation::PrincipalType;
+use goose::permission::{Permission, PermissionConfirmation};
+use goose::providers::replay::{ReplayProvider, ReplayResponse};
+use goose::recipe::Recipe;
+use mcp_core::role::Role;
+use mcp_core::{Content, ToolError};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+/// A scripted run of a recipe: the parameters it's rendered with, the responses the model
+/// gives, tools that return canned results instead of running, and what the run must do
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecipeTestFixture {
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    /// Message to start the run with instead of the recipe's prompt
+    pub prompt: Option<String>,
+    /// Files written to the working directory before the run
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    pub responses: Vec<ReplayResponse>,
+    #[serde(default)]
+    pub stubs: BTreeMap<String, ToolStub>,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+/// The output or the error a stubbed tool returns
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolStub {
+    pub output: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    /// Tool calls the run must make in this order, among any others
+    #[serde(default)]
+    pub tool_calls: Vec<ExpectedToolCall>,
+    /// Files the run must leave in the working directory
+    #[serde(default)]
+    pub files: Vec<ExpectedFile>,
+    /// The structured output the run must finish with
+    pub final_output: Option<Value>,
+    /// Text the last assistant message must contain
+    pub output_contains: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedToolCall {
+    pub name: String,
+    /// Arguments the call must have, other arguments are ignored
+    pub arguments: Option<Value>,
+    /// Text the tool's output must contain
+    pub output_contains: Option<String>,
+    pub is_error: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedFile {
+    pub path: String,
+    #[serde(default = "default_exists")]
+    pub exists: bool,
+    pub equals: Option<String>,
+    pub contains: Option<String>,
+}
+
+fn default_exists() -> bool {
+    true
+}
+
+/// A tool call the agent made during a test run and what the tool returned
+#[derive(Debug, Clone)]
+pub struct ToolInvocation {
+    pub name: String,
+    pub arguments: Value,
+    pub output: String,
+    pub is_error: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct RecipeTestRun {
+    pub tool_calls: Vec<ToolInvocation>,
+    pub final_output: Option<Value>,
+    /// Text of the last assistant message that had any
+    pub last_text: String,
+    pub unused_responses: usize,
+    /// Whether the agent asked for more responses than the fixture has
+    pub exhausted: bool,
+}
+
+impl RecipeTestFixture {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read fixture {}: {}", path.display(), e))?;
+        // JSON is YAML too, so this reads both
+        serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid fixture {}: {}", path.display(), e))
+    }
+
+    pub fn params(&self) -> Vec<(String, String)> {
+        self.params
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// Run a rendered recipe against the fixture's responses, with its sub-recipe paths already
+/// resolved; extensions start in the current directory, which should be `workdir`
+pub async fn run_recipe_test(
+    recipe: Recipe,
+    fixture: &RecipeTestFixture,
+    workdir: &Path,
+) -> Result<RecipeTestRun> {
+    for (path, content) in &fixture.files {
+        let path = workdir.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)?;
+    }
+
+    let stubs = fixture
+        .stubs
+        .iter()
+        .map(|(name, stub)| {
+            let result = match (&stub.output, &stub.error) {
+                (_, Some(error)) => Err(ToolError::ExecutionError(error.clone())),
+                (output, None) => Ok(vec![Content::text(output.clone().unwrap_or_default())]),
+            };
+            (name.clone(), result)
+        })
+        .collect();
+    let provider = Arc::new(ReplayProvider::new(fixture.responses.clone()));
+    let agent = Agent::new().with_tool_stubs(stubs);
+    agent.update_provider(provider.clone()).await?;
+    // The fixture decides which tools run, so nothing waits for approval
+    agent.set_goose_mode(Some("auto".to_string())).await;
+
+    let recipe_prompt = agent.prepare_recipe(&recipe).await?;
+    let prompt = fixture.prompt.clone().unwrap_or(recipe_prompt);
+    let messages = vec![Message::user().with_text(prompt)];
+
+    let mut run = RecipeTestRun::default();
+    let mut requests: HashMap<String, usize> = HashMap::new();
+    let mut stream = agent.reply(&messages, None).await?;
+    while let Some(event) = stream.next().await {
+        let message = match event? {
+            AgentEvent::Message(message) => message,
+            _ => continue,
+        };
+        for content in &message.content {
+            match content {
+                MessageContent::ToolConfirmationRequest(confirmation) => {
+                    agent
+                        .handle_confirmation(
+                            confirmation.id.clone(),
+                            PermissionConfirmation {
+                                principal_type: PrincipalType::Tool,
+                                permission: Permission::AllowOnce,
+                            },
+                        )
+                        .await;
+                }
+                MessageContent::ToolRequest(request) => {
+                    if let Ok(tool_call) = &request.tool_call {
+                        requests.insert(request.id.clone(), run.tool_calls.len());
+                        run.tool_calls.push(ToolInvocation {
+                            name: tool_call.name.clone(),
+                            arguments: tool_call.arguments.clone(),
+                            output: String::new(),
+                            is_error: false,
+                        });
+                    }
+                }
+                MessageContent::ToolResponse(response) => {
+                    if let Some(&index) = requests.get(&response.id) {
+                        let invocation = &mut run.tool_calls[index];
+                        match &response.tool_result {
+                            Ok(contents) => {
+                                invocation.output = contents
+                                    .iter()
+                                    .filter_map(|content| content.as_text())
+                                    .collect::<Vec<_>>()
+                                    .join("\n");
+                            }
+                            Err(e) => {
+                                invocation.output = e.to_string();
+                                invocation.is_error = true;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let text = message.as_concat_text();
+        if message.role == Role::Assistant && !text.trim().is_empty() {
+            run.last_text = text;
+        }
+    }
+    drop(stream);
+
+    run.final_output = agent.final_output().await;
+    run.unused_responses = provider.remaining();
+    run.exhausted = provider.exhausted();
+    Ok(run)
+}
+
+/// Everything about the run that doesn't match the expectations, empty when the test passes
+pub fn check_expectations(
+    run: &RecipeTestRun,
+    expect: &Expectations,
+    workdir: &Path,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+    if run.exhausted {
+        failures.push("The agent asked for more responses than the fixture has".to_string());
+    }
+    if run.unused_responses > 0 {
+        failures.push(format!(
+            "The run ended with {} scripted responses unused",
+            run.unused_responses
+        ));
+    }
+
+    // Expected calls are matched in order, skipping calls that aren't expected
+    let mut remaining = run.tool_calls.iter();
+    for expected in &expect.tool_calls {
+        if !remaining.any(|call| tool_call_matches(call, expected)) {
+            let called: Vec<&str> = run.tool_calls.iter().map(|c| c.name.as_str()).collect();
+            let arguments = expected
+                .arguments
+                .as_ref()
+                .map(|arguments| format!(" with arguments {}", arguments))
+                .unwrap_or_default();
+            failures.push(format!(
+                "Expected a call of {}{}, in order, tools called: {}",
+                expected.name,
+                arguments,
+                if called.is_empty() {
+                    "none".to_string()
+                } else {
+                    called.join(", ")
+                }
+            ));
+            break;
+        }
+    }
+
+    if let Some(expected) = &expect.final_output {
+        match &run.final_output {
+            Some(output) if output == expected => {}
+            Some(output) => failures.push(format!(
+                "Expected the final output {}, got {}",
+                expected, output
+            )),
+            None => failures.push(format!(
+                "Expected the final output {}, the run finished without one",
+                expected
+            )),
+        }
+    }
+
+    if let Some(text) = &expect.output_contains {
+        if !run.last_text.contains(text) {
+            failures.push(format!(
+                "Expected the last response to contain '{}', it was '{}'",
+                text, run.last_text
+            ));
+        }
+    }
+
+    for file in &expect.files {
+        let path = workdir.join(&file.path);
+        let content = std::fs::read_to_string(&path).ok();
+        match (&content, file.exists) {
+            (None, true) => failures.push(format!("Expected the file {} to exist", file.path)),
+            (Some(_), false) => {
+                failures.push(format!("Expected the file {} not to exist", file.path))
+            }
+            _ => {}
+        }
+        let Some(content) = content else { continue };
+        if file
+            .equals
+            .as_ref()
+            .is_some_and(|equals| &content != equals)
+        {
+            failures.push(format!(
+                "The file {} doesn't have the expected content",
+                file.path
+            ));
+        }
+        if let Some(text) = &file.contains {
+            if !content.contains(text) {
+                failures.push(format!(
+                    "Expected the file {} to contain '{}'",
+                    file.path, text
+                ));
+            }
+        }
+    }
+    failures
+}
+
+fn tool_call_matches(call: &ToolInvocation, expected: &ExpectedToolCall) -> bool {
+    call.name == expected.name
+        && expected
+            .arguments
+            .as_ref()
+            .is_none_or(|arguments| value_contains(&call.arguments, arguments))
+        && expected
+            .output_contains
+            .as_ref()
+            .is_none_or(|text| call.output.contains(text))
+        && expected
+            .is_error
+            .is_none_or(|is_error| call.is_error == is_error)
+}
+
+/// Whether the actual value has everything the expected one has; objects may have more keys
+fn value_contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected.iter().all(|(key, value)| {
+            actual
+                .get(key)
+                .is_some_and(|actual| value_contains(actual, value))
+        }),
+        (Value::Array(actual), Value::Array(expected)) => {
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(actual, expected)| value_contains(actual, expected))
+        }
+        _ => actual == expected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::recipe::parse_recipe_content;
+
+    const RECIPE: &str = r#"
+title: Triage
+description: Triage the test failures
+prompt: Triage the failing tests
+extensions: []
+response:
+  json_schema:
+    type: object
+    required: [status]
+    properties:
+      status: {type: string}
+"#;
+
+    const FIXTURE: &str = r#"
+files:
+  notes.md: "flaky: test_login"
+responses:
+  - text: Running the tests
+    tool_calls:
+      - name: developer__shell
+        arguments: {command: cargo test}
+  - tool_calls:
+      - name: recipe__final_output
+        arguments: {status: flaky}
+stubs:
+  developer__shell:
+    output: "test result: FAILED. 1 failed"
+expect:
+  tool_calls:
+    - name: developer__shell
+      arguments: {command: cargo test}
+      output_contains: FAILED
+    - name: recipe__final_output
+  final_output: {status: flaky}
+  output_contains: Running the tests
+  files:
+    - path: notes.md
+      contains: test_login
+    - path: report.md
+      exists: false
+"#;
+
+    #[tokio::test]
+    async fn test_recipe_runs_against_the_fixture() {
+        let workdir = tempfile::tempdir().unwrap();
+        let recipe = parse_recipe_content(RECIPE).unwrap();
+        let fixture: RecipeTestFixture = serde_yaml::from_str(FIXTURE).unwrap();
+
+        let run = run_recipe_test(recipe, &fixture, workdir.path())
+            .await
+            .unwrap();
+        assert_eq!(run.tool_calls.len(), 2);
+        assert!(!run.tool_calls[0].is_error);
+        let failures = check_expectations(&run, &fixture.expect, workdir.path());
+        assert!(failures.is_empty(), "{:?}", failures);
+    }
+
+    #[tokio::test]
+    async fn test_mismatches_are_reported() {
+        let workdir = tempfile::tempdir().unwrap();
+        let recipe = parse_recipe_content(RECIPE).unwrap();
+        let fixture: RecipeTestFixture = serde_yaml::from_str(
+            r#"
+responses:
+  - tool_calls:
+      - name: developer__shell
+        arguments: {command: ls}
+stubs:
+  developer__shell:
+    error: command not found
+expect:
+  tool_calls:
+    - name: developer__shell
+      is_error: false
+  final_output: {status: fixed}
+"#,
+        )
+        .unwrap();
+
+        let run = run_recipe_test(recipe, &fixture, workdir.path())
+            .await
+            .unwrap();
+        assert!(run.tool_calls[0].is_error);
+        let failures = check_expectations(&run, &fixture.expect, workdir.path());
+        assert!(failures.iter().any(|f| f.contains("more responses")));
+        assert!(failures.iter().any(|f| f.contains("developer__shell")));
+        assert!(failures.iter().any(|f| f.contains("without one")));
+    }
+
+    #[test]
+    fn test_arguments_match_as_a_subset() {
+        let actual = serde_json::json!({"command": "write", "path": "a.md", "lines": [1, 2]});
+        assert!(value_contains(
+            &actual,
+            &serde_json::json!({"path": "a.md"})
+        ));
+        assert!(!value_contains(
+            &actual,
+            &serde_json::json!({"path": "b.md"})
+        ));
+        assert!(!value_contains(&actual, &serde_json::json!({"lines": [1]})));
+    }
+}
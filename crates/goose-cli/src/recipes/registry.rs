@@ -0,0 +1,1076 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use etcetera::{choose_app_strategy, AppStrategy};
+use flate2::read::GzDecoder;
+use goose::config::Config;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use tar::Archive;
+
+use crate::recipes::recipe::{load_recipe, RECIPE_FILE_EXTENSIONS};
+
+pub const GOOSE_RECIPE_REGISTRY_DIR_CONFIG_KEY: &str = "GOOSE_RECIPE_REGISTRY_DIR";
+const LOCKFILE_NAME: &str = "recipes.lock";
+const STAGING_DIR_NAME: &str = ".staging";
+const ARCHIVE_EXTENSIONS: &[&str] = &[".tar.gz", ".tgz", ".tar"];
+/// Versions of directories and tarballs are this many characters of their checksum
+const CHECKSUM_VERSION_LEN: usize = 12;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    Git,
+    Directory,
+    Tarball,
+}
+
+/// A recipe installed in the registry, pinned to the commit or checksum it was installed at
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstalledRecipe {
+    pub name: String,
+    pub version: String,
+    pub kind: SourceKind,
+    /// Git remote, absolute path or tarball URL the recipe was installed from
+    pub source: String,
+    /// The git tag, branch or commit asked for, followed again on update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Directory of the recipe within the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The git commit, or the sha256 checksum of a directory or tarball
+    pub revision: String,
+    pub installed_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Lockfile {
+    #[serde(default)]
+    recipes: Vec<InstalledRecipe>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct InstallOptions {
+    /// Name to install the recipe as, instead of the one taken from the source
+    pub name: Option<String>,
+    /// Git tag, branch or commit to install
+    pub version: Option<String>,
+    /// Directory of the recipe within the source
+    pub path: Option<String>,
+}
+
+/// The result of updating one installed recipe
+#[derive(Debug)]
+pub struct RecipeUpdate {
+    pub previous: InstalledRecipe,
+    /// The newly installed recipe, or `None` when the source hasn't changed
+    pub updated: Option<InstalledRecipe>,
+}
+
+struct Source {
+    kind: SourceKind,
+    location: String,
+}
+
+impl Source {
+    /// Work out the kind of a source: `git+` forces git, archives are tarballs, other URLs
+    /// are git remotes and local directories are copied as they are
+    fn parse(source: &str) -> Result<Self> {
+        let is_archive = ARCHIVE_EXTENSIONS.iter().any(|ext| source.ends_with(ext));
+        let is_url = source.starts_with("http://") || source.starts_with("https://");
+
+        let (kind, location) = if let Some(remote) = source.strip_prefix("git+") {
+            (SourceKind::Git, local_or_remote(remote))
+        } else if is_url && is_archive {
+            (SourceKind::Tarball, source.to_string())
+        } else if is_url
+            || source.starts_with("ssh://")
+            || source.starts_with("file://")
+            || source.starts_with("git@")
+            || source.ends_with(".git")
+        {
+            (SourceKind::Git, local_or_remote(source))
+        } else {
+            let path = Path::new(source);
+            let kind = if path.is_dir() {
+                SourceKind::Directory
+            } else if path.is_file() && is_archive {
+                SourceKind::Tarball
+            } else {
+                return Err(anyhow!(
+                    "Recipe source {} is not a directory, tarball or git remote",
+                    source
+                ));
+            };
+            (kind, absolute(path)?)
+        };
+        Ok(Self { kind, location })
+    }
+
+    /// The recipe name a source suggests, from the last part of its path
+    fn default_name(&self, path: Option<&str>) -> String {
+        let location = path.unwrap_or(&self.location).trim_end_matches(['/', '\\']);
+        let last = location.rsplit(['/', '\\', ':']).next().unwrap_or(location);
+        let mut name = last.to_string();
+        for suffix in ARCHIVE_EXTENSIONS.iter().chain(&[".git"]) {
+            if let Some(stripped) = name.strip_suffix(suffix) {
+                name = stripped.to_string();
+            }
+        }
+        name
+    }
+}
+
+/// A fetched source, ready to be moved into the registry
+struct FetchedRecipe {
+    dir: PathBuf,
+    version: String,
+    revision: String,
+}
+
+/// Removes a staging directory however the install ends
+struct Staging(PathBuf);
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Recipes installed from git remotes, directories and tarballs, each version in a directory
+/// of its own and all of them pinned in a lockfile
+pub struct RecipeRegistry {
+    root: PathBuf,
+}
+
+impl RecipeRegistry {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The registry in `GOOSE_RECIPE_REGISTRY_DIR`, or in goose's data directory
+    pub fn from_config() -> Result<Self> {
+        if let Ok(dir) = Config::global().get_param::<String>(GOOSE_RECIPE_REGISTRY_DIR_CONFIG_KEY)
+        {
+            return Ok(Self::new(PathBuf::from(dir)));
+        }
+        let data_dir = choose_app_strategy(crate::APP_STRATEGY.clone())
+            .map_err(|e| anyhow!("Failed to find the data directory: {}", e))?
+            .data_dir();
+        Ok(Self::new(data_dir.join("recipes")))
+    }
+
+    /// Installed recipes, sorted by name and then by when they were installed
+    pub fn list(&self) -> Result<Vec<InstalledRecipe>> {
+        let mut recipes = self.read_lockfile()?.recipes;
+        recipes.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then(a.installed_at.cmp(&b.installed_at))
+        });
+        Ok(recipes)
+    }
+
+    pub async fn install(&self, source: &str, options: InstallOptions) -> Result<InstalledRecipe> {
+        let source = Source::parse(source)?;
+        if options.version.is_some() && source.kind != SourceKind::Git {
+            return Err(anyhow!(
+                "A version selects a git tag, branch or commit, and {} is not a git source",
+                source.location
+            ));
+        }
+        if let Some(path) = &options.path {
+            validate_path(path)?;
+        }
+        let name = options
+            .name
+            .unwrap_or_else(|| source.default_name(options.path.as_deref()));
+        validate_name(&name)?;
+
+        let staging = self.staging()?;
+        let fetched = fetch(
+            &source,
+            options.version.as_deref(),
+            options.path.as_deref(),
+            &staging.0,
+        )
+        .await?;
+
+        let mut lockfile = self.read_lockfile()?;
+        let installed = self.commit(
+            &mut lockfile,
+            InstalledRecipe {
+                name,
+                version: fetched.version.clone(),
+                kind: source.kind,
+                source: source.location,
+                reference: options.version,
+                path: options.path,
+                revision: fetched.revision.clone(),
+                installed_at: Utc::now(),
+            },
+            &fetched.dir,
+        )?;
+        self.write_lockfile(&lockfile)?;
+        Ok(installed)
+    }
+
+    /// Fetch the sources of installed recipes again, all of them or those with the name, and
+    /// install them where they changed; git recipes follow the reference they were installed at.
+    /// Only the latest install of a recipe from each reference is updated, older ones stay
+    /// pinned to the version they were installed at.
+    pub async fn update(&self, name: Option<&str>) -> Result<Vec<RecipeUpdate>> {
+        let mut lockfile = self.read_lockfile()?;
+        let mut installed: Vec<InstalledRecipe> = lockfile
+            .recipes
+            .iter()
+            .filter(|recipe| name.is_none_or(|name| recipe.name == name))
+            .cloned()
+            .collect();
+        installed.sort_by_key(|recipe| recipe.installed_at);
+        let mut targets: Vec<InstalledRecipe> = Vec::new();
+        for recipe in installed {
+            targets.retain(|target| {
+                !(target.name == recipe.name && target.reference == recipe.reference)
+            });
+            targets.push(recipe);
+        }
+        if let Some(name) = name.filter(|_| targets.is_empty()) {
+            return Err(anyhow!("Recipe {} is not installed", name));
+        }
+
+        let mut updates = Vec::new();
+        for previous in targets {
+            let source = Source {
+                kind: previous.kind,
+                location: previous.source.clone(),
+            };
+            let staging = self.staging()?;
+            let fetched = fetch(
+                &source,
+                previous.reference.as_deref(),
+                previous.path.as_deref(),
+                &staging.0,
+            )
+            .await?;
+            if fetched.revision == previous.revision {
+                updates.push(RecipeUpdate {
+                    previous,
+                    updated: None,
+                });
+                continue;
+            }
+
+            // The previous version is only removed once the new one is in place
+            let updated = self.commit(
+                &mut lockfile,
+                InstalledRecipe {
+                    version: fetched.version.clone(),
+                    revision: fetched.revision.clone(),
+                    installed_at: Utc::now(),
+                    ..previous.clone()
+                },
+                &fetched.dir,
+            )?;
+            let removed = if updated.version != previous.version {
+                self.uninstall(&mut lockfile, &previous)
+            } else {
+                Ok(())
+            };
+            // Pin each update as it's made, so a later failure doesn't lose the ones before it
+            self.write_lockfile(&lockfile)?;
+            removed?;
+            updates.push(RecipeUpdate {
+                previous,
+                updated: Some(updated),
+            });
+        }
+        Ok(updates)
+    }
+
+    /// Remove every installed version of a recipe, or just one with `name@version`
+    pub fn remove(&self, spec: &str) -> Result<Vec<InstalledRecipe>> {
+        let (name, version) = parse_spec(spec);
+        let mut lockfile = self.read_lockfile()?;
+        let removed: Vec<InstalledRecipe> = lockfile
+            .recipes
+            .iter()
+            .filter(|recipe| {
+                recipe.name == name && version.is_none_or(|version| recipe.version == version)
+            })
+            .cloned()
+            .collect();
+        if removed.is_empty() {
+            return Err(anyhow!("Recipe {} is not installed", spec));
+        }
+
+        for recipe in &removed {
+            self.uninstall(&mut lockfile, recipe)?;
+        }
+        let name_dir = self.root.join(name);
+        if name_dir.exists() && fs::read_dir(&name_dir)?.next().is_none() {
+            fs::remove_dir(&name_dir)?;
+        }
+        self.write_lockfile(&lockfile)?;
+        Ok(removed)
+    }
+
+    /// The recipe file of an installed recipe, `name` for its latest installed version or
+    /// `name@version` for a particular one
+    pub fn resolve(&self, spec: &str) -> Result<PathBuf> {
+        let (name, version) = parse_spec(spec);
+        let installed: Vec<InstalledRecipe> = self
+            .list()?
+            .into_iter()
+            .filter(|recipe| recipe.name == name)
+            .collect();
+        if installed.is_empty() {
+            return Err(anyhow!("Recipe {} is not installed", name));
+        }
+
+        let recipe = match version {
+            Some(version) => installed
+                .iter()
+                .find(|recipe| recipe.version == version)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Recipe {} has no installed version {}, installed versions: {}",
+                        name,
+                        version,
+                        installed
+                            .iter()
+                            .map(|recipe| recipe.version.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?,
+            None => installed.last().expect("installed is not empty"),
+        };
+        find_recipe_file(&self.recipe_dir(recipe))
+    }
+
+    fn recipe_dir(&self, recipe: &InstalledRecipe) -> PathBuf {
+        self.root.join(&recipe.name).join(&recipe.version)
+    }
+
+    /// Move a fetched recipe into place and pin it, replacing the same version if it's there.
+    /// Nothing installed changes unless the new files are in place.
+    fn commit(
+        &self,
+        lockfile: &mut Lockfile,
+        recipe: InstalledRecipe,
+        fetched_dir: &Path,
+    ) -> Result<InstalledRecipe> {
+        validate_version(&recipe.version)?;
+        let dir = self.recipe_dir(&recipe);
+        let parent = dir.parent().expect("recipe dirs have a parent");
+        fs::create_dir_all(parent)?;
+
+        // Versions never start with '.', so this can't be another version's directory
+        let replaced = parent.join(format!(".{}.replaced", recipe.version));
+        if replaced.exists() {
+            fs::remove_dir_all(&replaced)?;
+        }
+        if dir.exists() {
+            fs::rename(&dir, &replaced)?;
+        }
+        if let Err(e) = fs::rename(fetched_dir, &dir) {
+            if replaced.exists() {
+                fs::rename(&replaced, &dir)?;
+            }
+            return Err(anyhow!(
+                "Failed to install {}@{}: {}",
+                recipe.name,
+                recipe.version,
+                e
+            ));
+        }
+        if replaced.exists() {
+            fs::remove_dir_all(&replaced)?;
+        }
+
+        lockfile.recipes.retain(|installed| {
+            !(installed.name == recipe.name && installed.version == recipe.version)
+        });
+        lockfile.recipes.push(recipe.clone());
+        Ok(recipe)
+    }
+
+    fn uninstall(&self, lockfile: &mut Lockfile, recipe: &InstalledRecipe) -> Result<()> {
+        let dir = self.recipe_dir(recipe);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        lockfile.recipes.retain(|installed| {
+            !(installed.name == recipe.name && installed.version == recipe.version)
+        });
+        Ok(())
+    }
+
+    fn staging(&self) -> Result<Staging> {
+        let dir = self.root.join(STAGING_DIR_NAME).join(format!(
+            "{}-{}",
+            std::process::id(),
+            rand::random::<u32>()
+        ));
+        fs::create_dir_all(&dir)?;
+        Ok(Staging(dir))
+    }
+
+    fn read_lockfile(&self) -> Result<Lockfile> {
+        let path = self.root.join(LOCKFILE_NAME);
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid recipe lockfile {}: {}", path.display(), e))
+    }
+
+    fn write_lockfile(&self, lockfile: &Lockfile) -> Result<()> {
+        fs::create_dir_all(&self.root)?;
+        let path = self.root.join(LOCKFILE_NAME);
+        // Write it whole or not at all, a half-written lockfile would lose every pin
+        let tmp_path = self.root.join(format!("{}.tmp", LOCKFILE_NAME));
+        fs::write(&tmp_path, serde_yaml::to_string(lockfile)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
+
+/// Split `name@version` into its name and version
+fn parse_spec(spec: &str) -> (&str, Option<&str>) {
+    match spec.rsplit_once('@') {
+        Some((name, version)) if !name.is_empty() && !version.is_empty() => (name, Some(version)),
+        _ => (spec, None),
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Recipe name '{}' may only contain letters, numbers, '.', '_' and '-', \
+            use --name to choose another",
+            name
+        ))
+    }
+}
+
+fn validate_version(version: &str) -> Result<()> {
+    let valid = !version.is_empty()
+        && !version.starts_with('.')
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Version '{}' can't be used as a directory name",
+            version
+        ))
+    }
+}
+
+/// The directory of a recipe within its source has to stay inside the source
+fn validate_path(path: &str) -> Result<()> {
+    let inside = Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if inside {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Recipe path '{}' must be a relative path inside the source",
+            path
+        ))
+    }
+}
+
+fn absolute(path: &Path) -> Result<String> {
+    let path = path
+        .canonicalize()
+        .map_err(|e| anyhow!("Failed to resolve {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Local git repositories are kept as absolute paths, so updates work from anywhere
+fn local_or_remote(remote: &str) -> String {
+    let path = Path::new(remote);
+    if !remote.contains("://") && !remote.starts_with("git@") && path.exists() {
+        absolute(path).unwrap_or_else(|_| remote.to_string())
+    } else {
+        remote.to_string()
+    }
+}
+
+async fn fetch(
+    source: &Source,
+    reference: Option<&str>,
+    path: Option<&str>,
+    staging: &Path,
+) -> Result<FetchedRecipe> {
+    let checkout = staging.join("source");
+    let (version, revision) = match source.kind {
+        SourceKind::Git => fetch_git(&source.location, reference, &checkout)?,
+        SourceKind::Directory => {
+            copy_dir(Path::new(&source.location), &checkout)?;
+            let checksum = checksum_dir(&checkout)?;
+            (checksum[..CHECKSUM_VERSION_LEN].to_string(), checksum)
+        }
+        SourceKind::Tarball => {
+            let bytes = read_tarball(&source.location).await?;
+            let checksum = format!("{:x}", Sha256::digest(&bytes));
+            unpack_tarball(&source.location, &bytes, &checkout)?;
+            (checksum[..CHECKSUM_VERSION_LEN].to_string(), checksum)
+        }
+    };
+
+    let root = match source.kind {
+        // Archives usually hold a single directory named after the project
+        SourceKind::Tarball => single_subdir(&checkout)?.unwrap_or(checkout),
+        _ => checkout,
+    };
+    let dir = match path {
+        Some(path) => {
+            validate_path(path)?;
+            root.join(path)
+        }
+        None => root,
+    };
+    let recipe_file = find_recipe_file(&dir)?;
+    load_recipe(&recipe_file.to_string_lossy())
+        .map_err(|e| anyhow!("Invalid recipe {}: {}", recipe_file.display(), e))?;
+
+    // Move the recipe directory out of the checkout, which is left for the staging cleanup
+    let fetched = staging.join("recipe");
+    fs::rename(&dir, &fetched)?;
+    let _ = fs::remove_dir_all(fetched.join(".git"));
+    Ok(FetchedRecipe {
+        dir: fetched,
+        version,
+        revision,
+    })
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| anyhow!("Failed to run git, make sure it is installed: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Clone the remote and check out the reference, returning the version to install it as,
+/// which is the tag if there is one and otherwise the short commit, and the commit
+fn fetch_git(remote: &str, reference: Option<&str>, checkout: &Path) -> Result<(String, String)> {
+    let parent = checkout
+        .parent()
+        .expect("the checkout is in the staging dir");
+    if let Some(reference) = reference.filter(|reference| reference.starts_with('-')) {
+        return Err(anyhow!(
+            "Version '{}' is not a git tag, branch or commit",
+            reference
+        ));
+    }
+    let checkout_str = checkout.to_string_lossy();
+    git(
+        parent,
+        &["clone", "--quiet", "--", remote, checkout_str.as_ref()],
+    )?;
+    if let Some(reference) = reference {
+        git(
+            checkout,
+            &[
+                "-c",
+                "advice.detachedHead=false",
+                "checkout",
+                "--quiet",
+                reference,
+                "--",
+            ],
+        )?;
+    }
+
+    let commit = git(checkout, &["rev-parse", "HEAD"])?;
+    let is_tag = |name: &str| {
+        git(
+            checkout,
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("refs/tags/{}", name),
+            ],
+        )
+        .is_ok()
+    };
+    let tag = match reference {
+        Some(reference) if is_tag(reference) => Some(reference.to_string()),
+        _ => git(checkout, &["describe", "--tags", "--exact-match", "HEAD"]).ok(),
+    };
+    // Tags such as release/1.0 can't name a directory, so those versions are their commit
+    let version = match tag.filter(|tag| validate_version(tag).is_ok()) {
+        Some(tag) => tag,
+        None => git(checkout, &["rev-parse", "--short=12", "HEAD"])?,
+    };
+    Ok((version, commit))
+}
+
+async fn read_tarball(location: &str) -> Result<Vec<u8>> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        let response = reqwest::get(location)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| anyhow!("Failed to download {}: {}", location, e))?;
+        Ok(response.bytes().await?.to_vec())
+    } else {
+        fs::read(location).map_err(|e| anyhow!("Failed to read {}: {}", location, e))
+    }
+}
+
+fn unpack_tarball(location: &str, bytes: &[u8], dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest)?;
+    let result = if location.ends_with(".tar") {
+        Archive::new(bytes).unpack(dest)
+    } else {
+        Archive::new(GzDecoder::new(bytes)).unpack(dest)
+    };
+    result.map_err(|e| anyhow!("Failed to unpack {}: {}", location, e))
+}
+
+fn single_subdir(dir: &Path) -> Result<Option<PathBuf>> {
+    let entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<_>>()?;
+    match entries.as_slice() {
+        [only] if only.is_dir() => Ok(Some(only.clone())),
+        _ => Ok(None),
+    }
+}
+
+fn find_recipe_file(dir: &Path) -> Result<PathBuf> {
+    RECIPE_FILE_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("recipe.{}", ext)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            anyhow!(
+                "No recipe.yaml or recipe.json found in {}, \
+                use --path for a recipe in a subdirectory",
+                dir.display()
+            )
+        })
+}
+
+/// Copy a directory without its git metadata
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// The sha256 of a directory's files and their paths, in a stable order
+fn checksum_dir(dir: &Path) -> Result<String> {
+    fn add_files(hasher: &mut Sha256, root: &Path, dir: &Path) -> Result<()> {
+        let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<_>>()?;
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                add_files(hasher, root, &path)?;
+            } else {
+                let relative = path
+                    .strip_prefix(root)?
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                hasher.update(relative.as_bytes());
+                hasher.update([0]);
+                hasher.update(fs::read(&path)?);
+                hasher.update([0]);
+            }
+        }
+        Ok(())
+    }
+
+    let mut hasher = Sha256::new();
+    add_files(&mut hasher, dir, dir)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const RECIPE: &str = "title: Review\ndescription: Review code\nprompt: Review the code\n";
+
+    fn git_repo(dir: &Path) {
+        fs::create_dir_all(dir).unwrap();
+        git(dir, &["init", "--quiet"]).unwrap();
+        fs::write(dir.join("recipe.yaml"), RECIPE).unwrap();
+        commit(dir, "first");
+    }
+
+    fn commit(dir: &Path, message: &str) {
+        git(dir, &["add", "-A"]).unwrap();
+        git(
+            dir,
+            &[
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                message,
+            ],
+        )
+        .unwrap();
+    }
+
+    fn registry() -> (TempDir, RecipeRegistry) {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = RecipeRegistry::new(dir.path().join("registry"));
+        (dir, registry)
+    }
+
+    #[tokio::test]
+    async fn test_git_install_pins_tags_and_commits() {
+        let (dir, registry) = registry();
+        let repo = dir.path().join("review");
+        git_repo(&repo);
+        git(&repo, &["tag", "v1.0.0"]).unwrap();
+        fs::write(repo.join("recipe.yaml"), RECIPE.replace("code", "changes")).unwrap();
+        commit(&repo, "second");
+
+        let source = format!("git+{}", repo.display());
+        let tagged = registry
+            .install(
+                &source,
+                InstallOptions {
+                    version: Some("v1.0.0".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(tagged.name, "review");
+        assert_eq!(tagged.version, "v1.0.0");
+
+        let latest = registry
+            .install(&source, InstallOptions::default())
+            .await
+            .unwrap();
+        let head = git(&repo, &["rev-parse", "HEAD"]).unwrap();
+        assert_eq!(latest.revision, head);
+        assert_eq!(latest.version, head[..12]);
+
+        let pinned = fs::read_to_string(registry.resolve("review@v1.0.0").unwrap()).unwrap();
+        assert_eq!(pinned, RECIPE);
+        let newest = fs::read_to_string(registry.resolve("review").unwrap()).unwrap();
+        assert!(newest.contains("Review changes"));
+        assert!(registry.resolve("review@v2.0.0").is_err());
+        assert!(!registry.recipe_dir(&latest).join(".git").exists());
+
+        // The lockfile keeps the pins across registries opened on the same directory
+        let reopened = RecipeRegistry::new(registry.root.clone());
+        assert_eq!(reopened.list().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_update_follows_the_installed_reference() {
+        let (dir, registry) = registry();
+        let repo = dir.path().join("review");
+        git_repo(&repo);
+        git(&repo, &["tag", "v1.0.0"]).unwrap();
+
+        let source = format!("git+{}", repo.display());
+        registry
+            .install(
+                &source,
+                InstallOptions {
+                    version: Some("v1.0.0".to_string()),
+                    name: Some("pinned".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let before = registry
+            .install(&source, InstallOptions::default())
+            .await
+            .unwrap();
+
+        let updates = registry.update(None).await.unwrap();
+        assert!(updates.iter().all(|update| update.updated.is_none()));
+
+        fs::write(repo.join("notes.md"), "notes").unwrap();
+        commit(&repo, "second");
+        let updates = registry.update(None).await.unwrap();
+        let changed: Vec<_> = updates
+            .iter()
+            .filter_map(|update| update.updated.as_ref())
+            .collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].name, "review");
+        assert_ne!(changed[0].version, before.version);
+        assert!(!registry.recipe_dir(&before).exists());
+        assert_eq!(registry.list().unwrap().len(), 2);
+
+        assert!(registry.update(Some("missing")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_older_commits_pinned() {
+        let (dir, registry) = registry();
+        let repo = dir.path().join("review");
+        git_repo(&repo);
+        let source = format!("git+{}", repo.display());
+        let first = registry
+            .install(&source, InstallOptions::default())
+            .await
+            .unwrap();
+        fs::write(repo.join("notes.md"), "notes").unwrap();
+        commit(&repo, "second");
+        let second = registry
+            .install(&source, InstallOptions::default())
+            .await
+            .unwrap();
+
+        fs::write(repo.join("notes.md"), "more notes").unwrap();
+        commit(&repo, "third");
+        let updates = registry.update(Some("review")).await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].previous.version, second.version);
+        let head = git(&repo, &["rev-parse", "HEAD"]).unwrap();
+        assert_eq!(updates[0].updated.as_ref().unwrap().revision, head);
+
+        let versions: Vec<String> = registry
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|recipe| recipe.version)
+            .collect();
+        assert_eq!(
+            versions,
+            vec![first.version.clone(), head[..12].to_string()]
+        );
+        assert!(registry
+            .resolve(&format!("review@{}", first.version))
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_update_keeps_the_installed_version() {
+        let (dir, registry) = registry();
+        let repo = dir.path().join("review");
+        git_repo(&repo);
+        let source = format!("git+{}", repo.display());
+        let installed = registry
+            .install(&source, InstallOptions::default())
+            .await
+            .unwrap();
+
+        fs::write(repo.join("recipe.yaml"), "title: [broken").unwrap();
+        commit(&repo, "broken");
+        assert!(registry.update(None).await.is_err());
+        let reopened = RecipeRegistry::new(registry.root.clone());
+        assert_eq!(reopened.list().unwrap()[0].version, installed.version);
+        assert_eq!(
+            fs::read_to_string(reopened.resolve("review").unwrap()).unwrap(),
+            RECIPE
+        );
+
+        // A failed move puts the files it replaces back
+        let mut lockfile = registry.read_lockfile().unwrap();
+        let missing = dir.path().join("missing");
+        assert!(registry
+            .commit(&mut lockfile, installed.clone(), &missing)
+            .is_err());
+        assert_eq!(
+            fs::read_to_string(registry.resolve("review").unwrap()).unwrap(),
+            RECIPE
+        );
+
+        // A tag that can't name a directory installs as its commit
+        fs::write(repo.join("recipe.yaml"), RECIPE).unwrap();
+        fs::write(repo.join("notes.md"), "notes").unwrap();
+        commit(&repo, "fixed");
+        git(&repo, &["tag", "release/2.0"]).unwrap();
+        let updates = registry.update(None).await.unwrap();
+        let head = git(&repo, &["rev-parse", "HEAD"]).unwrap();
+        let updated = updates[0].updated.as_ref().unwrap();
+        assert_eq!(updated.version, head[..12]);
+        assert!(!registry.recipe_dir(&installed).exists());
+        assert_eq!(registry.list().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_pinned_when_a_later_one_fails() {
+        let (dir, registry) = registry();
+        for name in ["first", "second"] {
+            let repo = dir.path().join(name);
+            git_repo(&repo);
+            registry
+                .install(
+                    &format!("git+{}", repo.display()),
+                    InstallOptions::default(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let first = dir.path().join("first");
+        fs::write(first.join("notes.md"), "notes").unwrap();
+        commit(&first, "second");
+        fs::remove_dir_all(dir.path().join("second")).unwrap();
+        assert!(registry.update(None).await.is_err());
+
+        let head = git(&first, &["rev-parse", "HEAD"]).unwrap();
+        let reopened = RecipeRegistry::new(registry.root.clone());
+        let installed = reopened.list().unwrap();
+        assert_eq!(installed.len(), 2);
+        assert_eq!(installed[0].revision, head);
+    }
+
+    #[tokio::test]
+    async fn test_unsafe_and_invalid_sources_are_rejected() {
+        let (dir, registry) = registry();
+        let repo = dir.path().join("review");
+        git_repo(&repo);
+        let source = format!("git+{}", repo.display());
+
+        for path in ["../escape", "/etc"] {
+            let options = InstallOptions {
+                path: Some(path.to_string()),
+                ..Default::default()
+            };
+            assert!(registry.install(&source, options).await.is_err());
+        }
+        let options = InstallOptions {
+            version: Some("--upload-pack=touch pwned".to_string()),
+            ..Default::default()
+        };
+        assert!(registry.install(&source, options).await.is_err());
+
+        fs::write(
+            repo.join("recipe.yaml"),
+            format!("{}instructions: Review {{{{ target }}}}\n", RECIPE),
+        )
+        .unwrap();
+        commit(&repo, "undeclared parameter");
+        let err = registry
+            .install(&source, InstallOptions::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid recipe"));
+        assert!(registry.list().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_directory_and_tarball_sources() {
+        let (dir, registry) = registry();
+        let source_dir = dir.path().join("recipes").join("triage");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::write(source_dir.join("recipe.yaml"), RECIPE).unwrap();
+
+        let installed = registry
+            .install(
+                dir.path().join("recipes").to_str().unwrap(),
+                InstallOptions {
+                    path: Some("triage".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(installed.name, "triage");
+        assert_eq!(installed.kind, SourceKind::Directory);
+        assert_eq!(installed.version.len(), CHECKSUM_VERSION_LEN);
+        assert!(registry
+            .install(
+                source_dir.to_str().unwrap(),
+                InstallOptions {
+                    version: Some("v1".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .is_err());
+
+        let tarball = dir.path().join("lint-1.0.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&tarball).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        builder.append_dir_all("lint-1.0", &source_dir).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let installed = registry
+            .install(tarball.to_str().unwrap(), InstallOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(installed.name, "lint-1.0");
+        assert_eq!(installed.kind, SourceKind::Tarball);
+        assert_eq!(
+            fs::read_to_string(registry.resolve("lint-1.0").unwrap()).unwrap(),
+            RECIPE
+        );
+
+        let removed = registry.remove("triage").unwrap();
+        assert_eq!(removed.len(), 1);
+        assert!(!registry.root.join("triage").exists());
+        assert!(registry.resolve("triage").is_err());
+        assert!(registry.remove("triage").is_err());
+    }
+
+    #[test]
+    fn test_source_names_and_specs() {
+        let source = Source {
+            kind: SourceKind::Git,
+            location: "https://github.com/block/goose-recipes.git".to_string(),
+        };
+        assert_eq!(source.default_name(None), "goose-recipes");
+        assert_eq!(source.default_name(Some("recipes/review/")), "review");
+
+        assert_eq!(parse_spec("review@v1.0.0"), ("review", Some("v1.0.0")));
+        assert_eq!(parse_spec("review"), ("review", None));
+        assert!(validate_name("../escape").is_err());
+        assert!(validate_version("release/1.0").is_err());
+        assert!(validate_path("recipes/review").is_ok());
+        assert!(validate_path("recipes/../../escape").is_err());
+    }
+}
@@ -6,6 +6,7 @@ use std::{env, fs};
 use crate::recipes::recipe::RECIPE_FILE_EXTENSIONS;
 
 use super::github_recipe::{retrieve_recipe_from_github, GOOSE_RECIPE_GITHUB_REPO_CONFIG_KEY};
+use super::registry::RecipeRegistry;
 
 const GOOSE_RECIPE_PATH_ENV_VAR: &str = "GOOSE_RECIPE_PATH";
 
@@ -18,13 +19,24 @@ pub fn retrieve_recipe_file(recipe_name: &str) -> Result<(String, PathBuf)> {
         let path = PathBuf::from(recipe_name);
         return read_recipe_file(path);
     }
-    retrieve_recipe_from_local_path(recipe_name).or_else(|e| {
-        if let Some(recipe_repo_full_name) = configured_github_recipe_repo() {
-            retrieve_recipe_from_github(recipe_name, &recipe_repo_full_name)
-        } else {
-            Err(e)
-        }
-    })
+    // name@version always refers to a recipe installed in the registry
+    if recipe_name.contains('@') {
+        return retrieve_recipe_from_registry(recipe_name);
+    }
+    retrieve_recipe_from_local_path(recipe_name)
+        .or_else(|e| retrieve_recipe_from_registry(recipe_name).map_err(|_| e))
+        .or_else(|e| {
+            if let Some(recipe_repo_full_name) = configured_github_recipe_repo() {
+                retrieve_recipe_from_github(recipe_name, &recipe_repo_full_name)
+            } else {
+                Err(e)
+            }
+        })
+}
+
+fn retrieve_recipe_from_registry(recipe_spec: &str) -> Result<(String, PathBuf)> {
+    let recipe_path = RecipeRegistry::from_config()?.resolve(recipe_spec)?;
+    read_recipe_file(recipe_path)
 }
 
 fn read_recipe_in_dir(dir: &Path, recipe_name: &str) -> Result<(String, PathBuf)> {
@@ -1,8 +1,9 @@
+use chrono::Utc;
 use console::style;
 use goose::agents::extension::ExtensionError;
 use goose::agents::Agent;
 use goose::config::{Config, ExtensionConfig, ExtensionConfigManager};
-use goose::providers::create;
+use goose::recipe::{Response, Settings, SubRecipe};
 use goose::session;
 use goose::session::Identifier;
 use mcp_client::transport::Error as McpClientError;
@@ -28,6 +29,8 @@ pub struct SessionBuilderConfig {
     pub extensions: Vec<String>,
     /// List of remote extension commands to add
     pub remote_extensions: Vec<String>,
+    /// List of streamable HTTP extension URLs to add
+    pub streamable_http_extensions: Vec<String>,
     /// List of builtin extension commands to add
     pub builtins: Vec<String>,
     /// List of extensions to enable, enable only this set and ignore configured ones
@@ -38,24 +41,65 @@ pub struct SessionBuilderConfig {
     pub debug: bool,
     /// Maximum number of consecutive identical tool calls allowed
     pub max_tool_repetitions: Option<u32>,
+    /// Maximum number of agent turns per reply before the run stops
+    pub max_turns: Option<u32>,
+    /// Maximum number of accumulated session tokens before the run stops
+    pub max_tokens: Option<i32>,
+    /// Maximum wall-clock time from the start of the session before the run stops
+    pub max_duration: Option<std::time::Duration>,
+    /// JSON Schema the run must finish with structured output for
+    pub final_output_response: Option<Response>,
+    /// Recipes the agent can run as tools, with their paths resolved
+    pub sub_recipes: Option<Vec<SubRecipe>>,
+    /// Don't display session info or the conversation, only the final output
+    pub quiet: bool,
+    /// The provider, model and mode a recipe was designed for
+    pub settings: Option<Settings>,
+    /// Provider to use instead of the recipe's or the configured one
+    pub provider: Option<String>,
+    /// Model to use instead of the recipe's or the configured one
+    pub model: Option<String>,
+}
+
+/// The recipe's settings with the provider and model given on the command line on top
+fn resolve_settings(session_config: &SessionBuilderConfig) -> Settings {
+    let mut settings = session_config.settings.clone().unwrap_or_default();
+    if let Some(provider) = &session_config.provider {
+        // The recipe's model belongs to the recipe's provider
+        if settings.goose_provider.as_ref() != Some(provider) {
+            settings.goose_model = None;
+        }
+        settings.goose_provider = Some(provider.clone());
+    }
+    if let Some(model) = &session_config.model {
+        settings.goose_model = Some(model.clone());
+    }
+    settings
 }
 
 pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
     // Load config and get provider/model
     let config = Config::global();
 
-    let provider_name: String = config
-        .get_param("GOOSE_PROVIDER")
-        .expect("No provider configured. Run 'goose configure' first");
-
-    let model: String = config
-        .get_param("GOOSE_MODEL")
-        .expect("No model configured. Run 'goose configure' first");
-    let model_config = goose::model::ModelConfig::new(model.clone());
+    let settings = resolve_settings(&session_config);
+    let (provider_name, model, goose_mode) = match (
+        settings.provider_name(),
+        settings.model_name(),
+        settings.goose_mode(),
+    ) {
+        (Ok(provider_name), Ok(model), Ok(goose_mode)) => (provider_name, model, goose_mode),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            output::render_error(&e.to_string());
+            process::exit(1);
+        }
+    };
 
     // Create the agent
     let agent: Agent = Agent::new();
-    let new_provider = create(&provider_name, model_config).unwrap();
+    let new_provider = settings.create_provider().unwrap_or_else(|e| {
+        output::render_error(&format!("Failed to create provider: {}", e));
+        process::exit(1);
+    });
 
     // Keep a reference to the provider for display_session_info
     let provider_for_display = Arc::clone(&new_provider);
@@ -79,6 +123,9 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
             output::render_error(&format!("Failed to initialize agent: {}", e));
             process::exit(1);
         });
+    if goose_mode.is_some() {
+        agent.set_goose_mode(goose_mode).await;
+    }
 
     // Configure tool monitoring if max_tool_repetitions is set
     if let Some(max_repetitions) = session_config.max_tool_repetitions {
@@ -87,19 +134,11 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
 
     // Handle session file resolution and resuming
     let session_file = if session_config.no_session {
-        // Use a temporary path that won't be written to
-        #[cfg(unix)]
-        {
-            std::path::PathBuf::from("/dev/null")
-        }
-        #[cfg(windows)]
-        {
-            std::path::PathBuf::from("NUL")
-        }
+        session::unsaved_session_file()
     } else if session_config.resume {
         if let Some(identifier) = session_config.identifier {
             let session_file = session::get_path(identifier);
-            if !session_file.exists() {
+            if !session::session_exists(&session_file) {
                 output::render_error(&format!(
                     "Cannot resume session {} - no such session exists",
                     style(session_file.display()).cyan()
@@ -191,6 +230,31 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
 
     // Create new session
     let mut session = Session::new(agent, session_file.clone(), session_config.debug);
+    let deadline = session_config.max_duration.and_then(|max_duration| {
+        chrono::Duration::from_std(max_duration)
+            .ok()
+            .and_then(|max_duration| Utc::now().checked_add_signed(max_duration))
+    });
+    session.set_run_limits(
+        session_config.max_turns,
+        session_config.max_tokens,
+        deadline,
+    );
+    session.set_quiet(session_config.quiet);
+
+    if let Some(response) = session_config.final_output_response {
+        if let Err(e) = session.agent.add_final_output_tool(response).await {
+            eprintln!("Failed to set up the output schema: {}", e);
+            process::exit(1);
+        }
+    }
+
+    if let Some(sub_recipes) = session_config.sub_recipes {
+        if let Err(e) = session.agent.add_sub_recipes(&sub_recipes).await {
+            eprintln!("Failed to load sub-recipes: {}", e);
+            process::exit(1);
+        }
+    }
 
     // Add extensions if provided
     for extension_str in session_config.extensions {
@@ -208,6 +272,14 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         }
     }
 
+    // Add streamable HTTP extensions if provided
+    for extension_url in session_config.streamable_http_extensions {
+        if let Err(e) = session.add_streamable_http_extension(extension_url).await {
+            eprintln!("Failed to start extension: {}", e);
+            process::exit(1);
+        }
+    }
+
     // Add builtin extensions
     for builtin in session_config.builtins {
         if let Err(e) = session.add_builtin(builtin).await {
@@ -234,12 +306,14 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         session.agent.override_system_prompt(override_prompt).await;
     }
 
-    output::display_session_info(
-        session_config.resume,
-        &provider_name,
-        &model,
-        &session_file,
-        Some(&provider_for_display),
-    );
+    if !session_config.quiet {
+        output::display_session_info(
+            session_config.resume,
+            &provider_name,
+            &model,
+            &session_file,
+            Some(&provider_for_display),
+        );
+    }
     session
 }
@@ -9,6 +9,7 @@ mod thinking;
 pub use self::export::message_to_markdown;
 pub use builder::{build_session, SessionBuilderConfig};
 use console::Color;
+use goose::agents::sampling::is_sampling_request;
 use goose::agents::AgentEvent;
 use goose::permission::permission_confirmation::PrincipalType;
 use goose::permission::Permission;
@@ -17,11 +18,13 @@ use goose::providers::base::Provider;
 pub use goose::session::Identifier;
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use completion::GooseCompleter;
 use etcetera::{choose_app_strategy, AppStrategy};
 use goose::agents::extension::{Envs, ExtensionConfig};
 use goose::agents::{Agent, SessionConfig};
 use goose::config::Config;
+use goose::context_mgmt::CompactionPolicy;
 use goose::message::{Message, MessageContent};
 use goose::session;
 use input::InputResult;
@@ -51,6 +54,11 @@ pub struct Session {
     completion_cache: Arc<std::sync::RwLock<CompletionCache>>,
     debug: bool, // New field for debug mode
     run_mode: RunMode,
+    max_turns: Option<u32>,
+    max_tokens: Option<i32>,
+    deadline: Option<DateTime<Utc>>,
+    /// Don't render the conversation, so stdout only carries the run's structured output
+    quiet: bool,
 }
 
 // Cache structure for completion data
@@ -123,6 +131,45 @@ impl Session {
             completion_cache: Arc::new(std::sync::RwLock::new(CompletionCache::new())),
             debug,
             run_mode: RunMode::Normal,
+            max_turns: None,
+            max_tokens: None,
+            deadline: None,
+            quiet: false,
+        }
+    }
+
+    /// Limit the number of agent turns per reply, the accumulated tokens for the session and
+    /// the time after which the agent stops taking new turns
+    pub fn set_run_limits(
+        &mut self,
+        max_turns: Option<u32>,
+        max_tokens: Option<i32>,
+        deadline: Option<DateTime<Utc>>,
+    ) {
+        self.max_turns = max_turns;
+        self.max_tokens = max_tokens;
+        self.deadline = deadline;
+    }
+
+    /// Stop rendering the conversation as it streams
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+
+    /// The structured output the agent finished the run with, if it was asked for one
+    pub async fn final_output(&self) -> Option<Value> {
+        self.agent.final_output().await
+    }
+
+    fn session_config(&self) -> SessionConfig {
+        SessionConfig {
+            id: session::Identifier::Path(self.session_file.clone()),
+            working_dir: std::env::current_dir()
+                .expect("failed to get current session working directory"),
+            schedule_id: None,
+            max_turns: self.max_turns,
+            max_tokens: self.max_tokens,
+            deadline: self.deadline,
         }
     }
 
@@ -228,6 +275,40 @@ impl Session {
         Ok(())
     }
 
+    /// Add a streamable HTTP extension to the session
+    ///
+    /// # Arguments
+    /// * `extension_url` - URL of the server's MCP endpoint
+    pub async fn add_streamable_http_extension(&mut self, extension_url: String) -> Result<()> {
+        let name: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(8)
+            .map(char::from)
+            .collect();
+
+        let config = ExtensionConfig::StreamableHttp {
+            name,
+            uri: extension_url,
+            envs: Envs::new(HashMap::new()),
+            env_keys: Vec::new(),
+            headers: HashMap::new(),
+            description: Some(goose::config::DEFAULT_EXTENSION_DESCRIPTION.to_string()),
+            // TODO: should set timeout
+            timeout: Some(goose::config::DEFAULT_EXTENSION_TIMEOUT),
+            bundled: None,
+        };
+
+        self.agent
+            .add_extension(config)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to start extension: {}", e))?;
+
+        // Invalidate the completion cache when a new extension is added
+        self.invalidate_completion_cache().await;
+
+        Ok(())
+    }
+
     /// Add a builtin extension to the session
     ///
     /// # Arguments
@@ -498,6 +579,8 @@ impl Session {
                     config
                         .set_param("GOOSE_MODE", Value::String(mode.to_string()))
                         .unwrap();
+                    // The chosen mode also replaces one the recipe set
+                    self.agent.set_goose_mode(None).await;
                     output::goose_mode_message(&format!("Goose mode set to '{}'", mode));
                     continue;
                 }
@@ -659,15 +742,9 @@ impl Session {
                 if should_act {
                     output::render_act_on_plan();
                     self.run_mode = RunMode::Normal;
-                    // set goose mode: auto if that isn't already the case
-                    let config = Config::global();
-                    let curr_goose_mode =
-                        config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
-                    if curr_goose_mode != "auto" {
-                        config
-                            .set_param("GOOSE_MODE", Value::String("auto".to_string()))
-                            .unwrap();
-                    }
+                    // act in auto mode, over GOOSE_MODE or a mode the recipe set
+                    let goose_mode_override = self.agent.goose_mode_override();
+                    self.agent.set_goose_mode(Some("auto".to_string())).await;
 
                     // clear the messages before acting on the plan
                     self.messages.clear();
@@ -679,12 +756,8 @@ impl Session {
                     self.process_agent_response(true).await?;
                     output::hide_thinking();
 
-                    // Reset run & goose mode
-                    if curr_goose_mode != "auto" {
-                        config
-                            .set_param("GOOSE_MODE", Value::String(curr_goose_mode.to_string()))
-                            .unwrap();
-                    }
+                    // Reset goose mode
+                    self.agent.set_goose_mode(goose_mode_override).await;
                 } else {
                     // add the plan response (assistant message) & carry the conversation forward
                     // in the next round, the user might wanna slightly modify the plan
@@ -707,18 +780,9 @@ impl Session {
     }
 
     async fn process_agent_response(&mut self, interactive: bool) -> Result<()> {
-        let session_id = session::Identifier::Path(self.session_file.clone());
         let mut stream = self
             .agent
-            .reply(
-                &self.messages,
-                Some(SessionConfig {
-                    id: session_id.clone(),
-                    working_dir: std::env::current_dir()
-                        .expect("failed to get current session working directory"),
-                    schedule_id: None,
-                }),
-            )
+            .reply(&self.messages, Some(self.session_config()))
             .await?;
 
         let mut progress_bars = output::McpSpinners::new();
@@ -733,8 +797,12 @@ impl Session {
                             if let Some(MessageContent::ToolConfirmationRequest(confirmation)) = message.content.first() {
                                 output::hide_thinking();
 
-                                // Format the confirmation prompt
-                                let prompt = "Goose would like to call the above tool, do you allow?".to_string();
+                                // Format the confirmation prompt, sampling requests carry their own
+                                let is_sampling = is_sampling_request(&confirmation.id);
+                                let prompt = match &confirmation.prompt {
+                                    Some(prompt) if is_sampling => prompt.clone(),
+                                    _ => "Goose would like to call the above tool, do you allow?".to_string(),
+                                };
 
                                 // Get confirmation from user
                                 let permission_result = cliclack::select(prompt)
@@ -756,7 +824,13 @@ impl Session {
                                     }
                                 };
 
-                                if permission == Permission::Cancel {
+                                if permission == Permission::Cancel && is_sampling {
+                                    // There is no tool call to cancel, decline the extension's request
+                                    self.agent.handle_confirmation(confirmation.id.clone(), PermissionConfirmation {
+                                        principal_type: PrincipalType::Extension,
+                                        permission: Permission::DenyOnce,
+                                    },).await;
+                                } else if permission == Permission::Cancel {
                                     output::render_text("Tool call cancelled. Returning to chat...", Some(Color::Yellow), true);
 
                                     let mut response_message = Message::user();
@@ -845,15 +919,7 @@ impl Session {
                                 // Restart the stream after handling ContextLengthExceeded
                                 stream = self
                                     .agent
-                                    .reply(
-                                        &self.messages,
-                                        Some(SessionConfig {
-                                            id: session_id.clone(),
-                                            working_dir: std::env::current_dir()
-                                                .expect("failed to get current session working directory"),
-                                            schedule_id: None,
-                                        }),
-                                    )
+                                    .reply(&self.messages, Some(self.session_config()))
                                     .await?;
                             }
                             // otherwise we have a model/tool to render
@@ -865,10 +931,45 @@ impl Session {
 
                                 if interactive {output::hide_thinking()};
                                 let _ = progress_bars.hide();
-                                output::render_message(&message, self.debug);
+                                if !self.quiet {
+                                    output::render_streamed_message(&message, self.debug);
+                                }
                                 if interactive {output::show_thinking()};
                             }
                         }
+                        Some(Ok(AgentEvent::ContextCompacted { policy, messages })) => {
+                            if interactive {output::hide_thinking()};
+                            let _ = progress_bars.hide();
+                            self.messages = messages;
+                            session::persist_messages(&self.session_file, &self.messages, None).await?;
+                            let msg = match policy {
+                                CompactionPolicy::Summarize => "Context limit reached - Goose automatically summarized earlier messages and is continuing.",
+                                _ => "Context limit reached - Goose automatically truncated earlier messages and is continuing.",
+                            };
+                            if !self.quiet {
+                                output::render_text(msg, Some(Color::Yellow), true);
+                            }
+                            if interactive {output::show_thinking()};
+                        }
+                        Some(Ok(AgentEvent::ExtensionRestarted { name, reason })) => {
+                            if interactive {output::hide_thinking()};
+                            let _ = progress_bars.hide();
+                            if !self.quiet {
+                                output::render_text(
+                                    &format!("Extension {} was restarted because {}.", name, reason),
+                                    Some(Color::Yellow),
+                                    true,
+                                );
+                            }
+                            if interactive {output::show_thinking()};
+                        }
+                        Some(Ok(AgentEvent::MessageDelta(delta))) => {
+                            if interactive {output::hide_thinking()};
+                            let _ = progress_bars.hide();
+                            if !self.quiet {
+                                output::render_message_delta(&delta);
+                            }
+                        }
                         Some(Ok(AgentEvent::McpNotification((_id, message)))) => {
                                 if let JsonRpcMessage::Notification(JsonRpcNotification{
                                     method,
@@ -1109,7 +1210,7 @@ impl Session {
 
     /// Get the session metadata
     pub fn get_metadata(&self) -> Result<session::SessionMetadata> {
-        if !self.session_file.exists() {
+        if !session::session_exists(&self.session_file) {
             return Err(anyhow::anyhow!("Session file does not exist"));
         }
 
@@ -2,13 +2,14 @@ use bat::WrappingMode;
 use console::{style, Color};
 use goose::config::Config;
 use goose::message::{Message, MessageContent, ToolRequest, ToolResponse};
+use goose::providers::base::MessageDelta;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use mcp_core::prompt::PromptArgument;
 use mcp_core::tool::ToolCall;
 use serde_json::Value;
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::io::Error;
+use std::io::{Error, Write};
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
@@ -147,6 +148,67 @@ pub fn render_message(message: &Message, debug: bool) {
     println!();
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum StreamedContent {
+    Text,
+    Thinking,
+}
+
+// Tracks what has been printed from the response currently being streamed
+thread_local! {
+    static STREAMED: RefCell<Option<StreamedContent>> = const { RefCell::new(None) };
+}
+
+/// Print a fragment of a streamed response as soon as it arrives
+pub fn render_message_delta(delta: &MessageDelta) {
+    match delta {
+        MessageDelta::Text { text } => {
+            STREAMED.with(|s| {
+                if s.borrow().as_ref() == Some(&StreamedContent::Thinking) {
+                    println!("\n");
+                }
+                *s.borrow_mut() = Some(StreamedContent::Text);
+            });
+            print!("{}", text);
+        }
+        MessageDelta::Thinking { thinking } => {
+            if std::env::var("GOOSE_CLI_SHOW_THINKING").is_err() {
+                return;
+            }
+            STREAMED.with(|s| {
+                if s.borrow().as_ref() != Some(&StreamedContent::Thinking) {
+                    println!("\n{}", style("Thinking:").dim().italic());
+                }
+                *s.borrow_mut() = Some(StreamedContent::Thinking);
+            });
+            print!("{}", style(thinking).dim());
+        }
+        // Tool calls are rendered once their arguments are complete
+        MessageDelta::ToolCall { .. } => return,
+    }
+    let _ = std::io::stdout().flush();
+}
+
+/// Render a message, skipping the text and thinking already printed by `render_message_delta`
+pub fn render_streamed_message(message: &Message, debug: bool) {
+    if STREAMED.with(|s| s.borrow_mut().take()).is_none() {
+        render_message(message, debug);
+        return;
+    }
+
+    println!();
+    let remaining = Message {
+        content: message
+            .content
+            .iter()
+            .filter(|c| !matches!(c, MessageContent::Text(_) | MessageContent::Thinking(_)))
+            .cloned()
+            .collect(),
+        ..message.clone()
+    };
+    render_message(&remaining, debug);
+}
+
 pub fn render_text(text: &str, color: Option<Color>, dim: bool) {
     render_text_no_newlines(format!("\n{}\n\n", text).as_str(), color, dim);
 }
@@ -546,7 +608,7 @@ pub fn display_session_info(
 ) {
     let start_session_msg = if resume {
         "resuming session |"
-    } else if session_file.to_str() == Some("/dev/null") || session_file.to_str() == Some("NUL") {
+    } else if goose::session::is_unsaved_session(session_file) {
         "running without session |"
     } else {
         "starting session |"
@@ -588,7 +650,7 @@ pub fn display_session_info(
         );
     }
 
-    if session_file.to_str() != Some("/dev/null") && session_file.to_str() != Some("NUL") {
+    if !goose::session::is_unsaved_session(session_file) {
         println!(
             "    {} {}",
             style("logging to").dim(),
@@ -680,7 +742,7 @@ impl McpSpinners {
             if let Some(total) = total {
                 self.multi_bar.add(
                     ProgressBar::new((total * 100.0) as u64).with_style(
-                        ProgressStyle::with_template("[{elapsed}] {bar:40} {pos:>3}/{len:3} {msg}")
+                        ProgressStyle::with_template("[{elapsed}] {bar:40} {percent:>3}% {msg}")
                             .unwrap(),
                     ),
                 )
@@ -266,6 +266,15 @@ pub unsafe extern "C" fn goose_agent_send_message(
                 Ok(AgentEvent::McpNotification(_)) => {
                     // TODO: Handle MCP notifications.
                 }
+                Ok(AgentEvent::MessageDelta(_)) => {
+                    // Only complete messages are returned over FFI
+                }
+                Ok(AgentEvent::ContextCompacted { .. }) => {
+                    // The FFI caller does not keep history between replies
+                }
+                Ok(AgentEvent::ExtensionRestarted { .. }) => {
+                    // Restarts are logged by the extension manager
+                }
                 Err(e) => {
                     full_response.push_str(&format!("\nError in message stream: {}", e));
                 }
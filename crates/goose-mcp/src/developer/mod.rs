@@ -33,13 +33,13 @@ use mcp_core::{
     tool::ToolAnnotations,
 };
 use mcp_server::router::CapabilitiesBuilder;
-use mcp_server::Router;
+use mcp_server::{Peer, Router};
 
 use mcp_core::role::Role;
 
 use self::shell::{
     expand_path, format_command_for_platform, get_shell_config, is_absolute_path,
-    normalize_line_endings,
+    normalize_line_endings, parse_progress_percent,
 };
 use indoc::indoc;
 use std::process::Stdio;
@@ -474,6 +474,7 @@ impl DeveloperRouter {
         &self,
         params: Value,
         notifier: mpsc::Sender<JsonRpcMessage>,
+        peer: Option<Peer>,
     ) -> Result<Vec<Content>, ToolError> {
         let command =
             params
@@ -528,6 +529,17 @@ impl DeveloperRouter {
         let output_task = tokio::spawn(async move {
             let mut combined_output = String::new();
 
+            // Percentages printed by the command are reported as progress, which must only grow
+            let mut last_progress = 0.0;
+            let mut report_progress = |line: &str| {
+                if let (Some(peer), Some(percent)) = (&peer, parse_progress_percent(line)) {
+                    if percent > last_progress {
+                        last_progress = percent;
+                        peer.notify_progress(percent, Some(100.0), Some(line.trim()));
+                    }
+                }
+            };
+
             let mut stdout_buf = Vec::new();
             let mut stderr_buf = Vec::new();
 
@@ -554,6 +566,7 @@ impl DeveloperRouter {
                                 })),
                             })).ok();
 
+                            report_progress(&line);
                             combined_output.push_str(&line);
                             stdout_buf.clear();
                         }
@@ -577,6 +590,7 @@ impl DeveloperRouter {
                                 })),
                             })).ok();
 
+                            report_progress(&line);
                             combined_output.push_str(&line);
                             stderr_buf.clear();
                         }
@@ -1115,6 +1129,27 @@ impl DeveloperRouter {
             Content::image(data, "image/png").with_priority(0.0),
         ])
     }
+
+    fn dispatch_tool(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        notifier: mpsc::Sender<JsonRpcMessage>,
+        peer: Option<Peer>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
+        let this = self.clone();
+        let tool_name = tool_name.to_string();
+        Box::pin(async move {
+            match tool_name.as_str() {
+                "shell" => this.bash(arguments, notifier, peer).await,
+                "text_editor" => this.text_editor(arguments).await,
+                "list_windows" => this.list_windows(arguments).await,
+                "screen_capture" => this.screen_capture(arguments).await,
+                "image_processor" => this.image_processor(arguments).await,
+                _ => Err(ToolError::NotFound(format!("Tool {} not found", tool_name))),
+            }
+        })
+    }
 }
 
 impl Router for DeveloperRouter {
@@ -1143,18 +1178,17 @@ impl Router for DeveloperRouter {
         arguments: Value,
         notifier: mpsc::Sender<JsonRpcMessage>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
-        let this = self.clone();
-        let tool_name = tool_name.to_string();
-        Box::pin(async move {
-            match tool_name.as_str() {
-                "shell" => this.bash(arguments, notifier).await,
-                "text_editor" => this.text_editor(arguments).await,
-                "list_windows" => this.list_windows(arguments).await,
-                "screen_capture" => this.screen_capture(arguments).await,
-                "image_processor" => this.image_processor(arguments).await,
-                _ => Err(ToolError::NotFound(format!("Tool {} not found", tool_name))),
-            }
-        })
+        self.dispatch_tool(tool_name, arguments, notifier, None)
+    }
+
+    fn call_tool_with_peer(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        notifier: mpsc::Sender<JsonRpcMessage>,
+        peer: Peer,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
+        self.dispatch_tool(tool_name, arguments, notifier, Some(peer))
     }
 
     // TODO see if we can make it easy to skip implementing these
@@ -1222,6 +1256,18 @@ mod tests {
     use tempfile::TempDir;
     use tokio::sync::OnceCell;
 
+    #[test]
+    fn test_parse_progress_percent() {
+        assert_eq!(parse_progress_percent("Downloading... 45%"), Some(45.0));
+        assert_eq!(
+            parse_progress_percent("\r 10% done\r 62.5% done"),
+            Some(62.5)
+        );
+        assert_eq!(parse_progress_percent("Compiling goose v1.0.0"), None);
+        assert_eq!(parse_progress_percent("100% of 250%"), Some(100.0));
+        assert_eq!(parse_progress_percent("%"), None);
+    }
+
     #[test]
     #[serial]
     fn test_global_goosehints() {
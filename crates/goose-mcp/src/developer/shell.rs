@@ -71,3 +71,22 @@ pub fn normalize_line_endings(text: &str) -> String {
         text.replace("\r\n", "\n")
     }
 }
+
+/// Find the last percentage printed in a line of command output, such as the
+/// "45%" of a download or build progress display
+pub fn parse_progress_percent(line: &str) -> Option<f64> {
+    let bytes = line.as_bytes();
+    let mut percent = None;
+    for (i, _) in line.match_indices('%') {
+        let start = bytes[..i]
+            .iter()
+            .rposition(|b| !(b.is_ascii_digit() || *b == b'.'))
+            .map_or(0, |p| p + 1);
+        if let Ok(value) = line[start..i].parse::<f64>() {
+            if (0.0..=100.0).contains(&value) {
+                percent = Some(value);
+            }
+        }
+    }
+    percent
+}
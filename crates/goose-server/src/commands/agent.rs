@@ -1,4 +1,5 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::configuration;
 use crate::state;
@@ -31,6 +32,19 @@ pub async fn run() -> Result<()> {
     let scheduler_instance = GooseScheduler::new(schedule_file_path).await?;
     app_state.set_scheduler(scheduler_instance).await;
 
+    // Close agent sessions nobody used for a while, which also stops their extensions
+    let idle_timeout = Duration::from_secs(settings.session_idle_timeout);
+    let eviction_state = app_state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            for session_id in eviction_state.evict_idle_sessions(idle_timeout).await {
+                info!("closed idle agent session {}", session_id);
+            }
+        }
+    });
+
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
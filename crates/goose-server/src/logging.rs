@@ -9,7 +9,7 @@ use tracing_subscriber::{
 };
 
 use goose::config::APP_STRATEGY;
-use goose::tracing::langfuse_layer;
+use goose::tracing::{langfuse_layer, otlp_layer};
 
 /// Returns the directory where log files should be stored.
 /// Creates the directory structure if it doesn't exist.
@@ -91,22 +91,19 @@ pub fn setup_logging(name: Option<&str>) -> Result<()> {
             .add_directive(LevelFilter::WARN.into())
     });
 
+    // Langfuse and OTLP export are each added only when configured
+    let langfuse = langfuse_layer::create_langfuse_observer()
+        .map(|langfuse| langfuse.with_filter(LevelFilter::DEBUG));
+    let otlp = otlp_layer::create_otlp_layer().map(|otlp| otlp.with_filter(LevelFilter::DEBUG));
+
     // Build the subscriber with required layers
-    let subscriber = Registry::default()
+    Registry::default()
         .with(file_layer.with_filter(env_filter))
-        .with(console_layer.with_filter(LevelFilter::INFO));
-
-    // Initialize with Langfuse if available
-    if let Some(langfuse) = langfuse_layer::create_langfuse_observer() {
-        subscriber
-            .with(langfuse.with_filter(LevelFilter::DEBUG))
-            .try_init()
-            .context("Failed to set global subscriber")?;
-    } else {
-        subscriber
-            .try_init()
-            .context("Failed to set global subscriber")?;
-    }
+        .with(console_layer.with_filter(LevelFilter::INFO))
+        .with(langfuse)
+        .with(otlp)
+        .try_init()
+        .context("Failed to set global subscriber")?;
 
     Ok(())
 }
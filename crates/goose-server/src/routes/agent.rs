@@ -1,9 +1,9 @@
-use super::utils::verify_secret_key;
-use crate::state::AppState;
+use super::utils::{is_valid_session_id, session_from_headers, verify_secret_key};
+use crate::state::{AgentSessionInfo, AppState};
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
 use goose::config::Config;
@@ -11,12 +11,13 @@ use goose::config::PermissionManager;
 use goose::model::ModelConfig;
 use goose::providers::create;
 use goose::{
-    agents::{extension::ToolInfo, extension_manager::get_parameter_names},
+    agents::{extension::ToolInfo, extension_manager::get_parameter_names, Agent},
     config::permission::PermissionLevel,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
 #[derive(Serialize)]
 struct VersionsResponse {
@@ -67,6 +68,21 @@ pub struct GetToolsQuery {
     extension_name: Option<String>,
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct CreateSessionRequest {
+    /// Id for the new session, made of letters, digits, `_` and `-`. Generated when not given
+    session_id: Option<String>,
+    /// Provider for the session's agent, defaults to the configured GOOSE_PROVIDER
+    provider: Option<String>,
+    /// Model for the session's agent, defaults to the configured GOOSE_MODEL
+    model: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateSessionResponse {
+    session_id: String,
+}
+
 async fn get_versions() -> Json<VersionsResponse> {
     let versions = ["goose".to_string()];
     let default_version = "goose".to_string();
@@ -85,7 +101,7 @@ async fn extend_prompt(
     verify_secret_key(&headers, &state)?;
 
     let agent = state
-        .get_agent()
+        .get_agent(session_from_headers(&headers)?.as_deref())
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     agent.extend_system_prompt(payload.extension.clone()).await;
@@ -138,7 +154,7 @@ async fn get_tools(
     let config = Config::global();
     let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
     let agent = state
-        .get_agent()
+        .get_agent(session_from_headers(&headers)?.as_deref())
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     let permission_manager = PermissionManager::default();
@@ -197,7 +213,7 @@ async fn update_agent_provider(
     }
 
     let agent = state
-        .get_agent()
+        .get_agent(session_from_headers(&headers)?.as_deref())
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
 
@@ -217,6 +233,98 @@ async fn update_agent_provider(
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/agent/sessions",
+    request_body = CreateSessionRequest,
+    responses(
+        (status = 200, description = "Session created with its own agent", body = CreateSessionResponse),
+        (status = 400, description = "Invalid session id, or no model for the provider"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 409, description = "A session with this id already exists"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+async fn create_agent_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateSessionRequest>,
+) -> Result<Json<CreateSessionResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+    if payload
+        .session_id
+        .as_deref()
+        .is_some_and(|session_id| !is_valid_session_id(session_id))
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let agent = Agent::new();
+    let config = Config::global();
+    let provider_name: Option<String> = payload
+        .provider
+        .or_else(|| config.get_param("GOOSE_PROVIDER").ok());
+    if let Some(provider_name) = provider_name {
+        let model: String = payload
+            .model
+            .or_else(|| config.get_param("GOOSE_MODEL").ok())
+            .ok_or(StatusCode::BAD_REQUEST)?;
+        let provider = create(&provider_name, ModelConfig::new(model))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        agent
+            .update_provider(provider)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    let session_id = state
+        .create_session(payload.session_id, agent)
+        .await
+        .ok_or(StatusCode::CONFLICT)?;
+    Ok(Json(CreateSessionResponse { session_id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/agent/sessions",
+    responses(
+        (status = 200, description = "Sessions with their own agent", body = Vec<AgentSessionInfo>),
+        (status = 401, description = "Unauthorized - invalid secret key")
+    )
+)]
+async fn list_agent_sessions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AgentSessionInfo>>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+    Ok(Json(state.list_sessions().await))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/agent/sessions/{session_id}",
+    params(
+        ("session_id" = String, Path, description = "Session to close")
+    ),
+    responses(
+        (status = 200, description = "Session closed and its extensions stopped"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 404, description = "Session not found")
+    )
+)]
+async fn close_agent_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+    if state.close_session(&session_id).await {
+        Ok(StatusCode::OK)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/agent/versions", get(get_versions))
@@ -224,5 +332,119 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/agent/prompt", post(extend_prompt))
         .route("/agent/tools", get(get_tools))
         .route("/agent/update_provider", post(update_agent_provider))
+        .route(
+            "/agent/sessions",
+            post(create_agent_session).get(list_agent_sessions),
+        )
+        .route("/agent/sessions/{session_id}", delete(close_agent_session))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    async fn test_app() -> (Arc<AppState>, Router) {
+        let state = AppState::new(Arc::new(Agent::new()), "test-secret".to_string()).await;
+        (state.clone(), routes(state))
+    }
+
+    fn request(method: &str, uri: &str, session_id: Option<&str>, body: Value) -> Request<Body> {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header("x-secret-key", "test-secret");
+        if let Some(session_id) = session_id {
+            builder = builder.header("x-session-id", session_id);
+        }
+        builder.body(Body::from(body.to_string())).unwrap()
+    }
+
+    fn create_request(session_id: &str) -> Request<Body> {
+        request(
+            "POST",
+            "/agent/sessions",
+            None,
+            json!({"session_id": session_id, "provider": "ollama", "model": "llama3.2"}),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_create_list_and_close_sessions() {
+        let (_, app) = test_app().await;
+
+        let response = app.clone().oneshot(create_request("first")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(create_request("first")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let response = app
+            .clone()
+            .oneshot(create_request("../../etc/passwd"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/agent/sessions", None, Value::Null))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let sessions: Vec<Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0]["session_id"], "first");
+        assert_eq!(sessions[0]["model"], "llama3.2");
+
+        let close = || request("DELETE", "/agent/sessions/first", None, Value::Null);
+        let response = app.clone().oneshot(close()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(close()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_routed_by_session_header() {
+        let (state, app) = test_app().await;
+        state
+            .create_session(Some("routed".to_string()), Agent::new())
+            .await
+            .unwrap();
+        let extend_prompt = |session_id: Option<&str>| {
+            request(
+                "POST",
+                "/agent/prompt",
+                session_id,
+                json!({"extension": "Be brief"}),
+            )
+        };
+
+        let response = app.clone().oneshot(extend_prompt(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(extend_prompt(Some("routed")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .oneshot(extend_prompt(Some("missing")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+        let response = app
+            .clone()
+            .oneshot(extend_prompt(Some("../../secrets")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,10 +1,16 @@
 use std::sync::Arc;
 
-use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
 use goose::message::Message;
-use goose::recipe::Recipe;
+use goose::recipe::{Recipe, Settings};
 use serde::{Deserialize, Serialize};
 
+use super::utils::{session_from_headers, verify_secret_key};
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -37,14 +43,22 @@ pub struct CreateRecipeResponse {
 /// Create a Recipe configuration from the current state of an agent
 async fn create_recipe(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(request): Json<CreateRecipeRequest>,
 ) -> Result<Json<CreateRecipeResponse>, (StatusCode, Json<CreateRecipeResponse>)> {
     let error_response = CreateRecipeResponse {
         recipe: None,
         error: Some("Missing agent".to_string()),
     };
+    let session_id = session_from_headers(&headers).map_err(|status| {
+        let error_response = CreateRecipeResponse {
+            recipe: None,
+            error: Some("Invalid session id".to_string()),
+        };
+        (status, Json(error_response))
+    })?;
     let agent = state
-        .get_agent()
+        .get_agent(session_id.as_deref())
         .await
         .map_err(|_| (StatusCode::PRECONDITION_FAILED, Json(error_response)))?;
 
@@ -84,8 +98,170 @@ async fn create_recipe(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ApplySettingsRequest {
+    settings: Settings,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApplySettingsResponse {
+    error: Option<String>,
+}
+
+/// Switch an agent to the provider, model and mode of a recipe's settings, for a session
+/// started from the recipe
+async fn apply_settings(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<ApplySettingsRequest>,
+) -> Result<Json<ApplySettingsResponse>, (StatusCode, Json<ApplySettingsResponse>)> {
+    verify_secret_key(&headers, &state).map_err(|status| {
+        let error_response = ApplySettingsResponse {
+            error: Some("Unauthorized".to_string()),
+        };
+        (status, Json(error_response))
+    })?;
+
+    let error_response = ApplySettingsResponse {
+        error: Some("Missing agent".to_string()),
+    };
+    let session_id = session_from_headers(&headers).map_err(|status| {
+        let error_response = ApplySettingsResponse {
+            error: Some("Invalid session id".to_string()),
+        };
+        (status, Json(error_response))
+    })?;
+    let agent = state
+        .get_agent(session_id.as_deref())
+        .await
+        .map_err(|_| (StatusCode::PRECONDITION_FAILED, Json(error_response)))?;
+
+    match agent.apply_recipe_settings(&request.settings).await {
+        Ok(()) => Ok(Json(ApplySettingsResponse { error: None })),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApplySettingsResponse {
+                error: Some(e.to_string()),
+            }),
+        )),
+    }
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/recipe/create", post(create_recipe))
+        .route("/recipe/apply_settings", post(apply_settings))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use goose::agents::Agent;
+    use goose::providers::replay::{ReplayProvider, REPLAY_PROVIDER_NAME};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    fn apply_settings_request(session_id: &str, settings: Value) -> Request<Body> {
+        apply_settings_request_with_key(session_id, settings, Some("test-secret"))
+    }
+
+    fn apply_settings_request_with_key(
+        session_id: &str,
+        settings: Value,
+        secret_key: Option<&str>,
+    ) -> Request<Body> {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/recipe/apply_settings")
+            .header("content-type", "application/json")
+            .header("x-session-id", session_id);
+        if let Some(secret_key) = secret_key {
+            builder = builder.header("x-secret-key", secret_key);
+        }
+        builder
+            .body(Body::from(json!({ "settings": settings }).to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_apply_settings() {
+        let state = AppState::new(Arc::new(Agent::new()), "test-secret".to_string()).await;
+        let agent = Agent::new();
+        agent
+            .update_provider(Arc::new(ReplayProvider::new(vec![])))
+            .await
+            .unwrap();
+        state
+            .create_session(Some("recipe".to_string()), agent)
+            .await
+            .unwrap();
+        let app = routes(state.clone());
+
+        // Without the secret key nothing changes, not even the mode
+        for secret_key in [None, Some("wrong-secret")] {
+            let response = app
+                .clone()
+                .oneshot(apply_settings_request_with_key(
+                    "recipe",
+                    json!({"goose_mode": "auto"}),
+                    secret_key,
+                ))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+        let agent = state.get_agent(Some("recipe")).await.unwrap();
+        assert_eq!(agent.goose_mode_override(), None);
+
+        // A mode alone keeps the session's provider
+        let response = app
+            .clone()
+            .oneshot(apply_settings_request(
+                "recipe",
+                json!({"goose_mode": "chat"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let agent = state.get_agent(Some("recipe")).await.unwrap();
+        assert_eq!(agent.goose_mode_override().as_deref(), Some("chat"));
+        let provider = agent.provider().await.unwrap();
+        assert_eq!(provider.get_model_config().model_name, REPLAY_PROVIDER_NAME);
+
+        let response = app
+            .clone()
+            .oneshot(apply_settings_request(
+                "recipe",
+                json!({"goose_mode": "reckless"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = app
+            .clone()
+            .oneshot(apply_settings_request(
+                "recipe",
+                json!({"goose_provider": "nonexistent", "goose_model": "model"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["error"]
+            .as_str()
+            .unwrap()
+            .contains("Unknown provider: nonexistent"));
+
+        let response = app
+            .oneshot(apply_settings_request("missing", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+    }
+}
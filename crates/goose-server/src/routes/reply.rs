@@ -1,4 +1,4 @@
-use super::utils::verify_secret_key;
+use super::utils::{session_from_headers, verify_secret_key};
 use crate::state::AppState;
 use axum::{
     extract::State,
@@ -11,8 +11,10 @@ use bytes::Bytes;
 use futures::{stream::StreamExt, Stream};
 use goose::{
     agents::{AgentEvent, SessionConfig},
+    context_mgmt::CompactionPolicy,
     message::{Message, MessageContent},
     permission::permission_confirmation::PrincipalType,
+    providers::base::MessageDelta,
 };
 use goose::{
     permission::{Permission, PermissionConfirmation},
@@ -92,6 +94,17 @@ enum MessageEvent {
         request_id: String,
         message: JsonRpcMessage,
     },
+    MessageDelta {
+        delta: MessageDelta,
+    },
+    ContextCompacted {
+        policy: CompactionPolicy,
+        messages: Vec<Message>,
+    },
+    ExtensionRestarted {
+        name: String,
+        reason: String,
+    },
 }
 
 async fn stream_event(
@@ -120,12 +133,15 @@ async fn handler(
     let messages = request.messages;
     let session_working_dir = request.session_working_dir;
 
+    // A session created through /agent/sessions is stored under its own id by default
+    let agent_session = session_from_headers(&headers)?;
     let session_id = request
         .session_id
+        .or_else(|| agent_session.clone())
         .unwrap_or_else(session::generate_session_id);
 
     tokio::spawn(async move {
-        let agent = state.get_agent().await;
+        let agent = state.get_agent(agent_session.as_deref()).await;
         let agent = match agent {
             Ok(agent) => {
                 let provider = agent.provider().await;
@@ -178,6 +194,9 @@ async fn handler(
                     id: session::Identifier::Name(session_id.clone()),
                     working_dir: PathBuf::from(session_working_dir),
                     schedule_id: None,
+                    max_turns: None,
+                    max_tokens: None,
+                    deadline: None,
                 }),
             )
             .await
@@ -245,6 +264,27 @@ async fn handler(
                                     },
                                     &tx,
                                 ).await;
+                                // The client is gone, dropping the stream cancels running tools
+                                break;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::ContextCompacted { policy, messages }))) => {
+                            all_messages = messages.clone();
+                            if let Err(e) = stream_event(MessageEvent::ContextCompacted { policy, messages }, &tx).await {
+                                tracing::error!("Error sending message through channel: {}", e);
+                                break;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::ExtensionRestarted { name, reason }))) => {
+                            if let Err(e) = stream_event(MessageEvent::ExtensionRestarted { name, reason }, &tx).await {
+                                tracing::error!("Error sending message through channel: {}", e);
+                                break;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::MessageDelta(delta)))) => {
+                            if let Err(e) = stream_event(MessageEvent::MessageDelta { delta }, &tx).await {
+                                tracing::error!("Error sending message through channel: {}", e);
+                                break;
                             }
                         }
                         Ok(Some(Err(e))) => {
@@ -271,6 +311,11 @@ async fn handler(
             }
         }
 
+        // A long reply counts as using the session, so idle time starts from its end
+        if let Some(agent_session) = &agent_session {
+            state.touch_session(agent_session).await;
+        }
+
         let _ = stream_event(
             MessageEvent::Finish {
                 reason: "stop".to_string(),
@@ -304,12 +349,14 @@ async fn ask_handler(
 
     let session_working_dir = request.session_working_dir;
 
+    let agent_session = session_from_headers(&headers)?;
     let session_id = request
         .session_id
+        .or_else(|| agent_session.clone())
         .unwrap_or_else(session::generate_session_id);
 
     let agent = state
-        .get_agent()
+        .get_agent(agent_session.as_deref())
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
 
@@ -325,6 +372,9 @@ async fn ask_handler(
                 id: session::Identifier::Name(session_id.clone()),
                 working_dir: PathBuf::from(session_working_dir),
                 schedule_id: None,
+                max_turns: None,
+                max_tokens: None,
+                deadline: None,
             }),
         )
         .await
@@ -356,6 +406,15 @@ async fn ask_handler(
                 // Handle notifications if needed
                 tracing::info!("Received notification: {:?}", n);
             }
+            Ok(AgentEvent::MessageDelta(_)) => {
+                // The complete message follows and is collected above
+            }
+            Ok(AgentEvent::ContextCompacted { messages, .. }) => {
+                all_messages = messages;
+            }
+            Ok(AgentEvent::ExtensionRestarted { name, reason }) => {
+                tracing::warn!("Extension {} was restarted because {}", name, reason);
+            }
             Err(e) => {
                 tracing::error!("Error processing as_ai message: {}", e);
                 return Err(StatusCode::INTERNAL_SERVER_ERROR);
@@ -363,6 +422,10 @@ async fn ask_handler(
         }
     }
 
+    if let Some(agent_session) = &agent_session {
+        state.touch_session(agent_session).await;
+    }
+
     if !response_message.content.is_empty() {
         all_messages.push(response_message);
     }
@@ -413,7 +476,7 @@ pub async fn confirm_permission(
     verify_secret_key(&headers, &state)?;
 
     let agent = state
-        .get_agent()
+        .get_agent(session_from_headers(&headers)?.as_deref())
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
 
@@ -467,7 +530,7 @@ async fn submit_tool_result(
     };
 
     let agent = state
-        .get_agent()
+        .get_agent(session_from_headers(&headers)?.as_deref())
         .await
         .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
     agent.handle_tool_result(payload.id, payload.result).await;
@@ -3,17 +3,17 @@ use std::sync::Arc;
 
 use crate::state::AppState;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use goose::message::Message;
 use goose::session;
 use goose::session::info::{get_session_info, SessionInfo, SortOrder};
-use goose::session::SessionMetadata;
-use serde::Serialize;
-use utoipa::ToSchema;
+use goose::session::{SessionMetadata, SessionSearchHit};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
 #[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -33,6 +33,42 @@ pub struct SessionHistoryResponse {
     messages: Vec<Message>,
 }
 
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct SessionSearchQuery {
+    /// Words to search for; messages must contain all of them
+    query: String,
+    /// Maximum number of matching messages to return
+    #[serde(default = "default_search_limit")]
+    limit: usize,
+}
+
+fn default_search_limit() -> usize {
+    20
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchResponse {
+    /// Matching messages, best matches first
+    results: Vec<SessionSearchHit>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSessionRequest {
+    /// Number of messages to keep in the fork, counted from the start. Defaults to all.
+    at_message: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSessionResponse {
+    /// Unique identifier for the new session
+    session_id: String,
+    /// Metadata of the new session, including its parent and fork point
+    metadata: SessionMetadata,
+}
+
 #[utoipa::path(
     get,
     path = "/sessions",
@@ -104,10 +140,96 @@ async fn get_session_history(
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/sessions/search",
+    params(SessionSearchQuery),
+    responses(
+        (status = 200, description = "Matching messages retrieved successfully", body = SessionSearchResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Search the messages of all sessions
+async fn search_sessions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<SessionSearchQuery>,
+) -> Result<Json<SessionSearchResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let results = session::search_sessions(&query.query, query.limit).map_err(|e| {
+        tracing::error!("Failed to search sessions: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(SessionSearchResponse { results }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/fork",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session to fork")
+    ),
+    request_body = ForkSessionRequest,
+    responses(
+        (status = 200, description = "Session forked successfully", body = ForkSessionResponse),
+        (status = 400, description = "Fork point is past the end or splits a tool call"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Fork a session into a new session at a given message
+async fn fork_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    Json(request): Json<ForkSessionRequest>,
+) -> Result<Json<ForkSessionResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let session_path = session::get_path(session::Identifier::Name(session_id));
+    if !session::session_exists(&session_path) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let messages =
+        session::read_messages(&session_path).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(at_message) = request.at_message {
+        session::check_fork_point(&messages, at_message).map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+
+    let fork_id = session::fork_session(&session_path, request.at_message).map_err(|e| {
+        tracing::error!("Failed to fork session: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let metadata = session::read_metadata(&session::get_path(session::Identifier::Name(
+        fork_id.clone(),
+    )))
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ForkSessionResponse {
+        session_id: fork_id,
+        metadata,
+    }))
+}
+
 // Configure routes for this module
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/sessions", get(list_sessions))
+        .route("/sessions/search", get(search_sessions))
         .route("/sessions/{session_id}", get(get_session_history))
+        .route("/sessions/{session_id}/fork", post(fork_session))
         .with_state(state)
 }
@@ -1,6 +1,7 @@
 use crate::state::AppState;
 use goose::config::Config;
 use goose::providers::base::{ConfigKey, ProviderMetadata};
+use goose::providers::custom::find_custom_provider;
 use http::{HeaderMap, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::env;
@@ -37,6 +38,26 @@ pub fn verify_secret_key(headers: &HeaderMap, state: &AppState) -> Result<Status
     }
 }
 
+/// The agent session a request is for, named by the `X-Session-Id` header. Requests
+/// without the header are served by the server's default agent. Ids name session files,
+/// so anything but letters, digits, `_` and `-` is rejected.
+pub fn session_from_headers(headers: &HeaderMap) -> Result<Option<String>, StatusCode> {
+    let Some(value) = headers.get("X-Session-Id") else {
+        return Ok(None);
+    };
+    match value.to_str() {
+        Ok(session_id) if is_valid_session_id(session_id) => Ok(Some(session_id.to_string())),
+        _ => Err(StatusCode::BAD_REQUEST),
+    }
+}
+
+pub fn is_valid_session_id(session_id: &str) -> bool {
+    !session_id.is_empty()
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
 /// Inspects a configuration key to determine if it's set, its location, and value (for non-secret keys)
 #[allow(dead_code)]
 pub fn inspect_key(key_name: &str, is_secret: bool) -> Result<KeyInfo, Box<dyn Error>> {
@@ -116,6 +137,11 @@ pub fn check_provider_configured(metadata: &ProviderMetadata) -> bool {
         .filter(|key| key.required)
         .collect();
 
+    // A custom provider without an API key is fully configured by its declaration
+    if required_keys.is_empty() && find_custom_provider(&metadata.name).is_some() {
+        return true;
+    }
+
     // Special case: If a provider has exactly one required key and that key
     // has a default value, check if it's explicitly set
     if required_keys.len() == 1 && required_keys[0].default.is_some() {
@@ -1,13 +1,36 @@
 use goose::agents::Agent;
 use goose::scheduler::Scheduler;
+use goose::session;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
+use utoipa::ToSchema;
 
 pub type AgentRef = Arc<Agent>;
 
+/// An agent serving a single session, with its own provider, extensions and prompt extras
+struct SessionAgent {
+    agent: AgentRef,
+    last_used: Instant,
+}
+
+/// A session with its own agent, as listed by the server
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AgentSessionInfo {
+    pub session_id: String,
+    /// Model of the session's provider, if one is set
+    pub model: Option<String>,
+    /// Seconds since a request last used the session
+    pub idle_seconds: u64,
+}
+
 #[derive(Clone)]
 pub struct AppState {
+    /// Serves requests that do not name a session, as the desktop app sends them
     agent: Option<AgentRef>,
+    sessions: Arc<Mutex<HashMap<String, SessionAgent>>>,
     pub secret_key: String,
     pub scheduler: Arc<Mutex<Option<Arc<Scheduler>>>>,
 }
@@ -16,15 +39,119 @@ impl AppState {
     pub async fn new(agent: AgentRef, secret_key: String) -> Arc<AppState> {
         Arc::new(Self {
             agent: Some(agent.clone()),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
             secret_key,
             scheduler: Arc::new(Mutex::new(None)),
         })
     }
 
-    pub async fn get_agent(&self) -> Result<Arc<Agent>, anyhow::Error> {
-        self.agent
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("Agent needs to be created first."))
+    /// The agent of the given session, or the default agent when no session is named
+    pub async fn get_agent(&self, session_id: Option<&str>) -> Result<Arc<Agent>, anyhow::Error> {
+        let Some(session_id) = session_id else {
+            return self
+                .agent
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("Agent needs to be created first."));
+        };
+
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| anyhow::anyhow!("Session {} not found.", session_id))?;
+        session.last_used = Instant::now();
+        Ok(session.agent.clone())
+    }
+
+    /// Mark a session as just used, e.g. when a reply that started long ago finishes
+    pub async fn touch_session(&self, session_id: &str) {
+        if let Some(session) = self.sessions.lock().await.get_mut(session_id) {
+            session.last_used = Instant::now();
+        }
+    }
+
+    /// Register a new session served by `agent`, under `session_id` or a generated id.
+    /// Returns None if the requested id is already in use.
+    pub async fn create_session(&self, session_id: Option<String>, agent: Agent) -> Option<String> {
+        let mut sessions = self.sessions.lock().await;
+        let session_id = match session_id {
+            Some(id) if sessions.contains_key(&id) => return None,
+            Some(id) => id,
+            None => {
+                let base = session::generate_session_id();
+                let mut id = base.clone();
+                let mut suffix = 1;
+                while sessions.contains_key(&id) {
+                    suffix += 1;
+                    id = format!("{}_{}", base, suffix);
+                }
+                id
+            }
+        };
+
+        sessions.insert(
+            session_id.clone(),
+            SessionAgent {
+                agent: Arc::new(agent),
+                last_used: Instant::now(),
+            },
+        );
+        Some(session_id)
+    }
+
+    /// Close a session. Its extension processes stop once no request is using its agent.
+    pub async fn close_session(&self, session_id: &str) -> bool {
+        self.sessions.lock().await.remove(session_id).is_some()
+    }
+
+    pub async fn list_sessions(&self) -> Vec<AgentSessionInfo> {
+        // Looking up providers waits on each agent, so don't hold up other requests meanwhile
+        let sessions = self
+            .sessions
+            .lock()
+            .await
+            .iter()
+            .map(|(session_id, session)| {
+                (
+                    session_id.clone(),
+                    session.agent.clone(),
+                    session.last_used.elapsed().as_secs(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut infos = Vec::with_capacity(sessions.len());
+        for (session_id, agent, idle_seconds) in sessions {
+            let model = agent
+                .provider()
+                .await
+                .ok()
+                .map(|provider| provider.get_model_config().model_name);
+            infos.push(AgentSessionInfo {
+                session_id,
+                model,
+                idle_seconds,
+            });
+        }
+        infos.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        infos
+    }
+
+    /// Close sessions nobody used for `idle_timeout`, skipping any that a request is still
+    /// holding on to, e.g. a reply that is still streaming. Returns the closed session ids.
+    pub async fn evict_idle_sessions(&self, idle_timeout: Duration) -> Vec<String> {
+        let mut sessions = self.sessions.lock().await;
+        let idle = sessions
+            .iter()
+            .filter(|(_, session)| {
+                session.last_used.elapsed() >= idle_timeout
+                    && Arc::strong_count(&session.agent) == 1
+            })
+            .map(|(session_id, _)| session_id.clone())
+            .collect::<Vec<_>>();
+        for session_id in &idle {
+            sessions.remove(session_id);
+        }
+        idle
     }
 
     pub async fn set_scheduler(&self, sched: Arc<Scheduler>) {
@@ -40,3 +167,74 @@ impl AppState {
             .ok_or_else(|| anyhow::anyhow!("Scheduler not initialized"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_state() -> Arc<AppState> {
+        AppState::new(Arc::new(Agent::new()), "test".to_string()).await
+    }
+
+    #[tokio::test]
+    async fn test_sessions_have_their_own_agents() {
+        let state = test_state().await;
+        let first = state.create_session(None, Agent::new()).await.unwrap();
+        let second = state.create_session(None, Agent::new()).await.unwrap();
+        assert_ne!(first, second);
+
+        let first_agent = state.get_agent(Some(&first)).await.unwrap();
+        let second_agent = state.get_agent(Some(&second)).await.unwrap();
+        let default_agent = state.get_agent(None).await.unwrap();
+        assert!(!Arc::ptr_eq(&first_agent, &second_agent));
+        assert!(!Arc::ptr_eq(&first_agent, &default_agent));
+
+        assert!(state.get_agent(Some("missing")).await.is_err());
+        assert!(state
+            .create_session(Some(first.clone()), Agent::new())
+            .await
+            .is_none());
+
+        assert!(state.close_session(&first).await);
+        assert!(state.get_agent(Some(&first)).await.is_err());
+        assert_eq!(state.list_sessions().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_evict_idle_sessions_skips_sessions_in_use() {
+        let state = test_state().await;
+        let idle = state
+            .create_session(Some("idle".to_string()), Agent::new())
+            .await
+            .unwrap();
+        let busy = state
+            .create_session(Some("busy".to_string()), Agent::new())
+            .await
+            .unwrap();
+        let _in_use = state.get_agent(Some(&busy)).await.unwrap();
+
+        let evicted = state.evict_idle_sessions(Duration::ZERO).await;
+        assert_eq!(evicted, vec![idle]);
+        assert!(state.get_agent(Some(&busy)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_touch_session_resets_idle_time() {
+        let state = test_state().await;
+        let session_id = state
+            .create_session(Some("long-reply".to_string()), Agent::new())
+            .await
+            .unwrap();
+        state
+            .sessions
+            .lock()
+            .await
+            .get_mut(&session_id)
+            .unwrap()
+            .last_used = Instant::now() - Duration::from_secs(120);
+
+        state.touch_session(&session_id).await;
+        let evicted = state.evict_idle_sessions(Duration::from_secs(60)).await;
+        assert!(evicted.is_empty());
+    }
+}
@@ -1,43 +1,62 @@
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
+use futures::future::Either;
 use futures::stream::BoxStream;
 use futures::{FutureExt, Stream, TryStreamExt};
 use futures_util::stream;
 use futures_util::stream::StreamExt;
 use mcp_core::protocol::JsonRpcMessage;
 
-use crate::config::{Config, ExtensionConfigManager, PermissionManager};
+use crate::config::{ExtensionConfigManager, PermissionManager};
+use crate::context_mgmt::{compaction_threshold, CompactionPolicy};
 use crate::message::Message;
+use crate::model::DEFAULT_CONTEXT_LIMIT;
 use crate::permission::permission_judge::check_tool_permissions;
-use crate::permission::PermissionConfirmation;
-use crate::providers::base::Provider;
+use crate::permission::{Permission, PermissionConfirmation};
+use crate::providers::base::{CompletionChunk, MessageDelta, Provider};
 use crate::providers::errors::ProviderError;
-use crate::recipe::{Author, Recipe};
+use crate::recipe::sub_recipe::{max_sub_recipe_depth, SUB_RECIPE_TOOL_PREFIX};
+use crate::recipe::{Author, Recipe, Response, Settings, SubRecipe};
+use crate::token_counter::TokenCounter;
 use crate::tool_monitor::{ToolCall, ToolMonitor};
 use regex::Regex;
 use serde_json::Value;
 use tokio::sync::{mpsc, Mutex};
-use tracing::{debug, error, instrument};
+use tracing::{debug, error, instrument, warn};
 
 use crate::agents::extension::{ExtensionConfig, ExtensionError, ExtensionResult, ToolInfo};
 use crate::agents::extension_manager::{get_parameter_names, ExtensionManager};
+use crate::agents::final_output_tool::{
+    FinalOutputTool, FINAL_OUTPUT_CONTINUATION_MESSAGE, FINAL_OUTPUT_TOOL_NAME,
+    MAX_FINAL_OUTPUT_REMINDERS,
+};
 use crate::agents::platform_tools::{
     PLATFORM_LIST_RESOURCES_TOOL_NAME, PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME,
-    PLATFORM_READ_RESOURCE_TOOL_NAME, PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME,
+    PLATFORM_READ_RESOURCE_TOOL_NAME, PLATFORM_READ_TOOL_OUTPUT_TOOL_NAME,
+    PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME,
 };
 use crate::agents::prompt_manager::PromptManager;
+use crate::agents::reply_parts::add_tokens;
 use crate::agents::router_tool_selector::{
     create_tool_selector, RouterToolSelectionStrategy, RouterToolSelector,
 };
-use crate::agents::router_tools::ROUTER_VECTOR_SEARCH_TOOL_NAME;
+use crate::agents::router_tools::is_router_search_tool;
+use crate::agents::sampling::{
+    SamplingApproval, SamplingContext, SamplingListener, SharedProvider,
+};
+use crate::agents::sub_recipe_tools::SubRecipeTools;
+use crate::agents::tool_output_store::{ToolOutputLimits, ToolOutputStore};
 use crate::agents::tool_router_index_manager::ToolRouterIndexManager;
 use crate::agents::tool_vectordb::generate_table_id;
-use crate::agents::types::SessionConfig;
-use crate::agents::types::{FrontendTool, ToolResultReceiver};
+use crate::agents::types::{
+    current_goose_mode, FrontendTool, GooseModeOverride, ToolResultReceiver,
+};
+use crate::agents::types::{RunBudget, SessionConfig};
 use mcp_core::{
     prompt::Prompt, protocol::GetPromptResult, tool::Tool, Content, ToolError, ToolResult,
 };
@@ -48,7 +67,7 @@ use super::tool_execution::{ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DEC
 
 /// The main goose Agent
 pub struct Agent {
-    pub(super) provider: Mutex<Option<Arc<dyn Provider>>>,
+    pub(super) provider: SharedProvider,
     pub(super) extension_manager: Mutex<ExtensionManager>,
     pub(super) frontend_tools: Mutex<HashMap<String, FrontendTool>>,
     pub(super) frontend_instructions: Mutex<Option<String>>,
@@ -59,12 +78,37 @@ pub struct Agent {
     pub(super) tool_result_rx: ToolResultReceiver,
     pub(super) tool_monitor: Mutex<Option<ToolMonitor>>,
     pub(super) router_tool_selector: Mutex<Option<Arc<Box<dyn RouterToolSelector>>>>,
+    pub(super) sampling_rx: Mutex<mpsc::Receiver<SamplingApproval>>,
+    pub(super) sampling_listening: Arc<AtomicBool>,
+    /// Tool outputs too large for the conversation, kept for the session being served
+    pub(super) tool_output_store: Arc<std::sync::Mutex<ToolOutputStore>>,
+    /// Set when a run must finish with structured output matching a schema
+    pub(super) final_output_tool: Mutex<Option<FinalOutputTool>>,
+    /// Recipes the agent can run as tools, each in an agent of its own
+    pub(super) sub_recipe_tools: Mutex<Option<SubRecipeTools>>,
+    /// Set when the agent runs in a mode of its own, such as a recipe's, not GOOSE_MODE
+    pub(super) goose_mode: GooseModeOverride,
+    /// Results returned in place of running these tools, for testing recipes
+    pub(super) tool_stubs: HashMap<String, ToolResult<Vec<Content>>>,
 }
 
 #[derive(Clone, Debug)]
 pub enum AgentEvent {
     Message(Message),
     McpNotification((String, JsonRpcMessage)),
+    /// A partial assistant response; the complete message still follows as `Message`
+    MessageDelta(MessageDelta),
+    /// The conversation was compacted to fit the model's context window and the turn retried.
+    /// `messages` is the full compacted conversation, which replaces the caller's history.
+    ContextCompacted {
+        policy: CompactionPolicy,
+        messages: Vec<Message>,
+    },
+    /// An extension's server died or stopped answering and was started again
+    ExtensionRestarted {
+        name: String,
+        reason: String,
+    },
 }
 
 impl Agent {
@@ -72,10 +116,22 @@ impl Agent {
         // Create channels with buffer size 32 (adjust if needed)
         let (confirm_tx, confirm_rx) = mpsc::channel(32);
         let (tool_tx, tool_rx) = mpsc::channel(32);
+        let (sampling_tx, sampling_rx) = mpsc::channel(32);
+
+        let provider: SharedProvider = Arc::new(Mutex::new(None));
+        let goose_mode: GooseModeOverride = Arc::new(std::sync::RwLock::new(None));
+        let sampling_listening = Arc::new(AtomicBool::new(false));
+        let mut extension_manager = ExtensionManager::new();
+        extension_manager.set_sampling_context(SamplingContext::new(
+            provider.clone(),
+            sampling_tx,
+            goose_mode.clone(),
+            sampling_listening.clone(),
+        ));
 
         Self {
-            provider: Mutex::new(None),
-            extension_manager: Mutex::new(ExtensionManager::new()),
+            provider,
+            extension_manager: Mutex::new(extension_manager),
             frontend_tools: Mutex::new(HashMap::new()),
             frontend_instructions: Mutex::new(None),
             prompt_manager: Mutex::new(PromptManager::new()),
@@ -85,14 +141,148 @@ impl Agent {
             tool_result_rx: Arc::new(Mutex::new(tool_rx)),
             tool_monitor: Mutex::new(None),
             router_tool_selector: Mutex::new(None),
+            sampling_rx: Mutex::new(sampling_rx),
+            sampling_listening,
+            tool_output_store: Arc::new(std::sync::Mutex::new(ToolOutputStore::default())),
+            final_output_tool: Mutex::new(None),
+            sub_recipe_tools: Mutex::new(None),
+            goose_mode,
+            tool_stubs: HashMap::new(),
         }
     }
 
+    /// Return these results for every call of the tools they're keyed by instead of running
+    /// them. Results over the tool output limit are stored like the tools' own output would be.
+    pub fn with_tool_stubs(mut self, stubs: HashMap<String, ToolResult<Vec<Content>>>) -> Self {
+        self.tool_stubs = stubs;
+        self
+    }
+
     pub async fn configure_tool_monitor(&self, max_repetitions: Option<u32>) {
         let mut tool_monitor = self.tool_monitor.lock().await;
         *tool_monitor = Some(ToolMonitor::new(max_repetitions));
     }
 
+    /// Require runs to finish by calling the final output tool with output matching the
+    /// response's JSON Schema
+    pub async fn add_final_output_tool(&self, response: Response) -> Result<()> {
+        let tool = FinalOutputTool::new(response)?;
+        *self.final_output_tool.lock().await = Some(tool);
+        Ok(())
+    }
+
+    /// The validated final output, once the agent has provided it
+    pub async fn final_output(&self) -> Option<Value> {
+        self.final_output_tool
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|tool| tool.final_output().cloned())
+    }
+
+    async fn has_final_output(&self) -> bool {
+        self.final_output().await.is_some()
+    }
+
+    pub(crate) async fn has_final_output_tool(&self) -> bool {
+        self.final_output_tool.lock().await.is_some()
+    }
+
+    /// Offer recipes the agent can run as tools; their paths must be resolved already
+    pub async fn add_sub_recipes(&self, sub_recipes: &[SubRecipe]) -> Result<()> {
+        self.add_sub_recipes_at_depth(sub_recipes, 0).await
+    }
+
+    /// Offer sub-recipes to an agent that is itself running a sub-recipe `depth` levels deep
+    pub(crate) async fn add_sub_recipes_at_depth(
+        &self,
+        sub_recipes: &[SubRecipe],
+        depth: usize,
+    ) -> Result<()> {
+        let tools = SubRecipeTools::new(sub_recipes, depth)?;
+        *self.sub_recipe_tools.lock().await = Some(tools);
+        Ok(())
+    }
+
+    /// Set the agent up to run a recipe whose sub-recipe paths are resolved already: its
+    /// extensions, or the enabled ones when it names none, its instructions, final output and
+    /// sub-recipes. Returns the message to start the run with.
+    pub async fn prepare_recipe(&self, recipe: &Recipe) -> Result<String> {
+        self.prepare_recipe_at_depth(recipe, 0).await
+    }
+
+    /// Set the agent up to run a recipe as a sub-recipe `depth` levels deep
+    pub(crate) async fn prepare_recipe_at_depth(
+        &self,
+        recipe: &Recipe,
+        depth: usize,
+    ) -> Result<String> {
+        let extensions = match &recipe.extensions {
+            Some(extensions) => extensions.clone(),
+            None => ExtensionConfigManager::get_all()?
+                .into_iter()
+                .filter(|entry| entry.enabled)
+                .map(|entry| entry.config)
+                .collect(),
+        };
+        for extension in extensions {
+            let name = extension.name();
+            self.add_extension(extension)
+                .await
+                .map_err(|e| anyhow!("Failed to start extension {}: {}", name, e))?;
+        }
+
+        if let Some(instructions) = &recipe.instructions {
+            self.extend_system_prompt(instructions.clone()).await;
+        }
+        if let Some(response) = &recipe.response {
+            self.add_final_output_tool(response.clone()).await?;
+        }
+        if let Some(sub_recipes) = &recipe.sub_recipes {
+            if depth > 0 && depth >= max_sub_recipe_depth() {
+                warn!(
+                    "Not offering the sub-recipes of '{}', they would nest deeper than the limit",
+                    recipe.title
+                );
+            } else {
+                self.add_sub_recipes_at_depth(sub_recipes, depth).await?;
+            }
+        }
+
+        Ok(recipe
+            .prompt
+            .clone()
+            .unwrap_or_else(|| "Follow your instructions to complete the task.".to_string()))
+    }
+
+    /// Start running the sub-recipe a tool call names, if it names one
+    async fn dispatch_sub_recipe(
+        &self,
+        tool_call: &mcp_core::tool::ToolCall,
+        budget: RunBudget,
+    ) -> Option<ToolCallResult> {
+        if !tool_call.name.starts_with(SUB_RECIPE_TOOL_PREFIX) {
+            return None;
+        }
+        // Get the provider first, so the sub-recipe tools aren't locked while waiting for it
+        let provider = self.provider().await;
+        let sub_recipe_tools = self.sub_recipe_tools.lock().await;
+        let tools = sub_recipe_tools
+            .as_ref()
+            .filter(|tools| tools.is_sub_recipe_tool(&tool_call.name))?;
+        let result = match provider {
+            Ok(provider) => tools.run(&tool_call.name, &tool_call.arguments, provider, budget),
+            Err(e) => Err(ToolError::ExecutionError(e.to_string())),
+        };
+        Some(match result {
+            Ok(run) => ToolCallResult {
+                result: Box::new(run),
+                notification_stream: None,
+            },
+            Err(e) => ToolCallResult::from(Err(e)),
+        })
+    }
+
     pub async fn get_tool_stats(&self) -> Option<HashMap<String, u32>> {
         let tool_monitor = self.tool_monitor.lock().await;
         tool_monitor.as_ref().map(|monitor| monitor.get_stats())
@@ -183,11 +373,15 @@ impl Agent {
     }
 
     /// Dispatch a single tool call to the appropriate client
-    #[instrument(skip(self, tool_call, request_id), fields(input, output))]
+    #[instrument(
+        skip(self, tool_call, request_id),
+        fields(input, output, tool_name = %tool_call.name, tool_call_id = %request_id)
+    )]
     pub(super) async fn dispatch_tool_call(
         &self,
         tool_call: mcp_core::tool::ToolCall,
         request_id: String,
+        budget: RunBudget,
     ) -> (String, Result<ToolCallResult, ToolError>) {
         // Check if this tool call should be allowed based on repetition monitoring
         if let Some(monitor) = self.tool_monitor.lock().await.as_mut() {
@@ -203,6 +397,17 @@ impl Agent {
             }
         }
 
+        if let Some(result) = self.tool_stubs.get(&tool_call.name).cloned() {
+            let limits = self.tool_output_limits().await;
+            let result = super::large_response_handler::process_tool_response(
+                result,
+                &tool_call.name,
+                &self.tool_output_store,
+                &limits,
+            );
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+
         if tool_call.name == PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME {
             let extension_name = tool_call
                 .arguments
@@ -223,8 +428,30 @@ impl Agent {
             return (request_id, Ok(ToolCallResult::from(result)));
         }
 
+        if tool_call.name == FINAL_OUTPUT_TOOL_NAME {
+            let result = match self.final_output_tool.lock().await.as_mut() {
+                Some(tool) => tool.execute(&tool_call.arguments),
+                None => Err(ToolError::NotFound(tool_call.name.clone())),
+            };
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+
+        let limits = self.tool_output_limits().await;
+        if tool_call.name == PLATFORM_READ_TOOL_OUTPUT_TOOL_NAME {
+            // Pages of stored outputs already fit, so they skip the large response handling
+            let result = self
+                .tool_output_store
+                .lock()
+                .unwrap()
+                .read(&tool_call.arguments, &limits);
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+
+        let sub_recipe_run = self.dispatch_sub_recipe(&tool_call, budget).await;
         let extension_manager = self.extension_manager.lock().await;
-        let result: ToolCallResult = if tool_call.name == PLATFORM_READ_RESOURCE_TOOL_NAME {
+        let result: ToolCallResult = if let Some(sub_recipe_run) = sub_recipe_run {
+            sub_recipe_run
+        } else if tool_call.name == PLATFORM_READ_RESOURCE_TOOL_NAME {
             // Check if the tool is read_resource and handle it separately
             ToolCallResult::from(
                 extension_manager
@@ -244,13 +471,13 @@ impl Agent {
             ToolCallResult::from(Err(ToolError::ExecutionError(
                 "Frontend tool execution required".to_string(),
             )))
-        } else if tool_call.name == ROUTER_VECTOR_SEARCH_TOOL_NAME {
+        } else if is_router_search_tool(&tool_call.name) {
             let selector = self.router_tool_selector.lock().await.clone();
             ToolCallResult::from(if let Some(selector) = selector {
                 selector.select_tools(tool_call.arguments.clone()).await
             } else {
                 Err(ToolError::ExecutionError(
-                    "Encountered vector search error.".to_string(),
+                    "Encountered tool search error.".to_string(),
                 ))
             })
         } else {
@@ -264,19 +491,30 @@ impl Agent {
             }
         };
 
+        let store = self.tool_output_store.clone();
+        let tool_name = tool_call.name;
         (
             request_id,
             Ok(ToolCallResult {
                 notification_stream: result.notification_stream,
-                result: Box::new(
-                    result
-                        .result
-                        .map(super::large_response_handler::process_tool_response),
-                ),
+                result: Box::new(result.result.map(move |output| {
+                    super::large_response_handler::process_tool_response(
+                        output, &tool_name, &store, &limits,
+                    )
+                })),
             }),
         )
     }
 
+    /// How large a tool output may get before it is stored, for the current model
+    async fn tool_output_limits(&self) -> ToolOutputLimits {
+        let context_limit = match self.provider().await {
+            Ok(provider) => provider.get_model_config().context_limit(),
+            Err(_) => DEFAULT_CONTEXT_LIMIT,
+        };
+        ToolOutputLimits::from_config(context_limit)
+    }
+
     pub(super) async fn manage_extensions(
         &self,
         action: String,
@@ -332,10 +570,10 @@ impl Agent {
             })
             .map_err(|e| ToolError::ExecutionError(e.to_string()));
 
-        // Update vector index if operation was successful and vector routing is enabled
+        // Update the router index if operation was successful and tool routing is enabled
         if result.is_ok() {
             let selector = self.router_tool_selector.lock().await.clone();
-            if ToolRouterIndexManager::vector_tool_router_enabled(&selector) {
+            if ToolRouterIndexManager::tool_router_enabled(&selector) {
                 if let Some(selector) = selector {
                     let vector_action = if action == "disable" { "remove" } else { "add" };
                     let extension_manager = self.extension_manager.lock().await;
@@ -350,7 +588,7 @@ impl Agent {
                         return (
                             request_id,
                             Err(ToolError::ExecutionError(format!(
-                                "Failed to update vector index: {}",
+                                "Failed to update tool router index: {}",
                                 e
                             ))),
                         );
@@ -396,9 +634,9 @@ impl Agent {
             }
         };
 
-        // If vector tool selection is enabled, index the tools
+        // If tool routing is enabled, index the tools
         let selector = self.router_tool_selector.lock().await.clone();
-        if ToolRouterIndexManager::vector_tool_router_enabled(&selector) {
+        if ToolRouterIndexManager::tool_router_enabled(&selector) {
             if let Some(selector) = selector {
                 let extension_manager = self.extension_manager.lock().await;
                 if let Err(e) = ToolRouterIndexManager::update_extension_tools(
@@ -438,6 +676,9 @@ impl Agent {
                 prefixed_tools.push(platform_tools::read_resource_tool());
                 prefixed_tools.push(platform_tools::list_resources_tool());
             }
+
+            // Always offered, so a reply that stores an output can read it back on its next turn
+            prefixed_tools.push(platform_tools::read_tool_output_tool());
         }
 
         prefixed_tools
@@ -448,12 +689,10 @@ impl Agent {
         strategy: Option<RouterToolSelectionStrategy>,
     ) -> Vec<Tool> {
         let mut prefixed_tools = vec![];
-        match strategy {
-            Some(RouterToolSelectionStrategy::Vector) => {
-                prefixed_tools.push(router_tools::vector_search_tool());
-            }
-            None => {}
+        if let Some(strategy) = &strategy {
+            prefixed_tools.push(router_tools::router_search_tool(strategy));
         }
+        prefixed_tools.push(platform_tools::read_tool_output_tool());
 
         // Get recent tool calls from router tool selector if available
         let selector = self.router_tool_selector.lock().await.clone();
@@ -482,9 +721,9 @@ impl Agent {
         let mut extension_manager = self.extension_manager.lock().await;
         extension_manager.remove_extension(name).await?;
 
-        // If vector tool selection is enabled, remove tools from the index
+        // If tool routing is enabled, remove tools from the index
         let selector = self.router_tool_selector.lock().await.clone();
-        if ToolRouterIndexManager::vector_tool_router_enabled(&selector) {
+        if ToolRouterIndexManager::tool_router_enabled(&selector) {
             if let Some(selector) = selector {
                 let extension_manager = self.extension_manager.lock().await;
                 ToolRouterIndexManager::update_extension_tools(
@@ -508,6 +747,17 @@ impl Agent {
             .expect("Failed to list extensions")
     }
 
+    /// Wait for the confirmation with the given request id, denying if the channel closes
+    async fn wait_for_confirmation(&self, request_id: &str) -> Permission {
+        let mut rx = self.confirmation_rx.lock().await;
+        while let Some((req_id, confirmation)) = rx.recv().await {
+            if req_id == request_id {
+                return confirmation.permission;
+            }
+        }
+        Permission::DenyOnce
+    }
+
     /// Handle a confirmation response for a tool request
     pub async fn handle_confirmation(
         &self,
@@ -528,18 +778,41 @@ impl Agent {
         let mut messages = messages.to_vec();
         let reply_span = tracing::Span::current();
 
-        // Load settings from config
-        let config = Config::global();
+        // Stored tool outputs belong to a session, so drop them when serving a different one
+        if let Some(session_config) = &session {
+            let session_file_path = crate::session::storage::get_path(session_config.id.clone());
+            self.tool_output_store
+                .lock()
+                .unwrap()
+                .start_session(session_file_path.to_string_lossy().to_string());
+        }
+
+        // Each reply has to provide its own final output rather than ending on an earlier one
+        if let Some(tool) = self.final_output_tool.lock().await.as_mut() {
+            tool.reset();
+        }
 
         // Setup tools and prompt
         let (mut tools, mut toolshim_tools, mut system_prompt) =
             self.prepare_tools_and_prompt().await?;
 
-        let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
+        let goose_mode = current_goose_mode(&self.goose_mode);
 
-        let (tools_with_readonly_annotation, tools_without_annotation) =
+        let (mut tools_with_readonly_annotation, mut tools_without_annotation) =
             Self::categorize_tools_by_annotation(&tools);
 
+        // Only load a tokenizer when the agent is allowed to compact on its own
+        let compaction_policy = CompactionPolicy::from_config();
+        let compaction = if compaction_policy == CompactionPolicy::Abort {
+            None
+        } else {
+            let model_config = self.provider().await?.get_model_config();
+            Some((
+                TokenCounter::new(model_config.tokenizer_name()),
+                compaction_threshold(model_config.context_limit()),
+            ))
+        };
+
         if let Some(content) = messages
             .last()
             .and_then(|msg| msg.content.first())
@@ -548,20 +821,158 @@ impl Agent {
             debug!("user_message" = &content);
         }
 
+        // Seed the token budget with what the session has already used in earlier replies
+        let mut accumulated_tokens = session.as_ref().and_then(|session_config| {
+            let session_file_path = crate::session::storage::get_path(session_config.id.clone());
+            crate::session::storage::read_metadata(&session_file_path)
+                .ok()
+                .and_then(|metadata| metadata.accumulated_total_tokens)
+        });
+
         Ok(Box::pin(async_stream::try_stream! {
             let _ = reply_span.enter();
+            let _sampling_listener =
+                SamplingListener::new(&self.sampling_listening, &self.sampling_rx);
+            // Compaction is attempted at most once per provider call to avoid looping
+            let mut compacted_this_turn = false;
+            // Compacting ahead of a provider call stops for the rest of the reply once it fails or
+            // can't get under the threshold, otherwise every call would compact again for nothing
+            let mut proactive_compaction = compaction.is_some();
+            let mut turns: u32 = 0;
+            let mut final_output_reminders: u32 = 0;
             loop {
-                match Self::generate_response_from_provider(
+                // Choosing tools with the model costs tokens outside of any turn
+                if let Some(session_config) = &session {
+                    for usage in self.record_tool_selection_usage(session_config).await? {
+                        accumulated_tokens =
+                            add_tokens(accumulated_tokens, usage.usage.total_tokens);
+                    }
+                }
+
+                if let Some(limit_message) = session
+                    .as_ref()
+                    .and_then(|session_config| session_config.limit_reached(turns, accumulated_tokens))
+                {
+                    debug!("Stopping reply after {} turns: {}", turns, limit_message);
+                    yield AgentEvent::Message(Message::assistant().with_text(limit_message));
+                    break;
+                }
+
+                let restarts = self.extension_manager.lock().await.check_extension_health().await;
+                for restart in restarts {
+                    yield AgentEvent::ExtensionRestarted {
+                        name: restart.name,
+                        reason: restart.reason,
+                    };
+                }
+
+                // Subscribed resources are re-read when they change, so they join the prompt per turn
+                let turn_system_prompt = match self
+                    .extension_manager
+                    .lock()
+                    .await
+                    .subscribed_resources_prompt()
+                    .await
+                {
+                    Some(resources) => format!("{}\n\n{}", system_prompt, resources),
+                    None => system_prompt.clone(),
+                };
+
+                let proactive = compaction.as_ref().filter(|_| proactive_compaction);
+                if let Some((token_counter, threshold)) = proactive {
+                    let estimate = token_counter.count_chat_tokens(&turn_system_prompt, &messages, &tools);
+                    if !compacted_this_turn && estimate > *threshold {
+                        debug!("Estimated {} tokens exceeds compaction threshold {}", estimate, threshold);
+                        compacted_this_turn = true;
+                        match self.compact_context(&messages, compaction_policy).await {
+                            Ok(compacted) => {
+                                messages = compacted;
+                                let estimate = token_counter.count_chat_tokens(
+                                    &turn_system_prompt,
+                                    &messages,
+                                    &tools,
+                                );
+                                if estimate > *threshold {
+                                    debug!(
+                                        "Still {} tokens after compacting, only compacting on overflow now",
+                                        estimate
+                                    );
+                                    proactive_compaction = false;
+                                }
+                                yield AgentEvent::ContextCompacted {
+                                    policy: compaction_policy,
+                                    messages: messages.clone(),
+                                };
+                            }
+                            Err(e) => {
+                                warn!("Failed to compact context ahead of the provider call: {}", e);
+                                proactive_compaction = false;
+                            }
+                        }
+                    }
+                }
+
+                let mut completion = Err(ProviderError::ExecutionError(
+                    "Provider stream ended without a response".to_string(),
+                ));
+                match Self::stream_response_from_provider(
                     self.provider().await?,
-                    &system_prompt,
+                    &turn_system_prompt,
                     &messages,
                     &tools,
                     &toolshim_tools,
                 ).await {
+                    Ok(mut chunks) => {
+                        loop {
+                            // Extensions can ask to use the model at any point, not only
+                            // while their tools run
+                            let next = {
+                                let mut sampling_rx = self.sampling_rx.lock().await;
+                                tokio::select! {
+                                    chunk = chunks.next() => Either::Left(chunk),
+                                    Some(approval) = sampling_rx.recv() => Either::Right(approval),
+                                }
+                            };
+                            let chunk = match next {
+                                Either::Left(Some(chunk)) => chunk,
+                                Either::Left(None) => break,
+                                Either::Right(approval) => {
+                                    yield AgentEvent::Message(approval.confirmation_message());
+                                    let permission = self.wait_for_confirmation(&approval.id).await;
+                                    let _ = approval.respond_to.send(permission);
+                                    continue;
+                                }
+                            };
+                            match chunk {
+                                Ok(CompletionChunk::Delta(delta)) => {
+                                    yield AgentEvent::MessageDelta(delta);
+                                }
+                                Ok(CompletionChunk::Done(response, usage)) => {
+                                    completion = Ok((response, usage));
+                                    break;
+                                }
+                                Err(e) => {
+                                    completion = Err(e);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => completion = Err(e),
+                }
+
+                match completion {
                     Ok((response, usage)) => {
-                        // record usage for the session in the session file
+                        compacted_this_turn = false;
+                        turns += 1;
+
+                        // record usage for the session in the session file, and keep a running
+                        // total here so the budget also holds for sessions that aren't saved
                         if let Some(session_config) = session.clone() {
-                            Self::update_session_metrics(session_config, &usage, messages.len()).await?;
+                            Self::update_session_metrics(session_config, &usage, messages.len())
+                                .await?;
+                            accumulated_tokens =
+                                add_tokens(accumulated_tokens, usage.usage.total_tokens);
                         }
 
                         // categorize the type of requests we need to handle
@@ -597,6 +1008,21 @@ impl Agent {
 
                         let num_tool_requests = frontend_requests.len() + remaining_requests.len();
                         if num_tool_requests == 0 {
+                            // A run with a response schema only ends once the final output is in
+                            let needs_final_output = self
+                                .final_output_tool
+                                .lock()
+                                .await
+                                .as_ref()
+                                .is_some_and(|tool| tool.final_output().is_none());
+                            if needs_final_output && final_output_reminders < MAX_FINAL_OUTPUT_REMINDERS {
+                                final_output_reminders += 1;
+                                let reminder = Message::user().with_text(FINAL_OUTPUT_CONTINUATION_MESSAGE);
+                                yield AgentEvent::Message(reminder.clone());
+                                messages.push(response);
+                                messages.push(reminder);
+                                continue;
+                            }
                             break;
                         }
 
@@ -640,13 +1066,23 @@ impl Agent {
                                 &mut permission_manager,
                                 self.provider().await?).await;
 
+                            // Sub-recipes run within what is left of this run's limits
+                            let budget = session
+                                .as_ref()
+                                .map(|session_config| {
+                                    session_config.remaining_budget(accumulated_tokens)
+                                })
+                                .unwrap_or_default();
+
                             // Handle pre-approved and read-only tools in parallel
                             let mut tool_futures: Vec<(String, ToolStream)> = Vec::new();
 
                             // Skip the confirmation for approved tools
                             for request in &permission_check_result.approved {
                                 if let Ok(tool_call) = request.tool_call.clone() {
-                                    let (req_id, tool_result) = self.dispatch_tool_call(tool_call, request.id.clone()).await;
+                                    let (req_id, tool_result) = self
+                                        .dispatch_tool_call(tool_call, request.id.clone(), budget)
+                                        .await;
 
                                     tool_futures.push((req_id, match tool_result {
                                         Ok(result) => tool_stream(
@@ -677,7 +1113,8 @@ impl Agent {
                                 &permission_check_result.needs_approval,
                                 tool_futures_arc.clone(),
                                 &mut permission_manager,
-                                message_tool_response.clone()
+                                message_tool_response.clone(),
+                                budget,
                             );
 
                             // We have a stream of tool_approval_requests to handle
@@ -706,7 +1143,26 @@ impl Agent {
 
                             let mut all_install_successful = true;
 
-                            while let Some((request_id, item)) = combined.next().await {
+                            loop {
+                                // Extensions may ask to use the model while their tools run,
+                                // surface those requests for approval as they arrive
+                                let next = {
+                                    let mut sampling_rx = self.sampling_rx.lock().await;
+                                    tokio::select! {
+                                        item = combined.next() => Either::Left(item),
+                                        Some(approval) = sampling_rx.recv() => Either::Right(approval),
+                                    }
+                                };
+                                let (request_id, item) = match next {
+                                    Either::Left(Some(next)) => next,
+                                    Either::Left(None) => break,
+                                    Either::Right(approval) => {
+                                        yield AgentEvent::Message(approval.confirmation_message());
+                                        let permission = self.wait_for_confirmation(&approval.id).await;
+                                        let _ = approval.respond_to.send(permission);
+                                        continue;
+                                    }
+                                };
                                 match item {
                                     ToolStreamItem::Result(output) => {
                                         if enable_extension_request_ids.contains(&request_id) && output.is_err(){
@@ -724,6 +1180,8 @@ impl Agent {
                             // Update system prompt and tools if installations were successful
                             if all_install_successful {
                                 (tools, toolshim_tools, system_prompt) = self.prepare_tools_and_prompt().await?;
+                                (tools_with_readonly_annotation, tools_without_annotation) =
+                                    Self::categorize_tools_by_annotation(&tools);
                             }
                         }
 
@@ -732,6 +1190,33 @@ impl Agent {
 
                         messages.push(response);
                         messages.push(final_message_tool_resp);
+
+                        if self.has_final_output().await {
+                            break;
+                        }
+                    },
+                    Err(ProviderError::ContextLengthExceeded(_))
+                        if compaction_policy != CompactionPolicy::Abort && !compacted_this_turn =>
+                    {
+                        compacted_this_turn = true;
+                        match self.compact_context(&messages, compaction_policy).await {
+                            Ok(compacted) => {
+                                messages = compacted;
+                                yield AgentEvent::ContextCompacted {
+                                    policy: compaction_policy,
+                                    messages: messages.clone(),
+                                };
+                                // Retry the same turn with the compacted conversation
+                                continue;
+                            }
+                            Err(e) => {
+                                error!("Failed to compact context: {}", e);
+                                yield AgentEvent::Message(Message::assistant().with_context_length_exceeded(
+                                    "The context length of the model has been exceeded. Please start a new session and try again.",
+                                ));
+                                break;
+                            }
+                        }
                     },
                     Err(ProviderError::ContextLengthExceeded(_)) => {
                         // At this point, the last message should be a user message
@@ -769,18 +1254,32 @@ impl Agent {
         Ok(())
     }
 
-    async fn update_router_tool_selector(&self, provider: Arc<dyn Provider>) -> Result<()> {
-        let config = Config::global();
-        let router_tool_selection_strategy = config
-            .get_param("GOOSE_ROUTER_TOOL_SELECTION_STRATEGY")
-            .unwrap_or_else(|_| "default".to_string());
-
-        let strategy = match router_tool_selection_strategy.to_lowercase().as_str() {
-            "vector" => Some(RouterToolSelectionStrategy::Vector),
-            _ => None,
-        };
+    /// Run in the given mode instead of GOOSE_MODE, or follow GOOSE_MODE again with `None`
+    pub async fn set_goose_mode(&self, goose_mode: Option<String>) {
+        *self.goose_mode.write().unwrap() = goose_mode.clone();
+        self.prompt_manager.lock().await.set_goose_mode(goose_mode);
+    }
 
-        if let Some(strategy) = strategy {
+    /// The mode the agent runs in instead of GOOSE_MODE, if it was given one
+    pub fn goose_mode_override(&self) -> Option<String> {
+        self.goose_mode.read().unwrap().clone()
+    }
+
+    /// Switch to the provider, model and mode of a recipe's settings. The agent keeps its
+    /// provider unless the settings choose a provider or model, or it doesn't have one yet.
+    pub async fn apply_recipe_settings(&self, settings: &Settings) -> Result<()> {
+        let goose_mode = settings.goose_mode()?;
+        if settings.configures_provider() || self.provider().await.is_err() {
+            self.update_provider(settings.create_provider()?).await?;
+        }
+        if goose_mode.is_some() {
+            self.set_goose_mode(goose_mode).await;
+        }
+        Ok(())
+    }
+
+    async fn update_router_tool_selector(&self, provider: Arc<dyn Provider>) -> Result<()> {
+        if let Some(strategy) = RouterToolSelectionStrategy::from_config() {
             let table_name = generate_table_id();
             let selector = create_tool_selector(Some(strategy), provider, table_name)
                 .await
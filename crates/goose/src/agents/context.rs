@@ -1,11 +1,14 @@
-use anyhow::Ok;
+use anyhow::{anyhow, Ok};
+use mcp_core::Role;
 
 use crate::message::Message;
 use crate::token_counter::TokenCounter;
 
 use crate::context_mgmt::summarize::summarize_messages;
 use crate::context_mgmt::truncate::{truncate_messages, OldestFirstTruncation};
-use crate::context_mgmt::{estimate_target_context_limit, get_messages_token_counts};
+use crate::context_mgmt::{
+    estimate_target_context_limit, get_messages_token_counts, CompactionPolicy,
+};
 
 use super::super::agents::Agent;
 
@@ -60,4 +63,178 @@ impl Agent {
 
         Ok((new_messages, new_token_counts))
     }
+
+    /// Compacts the conversation in the middle of a reply so the current turn can be retried.
+    ///
+    /// Unlike `truncate_context` and `summarize_context`, this keeps the user prompt that started
+    /// the current turn and everything after it (tool requests and responses), so the provider
+    /// can be called again without losing progress. Only the earlier history is compacted.
+    pub(super) async fn compact_context(
+        &self,
+        messages: &[Message],
+        policy: CompactionPolicy,
+    ) -> Result<Vec<Message>, anyhow::Error> {
+        let turn_start = current_turn_start(messages)
+            .ok_or_else(|| anyhow!("No user prompt found to anchor compaction"))?;
+
+        let provider = self.provider().await?;
+        let token_counter = TokenCounter::new(provider.get_model_config().tokenizer_name());
+        let current_turn_tokens: usize =
+            get_messages_token_counts(&token_counter, &messages[turn_start + 1..])
+                .iter()
+                .sum();
+        let target_context_limit =
+            estimate_target_context_limit(provider.clone()).saturating_sub(current_turn_tokens);
+
+        let mut compacted = match policy {
+            CompactionPolicy::Truncate => {
+                // The prompt is kept as the last message so truncation never drops it
+                let history = &messages[..=turn_start];
+                let token_counts = get_messages_token_counts(&token_counter, history);
+                let (truncated, _) = truncate_messages(
+                    history,
+                    &token_counts,
+                    target_context_limit,
+                    &OldestFirstTruncation,
+                )?;
+                truncated
+            }
+            CompactionPolicy::Summarize => {
+                let history = &messages[..turn_start];
+                if history.is_empty() {
+                    return Err(anyhow!("No earlier messages to summarize"));
+                }
+                let (mut summarized, _) =
+                    summarize_messages(provider, history, &token_counter, target_context_limit)
+                        .await?;
+
+                // The summary is a user message, keep the roles alternating before the prompt
+                if summarized.last().is_some_and(|m| m.role == Role::User) {
+                    summarized.push(Message::assistant().with_text(
+                        "I summarized our earlier conversation to stay within the context limit.",
+                    ));
+                }
+                summarized.push(messages[turn_start].clone());
+                summarized
+            }
+            CompactionPolicy::Abort => {
+                return Err(anyhow!("Context compaction is disabled"));
+            }
+        };
+
+        compacted.extend_from_slice(&messages[turn_start + 1..]);
+        Ok(compacted)
+    }
+}
+
+/// Index of the user prompt that started the current turn: the last user message
+/// made only of text (tool responses are also sent with the user role).
+fn current_turn_start(messages: &[Message]) -> Option<usize> {
+    messages
+        .iter()
+        .rposition(|m| m.role == Role::User && m.has_only_text_content())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::replay::{ReplayProvider, ReplayResponse};
+    use mcp_core::{Content, ToolCall};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    /// Earlier history followed by a turn that is still running a tool
+    fn conversation(history_text: &str) -> Vec<Message> {
+        vec![
+            Message::user().with_text(format!("first {}", history_text)),
+            Message::assistant().with_text(format!("answer {}", history_text)),
+            Message::user().with_text(format!("second {}", history_text)),
+            Message::assistant().with_text(format!("another answer {}", history_text)),
+            Message::user().with_text("now"),
+            Message::assistant()
+                .with_tool_request("1", Ok(ToolCall::new("read", json!({"path": "a.txt"})))),
+            Message::user().with_tool_response("1", Ok(vec![Content::text("contents")])),
+        ]
+    }
+
+    async fn agent_with(provider: Arc<ReplayProvider>) -> Agent {
+        let agent = Agent::new();
+        agent.update_provider(provider).await.unwrap();
+        agent
+    }
+
+    #[tokio::test]
+    async fn test_compact_context_truncates_history_before_the_turn() {
+        let provider = Arc::new(ReplayProvider::new(vec![]).with_context_limit(20_000));
+        let agent = agent_with(provider).await;
+        let messages = conversation(&"word ".repeat(2_000));
+
+        let compacted = agent
+            .compact_context(&messages, CompactionPolicy::Truncate)
+            .await
+            .unwrap();
+
+        assert!(compacted.len() < messages.len());
+        assert!(!compacted.contains(&messages[0]));
+        assert!(compacted.ends_with(&messages[4..]));
+    }
+
+    #[tokio::test]
+    async fn test_compact_context_summarizes_history_before_the_turn() {
+        let provider = Arc::new(ReplayProvider::new(vec![ReplayResponse {
+            text: Some("We talked about a.txt".to_string()),
+            tool_calls: vec![],
+        }]));
+        let agent = agent_with(provider.clone()).await;
+        let messages = conversation("");
+
+        let compacted = agent
+            .compact_context(&messages, CompactionPolicy::Summarize)
+            .await
+            .unwrap();
+
+        assert_eq!(compacted.len(), 5);
+        assert_eq!(compacted[0].as_concat_text(), "We talked about a.txt");
+        assert_eq!(compacted[1].role, Role::Assistant);
+        assert!(compacted.ends_with(&messages[4..]));
+        assert_eq!(provider.remaining(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_compact_context_needs_a_prompt_and_a_policy() {
+        let agent = agent_with(Arc::new(ReplayProvider::new(vec![]))).await;
+        let messages = conversation("");
+
+        assert!(agent
+            .compact_context(&messages, CompactionPolicy::Abort)
+            .await
+            .is_err());
+        assert!(agent
+            .compact_context(&messages[4..], CompactionPolicy::Summarize)
+            .await
+            .is_err());
+        assert!(agent
+            .compact_context(&[messages[3].clone()], CompactionPolicy::Truncate)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_current_turn_start_skips_tool_responses() {
+        let messages = vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("answer"),
+            Message::user().with_text("second"),
+            Message::assistant()
+                .with_tool_request("1", Ok(ToolCall::new("read", json!({"path": "a.txt"})))),
+            Message::user().with_tool_response("1", Ok(vec![Content::text("contents")])),
+        ];
+        assert_eq!(current_turn_start(&messages), Some(2));
+    }
+
+    #[test]
+    fn test_current_turn_start_without_prompt() {
+        let messages = vec![Message::assistant().with_text("hello")];
+        assert_eq!(current_turn_start(&messages), None);
+    }
 }
@@ -2,23 +2,26 @@ use anyhow::Result;
 use chrono::{DateTime, TimeZone, Utc};
 use futures::stream::{FuturesUnordered, StreamExt};
 use futures::{future, FutureExt};
-use mcp_core::protocol::GetPromptResult;
+use mcp_core::protocol::{GetPromptResult, InitializeResult, JsonRpcMessage};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::LazyLock;
-use std::time::Duration;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Mutex};
 use tokio::task;
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{error, warn};
 
 use super::extension::{ExtensionConfig, ExtensionError, ExtensionInfo, ExtensionResult, ToolInfo};
+use super::sampling::SamplingContext;
 use super::tool_execution::ToolCallResult;
 use crate::agents::extension::Envs;
 use crate::config::{Config, ExtensionConfigManager};
 use crate::prompt_template;
-use mcp_client::client::{ClientCapabilities, ClientInfo, McpClient, McpClientTrait};
-use mcp_client::transport::{SseTransport, StdioTransport, Transport};
+use mcp_client::client::{
+    ClientCapabilities, ClientInfo, McpClient, McpClientTrait, SamplingCapability,
+};
+use mcp_client::transport::{SseTransport, StdioTransport, StreamableHttpTransport, Transport};
 use mcp_core::{prompt::Prompt, Content, Tool, ToolCall, ToolError};
 use serde_json::Value;
 
@@ -29,11 +32,33 @@ static DEFAULT_TIMESTAMP: LazyLock<DateTime<Utc>> =
 
 type McpClientBox = Arc<Mutex<Box<dyn McpClientTrait>>>;
 
+/// How often running stdio extensions are pinged to check they still answer
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Consecutive failed restarts after which an extension is left alone
+const MAX_RESTART_ATTEMPTS: u32 = 3;
+
 /// Manages Goose extensions / MCP clients and their interactions
 pub struct ExtensionManager {
     clients: HashMap<String, McpClientBox>,
     instructions: HashMap<String, String>,
     resource_capable_extensions: HashSet<String>,
+    sampling: Option<SamplingContext>,
+    /// Resources the agent subscribed to, keyed by (extension, uri), with their latest
+    /// text content or None when the extension reported a change that was not yet re-read
+    subscribed_resources: Arc<Mutex<HashMap<(String, String), Option<String>>>>,
+    /// The configuration each extension was started from, used to restart it
+    configs: HashMap<String, ExtensionConfig>,
+    /// Restart attempts that failed in a row, per extension
+    failed_restarts: HashMap<String, u32>,
+    last_health_check: Option<Instant>,
+}
+
+/// An extension that was restarted after its server died or stopped answering
+#[derive(Debug, Clone)]
+pub struct ExtensionRestart {
+    pub name: String,
+    pub reason: String,
 }
 
 /// A flattened representation of a resource used by the agent to prepare inference
@@ -83,6 +108,33 @@ fn normalize(input: String) -> String {
     result.to_lowercase()
 }
 
+/// Replaces `${NAME}` references in a value with entries from the extension's env vars,
+/// leaving unknown references untouched
+fn substitute_env_vars(value: &str, envs: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let name = &after[..end];
+                match envs.get(name) {
+                    Some(env_value) => result.push_str(env_value),
+                    None => result.push_str(&rest[start..start + 2 + end + 1]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                result.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
 pub fn get_parameter_names(tool: &Tool) -> Vec<String> {
     tool.input_schema
         .get("properties")
@@ -104,9 +156,19 @@ impl ExtensionManager {
             clients: HashMap::new(),
             instructions: HashMap::new(),
             resource_capable_extensions: HashSet::new(),
+            sampling: None,
+            subscribed_resources: Arc::new(Mutex::new(HashMap::new())),
+            configs: HashMap::new(),
+            failed_restarts: HashMap::new(),
+            last_health_check: None,
         }
     }
 
+    /// Let extensions added from now on use the agent's model through MCP sampling
+    pub(crate) fn set_sampling_context(&mut self, sampling: SamplingContext) {
+        self.sampling = Some(sampling);
+    }
+
     pub fn supports_resources(&self) -> bool {
         !self.resource_capable_extensions.is_empty()
     }
@@ -114,9 +176,42 @@ impl ExtensionManager {
     /// Add a new MCP extension based on the provided client type
     // TODO IMPORTANT need to ensure this times out if the extension command is broken!
     pub async fn add_extension(&mut self, config: ExtensionConfig) -> ExtensionResult<()> {
-        let config_name = config.key().to_string();
-        let sanitized_name = normalize(config_name.clone());
+        let sanitized_name = normalize(config.key().to_string());
+        let (mut client, init_result) = self.connect_extension(&sanitized_name, &config).await?;
+
+        if let Some(instructions) = init_result.instructions {
+            self.instructions
+                .insert(sanitized_name.clone(), instructions);
+        }
+
+        if init_result.capabilities.resources.is_some() {
+            self.resource_capable_extensions
+                .insert(sanitized_name.clone());
+        }
+
+        let supports_subscribe = init_result
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false);
+        if supports_subscribe {
+            self.watch_resource_updates(&sanitized_name, client.subscribe().await);
+        }
 
+        self.clients
+            .insert(sanitized_name.clone(), Arc::new(Mutex::new(client)));
+        self.configs.insert(sanitized_name, config);
+
+        Ok(())
+    }
+
+    /// Start the extension's server and initialize a client connected to it
+    async fn connect_extension(
+        &self,
+        sanitized_name: &str,
+        config: &ExtensionConfig,
+    ) -> ExtensionResult<(Box<dyn McpClientTrait>, InitializeResult)> {
         /// Helper function to merge environment variables from direct envs and keychain-stored env_keys
         async fn merge_environments(
             envs: &Envs,
@@ -174,7 +269,12 @@ impl ExtensionManager {
             Ok(all_envs)
         }
 
-        let mut client: Box<dyn McpClientTrait> = match &config {
+        let sampling_handler = self
+            .sampling
+            .as_ref()
+            .map(|sampling| sampling.handler(sanitized_name));
+
+        let mut client: Box<dyn McpClientTrait> = match config {
             ExtensionConfig::Sse {
                 uri,
                 envs,
@@ -182,15 +282,42 @@ impl ExtensionManager {
                 timeout,
                 ..
             } => {
-                let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
+                let all_envs = merge_environments(envs, env_keys, sanitized_name).await?;
                 let transport = SseTransport::new(uri, all_envs);
                 let handle = transport.start().await?;
                 Box::new(
-                    McpClient::connect(
+                    McpClient::connect_with_sampling(
+                        handle,
+                        Duration::from_secs(
+                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
+                        ),
+                        sampling_handler.clone(),
+                    )
+                    .await?,
+                )
+            }
+            ExtensionConfig::StreamableHttp {
+                uri,
+                envs,
+                env_keys,
+                headers,
+                timeout,
+                ..
+            } => {
+                let all_envs = merge_environments(envs, env_keys, sanitized_name).await?;
+                let headers = headers
+                    .iter()
+                    .map(|(key, value)| (key.clone(), substitute_env_vars(value, &all_envs)))
+                    .collect();
+                let transport = StreamableHttpTransport::new(uri, all_envs).with_headers(headers);
+                let handle = transport.start().await?;
+                Box::new(
+                    McpClient::connect_with_sampling(
                         handle,
                         Duration::from_secs(
                             timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
                         ),
+                        sampling_handler.clone(),
                     )
                     .await?,
                 )
@@ -203,15 +330,16 @@ impl ExtensionManager {
                 timeout,
                 ..
             } => {
-                let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
+                let all_envs = merge_environments(envs, env_keys, sanitized_name).await?;
                 let transport = StdioTransport::new(cmd, args.to_vec(), all_envs);
                 let handle = transport.start().await?;
                 Box::new(
-                    McpClient::connect(
+                    McpClient::connect_with_sampling(
                         handle,
                         Duration::from_secs(
                             timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
                         ),
+                        sampling_handler.clone(),
                     )
                     .await?,
                 )
@@ -234,11 +362,12 @@ impl ExtensionManager {
                 );
                 let handle = transport.start().await?;
                 Box::new(
-                    McpClient::connect(
+                    McpClient::connect_with_sampling(
                         handle,
                         Duration::from_secs(
                             timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
                         ),
+                        sampling_handler.clone(),
                     )
                     .await?,
                 )
@@ -246,30 +375,104 @@ impl ExtensionManager {
             _ => unreachable!(),
         };
 
-        // Initialize the client with default capabilities
+        // Initialize the client, advertising sampling when we can answer it
         let info = ClientInfo {
             name: "goose".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
-        let capabilities = ClientCapabilities::default();
+        let capabilities = ClientCapabilities {
+            sampling: sampling_handler.map(|_| SamplingCapability {}),
+        };
 
         let init_result = client
             .initialize(info, capabilities)
             .await
             .map_err(|e| ExtensionError::Initialization(config.clone(), e))?;
 
-        if let Some(instructions) = init_result.instructions {
-            self.instructions
-                .insert(sanitized_name.clone(), instructions);
+        Ok((client, init_result))
+    }
+
+    /// Restart stdio extensions whose process exited, and every HEALTH_CHECK_INTERVAL ping
+    /// them to catch servers that are still running but no longer answer. Extensions that
+    /// fail to come back are retried on later checks, up to MAX_RESTART_ATTEMPTS in a row.
+    pub async fn check_extension_health(&mut self) -> Vec<ExtensionRestart> {
+        let ping_due = self
+            .last_health_check
+            .is_none_or(|last| last.elapsed() >= HEALTH_CHECK_INTERVAL);
+        if ping_due {
+            self.last_health_check = Some(Instant::now());
         }
 
-        if init_result.capabilities.resources.is_some() {
-            self.resource_capable_extensions
-                .insert(sanitized_name.clone());
+        let mut unhealthy = Vec::new();
+        for (name, config) in &self.configs {
+            if !is_restartable(config) {
+                continue;
+            }
+            let Some(client) = self.clients.get(name) else {
+                continue;
+            };
+            let client = client.lock().await;
+            if client.is_closed() {
+                unhealthy.push((name.clone(), "the extension process exited".to_string()));
+            } else if ping_due {
+                if let Err(e) = client.ping().await {
+                    unhealthy.push((
+                        name.clone(),
+                        format!("the extension stopped answering: {}", e),
+                    ));
+                }
+            }
         }
 
-        self.clients
-            .insert(sanitized_name.clone(), Arc::new(Mutex::new(client)));
+        let mut restarts = Vec::new();
+        for (name, reason) in unhealthy {
+            let attempts = self.failed_restarts.get(&name).copied().unwrap_or(0);
+            if attempts >= MAX_RESTART_ATTEMPTS {
+                continue;
+            }
+
+            match self.restart_extension(&name).await {
+                Ok(()) => {
+                    warn!("Restarted extension {} because {}", name, reason);
+                    self.failed_restarts.remove(&name);
+                    restarts.push(ExtensionRestart { name, reason });
+                }
+                Err(e) => {
+                    error!("Failed to restart extension {}: {}", name, e);
+                    self.failed_restarts.insert(name, attempts + 1);
+                }
+            }
+        }
+        restarts
+    }
+
+    /// Start a fresh process for the extension from its original configuration
+    async fn restart_extension(&mut self, name: &str) -> ExtensionResult<()> {
+        let config = self.configs.get(name).cloned().ok_or_else(|| {
+            ExtensionError::SetupError(format!("No configuration for extension {}", name))
+        })?;
+
+        self.add_extension(config).await?;
+
+        // A server that starts but cannot list its tools is no better than a dead one
+        if let Some(client) = self.clients.get(name) {
+            client.lock().await.list_tools(None).await?;
+        }
+
+        // The new process knows nothing about our subscriptions
+        let uris = self
+            .subscribed_resources
+            .lock()
+            .await
+            .keys()
+            .filter(|(extension_name, _)| extension_name == name)
+            .map(|(_, uri)| uri.clone())
+            .collect::<Vec<_>>();
+        for uri in uris {
+            if let Err(e) = self.subscribe_resource(name, &uri).await {
+                warn!("Failed to resubscribe to {} after restart: {}", uri, e);
+            }
+        }
 
         Ok(())
     }
@@ -291,11 +494,134 @@ impl ExtensionManager {
         let sanitized_name = normalize(name.to_string());
 
         self.clients.remove(&sanitized_name);
+        self.configs.remove(&sanitized_name);
+        self.failed_restarts.remove(&sanitized_name);
         self.instructions.remove(&sanitized_name);
         self.resource_capable_extensions.remove(&sanitized_name);
+        self.subscribed_resources
+            .lock()
+            .await
+            .retain(|(extension_name, _), _| extension_name != &sanitized_name);
+        Ok(())
+    }
+
+    /// Mark an extension's subscribed resources stale when it reports that they changed,
+    /// so they are re-read before the next call to the provider
+    fn watch_resource_updates(
+        &self,
+        extension_name: &str,
+        mut notifications: mpsc::Receiver<JsonRpcMessage>,
+    ) {
+        let subscribed_resources = Arc::clone(&self.subscribed_resources);
+        let extension_name = extension_name.to_string();
+        task::spawn(async move {
+            while let Some(message) = notifications.recv().await {
+                let Some(uri) = resource_updated_uri(&message) else {
+                    continue;
+                };
+                if let Some(content) = subscribed_resources
+                    .lock()
+                    .await
+                    .get_mut(&(extension_name.clone(), uri))
+                {
+                    *content = None;
+                }
+            }
+        });
+    }
+
+    /// Subscribe to updates of a resource, so its latest content stays in the agent's context
+    pub async fn subscribe_resource(
+        &self,
+        extension_name: &str,
+        uri: &str,
+    ) -> Result<(), ToolError> {
+        let client = self.clients.get(extension_name).ok_or_else(|| {
+            ToolError::InvalidParameters(format!("Extension {} is not valid", extension_name))
+        })?;
+
+        client
+            .lock()
+            .await
+            .subscribe_resource(uri)
+            .await
+            .map_err(|e| {
+                ToolError::ExecutionError(format!(
+                    "Could not subscribe to resource with uri {}: {}",
+                    uri, e
+                ))
+            })?;
+
+        self.subscribed_resources
+            .lock()
+            .await
+            .insert((extension_name.to_string(), uri.to_string()), None);
         Ok(())
     }
 
+    /// Render the latest content of subscribed resources for the system prompt, re-reading
+    /// any that changed since the last turn
+    pub async fn subscribed_resources_prompt(&self) -> Option<String> {
+        // Resources are read without holding the lock, so update notifications that arrive
+        // meanwhile aren't blocked behind the reads
+        let stale = {
+            let subscribed_resources = self.subscribed_resources.lock().await;
+            if subscribed_resources.is_empty() {
+                return None;
+            }
+            subscribed_resources
+                .iter()
+                .filter(|(_, content)| content.is_none())
+                .map(|(key, _)| key.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let mut refreshed = Vec::new();
+        for (extension_name, uri) in stale {
+            match self
+                .read_resource_from_extension(&uri, &extension_name)
+                .await
+            {
+                Ok(contents) => {
+                    let content = contents
+                        .iter()
+                        .filter_map(|c| c.as_text())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    refreshed.push(((extension_name, uri), content));
+                }
+                Err(e) => warn!("Failed to refresh subscribed resource {}: {}", uri, e),
+            }
+        }
+
+        let mut subscribed_resources = self.subscribed_resources.lock().await;
+        for (key, content) in refreshed {
+            // Skip resources unsubscribed while they were being read
+            if let Some(entry) = subscribed_resources.get_mut(&key) {
+                *entry = Some(content);
+            }
+        }
+
+        let mut entries = subscribed_resources
+            .iter()
+            .filter_map(|((extension_name, uri), content)| {
+                content
+                    .as_ref()
+                    .map(|content| (extension_name, uri, content))
+            })
+            .collect::<Vec<_>>();
+        entries.sort();
+
+        let mut prompt = String::from(
+            "# Subscribed Resources\n\nThe latest content of the resources you subscribed to. \
+             It is refreshed whenever the extension reports a change.\n",
+        );
+        for (extension_name, uri, content) in entries {
+            prompt.push_str(&format!("\n## {} ({})\n{}\n", uri, extension_name, content));
+        }
+        Some(prompt)
+    }
+
     pub async fn suggest_disable_extensions_prompt(&self) -> Value {
         let enabled_extensions_count = self.clients.len();
 
@@ -462,13 +788,39 @@ impl ExtensionManager {
             .ok_or_else(|| ToolError::InvalidParameters("Missing 'uri' parameter".to_string()))?;
 
         let extension_name = params.get("extension_name").and_then(|v| v.as_str());
+        let subscribe = params
+            .get("subscribe")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let (extension_name, mut result) = self.find_resource(uri, extension_name).await?;
+
+        if subscribe {
+            // A failed subscription should not hide the content we already read
+            match self.subscribe_resource(&extension_name, uri).await {
+                Ok(()) => result.push(Content::text(format!(
+                    "Subscribed to {}. Its latest content will be kept in your context.",
+                    uri
+                ))),
+                Err(e) => result.push(Content::text(e.to_string())),
+            }
+        }
 
+        Ok(result)
+    }
+
+    /// Read a resource from the named extension, or from the first extension that has it
+    async fn find_resource(
+        &self,
+        uri: &str,
+        extension_name: Option<&str>,
+    ) -> Result<(String, Vec<Content>), ToolError> {
         // If extension name is provided, we can just look it up
-        if extension_name.is_some() {
+        if let Some(extension_name) = extension_name {
             let result = self
-                .read_resource_from_extension(uri, extension_name.unwrap())
+                .read_resource_from_extension(uri, extension_name)
                 .await?;
-            return Ok(result);
+            return Ok((extension_name.to_string(), result));
         }
 
         // If extension name is not provided, we need to search for the resource across all extensions
@@ -478,7 +830,7 @@ impl ExtensionManager {
         for extension_name in self.resource_capable_extensions.iter() {
             let result = self.read_resource_from_extension(uri, extension_name).await;
             match result {
-                Ok(result) => return Ok(result),
+                Ok(result) => return Ok((extension_name.clone(), result)),
                 Err(_) => continue,
             }
         }
@@ -546,29 +898,41 @@ impl ExtensionManager {
         })?;
 
         let client_guard = client.lock().await;
-        client_guard
-            .list_resources(None)
+        let resources = client_guard.list_resources(None).await.map_err(|e| {
+            ToolError::ExecutionError(format!(
+                "Unable to list resources for {}, {:?}",
+                extension_name, e
+            ))
+        })?;
+
+        // Templates are optional, servers without them just answer with an error
+        let templates = client_guard
+            .list_resource_templates(None)
             .await
-            .map_err(|e| {
-                ToolError::ExecutionError(format!(
-                    "Unable to list resources for {}, {:?}",
-                    extension_name, e
-                ))
-            })
-            .map(|lr| {
-                let resource_list = lr
-                    .resources
-                    .into_iter()
-                    .map(|r| format!("{} - {}, uri: ({})", extension_name, r.name, r.uri))
-                    .collect::<Vec<String>>()
-                    .join("\n");
+            .map(|lt| lt.resource_templates)
+            .unwrap_or_default();
+
+        let resource_list = resources
+            .resources
+            .into_iter()
+            .map(|r| format!("{} - {}, uri: ({})", extension_name, r.name, r.uri))
+            .chain(templates.into_iter().map(|t| {
+                format!(
+                    "{} - {}, uri template: ({})",
+                    extension_name, t.name, t.uri_template
+                )
+            }))
+            .collect::<Vec<String>>()
+            .join("\n");
 
-                vec![Content::text(resource_list)]
-            })
+        Ok(vec![Content::text(resource_list)])
     }
 
     pub async fn list_resources(&self, params: Value) -> Result<Vec<Content>, ToolError> {
-        let extension = params.get("extension").and_then(|v| v.as_str());
+        let extension = params
+            .get("extension_name")
+            .or_else(|| params.get("extension"))
+            .and_then(|v| v.as_str());
 
         match extension {
             Some(extension_name) => {
@@ -752,6 +1116,9 @@ impl ExtensionManager {
                     ExtensionConfig::Sse {
                         description, name, ..
                     }
+                    | ExtensionConfig::StreamableHttp {
+                        description, name, ..
+                    }
                     | ExtensionConfig::Stdio {
                         description, name, ..
                     } => {
@@ -799,19 +1166,46 @@ impl ExtensionManager {
     }
 }
 
+/// Only extensions running as a local process can be brought back by starting it again
+fn is_restartable(config: &ExtensionConfig) -> bool {
+    matches!(
+        config,
+        ExtensionConfig::Stdio { .. } | ExtensionConfig::Builtin { .. }
+    )
+}
+
+/// The uri of a `notifications/resources/updated` message, if that is what this message is
+fn resource_updated_uri(message: &JsonRpcMessage) -> Option<String> {
+    match message {
+        JsonRpcMessage::Notification(notification)
+            if notification.method == "notifications/resources/updated" =>
+        {
+            notification
+                .params
+                .as_ref()?
+                .get("uri")?
+                .as_str()
+                .map(String::from)
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use mcp_client::client::Error;
     use mcp_client::client::McpClientTrait;
     use mcp_core::protocol::{
-        CallToolResult, GetPromptResult, InitializeResult, JsonRpcMessage, ListPromptsResult,
-        ListResourcesResult, ListToolsResult, ReadResourceResult,
+        CallToolResult, GetPromptResult, InitializeResult, ListPromptsResult,
+        ListResourceTemplatesResult, ListResourcesResult, ListToolsResult, ReadResourceResult,
     };
     use serde_json::json;
-    use tokio::sync::mpsc;
 
-    struct MockClient {}
+    #[derive(Default)]
+    struct MockClient {
+        closed: bool,
+    }
 
     #[async_trait::async_trait]
     impl McpClientTrait for MockClient {
@@ -834,6 +1228,21 @@ mod tests {
             Err(Error::NotInitialized)
         }
 
+        async fn list_resource_templates(
+            &self,
+            _next_cursor: Option<String>,
+        ) -> Result<ListResourceTemplatesResult, Error> {
+            Err(Error::NotInitialized)
+        }
+
+        async fn subscribe_resource(&self, _uri: &str) -> Result<(), Error> {
+            Err(Error::NotInitialized)
+        }
+
+        async fn unsubscribe_resource(&self, _uri: &str) -> Result<(), Error> {
+            Err(Error::NotInitialized)
+        }
+
         async fn list_tools(&self, _next_cursor: Option<String>) -> Result<ListToolsResult, Error> {
             Err(Error::NotInitialized)
         }
@@ -866,6 +1275,111 @@ mod tests {
         async fn subscribe(&self) -> mpsc::Receiver<JsonRpcMessage> {
             mpsc::channel(1).1
         }
+
+        async fn ping(&self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn is_closed(&self) -> bool {
+            self.closed
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_extension_health_gives_up_after_failed_restarts() {
+        let mut extension_manager = ExtensionManager::new();
+        let name = normalize("flaky".to_string());
+        extension_manager.clients.insert(
+            name.clone(),
+            Arc::new(Mutex::new(Box::new(MockClient { closed: true }))),
+        );
+        extension_manager.configs.insert(
+            name.clone(),
+            ExtensionConfig::stdio("flaky", "/nonexistent/goose-test-extension", "", 5_u64),
+        );
+
+        for attempt in 1..=MAX_RESTART_ATTEMPTS {
+            let restarts = extension_manager.check_extension_health().await;
+            assert!(restarts.is_empty());
+            assert_eq!(extension_manager.failed_restarts.get(&name), Some(&attempt));
+        }
+
+        // Past the limit the extension is no longer restarted
+        extension_manager.check_extension_health().await;
+        assert_eq!(
+            extension_manager.failed_restarts.get(&name),
+            Some(&MAX_RESTART_ATTEMPTS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscribed_resources_prompt_names_each_resource() {
+        let extension_manager = ExtensionManager::new();
+        {
+            let mut subscribed_resources = extension_manager.subscribed_resources.lock().await;
+            subscribed_resources.insert(
+                ("notes".to_string(), "file:///b.txt".to_string()),
+                Some("second".to_string()),
+            );
+            subscribed_resources.insert(
+                ("notes".to_string(), "file:///a.txt".to_string()),
+                Some("first".to_string()),
+            );
+        }
+
+        let prompt = extension_manager
+            .subscribed_resources_prompt()
+            .await
+            .unwrap();
+        assert!(prompt.contains("## file:///a.txt (notes)\nfirst\n"));
+        assert!(prompt.contains("## file:///b.txt (notes)\nsecond\n"));
+        assert!(prompt.find("file:///a.txt").unwrap() < prompt.find("file:///b.txt").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_check_extension_health_skips_remote_extensions() {
+        let mut extension_manager = ExtensionManager::new();
+        let name = normalize("remote".to_string());
+        extension_manager.clients.insert(
+            name.clone(),
+            Arc::new(Mutex::new(Box::new(MockClient { closed: true }))),
+        );
+        extension_manager.configs.insert(
+            name.clone(),
+            ExtensionConfig::Sse {
+                name: "remote".to_string(),
+                uri: "http://localhost:1/sse".to_string(),
+                envs: Envs::default(),
+                env_keys: vec![],
+                description: None,
+                timeout: None,
+                bundled: None,
+            },
+        );
+
+        assert!(extension_manager.check_extension_health().await.is_empty());
+        assert!(extension_manager.failed_restarts.is_empty());
+    }
+
+    #[test]
+    fn test_resource_updated_uri() {
+        let updated: JsonRpcMessage = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/updated",
+            "params": { "uri": "file:///tmp/watched.txt" }
+        }))
+        .unwrap();
+        assert_eq!(
+            resource_updated_uri(&updated),
+            Some("file:///tmp/watched.txt".to_string())
+        );
+
+        let other: JsonRpcMessage = serde_json::from_value(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/list_changed",
+        }))
+        .unwrap();
+        assert_eq!(resource_updated_uri(&other), None);
     }
 
     #[test]
@@ -875,22 +1389,22 @@ mod tests {
         // Add some mock clients
         extension_manager.clients.insert(
             normalize("test_client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("__client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("__cli__ent__".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("client 🚀".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         // Test basic case
@@ -923,17 +1437,17 @@ mod tests {
         // Add some mock clients
         extension_manager.clients.insert(
             normalize("test_client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("__cli__ent__".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("client 🚀".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         // verify a normal tool call
@@ -1013,4 +1527,23 @@ mod tests {
             panic!("Expected ToolError::NotFound");
         }
     }
+
+    #[test]
+    fn test_substitute_env_vars() {
+        let envs = HashMap::from([("API_TOKEN".to_string(), "secret".to_string())]);
+
+        assert_eq!(
+            substitute_env_vars("Bearer ${API_TOKEN}", &envs),
+            "Bearer secret"
+        );
+        assert_eq!(
+            substitute_env_vars("${MISSING} and ${API_TOKEN}", &envs),
+            "${MISSING} and secret"
+        );
+        assert_eq!(substitute_env_vars("no vars", &envs), "no vars");
+        assert_eq!(
+            substitute_env_vars("broken ${API_TOKEN", &envs),
+            "broken ${API_TOKEN"
+        );
+    }
 }
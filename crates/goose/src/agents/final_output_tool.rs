@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+use indoc::formatdoc;
+use jsonschema::Validator;
+use mcp_core::tool::{Tool, ToolAnnotations};
+use mcp_core::{Content, ToolError};
+use serde_json::{json, Value};
+
+use crate::recipe::Response;
+
+pub const FINAL_OUTPUT_TOOL_NAME: &str = "recipe__final_output";
+/// How many times the agent is reminded to call the final output tool before it gives up
+pub const MAX_FINAL_OUTPUT_REMINDERS: u32 = 3;
+pub const FINAL_OUTPUT_CONTINUATION_MESSAGE: &str = "You have not provided the final output yet. \
+    Call the recipe__final_output tool with output that matches its schema to finish the task.";
+
+/// A tool the agent must call to finish a run, whose arguments are the run's result
+///
+/// The arguments are validated against the JSON Schema of the recipe's `response`, so the
+/// result is only accepted once it matches. Schemas that don't describe an object are
+/// wrapped in an `output` property, since tool arguments are always an object.
+pub struct FinalOutputTool {
+    schema: Value,
+    validator: Validator,
+    final_output: Option<Value>,
+}
+
+impl FinalOutputTool {
+    pub fn new(response: Response) -> Result<Self> {
+        let schema = response
+            .json_schema
+            .ok_or_else(|| anyhow!("The response has no json_schema"))?;
+        let validator = jsonschema::validator_for(&schema)
+            .map_err(|e| anyhow!("Invalid response JSON Schema: {}", e))?;
+        Ok(Self {
+            schema,
+            validator,
+            final_output: None,
+        })
+    }
+
+    fn wraps_output(&self) -> bool {
+        self.schema.get("type").and_then(Value::as_str) != Some("object")
+    }
+
+    pub fn tool(&self) -> Tool {
+        let input_schema = if self.wraps_output() {
+            json!({
+                "type": "object",
+                "required": ["output"],
+                "properties": {"output": self.schema}
+            })
+        } else {
+            self.schema.clone()
+        };
+
+        Tool::new(
+            FINAL_OUTPUT_TOOL_NAME.to_string(),
+            formatdoc! {r#"
+                Provide the final output of the task. This must be the last tool you call.
+
+                The arguments are validated against the output schema. If they don't match, the
+                errors are returned and you should call this tool again with corrected output.
+            "#},
+            input_schema,
+            Some(ToolAnnotations {
+                title: Some("Final output".to_string()),
+                read_only_hint: true,
+                destructive_hint: false,
+                idempotent_hint: true,
+                open_world_hint: false,
+            }),
+        )
+    }
+
+    pub fn system_prompt(&self) -> String {
+        formatdoc! {r#"
+            # Final Output
+
+            This task must end with a structured result. When you have finished the work, call
+            the {} tool with your result; its arguments must match this JSON Schema:
+
+            {}
+
+            Don't end your turn without calling {} successfully.
+        "#,
+            FINAL_OUTPUT_TOOL_NAME,
+            serde_json::to_string_pretty(&self.schema).unwrap_or_default(),
+            FINAL_OUTPUT_TOOL_NAME,
+        }
+    }
+
+    /// Handle a call to the final output tool, keeping the output once it matches the schema
+    pub fn execute(&mut self, arguments: &Value) -> Result<Vec<Content>, ToolError> {
+        let output = if self.wraps_output() {
+            arguments.get("output").cloned().unwrap_or(Value::Null)
+        } else {
+            arguments.clone()
+        };
+
+        let errors: Vec<String> = self
+            .validator
+            .iter_errors(&output)
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                if path.is_empty() {
+                    error.to_string()
+                } else {
+                    format!("{}: {}", path, error)
+                }
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(ToolError::InvalidParameters(format!(
+                "The output does not match the schema:\n- {}\nCall {} again with corrected output.",
+                errors.join("\n- "),
+                FINAL_OUTPUT_TOOL_NAME
+            )));
+        }
+
+        self.final_output = Some(output);
+        Ok(vec![Content::text(
+            "The final output was accepted. The task is complete.",
+        )])
+    }
+
+    pub fn final_output(&self) -> Option<&Value> {
+        self.final_output.as_ref()
+    }
+
+    /// Forget the output of an earlier run, so the next run must provide its own
+    pub fn reset(&mut self) {
+        self.final_output = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_for(schema: Value) -> FinalOutputTool {
+        FinalOutputTool::new(Response {
+            json_schema: Some(schema),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_output_is_kept() {
+        let mut tool = tool_for(json!({
+            "type": "object",
+            "required": ["status", "count"],
+            "properties": {
+                "status": {"type": "string", "enum": ["pass", "fail"]},
+                "count": {"type": "integer"}
+            }
+        }));
+        assert_eq!(
+            tool.tool().input_schema["required"],
+            json!(["status", "count"])
+        );
+
+        let output = json!({"status": "pass", "count": 3});
+        assert!(tool.execute(&output).is_ok());
+        assert_eq!(tool.final_output(), Some(&output));
+
+        tool.reset();
+        assert!(tool.final_output().is_none());
+    }
+
+    #[test]
+    fn test_invalid_output_is_rejected_with_errors() {
+        let mut tool = tool_for(json!({
+            "type": "object",
+            "required": ["status"],
+            "properties": {"status": {"type": "string", "enum": ["pass", "fail"]}}
+        }));
+
+        match tool.execute(&json!({"status": "maybe"})) {
+            Err(ToolError::InvalidParameters(message)) => {
+                assert!(message.contains("/status"));
+                assert!(message.contains(FINAL_OUTPUT_TOOL_NAME));
+            }
+            other => panic!("Expected invalid parameters, got {:?}", other),
+        }
+        assert!(tool.execute(&json!({})).is_err());
+        assert!(tool.final_output().is_none());
+    }
+
+    #[test]
+    fn test_non_object_schema_is_wrapped() {
+        let mut tool = tool_for(json!({"type": "array", "items": {"type": "string"}}));
+        assert_eq!(tool.tool().input_schema["required"], json!(["output"]));
+
+        assert!(tool.execute(&json!({"output": [1, 2]})).is_err());
+        assert!(tool.execute(&json!({"output": ["a", "b"]})).is_ok());
+        assert_eq!(tool.final_output(), Some(&json!(["a", "b"])));
+    }
+
+    #[test]
+    fn test_invalid_schema_is_an_error() {
+        assert!(FinalOutputTool::new(Response {
+            json_schema: Some(json!({"type": "not-a-type"})),
+        })
+        .is_err());
+        assert!(FinalOutputTool::new(Response { json_schema: None }).is_err());
+    }
+}
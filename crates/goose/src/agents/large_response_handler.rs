@@ -1,13 +1,19 @@
-use chrono::Utc;
+use std::sync::Mutex;
+
 use mcp_core::{Content, ToolError};
-use std::fs::File;
-use std::io::Write;
 
-const LARGE_TEXT_THRESHOLD: usize = 200_000;
+use super::platform_tools::PLATFORM_READ_TOOL_OUTPUT_TOOL_NAME;
+use super::tool_output_store::{ToolOutputLimits, ToolOutputStore};
 
 /// Process tool response and handle large text content
+///
+/// Text over the limit is kept in the session's tool output store and replaced with a preview
+/// of its first and last lines, along with the id the model can page through it with.
 pub fn process_tool_response(
     response: Result<Vec<Content>, ToolError>,
+    tool_name: &str,
+    store: &Mutex<ToolOutputStore>,
+    limits: &ToolOutputLimits,
 ) -> Result<Vec<Content>, ToolError> {
     match response {
         Ok(contents) => {
@@ -16,29 +22,20 @@ pub fn process_tool_response(
             for content in contents {
                 match content {
                     Content::Text(text_content) => {
-                        // Check if text exceeds threshold
-                        if text_content.text.len() > LARGE_TEXT_THRESHOLD {
-                            // Write to temp file
-                            match write_large_text_to_file(&text_content.text) {
-                                Ok(file_path) => {
-                                    // Create a new text content with reference to the file
-                                    let message = format!(
-                                        "The response returned from the tool call was larger ({} characters) and is stored in the file which you can use other tools to examine or search in: {}",
-                                        text_content.text.len(),
-                                        file_path
-                                    );
-                                    processed_contents.push(Content::text(message));
-                                }
-                                Err(e) => {
-                                    // If file writing fails, include original content with warning
-                                    let warning = format!(
-                                        "Warning: Failed to write large response to file: {}. Showing full content instead.\n\n{}",
-                                        e,
-                                        text_content.text
-                                    );
-                                    processed_contents.push(Content::text(warning));
-                                }
-                            }
+                        let total_chars = text_content.text.chars().count();
+                        if total_chars > limits.max_chars {
+                            let preview = preview(&text_content.text, limits.preview_chars);
+                            let output_id =
+                                store.lock().unwrap().store(tool_name, text_content.text);
+                            processed_contents.push(Content::text(format!(
+                                "The output of this tool call was too large to include ({} characters) and is stored as `{}`. \
+                                Use {} with output_id `{}` to read it a page at a time, grep it, or slice out lines.\n\n{}",
+                                total_chars,
+                                output_id,
+                                PLATFORM_READ_TOOL_OUTPUT_TOOL_NAME,
+                                output_id,
+                                preview
+                            )));
                         } else {
                             // Keep original content for smaller texts
                             processed_contents.push(Content::Text(text_content));
@@ -55,30 +52,50 @@ pub fn process_tool_response(
     }
 }
 
-/// Write large text content to a temporary file
-fn write_large_text_to_file(content: &str) -> Result<String, std::io::Error> {
-    // Create temp directory if it doesn't exist
-    let temp_dir = std::env::temp_dir().join("goose_mcp_responses");
-    std::fs::create_dir_all(&temp_dir)?;
-
-    // Generate a unique filename with timestamp
-    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("mcp_response_{}.txt", timestamp);
-    let file_path = temp_dir.join(&filename);
-
-    // Write content to file
-    let mut file = File::create(&file_path)?;
-    file.write_all(content.as_bytes())?;
-
-    Ok(file_path.to_string_lossy().to_string())
+/// The first and last `preview_chars` characters of the text, cut at line breaks where
+/// there are any
+fn preview(text: &str, preview_chars: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let preview_chars = preview_chars.min(chars.len() / 2);
+    let head: String = chars[..preview_chars].iter().collect();
+    let tail: String = chars[chars.len() - preview_chars..].iter().collect();
+
+    let head = match head.rfind('\n') {
+        Some(end) if end > 0 => &head[..end],
+        _ => head.as_str(),
+    };
+    let tail = match tail.find('\n') {
+        Some(start) if start + 1 < tail.len() => &tail[start + 1..],
+        _ => tail.as_str(),
+    };
+    let omitted = chars.len() - head.chars().count() - tail.chars().count();
+
+    format!(
+        "--- start of output ---\n{}\n--- {} characters omitted ---\n{}\n--- end of output ---",
+        head, omitted, tail
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use mcp_core::{Content, ImageContent, TextContent, ToolError};
-    use std::fs;
-    use std::path::Path;
+    use serde_json::json;
+
+    const MAX_CHARS: usize = 4_000;
+
+    fn process(
+        response: Result<Vec<Content>, ToolError>,
+    ) -> (Result<Vec<Content>, ToolError>, Mutex<ToolOutputStore>) {
+        let store = Mutex::new(ToolOutputStore::default());
+        let processed = process_tool_response(
+            response,
+            "developer__shell",
+            &store,
+            &ToolOutputLimits::new(MAX_CHARS),
+        );
+        (processed, store)
+    }
 
     #[test]
     fn test_small_text_response_passes_through() {
@@ -92,7 +109,8 @@ mod tests {
         let response = Ok(vec![content]);
 
         // Process the response
-        let processed = process_tool_response(response).unwrap();
+        let (processed, store) = process(response);
+        let processed = processed.unwrap();
 
         // Verify the response is unchanged
         assert_eq!(processed.len(), 1);
@@ -101,12 +119,17 @@ mod tests {
         } else {
             panic!("Expected text content");
         }
+        assert!(store.lock().unwrap().is_empty());
     }
 
     #[test]
-    fn test_large_text_response_redirected_to_file() {
-        // Create a text larger than the threshold
-        let large_text = "a".repeat(LARGE_TEXT_THRESHOLD + 1000);
+    fn test_large_text_response_stored_with_preview() {
+        // Create a text larger than the threshold, one numbered line at a time
+        let large_text = (1..=1000)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(large_text.len() > MAX_CHARS);
         let content = Content::Text(TextContent {
             text: large_text.clone(),
             annotations: None,
@@ -115,33 +138,39 @@ mod tests {
         let response = Ok(vec![content]);
 
         // Process the response
-        let processed = process_tool_response(response).unwrap();
+        let (processed, store) = process(response);
+        let processed = processed.unwrap();
 
-        // Verify the response contains a message about the file
+        // Verify the response previews both ends and refers to the stored output
         assert_eq!(processed.len(), 1);
         if let Content::Text(text_content) = &processed[0] {
+            assert!(text_content.text.contains("too large to include"));
+            assert!(text_content.text.contains("stored as `output_1`"));
             assert!(text_content
                 .text
-                .contains("The response returned from the tool call was larger"));
-            assert!(text_content.text.contains("characters"));
-
-            // Extract the file path from the message
-            if let Some(file_path) = text_content.text.split("stored in the file: ").nth(1) {
-                // Verify the file exists and contains the original text
-                let path = Path::new(file_path.trim());
-                if path.exists() {
-                    // Only check content if file exists (may not exist in CI environments)
-                    if let Ok(file_content) = fs::read_to_string(path) {
-                        assert_eq!(file_content, large_text);
-                    }
-
-                    // Clean up the file
-                    let _ = fs::remove_file(path); // Ignore errors on cleanup
-                }
-            }
+                .contains(PLATFORM_READ_TOOL_OUTPUT_TOOL_NAME));
+            assert!(text_content
+                .text
+                .contains("--- start of output ---\nline 1\n"));
+            assert!(text_content
+                .text
+                .ends_with("\nline 1000\n--- end of output ---"));
+            assert!(!text_content.text.contains("\nline 500\n"));
         } else {
             panic!("Expected text content");
         }
+
+        // The full output can be read back from the store
+        let limits = ToolOutputLimits::new(MAX_CHARS);
+        let contents = store
+            .lock()
+            .unwrap()
+            .read(
+                &json!({"output_id": "output_1", "action": "grep", "pattern": "^line 500$"}),
+                &limits,
+            )
+            .unwrap();
+        assert!(contents[0].as_text().unwrap().contains("500: line 500"));
     }
 
     #[test]
@@ -156,7 +185,8 @@ mod tests {
         let response = Ok(vec![image_content]);
 
         // Process the response
-        let processed = process_tool_response(response).unwrap();
+        let (processed, _) = process(response);
+        let processed = processed.unwrap();
 
         // Verify the response is unchanged
         assert_eq!(processed.len(), 1);
@@ -174,7 +204,7 @@ mod tests {
         // Create a response with mixed content types
         let small_text = Content::text("Small text");
         let large_text = Content::Text(TextContent {
-            text: "a".repeat(LARGE_TEXT_THRESHOLD + 1000),
+            text: "a".repeat(MAX_CHARS + 1000),
             annotations: None,
         });
         let image = Content::Image(ImageContent {
@@ -186,7 +216,8 @@ mod tests {
         let response = Ok(vec![small_text, large_text, image]);
 
         // Process the response
-        let processed = process_tool_response(response).unwrap();
+        let (processed, store) = process(response);
+        let processed = processed.unwrap();
 
         // Verify each item is handled correctly
         assert_eq!(processed.len(), 3);
@@ -198,22 +229,14 @@ mod tests {
             panic!("Expected text content");
         }
 
-        // Second item should be a message about the file
+        // Second item should be a preview of the stored output
         if let Content::Text(text_content) = &processed[1] {
-            assert!(text_content
-                .text
-                .contains("The response returned from the tool call was larger"));
-
-            // Extract the file path and clean up
-            if let Some(file_path) = text_content.text.split("stored in the file: ").nth(1) {
-                let path = Path::new(file_path.trim());
-                if path.exists() {
-                    let _ = fs::remove_file(path); // Ignore errors on cleanup
-                }
-            }
+            assert!(text_content.text.contains("too large to include"));
+            assert!(text_content.text.len() < MAX_CHARS);
         } else {
             panic!("Expected text content");
         }
+        assert!(!store.lock().unwrap().is_empty());
 
         // Third item should be unchanged image
         match &processed[2] {
@@ -232,7 +255,7 @@ mod tests {
         let response: Result<Vec<Content>, ToolError> = Err(error);
 
         // Process the response
-        let processed = process_tool_response(response);
+        let (processed, _) = process(response);
 
         // Verify the error is passed through unchanged
         assert!(processed.is_err());
@@ -2,13 +2,18 @@ mod agent;
 mod context;
 pub mod extension;
 pub mod extension_manager;
+pub mod final_output_tool;
 mod large_response_handler;
 pub mod platform_tools;
 pub mod prompt_manager;
 mod reply_parts;
-mod router_tool_selector;
+pub mod router_tool_selector;
 mod router_tools;
+pub mod sampling;
+mod sub_recipe_tools;
+mod tool_bm25_index;
 mod tool_execution;
+mod tool_output_store;
 mod tool_router_index_manager;
 pub(crate) mod tool_vectordb;
 mod types;
@@ -17,4 +22,4 @@ pub use agent::{Agent, AgentEvent};
 pub use extension::ExtensionConfig;
 pub use extension_manager::ExtensionManager;
 pub use prompt_manager::PromptManager;
-pub use types::{FrontendTool, SessionConfig};
+pub use types::{FrontendTool, RunBudget, SessionConfig};
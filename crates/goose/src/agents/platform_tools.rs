@@ -7,6 +7,7 @@ pub const PLATFORM_LIST_RESOURCES_TOOL_NAME: &str = "platform__list_resources";
 pub const PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME: &str =
     "platform__search_available_extensions";
 pub const PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME: &str = "platform__manage_extensions";
+pub const PLATFORM_READ_TOOL_OUTPUT_TOOL_NAME: &str = "platform__read_tool_output";
 
 pub fn read_resource_tool() -> Tool {
     Tool::new(
@@ -18,13 +19,18 @@ pub fn read_resource_tool() -> Tool {
             files, database schemas, or application-specific information. This tool searches for the
             resource URI in the provided extension, and reads in the resource content. If no extension
             is provided, the tool will search all extensions for the resource.
+
+            For a resource listed with a uri template, fill in the template's {variables} to build
+            the URI. Set subscribe to keep the latest content of the resource in your context, if
+            the extension supports subscriptions it is refreshed whenever the resource changes.
         "#}.to_string(),
         json!({
             "type": "object",
             "required": ["uri"],
             "properties": {
                 "uri": {"type": "string", "description": "Resource URI"},
-                "extension_name": {"type": "string", "description": "Optional extension name"}
+                "extension_name": {"type": "string", "description": "Optional extension name"},
+                "subscribe": {"type": "boolean", "description": "Keep the resource's latest content in context"}
             }
         }),
         Some(ToolAnnotations {
@@ -46,7 +52,8 @@ pub fn list_resources_tool() -> Tool {
             Resources allow extensions to share data that provide context to LLMs, such as
             files, database schemas, or application-specific information. This tool lists resources
             in the provided extension, and returns a list for the user to browse. If no extension
-            is provided, the tool will search all extensions for the resource.
+            is provided, the tool will search all extensions for the resource. Resource templates are
+            listed with their uri template, which describes a family of resources.
         "#}
         .to_string(),
         json!({
@@ -112,3 +119,38 @@ pub fn manage_extensions_tool() -> Tool {
         }),
     )
 }
+
+pub fn read_tool_output_tool() -> Tool {
+    Tool::new(
+        PLATFORM_READ_TOOL_OUTPUT_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Read a tool output that was too large to include in the conversation.
+
+            Large outputs are stored for the current session under an id like `output_1`, and
+            only their first and last lines are shown. Use this tool to read the rest:
+            - page: read the output one page at a time, starting from page 1
+            - grep: list the numbered lines matching a regular expression
+            - slice: read the lines from start_line to end_line
+        "#}
+        .to_string(),
+        json!({
+            "type": "object",
+            "required": ["output_id"],
+            "properties": {
+                "output_id": {"type": "string", "description": "Id of the stored output, e.g. output_1"},
+                "action": {"type": "string", "enum": ["page", "grep", "slice"], "description": "How to read the output, defaults to page"},
+                "page": {"type": "integer", "description": "Page to read, starting from 1"},
+                "pattern": {"type": "string", "description": "Regular expression to grep for, e.g. (?i)error"},
+                "start_line": {"type": "integer", "description": "First line of the slice, starting from 1"},
+                "end_line": {"type": "integer", "description": "Last line of the slice, defaults to the end"}
+            }
+        }),
+        Some(ToolAnnotations {
+            title: Some("Read a stored tool output".to_string()),
+            read_only_hint: true,
+            destructive_hint: false,
+            idempotent_hint: true,
+            open_world_hint: false,
+        }),
+    )
+}
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use crate::agents::extension::ExtensionInfo;
 use crate::agents::router_tool_selector::RouterToolSelectionStrategy;
-use crate::agents::router_tools::vector_search_tool_prompt;
+use crate::agents::router_tools::router_search_tool_prompt;
 use crate::providers::base::get_current_model;
 use crate::{config::Config, prompt_template};
 
@@ -12,6 +12,7 @@ pub struct PromptManager {
     system_prompt_override: Option<String>,
     system_prompt_extras: Vec<String>,
     current_date_timestamp: String,
+    goose_mode: Option<String>,
 }
 
 impl Default for PromptManager {
@@ -27,6 +28,7 @@ impl PromptManager {
             system_prompt_extras: Vec::new(),
             // Use the fixed current date time so that prompt cache can be used.
             current_date_timestamp: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            goose_mode: None,
         }
     }
 
@@ -40,6 +42,11 @@ impl PromptManager {
         self.system_prompt_override = Some(template);
     }
 
+    /// Describe the agent's own mode in the prompt instead of GOOSE_MODE
+    pub fn set_goose_mode(&mut self, goose_mode: Option<String>) {
+        self.goose_mode = goose_mode;
+    }
+
     /// Normalize a model name (replace - and / with _, lower case)
     fn normalize_model_name(name: &str) -> String {
         name.replace(['-', '/', '.'], "_").to_lowercase()
@@ -85,14 +92,11 @@ impl PromptManager {
 
         context.insert("extensions", serde_json::to_value(extensions_info).unwrap());
 
-        match tool_selection_strategy {
-            Some(RouterToolSelectionStrategy::Vector) => {
-                context.insert(
-                    "tool_selection_strategy",
-                    Value::String(vector_search_tool_prompt()),
-                );
-            }
-            None => {}
+        if let Some(strategy) = &tool_selection_strategy {
+            context.insert(
+                "tool_selection_strategy",
+                Value::String(router_search_tool_prompt(strategy)),
+            );
         }
 
         context.insert(
@@ -131,8 +135,12 @@ impl PromptManager {
         };
 
         let mut system_prompt_extras = self.system_prompt_extras.clone();
-        let config = Config::global();
-        let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
+        let goose_mode = match &self.goose_mode {
+            Some(mode) => mode.clone(),
+            None => Config::global()
+                .get_param("GOOSE_MODE")
+                .unwrap_or("auto".to_string()),
+        };
         if goose_mode == "chat" {
             system_prompt_extras.push(
                 "Right now you are in the chat only mode, no access to any tool use and system."
@@ -1,11 +1,12 @@
 use anyhow::Result;
-use std::collections::HashSet;
+use futures::StreamExt;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use crate::agents::router_tool_selector::RouterToolSelectionStrategy;
-use crate::config::Config;
 use crate::message::{Message, MessageContent, ToolRequest};
-use crate::providers::base::{Provider, ProviderUsage};
+use crate::model::ModelConfig;
+use crate::providers::base::{CompletionChunk, CompletionStream, Provider, ProviderUsage};
 use crate::providers::errors::ProviderError;
 use crate::providers::toolshim::{
     augment_message_with_tool_calls, convert_tool_messages_to_text,
@@ -13,6 +14,7 @@ use crate::providers::toolshim::{
 };
 use crate::session;
 use mcp_core::tool::Tool;
+use tracing::Instrument;
 
 use super::super::agents::Agent;
 
@@ -22,23 +24,14 @@ impl Agent {
         &self,
     ) -> anyhow::Result<(Vec<Tool>, Vec<Tool>, String)> {
         // Get tool selection strategy from config
-        let config = Config::global();
-        let router_tool_selection_strategy = config
-            .get_param("GOOSE_ROUTER_TOOL_SELECTION_STRATEGY")
-            .unwrap_or_else(|_| "default".to_string());
-
-        let tool_selection_strategy = match router_tool_selection_strategy.to_lowercase().as_str() {
-            "vector" => Some(RouterToolSelectionStrategy::Vector),
-            _ => None,
-        };
+        let tool_selection_strategy = RouterToolSelectionStrategy::from_config();
 
         // Get tools from extension manager
-        let mut tools = match tool_selection_strategy {
-            Some(RouterToolSelectionStrategy::Vector) => {
-                self.list_tools_for_router(Some(RouterToolSelectionStrategy::Vector))
-                    .await
-            }
-            _ => self.list_tools(None).await,
+        let mut tools = if tool_selection_strategy.is_some() {
+            self.list_tools_for_router(tool_selection_strategy.clone())
+                .await
+        } else {
+            self.list_tools(None).await
         };
         // Add frontend tools
         let frontend_tools = self.frontend_tools.lock().await;
@@ -46,6 +39,14 @@ impl Agent {
             tools.push(frontend_tool.tool.clone());
         }
 
+        if let Some(sub_recipe_tools) = self.sub_recipe_tools.lock().await.as_ref() {
+            tools.extend(sub_recipe_tools.tools());
+        }
+        let final_output_tool = self.final_output_tool.lock().await;
+        if let Some(final_output_tool) = final_output_tool.as_ref() {
+            tools.push(final_output_tool.tool());
+        }
+
         // Prepare system prompt
         let extension_manager = self.extension_manager.lock().await;
         let extensions_info = extension_manager.get_extensions_info().await;
@@ -63,6 +64,10 @@ impl Agent {
             Some(model_name),
             tool_selection_strategy,
         );
+        if let Some(final_output_tool) = final_output_tool.as_ref() {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&final_output_tool.system_prompt());
+        }
 
         // Handle toolshim if enabled
         let mut toolshim_tools = vec![];
@@ -82,9 +87,7 @@ impl Agent {
     /// Returns:
     /// - read_only_tools: Tools with read-only annotations
     /// - non_read_tools: Tools without read-only annotations
-    pub(crate) fn categorize_tools_by_annotation(
-        tools: &[Tool],
-    ) -> (HashSet<String>, HashSet<String>) {
+    pub fn categorize_tools_by_annotation(tools: &[Tool]) -> (HashSet<String>, HashSet<String>) {
         tools
             .iter()
             .fold((HashSet::new(), HashSet::new()), |mut acc, tool| {
@@ -142,6 +145,60 @@ impl Agent {
         Ok((response, usage))
     }
 
+    /// Stream a response from the LLM provider
+    /// Falls back to a single complete response when the provider can't stream or toolshim is
+    /// enabled, since toolshim needs the whole message before it can interpret tool calls
+    pub(crate) async fn stream_response_from_provider(
+        provider: Arc<dyn Provider>,
+        system_prompt: &str,
+        messages: &[Message],
+        tools: &[Tool],
+        toolshim_tools: &[Tool],
+    ) -> Result<CompletionStream, ProviderError> {
+        let config = provider.get_model_config();
+
+        if config.toolshim || !provider.supports_streaming() {
+            let (response, usage) = Self::generate_response_from_provider(
+                provider,
+                system_prompt,
+                messages,
+                tools,
+                toolshim_tools,
+            )
+            .await?;
+            return Ok(Box::pin(futures::stream::once(async move {
+                Ok(CompletionChunk::Done(response, usage))
+            })));
+        }
+
+        // Spans the whole stream like a provider's `complete` span, so it is traced the same way
+        let span = tracing::info_span!(
+            "complete",
+            provider = %provider.telemetry_name(),
+            model_config = %serde_json::to_string(&config).unwrap_or_default(),
+            input_tokens = tracing::field::Empty,
+            output_tokens = tracing::field::Empty,
+            total_tokens = tracing::field::Empty,
+        );
+        let stream = provider
+            .stream(system_prompt, messages, tools)
+            .instrument(span.clone())
+            .await?;
+
+        // Store the model information in the global store once the stream completes
+        Ok(Box::pin(stream.inspect(move |chunk| {
+            if let Ok(CompletionChunk::Done(_, usage)) = chunk {
+                crate::providers::base::set_current_model(&usage.model);
+                span.record("input_tokens", usage.usage.input_tokens.unwrap_or_default());
+                span.record(
+                    "output_tokens",
+                    usage.usage.output_tokens.unwrap_or_default(),
+                );
+                span.record("total_tokens", usage.usage.total_tokens.unwrap_or_default());
+            }
+        })))
+    }
+
     /// Categorize tool requests from the response into different types
     /// Returns:
     /// - frontend_requests: Tool requests that should be handled by the frontend
@@ -216,7 +273,7 @@ impl Agent {
         session_config: crate::agents::types::SessionConfig,
         usage: &crate::providers::base::ProviderUsage,
         messages_length: usize,
-    ) -> Result<()> {
+    ) -> Result<session::SessionMetadata> {
         let session_file_path = session::storage::get_path(session_config.id.clone());
         let mut metadata = session::storage::read_metadata(&session_file_path)?;
 
@@ -228,23 +285,109 @@ impl Agent {
 
         metadata.message_count = messages_length + 1;
 
-        let accumulate = |a: Option<i32>, b: Option<i32>| -> Option<i32> {
-            match (a, b) {
-                (Some(x), Some(y)) => Some(x + y),
-                _ => a.or(b),
-            }
+        metadata.cost = Self::accumulate_usage(&mut metadata, usage);
+
+        session::storage::update_metadata(&session_file_path, &metadata).await?;
+
+        Ok(metadata)
+    }
+
+    /// Add what the tool selector spent on choosing tools to the session's totals, returning
+    /// the usage recorded. It is not part of any turn, so the per-turn token counts are left
+    /// alone.
+    pub(crate) async fn record_tool_selection_usage(
+        &self,
+        session_config: &crate::agents::types::SessionConfig,
+    ) -> Result<Vec<ProviderUsage>> {
+        let selector = self.router_tool_selector.lock().await.clone();
+        let Some(selector) = selector else {
+            return Ok(Vec::new());
         };
+        let usages = selector.take_usage().await;
+        if usages.is_empty() {
+            return Ok(usages);
+        }
+
+        let session_file_path = session::storage::get_path(session_config.id.clone());
+        let mut metadata = session::storage::read_metadata(&session_file_path)?;
+        for usage in &usages {
+            Self::accumulate_usage(&mut metadata, usage);
+        }
+        session::storage::update_metadata(&session_file_path, &metadata).await?;
+
+        Ok(usages)
+    }
+
+    /// Add a completion's tokens and cost to the session's running totals, returning its cost
+    fn accumulate_usage(
+        metadata: &mut session::SessionMetadata,
+        usage: &ProviderUsage,
+    ) -> Option<f64> {
         metadata.accumulated_total_tokens =
-            accumulate(metadata.accumulated_total_tokens, usage.usage.total_tokens);
+            add_tokens(metadata.accumulated_total_tokens, usage.usage.total_tokens);
         metadata.accumulated_input_tokens =
-            accumulate(metadata.accumulated_input_tokens, usage.usage.input_tokens);
-        metadata.accumulated_output_tokens = accumulate(
+            add_tokens(metadata.accumulated_input_tokens, usage.usage.input_tokens);
+        metadata.accumulated_output_tokens = add_tokens(
             metadata.accumulated_output_tokens,
             usage.usage.output_tokens,
         );
 
-        session::storage::update_metadata(&session_file_path, &metadata).await?;
+        // Price each completion by the model that produced it, so sessions where a lead and
+        // worker model both contributed are costed correctly
+        let cost = ModelConfig::get_model_pricing(&usage.model)
+            .and_then(|pricing| pricing.cost(&usage.usage));
+        if let Some(cost) = cost {
+            metadata.accumulated_cost = Some(metadata.accumulated_cost.unwrap_or(0.0) + cost);
+            *metadata
+                .accumulated_cost_by_model
+                .get_or_insert_with(HashMap::new)
+                .entry(usage.model.clone())
+                .or_insert(0.0) += cost;
+        }
+        cost
+    }
+}
 
-        Ok(())
+/// Add two token counts, either of which may be unknown
+pub(super) fn add_tokens(a: Option<i32>, b: Option<i32>) -> Option<i32> {
+    match (a, b) {
+        (Some(x), Some(y)) => Some(x + y),
+        _ => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+
+    #[test]
+    fn test_accumulate_usage_prices_cache_reads() {
+        let model = "claude-3-7-sonnet-20250219".to_string();
+        let mut metadata = session::SessionMetadata::default();
+
+        // 10k fresh input tokens at $3 and 1k output tokens at $15 per million
+        let first = ProviderUsage::new(
+            model.clone(),
+            Usage::new(Some(10_000), Some(1_000), Some(11_000)),
+        );
+        let cost = Agent::accumulate_usage(&mut metadata, &first).unwrap();
+        assert!((cost - 0.045).abs() < 1e-9);
+
+        // The next turn reads 8k of its input from the cache, at $0.30 per million
+        let second = ProviderUsage::new(
+            model.clone(),
+            Usage::new(Some(10_000), Some(1_000), Some(11_000))
+                .with_cached_input_tokens(Some(8_000)),
+        );
+        let cost = Agent::accumulate_usage(&mut metadata, &second).unwrap();
+        assert!((cost - 0.0234).abs() < 1e-9);
+
+        assert_eq!(metadata.accumulated_total_tokens, Some(22_000));
+        assert_eq!(metadata.accumulated_input_tokens, Some(20_000));
+        let accumulated_cost = metadata.accumulated_cost.unwrap();
+        assert!((accumulated_cost - 0.0684).abs() < 1e-9);
+        let by_model = metadata.accumulated_cost_by_model.unwrap();
+        assert!((by_model[&model] - 0.0684).abs() < 1e-9);
     }
 }
@@ -2,21 +2,57 @@ use mcp_core::content::TextContent;
 use mcp_core::tool::Tool;
 use mcp_core::{Content, ToolError};
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
+use indoc::indoc;
 use serde_json::Value;
-use std::collections::VecDeque;
-use std::env;
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::agents::tool_bm25_index::ToolBm25Index;
 use crate::agents::tool_vectordb::ToolVectorDB;
-use crate::model::ModelConfig;
-use crate::providers::{self, base::Provider};
+use crate::config::Config;
+use crate::message::Message;
+use crate::providers::base::{Provider, ProviderUsage};
+use crate::providers::embedding::{create_embedder, embedding_dimension, EmbeddingCapable};
+
+/// How many recent tool calls each selector remembers
+const MAX_RECENT_TOOL_CALLS: usize = 100;
+
+const LLM_SELECTION_PROMPT: &str = indoc! {r#"
+    You select tools for an AI agent. You are given a request and the list of available tools.
+    Reply with the names of the tools most relevant to the request, one per line, most relevant first.
+    Reply with exact tool names only and nothing else. Reply with NONE if no tool is relevant.
+"#};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum RouterToolSelectionStrategy {
+    /// Embedding similarity search over a LanceDB index
     Vector,
+    /// Keyword search over tool names and descriptions, needs no embeddings
+    Bm25,
+    /// Asks the agent's provider to pick the tools
+    Llm,
+}
+
+impl RouterToolSelectionStrategy {
+    /// The strategy set by GOOSE_ROUTER_TOOL_SELECTION_STRATEGY, or None when all tools are loaded
+    pub fn from_config() -> Option<Self> {
+        let strategy = Config::global()
+            .get_param::<String>("GOOSE_ROUTER_TOOL_SELECTION_STRATEGY")
+            .unwrap_or_else(|_| "default".to_string());
+        Self::from_name(&strategy)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "vector" => Some(Self::Vector),
+            "bm25" => Some(Self::Bm25),
+            "llm" => Some(Self::Llm),
+            _ => None,
+        }
+    }
 }
 
 #[async_trait]
@@ -26,42 +62,80 @@ pub trait RouterToolSelector: Send + Sync {
     async fn remove_tool(&self, tool_name: &str) -> Result<(), ToolError>;
     async fn record_tool_call(&self, tool_name: &str) -> Result<(), ToolError>;
     async fn get_recent_tool_calls(&self, limit: usize) -> Result<Vec<String>, ToolError>;
+    /// Usage of the completions made to select tools since the last call, for the session's
+    /// token accounting
+    async fn take_usage(&self) -> Vec<ProviderUsage> {
+        Vec::new()
+    }
+    /// Delete whatever the selector stored outside the process, such as its LanceDB table
+    async fn drop_index(&self) -> Result<(), ToolError> {
+        Ok(())
+    }
     fn selector_type(&self) -> RouterToolSelectionStrategy;
 }
 
+/// The most recent tool calls, so routed sessions keep the tools they are working with
+#[derive(Default)]
+struct RecentToolCalls {
+    calls: RwLock<VecDeque<String>>,
+}
+
+impl RecentToolCalls {
+    async fn record(&self, tool_name: &str) {
+        let mut calls = self.calls.write().await;
+        if calls.len() >= MAX_RECENT_TOOL_CALLS {
+            calls.pop_front();
+        }
+        calls.push_back(tool_name.to_string());
+    }
+
+    async fn get(&self, limit: usize) -> Vec<String> {
+        let calls = self.calls.read().await;
+        calls.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+fn query_params(params: &Value) -> Result<(&str, usize), ToolError> {
+    let query = params
+        .get("query")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ToolError::InvalidParameters("Missing 'query' parameter".to_string()))?;
+
+    let k = params.get("k").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
+    Ok((query, k))
+}
+
+fn tool_schema(tool: &Tool) -> String {
+    serde_json::to_string_pretty(&tool.input_schema).unwrap_or_else(|_| "{}".to_string())
+}
+
+fn selected_tool_content(name: &str, description: &str, schema: &str) -> Content {
+    Content::Text(TextContent {
+        text: format!(
+            "Tool: {}\nDescription: {}\nSchema: {}",
+            name, description, schema
+        ),
+        annotations: None,
+    })
+}
+
 pub struct VectorToolSelector {
     vector_db: Arc<RwLock<ToolVectorDB>>,
-    embedding_provider: Arc<dyn Provider>,
-    recent_tool_calls: Arc<RwLock<VecDeque<String>>>,
+    embedder: Arc<dyn EmbeddingCapable>,
+    recent_tool_calls: RecentToolCalls,
 }
 
 impl VectorToolSelector {
     pub async fn new(provider: Arc<dyn Provider>, table_name: String) -> Result<Self> {
-        let vector_db = ToolVectorDB::new(Some(table_name)).await?;
-
-        let embedding_provider = if env::var("GOOSE_EMBEDDING_MODEL_PROVIDER").is_ok() {
-            // If env var is set, create a new provider for embeddings
-            // Get embedding model and provider from environment variables
-            let embedding_model = env::var("GOOSE_EMBEDDING_MODEL")
-                .unwrap_or_else(|_| "text-embedding-3-small".to_string());
-            let embedding_provider_name =
-                env::var("GOOSE_EMBEDDING_MODEL_PROVIDER").unwrap_or_else(|_| "openai".to_string());
-
-            // Create the provider using the factory
-            let model_config = ModelConfig::new(embedding_model);
-            providers::create(&embedding_provider_name, model_config).context(format!(
-                "Failed to create {} provider for embeddings. If using OpenAI, make sure OPENAI_API_KEY env var is set or that you have configured the OpenAI provider via Goose before.",
-                embedding_provider_name
-            ))?
-        } else {
-            // Otherwise fall back to using the same provider instance as used for base goose model
-            provider.clone()
-        };
+        // Embeddings come from GOOSE_EMBEDDING_PROVIDER when set, or the agent's own provider
+        let embedder = create_embedder(provider)?;
+        let dimension = embedding_dimension(embedder.as_ref()).await?;
+        let vector_db = ToolVectorDB::new(Some(table_name), dimension).await?;
 
         Ok(Self {
             vector_db: Arc::new(RwLock::new(vector_db)),
-            embedding_provider,
-            recent_tool_calls: Arc::new(RwLock::new(VecDeque::with_capacity(100))),
+            embedder,
+            recent_tool_calls: RecentToolCalls::default(),
         })
     }
 }
@@ -69,22 +143,10 @@ impl VectorToolSelector {
 #[async_trait]
 impl RouterToolSelector for VectorToolSelector {
     async fn select_tools(&self, params: Value) -> Result<Vec<Content>, ToolError> {
-        let query = params
-            .get("query")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| ToolError::InvalidParameters("Missing 'query' parameter".to_string()))?;
-
-        let k = params.get("k").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
-
-        // Check if provider supports embeddings
-        if !self.embedding_provider.supports_embeddings() {
-            return Err(ToolError::ExecutionError(
-                "Embedding provider does not support embeddings".to_string(),
-            ));
-        }
+        let (query, k) = query_params(&params)?;
 
         let embeddings = self
-            .embedding_provider
+            .embedder
             .create_embeddings(vec![query.to_string()])
             .await
             .map_err(|e| {
@@ -104,16 +166,7 @@ impl RouterToolSelector for VectorToolSelector {
 
         let selected_tools: Vec<Content> = tools
             .into_iter()
-            .map(|tool| {
-                let text = format!(
-                    "Tool: {}\nDescription: {}\nSchema: {}",
-                    tool.tool_name, tool.description, tool.schema
-                );
-                Content::Text(TextContent {
-                    text,
-                    annotations: None,
-                })
-            })
+            .map(|tool| selected_tool_content(&tool.tool_name, &tool.description, &tool.schema))
             .collect();
 
         Ok(selected_tools)
@@ -122,21 +175,11 @@ impl RouterToolSelector for VectorToolSelector {
     async fn index_tools(&self, tools: &[Tool]) -> Result<(), ToolError> {
         let texts_to_embed: Vec<String> = tools
             .iter()
-            .map(|tool| {
-                let schema_str = serde_json::to_string_pretty(&tool.input_schema)
-                    .unwrap_or_else(|_| "{}".to_string());
-                format!("{} {} {}", tool.name, tool.description, schema_str)
-            })
+            .map(|tool| format!("{} {} {}", tool.name, tool.description, tool_schema(tool)))
             .collect();
 
-        if !self.embedding_provider.supports_embeddings() {
-            return Err(ToolError::ExecutionError(
-                "Embedding provider does not support embeddings".to_string(),
-            ));
-        }
-
         let embeddings = self
-            .embedding_provider
+            .embedder
             .create_embeddings(texts_to_embed)
             .await
             .map_err(|e| {
@@ -147,15 +190,11 @@ impl RouterToolSelector for VectorToolSelector {
         let tool_records: Vec<crate::agents::tool_vectordb::ToolRecord> = tools
             .iter()
             .zip(embeddings.into_iter())
-            .map(|(tool, vector)| {
-                let schema_str = serde_json::to_string_pretty(&tool.input_schema)
-                    .unwrap_or_else(|_| "{}".to_string());
-                crate::agents::tool_vectordb::ToolRecord {
-                    tool_name: tool.name.clone(),
-                    description: tool.description.clone(),
-                    schema: schema_str,
-                    vector,
-                }
+            .map(|(tool, vector)| crate::agents::tool_vectordb::ToolRecord {
+                tool_name: tool.name.clone(),
+                description: tool.description.clone(),
+                schema: tool_schema(tool),
+                vector,
             })
             .collect();
 
@@ -178,17 +217,21 @@ impl RouterToolSelector for VectorToolSelector {
     }
 
     async fn record_tool_call(&self, tool_name: &str) -> Result<(), ToolError> {
-        let mut recent_calls = self.recent_tool_calls.write().await;
-        if recent_calls.len() >= 100 {
-            recent_calls.pop_front();
-        }
-        recent_calls.push_back(tool_name.to_string());
+        self.recent_tool_calls.record(tool_name).await;
         Ok(())
     }
 
     async fn get_recent_tool_calls(&self, limit: usize) -> Result<Vec<String>, ToolError> {
-        let recent_calls = self.recent_tool_calls.read().await;
-        Ok(recent_calls.iter().rev().take(limit).cloned().collect())
+        Ok(self.recent_tool_calls.get(limit).await)
+    }
+
+    async fn drop_index(&self) -> Result<(), ToolError> {
+        self.vector_db
+            .read()
+            .await
+            .drop_table()
+            .await
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to drop tool index: {}", e)))
     }
 
     fn selector_type(&self) -> RouterToolSelectionStrategy {
@@ -196,6 +239,177 @@ impl RouterToolSelector for VectorToolSelector {
     }
 }
 
+/// Selects tools with a local BM25 index, so routing works with providers that have no embeddings
+#[derive(Default)]
+pub struct Bm25ToolSelector {
+    index: RwLock<ToolBm25Index>,
+    recent_tool_calls: RecentToolCalls,
+}
+
+impl Bm25ToolSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RouterToolSelector for Bm25ToolSelector {
+    async fn select_tools(&self, params: Value) -> Result<Vec<Content>, ToolError> {
+        let (query, k) = query_params(&params)?;
+
+        let index = self.index.read().await;
+        Ok(index
+            .search(query, k)
+            .into_iter()
+            .map(|(tool, _)| {
+                selected_tool_content(&tool.name, &tool.description, &tool_schema(tool))
+            })
+            .collect())
+    }
+
+    async fn index_tools(&self, tools: &[Tool]) -> Result<(), ToolError> {
+        let mut index = self.index.write().await;
+        for tool in tools {
+            index.index_tool(tool.clone());
+        }
+        Ok(())
+    }
+
+    async fn remove_tool(&self, tool_name: &str) -> Result<(), ToolError> {
+        self.index.write().await.remove_tool(tool_name);
+        Ok(())
+    }
+
+    async fn record_tool_call(&self, tool_name: &str) -> Result<(), ToolError> {
+        self.recent_tool_calls.record(tool_name).await;
+        Ok(())
+    }
+
+    async fn get_recent_tool_calls(&self, limit: usize) -> Result<Vec<String>, ToolError> {
+        Ok(self.recent_tool_calls.get(limit).await)
+    }
+
+    fn selector_type(&self) -> RouterToolSelectionStrategy {
+        RouterToolSelectionStrategy::Bm25
+    }
+}
+
+/// Selects tools by asking the agent's provider to rank them against the query
+pub struct LlmToolSelector {
+    llm_provider: Arc<dyn Provider>,
+    tools: RwLock<BTreeMap<String, Tool>>,
+    recent_tool_calls: RecentToolCalls,
+    usage: RwLock<Vec<ProviderUsage>>,
+}
+
+impl LlmToolSelector {
+    pub fn new(provider: Arc<dyn Provider>) -> Self {
+        Self {
+            llm_provider: provider,
+            tools: RwLock::new(BTreeMap::new()),
+            recent_tool_calls: RecentToolCalls::default(),
+            usage: RwLock::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl RouterToolSelector for LlmToolSelector {
+    async fn select_tools(&self, params: Value) -> Result<Vec<Content>, ToolError> {
+        let (query, k) = query_params(&params)?;
+
+        let tools = self.tools.read().await;
+        if tools.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Only the first line of each description, to keep the catalog small
+        let catalog = tools
+            .values()
+            .map(|tool| {
+                format!(
+                    "- {}: {}",
+                    tool.name,
+                    tool.description.lines().next().unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let request = Message::user().with_text(format!(
+            "Request: {}\n\nReturn at most {} tools.\n\nAvailable tools:\n{}",
+            query, k, catalog
+        ));
+
+        let (response, usage) = self
+            .llm_provider
+            .complete(LLM_SELECTION_PROMPT, &[request], &[])
+            .await
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to rank tools: {}", e)))?;
+        self.usage.write().await.push(usage);
+
+        Ok(parse_selected_tools(&response.as_concat_text(), &tools, k)
+            .into_iter()
+            .map(|tool| selected_tool_content(&tool.name, &tool.description, &tool_schema(tool)))
+            .collect())
+    }
+
+    async fn index_tools(&self, tools: &[Tool]) -> Result<(), ToolError> {
+        let mut indexed = self.tools.write().await;
+        for tool in tools {
+            indexed.insert(tool.name.clone(), tool.clone());
+        }
+        Ok(())
+    }
+
+    async fn remove_tool(&self, tool_name: &str) -> Result<(), ToolError> {
+        self.tools.write().await.remove(tool_name);
+        Ok(())
+    }
+
+    async fn record_tool_call(&self, tool_name: &str) -> Result<(), ToolError> {
+        self.recent_tool_calls.record(tool_name).await;
+        Ok(())
+    }
+
+    async fn get_recent_tool_calls(&self, limit: usize) -> Result<Vec<String>, ToolError> {
+        Ok(self.recent_tool_calls.get(limit).await)
+    }
+
+    async fn take_usage(&self) -> Vec<ProviderUsage> {
+        std::mem::take(&mut *self.usage.write().await)
+    }
+
+    fn selector_type(&self) -> RouterToolSelectionStrategy {
+        RouterToolSelectionStrategy::Llm
+    }
+}
+
+/// The known tools named in the model's reply, in order and without duplicates.
+/// Tolerates list markers and backticks around the names.
+fn parse_selected_tools<'a>(
+    response: &str,
+    tools: &'a BTreeMap<String, Tool>,
+    k: usize,
+) -> Vec<&'a Tool> {
+    let mut selected: Vec<&Tool> = Vec::new();
+    for line in response.lines() {
+        let name = line
+            .trim()
+            .trim_start_matches(|c: char| c == '-' || c == '*' || c == '.' || c.is_ascii_digit())
+            .trim()
+            .trim_matches('`');
+        if let Some(tool) = tools.get(name) {
+            if !selected.iter().any(|t| t.name == tool.name) {
+                selected.push(tool);
+            }
+        }
+        if selected.len() >= k {
+            break;
+        }
+    }
+    selected
+}
+
 // Helper function to create a boxed tool selector
 pub async fn create_tool_selector(
     strategy: Option<RouterToolSelectionStrategy>,
@@ -207,9 +421,116 @@ pub async fn create_tool_selector(
             let selector = VectorToolSelector::new(provider, table_name).await?;
             Ok(Box::new(selector))
         }
+        Some(RouterToolSelectionStrategy::Bm25) => Ok(Box::new(Bm25ToolSelector::new())),
+        Some(RouterToolSelectionStrategy::Llm) => Ok(Box::new(LlmToolSelector::new(provider))),
         None => {
             let selector = VectorToolSelector::new(provider, table_name).await?;
             Ok(Box::new(selector))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tools() -> BTreeMap<String, Tool> {
+        [
+            "developer__shell",
+            "developer__text_editor",
+            "slack__send_message",
+        ]
+        .into_iter()
+        .map(|name| {
+            (
+                name.to_string(),
+                Tool::new(name, "", json!({"type": "object"}), None),
+            )
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_parse_selected_tools() {
+        let tools = tools();
+        let names = |response: &str, k: usize| {
+            parse_selected_tools(response, &tools, k)
+                .into_iter()
+                .map(|tool| tool.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(
+                "1. `slack__send_message`\n- developer__shell\nslack__send_message\nweather__forecast",
+                5
+            ),
+            vec!["slack__send_message", "developer__shell"]
+        );
+        assert_eq!(
+            names("developer__shell\ndeveloper__text_editor", 1),
+            vec!["developer__shell"]
+        );
+        assert!(names("NONE", 5).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bm25_selector_indexes_and_removes_tools() {
+        let selector = Bm25ToolSelector::new();
+        let tools: Vec<Tool> = vec![
+            Tool::new(
+                "developer__shell",
+                "Run a command in the shell",
+                json!({}),
+                None,
+            ),
+            Tool::new(
+                "slack__send_message",
+                "Send a message to a Slack channel",
+                json!({}),
+                None,
+            ),
+        ];
+        selector.index_tools(&tools).await.unwrap();
+
+        let selected = selector
+            .select_tools(json!({"query": "post a slack message"}))
+            .await
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert!(selected[0]
+            .as_text()
+            .unwrap()
+            .starts_with("Tool: slack__send_message\n"));
+
+        selector.remove_tool("slack__send_message").await.unwrap();
+        assert!(selector
+            .select_tools(json!({"query": "post a slack message"}))
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(selector.select_tools(json!({})).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_llm_selector_reports_selection_usage_once() {
+        use crate::providers::replay::{ReplayProvider, ReplayResponse};
+
+        let provider = Arc::new(ReplayProvider::new(vec![ReplayResponse {
+            text: Some("developer__shell".to_string()),
+            tool_calls: vec![],
+        }]));
+        let selector = LlmToolSelector::new(provider);
+        let tools = tools().into_values().collect::<Vec<_>>();
+        selector.index_tools(&tools).await.unwrap();
+
+        let selected = selector
+            .select_tools(json!({"query": "list files"}))
+            .await
+            .unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selector.take_usage().await.len(), 1);
+        assert!(selector.take_usage().await.is_empty());
+    }
+}
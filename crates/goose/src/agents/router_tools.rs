@@ -2,7 +2,37 @@ use indoc::indoc;
 use mcp_core::tool::{Tool, ToolAnnotations};
 use serde_json::json;
 
+use super::router_tool_selector::RouterToolSelectionStrategy;
+
 pub const ROUTER_VECTOR_SEARCH_TOOL_NAME: &str = "router__vector_search";
+pub const ROUTER_KEYWORD_SEARCH_TOOL_NAME: &str = "router__keyword_search";
+pub const ROUTER_LLM_SEARCH_TOOL_NAME: &str = "router__llm_search";
+
+pub fn is_router_search_tool(name: &str) -> bool {
+    [
+        ROUTER_VECTOR_SEARCH_TOOL_NAME,
+        ROUTER_KEYWORD_SEARCH_TOOL_NAME,
+        ROUTER_LLM_SEARCH_TOOL_NAME,
+    ]
+    .contains(&name)
+}
+
+/// The search tool the agent calls to retrieve tools under the given strategy
+pub fn router_search_tool(strategy: &RouterToolSelectionStrategy) -> Tool {
+    match strategy {
+        RouterToolSelectionStrategy::Vector => vector_search_tool(),
+        RouterToolSelectionStrategy::Bm25 => keyword_search_tool(),
+        RouterToolSelectionStrategy::Llm => llm_search_tool(),
+    }
+}
+
+pub fn router_search_tool_prompt(strategy: &RouterToolSelectionStrategy) -> String {
+    match strategy {
+        RouterToolSelectionStrategy::Vector => vector_search_tool_prompt(),
+        RouterToolSelectionStrategy::Bm25 => keyword_search_tool_prompt(),
+        RouterToolSelectionStrategy::Llm => llm_search_tool_prompt(),
+    }
+}
 
 pub fn vector_search_tool() -> Tool {
     Tool::new(
@@ -45,3 +75,83 @@ pub fn vector_search_tool_prompt() -> String {
     By dynamically enabling tools, you (Goose) as the agent save context window space and allow the user to dynamically retrieve the most relevant tools.
     Be sure to format the query to search rather than pass in the user's messages directly."#.to_string()
 }
+
+pub fn keyword_search_tool() -> Tool {
+    Tool::new(
+        ROUTER_KEYWORD_SEARCH_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Searches for relevant tools by keyword, matching tool names and descriptions.
+            Format a query of keywords that describe the tool needed for the user's messages.
+            Use the words a tool description would use, such as the action and the thing it acts on, and leave out filler words.
+            This tool should be invoked when the user's messages suggest they are asking for a tool to be run.
+            Examples:
+            - {"User": "what is the weather in Tokyo?", "Query": "weather forecast"}
+            - {"User": "read this pdf file for me", "Query": "read pdf file"}
+            - {"User": "run this command ls -l in the terminal", "Query": "run shell command"}
+        "#}
+        .to_string(),
+        json!({
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": {"type": "string", "description": "Keywords describing the tool needed for the user's messages"},
+                "k": {"type": "integer", "description": "The number of tools to retrieve (defaults to 5)", "default": 5}
+            }
+        }),
+        Some(ToolAnnotations {
+            title: Some("Keyword search for relevant tools".to_string()),
+            read_only_hint: true,
+            destructive_hint: false,
+            idempotent_hint: true,
+            open_world_hint: false,
+        }),
+    )
+}
+
+pub fn keyword_search_tool_prompt() -> String {
+    r#"# Tool Selection Instructions
+    Important: the user has opted to dynamically enable tools, so although an extension could be enabled, \
+    please invoke the keyword search tool to actually retrieve the most relevant tools to use according to the user's messages.
+    For example, if the user has 3 extensions enabled, but they are asking for a tool to read a pdf file, \
+    you would invoke the keyword_search tool with a query like "read pdf file" to find the most relevant tool.
+    The search matches words in tool names and descriptions, so use specific keywords rather than passing in the user's messages directly."#.to_string()
+}
+
+pub fn llm_search_tool() -> Tool {
+    Tool::new(
+        ROUTER_LLM_SEARCH_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Retrieves the tools most relevant to a task by having the model rank the available tools.
+            Describe the task the user wants done in one or two sentences, including any details that narrow down the right tool.
+            This tool should be invoked when the user's messages suggest they are asking for a tool to be run.
+            Examples:
+            - {"User": "what is the weather in Tokyo?", "Query": "look up the current weather forecast for a city"}
+            - {"User": "read this pdf file for me", "Query": "read the text content of a pdf file"}
+        "#}
+        .to_string(),
+        json!({
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": {"type": "string", "description": "A short description of the task the tools are needed for"},
+                "k": {"type": "integer", "description": "The number of tools to retrieve (defaults to 5)", "default": 5}
+            }
+        }),
+        Some(ToolAnnotations {
+            title: Some("Model-ranked search for relevant tools".to_string()),
+            read_only_hint: true,
+            destructive_hint: false,
+            idempotent_hint: false,
+            open_world_hint: false,
+        }),
+    )
+}
+
+pub fn llm_search_tool_prompt() -> String {
+    r#"# Tool Selection Instructions
+    Important: the user has opted to dynamically enable tools, so although an extension could be enabled, \
+    please invoke the llm search tool to actually retrieve the most relevant tools to use according to the user's messages.
+    For example, if the user has 3 extensions enabled, but they are asking for a tool to read a pdf file, \
+    you would invoke the llm_search tool with a short description of that task to retrieve the right tool.
+    Describe the task itself rather than passing in the user's messages directly."#.to_string()
+}
@@ -0,0 +1,328 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use mcp_client::client::SamplingHandler;
+use mcp_core::protocol::{
+    CreateMessageParams, CreateMessageResult, ErrorData, SamplingMessage, INTERNAL_ERROR,
+};
+use mcp_core::{Content, Role};
+use serde_json::json;
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+use crate::agents::types::{current_goose_mode, GooseModeOverride};
+use crate::message::{Message, MessageContent};
+use crate::permission::Permission;
+use crate::providers::base::Provider;
+
+/// The agent's provider slot, shared with sampling handlers so they always use the current one
+pub(crate) type SharedProvider = Arc<Mutex<Option<Arc<dyn Provider>>>>;
+
+/// Confirmation requests for sampling use ids with this prefix, so frontends can
+/// tell them apart from tool confirmations
+pub const SAMPLING_REQUEST_ID_PREFIX: &str = "sampling-";
+
+/// Error code returned to the extension when the user declines a sampling request
+const USER_REJECTED: i32 = -1;
+
+pub fn is_sampling_request(id: &str) -> bool {
+    id.starts_with(SAMPLING_REQUEST_ID_PREFIX)
+}
+
+/// A sampling request waiting for the user's approval, answered through
+/// `Agent::handle_confirmation` like a tool confirmation
+pub(crate) struct SamplingApproval {
+    pub id: String,
+    pub extension_name: String,
+    pub params: CreateMessageParams,
+    pub respond_to: oneshot::Sender<Permission>,
+}
+
+impl SamplingApproval {
+    pub fn confirmation_message(&self) -> Message {
+        Message::user().with_tool_confirmation_request(
+            self.id.clone(),
+            format!("{}__sampling", self.extension_name),
+            json!({
+                "systemPrompt": self.params.system_prompt,
+                "messages": self.params.messages,
+                "maxTokens": self.params.max_tokens,
+            }),
+            Some(format!(
+                "The {} extension would like to use the model to generate a response, do you allow?",
+                self.extension_name
+            )),
+        )
+    }
+}
+
+/// Marks the agent as answering sampling approvals for as long as a reply runs. Approvals
+/// still queued when it's dropped are rejected rather than left waiting for the next reply.
+pub(crate) struct SamplingListener<'a> {
+    listening: &'a AtomicBool,
+    approval_rx: &'a Mutex<mpsc::Receiver<SamplingApproval>>,
+}
+
+impl<'a> SamplingListener<'a> {
+    pub fn new(
+        listening: &'a AtomicBool,
+        approval_rx: &'a Mutex<mpsc::Receiver<SamplingApproval>>,
+    ) -> Self {
+        listening.store(true, Ordering::SeqCst);
+        Self {
+            listening,
+            approval_rx,
+        }
+    }
+}
+
+impl Drop for SamplingListener<'_> {
+    fn drop(&mut self) {
+        self.listening.store(false, Ordering::SeqCst);
+        if let Ok(mut approval_rx) = self.approval_rx.try_lock() {
+            // Dropping an approval drops its responder, which the sampler reads as a rejection
+            while approval_rx.try_recv().is_ok() {}
+        }
+    }
+}
+
+/// Everything the extension manager needs to answer sampling requests from extensions
+#[derive(Clone)]
+pub(crate) struct SamplingContext {
+    provider: SharedProvider,
+    approval_tx: mpsc::Sender<SamplingApproval>,
+    always_allowed: Arc<Mutex<HashSet<String>>>,
+    goose_mode: GooseModeOverride,
+    /// Whether a reply is running to surface approvals to the user
+    listening: Arc<AtomicBool>,
+}
+
+impl SamplingContext {
+    pub fn new(
+        provider: SharedProvider,
+        approval_tx: mpsc::Sender<SamplingApproval>,
+        goose_mode: GooseModeOverride,
+        listening: Arc<AtomicBool>,
+    ) -> Self {
+        Self {
+            provider,
+            approval_tx,
+            goose_mode,
+            listening,
+            always_allowed: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    pub fn handler(&self, extension_name: &str) -> Arc<dyn SamplingHandler> {
+        Arc::new(ProviderSampler {
+            extension_name: extension_name.to_string(),
+            context: self.clone(),
+        })
+    }
+}
+
+/// Answers `sampling/createMessage` requests from one extension with the agent's provider.
+/// The request's model preferences, temperature and token limit are advisory and the
+/// provider's own model configuration is used.
+struct ProviderSampler {
+    extension_name: String,
+    context: SamplingContext,
+}
+
+impl ProviderSampler {
+    /// Whether the user allows this request, following the agent's mode: auto approves,
+    /// chat declines and the approval modes ask through the agent, declining when no
+    /// reply is running to ask the user
+    async fn approve(&self, params: &CreateMessageParams) -> bool {
+        let goose_mode = current_goose_mode(&self.context.goose_mode);
+        match goose_mode.as_str() {
+            "auto" => return true,
+            "chat" => return false,
+            _ => {}
+        }
+
+        if self
+            .context
+            .always_allowed
+            .lock()
+            .await
+            .contains(&self.extension_name)
+        {
+            return true;
+        }
+
+        if !self.context.listening.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let approval = SamplingApproval {
+            id: format!("{}{}", SAMPLING_REQUEST_ID_PREFIX, uuid::Uuid::new_v4()),
+            extension_name: self.extension_name.clone(),
+            params: params.clone(),
+            respond_to: tx,
+        };
+        if self.context.approval_tx.send(approval).await.is_err() {
+            return false;
+        }
+
+        match rx.await {
+            Ok(Permission::AlwaysAllow) => {
+                self.context
+                    .always_allowed
+                    .lock()
+                    .await
+                    .insert(self.extension_name.clone());
+                true
+            }
+            Ok(Permission::AllowOnce) => true,
+            _ => false,
+        }
+    }
+}
+
+#[async_trait]
+impl SamplingHandler for ProviderSampler {
+    async fn create_message(
+        &self,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        if !self.approve(&params).await {
+            return Err(ErrorData {
+                code: USER_REJECTED,
+                message: "User rejected sampling request".to_string(),
+                data: None,
+            });
+        }
+
+        let provider = self
+            .context
+            .provider
+            .lock()
+            .await
+            .clone()
+            .ok_or_else(|| internal_error("Provider not set"))?;
+
+        let messages = to_messages(&params.messages);
+        let system = params.system_prompt.clone().unwrap_or_default();
+        let (response, usage) = provider
+            .complete(&system, &messages, &[])
+            .await
+            .map_err(|e| internal_error(&e.to_string()))?;
+
+        Ok(CreateMessageResult {
+            role: Role::Assistant,
+            content: Content::text(response.as_concat_text()),
+            model: usage.model,
+            stop_reason: Some("endTurn".to_string()),
+        })
+    }
+}
+
+fn internal_error(message: &str) -> ErrorData {
+    ErrorData {
+        code: INTERNAL_ERROR,
+        message: message.to_string(),
+        data: None,
+    }
+}
+
+fn to_messages(sampling_messages: &[SamplingMessage]) -> Vec<Message> {
+    sampling_messages
+        .iter()
+        .map(|sampling_message| {
+            let message = match sampling_message.role {
+                Role::User => Message::user(),
+                Role::Assistant => Message::assistant(),
+            };
+            message.with_content(MessageContent::from(sampling_message.content.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_messages_keeps_roles_and_content() {
+        let messages = to_messages(&[
+            SamplingMessage {
+                role: Role::User,
+                content: Content::text("What is 2 + 2?"),
+            },
+            SamplingMessage {
+                role: Role::Assistant,
+                content: Content::text("4"),
+            },
+        ]);
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, Role::User);
+        assert_eq!(messages[0].as_concat_text(), "What is 2 + 2?");
+        assert_eq!(messages[1].role, Role::Assistant);
+        assert_eq!(messages[1].as_concat_text(), "4");
+    }
+
+    #[test]
+    fn test_is_sampling_request() {
+        assert!(is_sampling_request("sampling-1234"));
+        assert!(!is_sampling_request("toolu_1234"));
+    }
+
+    fn approval_sampler(
+        listening: Arc<AtomicBool>,
+    ) -> (ProviderSampler, mpsc::Receiver<SamplingApproval>) {
+        let (approval_tx, approval_rx) = mpsc::channel(1);
+        let goose_mode = Arc::new(std::sync::RwLock::new(Some("approve".to_string())));
+        let context = SamplingContext::new(
+            Arc::new(Mutex::new(None)),
+            approval_tx,
+            goose_mode,
+            listening,
+        );
+        let sampler = ProviderSampler {
+            extension_name: "test".to_string(),
+            context,
+        };
+        (sampler, approval_rx)
+    }
+
+    fn params() -> CreateMessageParams {
+        CreateMessageParams {
+            messages: vec![],
+            model_preferences: None,
+            system_prompt: None,
+            include_context: None,
+            temperature: None,
+            max_tokens: 10,
+            stop_sequences: None,
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approval_is_declined_without_a_running_reply() {
+        let (sampler, mut approval_rx) = approval_sampler(Arc::new(AtomicBool::new(false)));
+        assert!(!sampler.approve(&params()).await);
+        assert!(approval_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_queued_approvals_are_declined_when_the_reply_ends() {
+        let listening = Arc::new(AtomicBool::new(false));
+        let (sampler, approval_rx) = approval_sampler(listening.clone());
+        let approval_rx = Mutex::new(approval_rx);
+
+        let listener = SamplingListener::new(&listening, &approval_rx);
+        let pending = tokio::spawn(async move { sampler.approve(&params()).await });
+        while approval_rx.lock().await.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        drop(listener);
+
+        assert!(!pending.await.unwrap());
+        assert!(!listening.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use mcp_core::tool::{Tool, ToolAnnotations};
+use mcp_core::{Content, ToolError};
+use serde_json::{json, Map, Value};
+
+use crate::agents::types::{RunBudget, SessionConfig};
+use crate::agents::{Agent, AgentEvent};
+use crate::message::{Message, MessageContent};
+use crate::permission::permission_confirmation::PrincipalType;
+use crate::permission::{Permission, PermissionConfirmation};
+use crate::providers::base::Provider;
+use crate::recipe::sub_recipe::{is_required, LoadedSubRecipe};
+use crate::recipe::{RecipeParameterInputType, SubRecipe};
+use crate::session::{self, Identifier};
+
+/// The sub-recipes an agent can run, each as a tool that runs the recipe in an agent of
+/// its own and returns its final output
+pub struct SubRecipeTools {
+    sub_recipes: Vec<LoadedSubRecipe>,
+    /// How many sub-recipes deep the agent itself runs, 0 for the top-level agent
+    depth: usize,
+}
+
+impl SubRecipeTools {
+    /// Load the sub-recipes, whose paths must already be resolved
+    pub fn new(sub_recipes: &[SubRecipe], depth: usize) -> Result<Self> {
+        let sub_recipes = sub_recipes
+            .iter()
+            .map(LoadedSubRecipe::load)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { sub_recipes, depth })
+    }
+
+    pub fn is_sub_recipe_tool(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    fn find(&self, tool_name: &str) -> Option<&LoadedSubRecipe> {
+        self.sub_recipes
+            .iter()
+            .find(|sub_recipe| sub_recipe.tool_name() == tool_name)
+    }
+
+    pub fn tools(&self) -> Vec<Tool> {
+        self.sub_recipes.iter().map(sub_recipe_tool).collect()
+    }
+
+    /// Run the sub-recipe a tool call names within what is left of the parent's `budget`; the
+    /// returned future owns all it needs, so it can run alongside other tool calls
+    pub fn run(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        provider: Arc<dyn Provider>,
+        budget: RunBudget,
+    ) -> Result<BoxFuture<'static, Result<Vec<Content>, ToolError>>, ToolError> {
+        let sub_recipe = self
+            .find(tool_name)
+            .cloned()
+            .ok_or_else(|| ToolError::NotFound(tool_name.to_string()))?;
+        let arguments = string_arguments(arguments);
+        let depth = self.depth + 1;
+
+        Ok(Box::pin(async move {
+            run_sub_recipe(sub_recipe, arguments, provider, depth, budget)
+                .await
+                .map_err(|e| ToolError::ExecutionError(e.to_string()))
+        }))
+    }
+}
+
+fn sub_recipe_tool(sub_recipe: &LoadedSubRecipe) -> Tool {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for param in &sub_recipe.parameters {
+        let param_type = match param.input_type {
+            RecipeParameterInputType::Number => "number",
+            RecipeParameterInputType::Boolean => "boolean",
+            _ => "string",
+        };
+        let mut property = json!({"type": param_type, "description": param.description});
+        match param.input_type {
+            RecipeParameterInputType::Select => {
+                property["enum"] = json!(param.options);
+            }
+            RecipeParameterInputType::Date => {
+                property["description"] =
+                    json!(format!("{} (an ISO 8601 date)", param.description));
+            }
+            RecipeParameterInputType::File => {
+                property["description"] =
+                    json!(format!("{} (the path of a file)", param.description));
+            }
+            _ => {}
+        }
+        properties.insert(param.key.clone(), property);
+        if is_required(param) {
+            required.push(param.key.clone());
+        }
+    }
+
+    Tool::new(
+        sub_recipe.tool_name(),
+        format!(
+            "Run the \"{}\" recipe: {}\n\nThe recipe runs to completion in a separate agent \
+            with its own extensions, and this tool returns its final output.",
+            sub_recipe.title, sub_recipe.description
+        ),
+        json!({
+            "type": "object",
+            "required": required,
+            "properties": properties
+        }),
+        Some(ToolAnnotations {
+            title: Some(sub_recipe.title.clone()),
+            read_only_hint: false,
+            destructive_hint: true,
+            idempotent_hint: false,
+            open_world_hint: true,
+        }),
+    )
+}
+
+/// Template parameters are strings, so other argument values are passed as JSON
+fn string_arguments(arguments: &Value) -> HashMap<String, String> {
+    arguments
+        .as_object()
+        .map(|arguments| {
+            arguments
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn run_sub_recipe(
+    sub_recipe: LoadedSubRecipe,
+    arguments: HashMap<String, String>,
+    provider: Arc<dyn Provider>,
+    depth: usize,
+    budget: RunBudget,
+) -> Result<Vec<Content>> {
+    let recipe = sub_recipe.render(&arguments)?;
+
+    // A sub-recipe designed for another model runs with it, others share the parent's
+    let agent = Agent::new();
+    if !recipe
+        .settings
+        .as_ref()
+        .is_some_and(|settings| settings.configures_provider())
+    {
+        agent.update_provider(provider).await?;
+    }
+    if let Some(settings) = &recipe.settings {
+        agent.apply_recipe_settings(settings).await?;
+    }
+
+    let prompt = agent.prepare_recipe_at_depth(&recipe, depth).await?;
+    let messages = vec![Message::user().with_text(prompt)];
+
+    // The run isn't saved, but keeps to the recipe's limits like a top-level run does, and
+    // can't outlast the parent's
+    let session_config = SessionConfig {
+        id: Identifier::Path(session::unsaved_session_file()),
+        working_dir: std::env::current_dir()?,
+        schedule_id: None,
+        max_turns: recipe.max_turns,
+        max_tokens: budget.max_tokens(recipe.max_tokens),
+        deadline: budget.deadline,
+    };
+    if let Some(limit_message) = session_config.limit_reached(0, Some(0)) {
+        return Err(anyhow!("The sub-recipe didn't start: {}", limit_message));
+    }
+
+    let mut last_text = String::new();
+    let mut stream = agent.reply(&messages, Some(session_config)).await?;
+    while let Some(event) = stream.next().await {
+        let message = match event? {
+            AgentEvent::Message(message) => message,
+            _ => continue,
+        };
+        if let Some(MessageContent::ToolConfirmationRequest(confirmation)) = message.content.first()
+        {
+            // Nobody can approve tools in a sub-recipe, so those that need approval are declined
+            agent
+                .handle_confirmation(
+                    confirmation.id.clone(),
+                    PermissionConfirmation {
+                        principal_type: PrincipalType::Tool,
+                        permission: Permission::DenyOnce,
+                    },
+                )
+                .await;
+            continue;
+        }
+        let text = message.as_concat_text();
+        if message.role == mcp_core::role::Role::Assistant && !text.trim().is_empty() {
+            last_text = text;
+        }
+    }
+    drop(stream);
+
+    if let Some(output) = agent.final_output().await {
+        return Ok(vec![Content::text(serde_json::to_string_pretty(&output)?)]);
+    }
+    if agent.has_final_output_tool().await {
+        return Err(anyhow!(
+            "The sub-recipe finished without providing output that matches its schema"
+        ));
+    }
+    Ok(vec![Content::text(last_text)])
+}
@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+
+use mcp_core::tool::Tool;
+
+/// Term frequency saturation
+const K1: f64 = 1.2;
+/// Document length normalization
+const B: f64 = 0.75;
+
+/// Words that carry no signal about which tool is wanted
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "for", "from", "in", "into", "is",
+    "it", "me", "my", "of", "on", "or", "please", "that", "the", "this", "to", "with", "you",
+];
+
+struct IndexedTool {
+    tool: Tool,
+    term_counts: HashMap<String, usize>,
+    length: usize,
+}
+
+/// An in-memory BM25 index over tool names and descriptions, for routing tools
+/// without an embedding model
+#[derive(Default)]
+pub struct ToolBm25Index {
+    tools: HashMap<String, IndexedTool>,
+    document_frequency: HashMap<String, usize>,
+    total_length: usize,
+}
+
+impl ToolBm25Index {
+    /// Index a tool, replacing any tool already indexed under the same name
+    pub fn index_tool(&mut self, tool: Tool) {
+        self.remove_tool(&tool.name);
+
+        let terms = tokenize(&format!("{} {}", tool.name, tool.description));
+        let mut term_counts: HashMap<String, usize> = HashMap::new();
+        for term in &terms {
+            *term_counts.entry(term.clone()).or_default() += 1;
+        }
+        for term in term_counts.keys() {
+            *self.document_frequency.entry(term.clone()).or_default() += 1;
+        }
+        self.total_length += terms.len();
+
+        self.tools.insert(
+            tool.name.clone(),
+            IndexedTool {
+                tool,
+                term_counts,
+                length: terms.len(),
+            },
+        );
+    }
+
+    /// Remove a tool from the index. Returns false if it was not indexed.
+    pub fn remove_tool(&mut self, tool_name: &str) -> bool {
+        let Some(indexed) = self.tools.remove(tool_name) else {
+            return false;
+        };
+
+        for term in indexed.term_counts.keys() {
+            if let Some(count) = self.document_frequency.get_mut(term) {
+                *count -= 1;
+                if *count == 0 {
+                    self.document_frequency.remove(term);
+                }
+            }
+        }
+        self.total_length -= indexed.length;
+        true
+    }
+
+    /// The `k` tools that best match the query, best first. Tools sharing no terms
+    /// with the query are never returned.
+    pub fn search(&self, query: &str, k: usize) -> Vec<(&Tool, f64)> {
+        if self.tools.is_empty() {
+            return Vec::new();
+        }
+
+        let query_terms: HashSet<String> = tokenize(query).into_iter().collect();
+        let document_count = self.tools.len() as f64;
+        let average_length = (self.total_length as f64 / document_count).max(1.0);
+
+        let mut scored: Vec<(&Tool, f64)> = self
+            .tools
+            .values()
+            .filter_map(|indexed| {
+                let score: f64 = query_terms
+                    .iter()
+                    .filter_map(|term| {
+                        let term_frequency = *indexed.term_counts.get(term)? as f64;
+                        let document_frequency = self.document_frequency[term] as f64;
+                        let idf = ((document_count - document_frequency + 0.5)
+                            / (document_frequency + 0.5)
+                            + 1.0)
+                            .ln();
+                        let length_norm = 1.0 - B + B * indexed.length as f64 / average_length;
+                        Some(
+                            idf * term_frequency * (K1 + 1.0) / (term_frequency + K1 * length_norm),
+                        )
+                    })
+                    .sum();
+                (score > 0.0).then_some((&indexed.tool, score))
+            })
+            .collect();
+
+        scored.sort_by(|(a_tool, a_score), (b_tool, b_score)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| a_tool.name.cmp(&b_tool.name))
+        });
+        scored.truncate(k);
+        scored
+    }
+}
+
+/// Split text into lowercase terms, breaking tool names like `developer__text_editor`
+/// into their words and folding simple plurals
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .map(|word| {
+            if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+                word[..word.len() - 1].to_string()
+            } else {
+                word
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tool(name: &str, description: &str) -> Tool {
+        Tool::new(name, description, json!({"type": "object"}), None)
+    }
+
+    fn index() -> ToolBm25Index {
+        let mut index = ToolBm25Index::default();
+        index.index_tool(tool(
+            "developer__shell",
+            "Execute a command in the shell and return its output",
+        ));
+        index.index_tool(tool(
+            "developer__text_editor",
+            "View, create and edit files on disk",
+        ));
+        index.index_tool(tool(
+            "slack__send_message",
+            "Send a message to a Slack channel",
+        ));
+        index.index_tool(tool(
+            "slack__read_thread",
+            "Read the messages in a Slack thread",
+        ));
+        index
+    }
+
+    #[test]
+    fn test_tokenize_splits_tool_names_and_folds_plurals() {
+        assert_eq!(
+            tokenize("developer__text_editor edits the Files"),
+            vec!["developer", "text", "editor", "edit", "file"]
+        );
+    }
+
+    #[test]
+    fn test_search_ranks_matching_tools_first() {
+        let index = index();
+
+        let results = index.search("send a slack message to the team", 2);
+        assert_eq!(results[0].0.name, "slack__send_message");
+        assert_eq!(results[1].0.name, "slack__read_thread");
+
+        let results = index.search("edit a file", 5);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.name, "developer__text_editor");
+
+        assert!(index.search("weather forecast", 5).is_empty());
+    }
+
+    #[test]
+    fn test_remove_and_reindex_tools() {
+        let mut index = index();
+        assert!(index.remove_tool("developer__shell"));
+        assert!(!index.remove_tool("developer__shell"));
+        assert_eq!(index.tools.len(), 3);
+        assert!(index.search("run a shell command", 5).is_empty());
+
+        index.index_tool(tool("slack__send_message", "Post to a channel"));
+        index.index_tool(tool("slack__send_message", "Post to a channel"));
+        assert_eq!(index.tools.len(), 3);
+        assert_eq!(index.document_frequency["post"], 1);
+        assert_eq!(
+            index.search("post", 5)[0].0.name,
+            "slack__send_message".to_string()
+        );
+    }
+}
@@ -0,0 +1,388 @@
+use std::collections::VecDeque;
+
+use mcp_core::{Content, ToolError};
+use regex::RegexBuilder;
+use serde_json::Value;
+
+use crate::config::Config;
+
+/// Rough number of characters per token, to turn the context limit into a character budget
+const CHARS_PER_TOKEN: usize = 4;
+/// Share of the model's context window a single tool output may take before it is stored
+const DEFAULT_CONTEXT_SHARE: f32 = 0.25;
+/// Upper bound on the text kept for a session; the oldest outputs are dropped past it
+const MAX_STORED_CHARS: usize = 50_000_000;
+/// Longest line shown in grep results
+const MAX_GREP_LINE_CHARS: usize = 500;
+const MAX_GREP_MATCHES: usize = 200;
+
+/// How large a tool output may be before it is stored, and how much of it is shown at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolOutputLimits {
+    /// Outputs longer than this many characters are stored and previewed
+    pub max_chars: usize,
+    /// Characters shown from each end of a stored output in its preview
+    pub preview_chars: usize,
+    /// Characters returned per page when reading a stored output
+    pub page_chars: usize,
+}
+
+impl ToolOutputLimits {
+    /// Limits for a model with the given context limit in tokens.
+    ///
+    /// `GOOSE_TOOL_OUTPUT_MAX_CHARS` sets the threshold directly; otherwise it is the share
+    /// of the context window given by `GOOSE_TOOL_OUTPUT_CONTEXT_SHARE`.
+    pub fn from_config(context_limit: usize) -> Self {
+        let config = Config::global();
+        let max_chars = config
+            .get_param::<usize>("GOOSE_TOOL_OUTPUT_MAX_CHARS")
+            .ok()
+            .filter(|chars| *chars > 0)
+            .unwrap_or_else(|| {
+                let share: f32 = config
+                    .get_param("GOOSE_TOOL_OUTPUT_CONTEXT_SHARE")
+                    .ok()
+                    .filter(|share: &f32| *share > 0.0 && *share <= 1.0)
+                    .unwrap_or(DEFAULT_CONTEXT_SHARE);
+                (context_limit as f32 * share) as usize * CHARS_PER_TOKEN
+            });
+        Self::new(max_chars)
+    }
+
+    pub fn new(max_chars: usize) -> Self {
+        Self {
+            max_chars,
+            preview_chars: (max_chars / 20).clamp(200, 4_000),
+            page_chars: (max_chars / 4).clamp(1_000, 50_000),
+        }
+    }
+}
+
+struct StoredOutput {
+    id: String,
+    tool_name: String,
+    text: String,
+}
+
+/// Tool outputs too large to put in the conversation, kept for the current session so
+/// the model can read them a page at a time
+#[derive(Default)]
+pub struct ToolOutputStore {
+    session_id: Option<String>,
+    outputs: VecDeque<StoredOutput>,
+    stored_chars: usize,
+    next_id: usize,
+}
+
+impl ToolOutputStore {
+    /// Start serving a session. Outputs stored for any other session are dropped.
+    pub fn start_session(&mut self, session_id: String) {
+        if self.session_id.as_ref() != Some(&session_id) {
+            self.clear();
+            self.session_id = Some(session_id);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.outputs.clear();
+        self.stored_chars = 0;
+    }
+
+    /// Keep a tool output and return the id it can be read back with
+    pub fn store(&mut self, tool_name: &str, text: String) -> String {
+        self.next_id += 1;
+        let id = format!("output_{}", self.next_id);
+
+        self.stored_chars += text.len();
+        self.outputs.push_back(StoredOutput {
+            id: id.clone(),
+            tool_name: tool_name.to_string(),
+            text,
+        });
+        while self.stored_chars > MAX_STORED_CHARS && self.outputs.len() > 1 {
+            if let Some(dropped) = self.outputs.pop_front() {
+                self.stored_chars -= dropped.text.len();
+            }
+        }
+        id
+    }
+
+    /// Handle a call to the read tool output platform tool
+    pub fn read(
+        &self,
+        arguments: &Value,
+        limits: &ToolOutputLimits,
+    ) -> Result<Vec<Content>, ToolError> {
+        let output_id = arguments
+            .get("output_id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ToolError::InvalidParameters("output_id is required".to_string()))?;
+        let output = self
+            .outputs
+            .iter()
+            .find(|output| output.id == output_id)
+            .ok_or_else(|| {
+                ToolError::InvalidParameters(format!(
+                    "No stored output '{}'. Stored outputs only last for the current session.",
+                    output_id
+                ))
+            })?;
+
+        let text = match arguments
+            .get("action")
+            .and_then(Value::as_str)
+            .unwrap_or("page")
+        {
+            "page" => {
+                let page = arguments.get("page").and_then(Value::as_u64).unwrap_or(1);
+                read_page(output, page.max(1) as usize, limits.page_chars)?
+            }
+            "grep" => {
+                let pattern = arguments
+                    .get("pattern")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters("pattern is required to grep".to_string())
+                    })?;
+                grep(output, pattern, limits.page_chars)?
+            }
+            "slice" => {
+                let start_line = arguments
+                    .get("start_line")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(1);
+                let end_line = arguments.get("end_line").and_then(Value::as_u64);
+                slice(
+                    output,
+                    start_line.max(1) as usize,
+                    end_line.map(|line| line as usize),
+                    limits.page_chars,
+                )?
+            }
+            other => {
+                return Err(ToolError::InvalidParameters(format!(
+                    "Unknown action '{}', expected page, grep or slice",
+                    other
+                )))
+            }
+        };
+        Ok(vec![Content::text(text)])
+    }
+}
+
+/// Byte offset of the character at `char_index`, or the end of the text
+fn byte_offset(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map(|(offset, _)| offset)
+        .unwrap_or(text.len())
+}
+
+fn read_page(output: &StoredOutput, page: usize, page_chars: usize) -> Result<String, ToolError> {
+    let total_chars = output.text.chars().count();
+    let pages = total_chars.div_ceil(page_chars).max(1);
+    if page > pages {
+        return Err(ToolError::InvalidParameters(format!(
+            "Page {} is past the end of {}, which has {} pages",
+            page, output.id, pages
+        )));
+    }
+
+    let start = (page - 1) * page_chars;
+    let end = (start + page_chars).min(total_chars);
+    let text = &output.text[byte_offset(&output.text, start)..byte_offset(&output.text, end)];
+    Ok(format!(
+        "Page {} of {} of {} from {} (characters {}-{} of {}):\n\n{}",
+        page,
+        pages,
+        output.id,
+        output.tool_name,
+        start + 1,
+        end,
+        total_chars,
+        text
+    ))
+}
+
+fn grep(output: &StoredOutput, pattern: &str, max_chars: usize) -> Result<String, ToolError> {
+    let regex = RegexBuilder::new(pattern)
+        .size_limit(1 << 20)
+        .build()
+        .map_err(|e| ToolError::InvalidParameters(format!("Invalid pattern: {}", e)))?;
+
+    let mut matches = Vec::new();
+    let mut match_count = 0;
+    let mut shown_chars = 0;
+    for (index, line) in output.text.lines().enumerate() {
+        if !regex.is_match(line) {
+            continue;
+        }
+        match_count += 1;
+        if matches.len() >= MAX_GREP_MATCHES || shown_chars >= max_chars {
+            continue;
+        }
+
+        let shown = if line.chars().count() > MAX_GREP_LINE_CHARS {
+            format!("{}…", &line[..byte_offset(line, MAX_GREP_LINE_CHARS)])
+        } else {
+            line.to_string()
+        };
+        shown_chars += shown.len();
+        matches.push(format!("{}: {}", index + 1, shown));
+    }
+
+    if match_count == 0 {
+        return Ok(format!("No lines of {} match `{}`", output.id, pattern));
+    }
+    let mut text = format!(
+        "{} lines of {} match `{}`:\n\n{}",
+        match_count,
+        output.id,
+        pattern,
+        matches.join("\n")
+    );
+    if matches.len() < match_count {
+        text.push_str(&format!(
+            "\n\n(showing the first {} matches, narrow the pattern to see the rest)",
+            matches.len()
+        ));
+    }
+    Ok(text)
+}
+
+fn slice(
+    output: &StoredOutput,
+    start_line: usize,
+    end_line: Option<usize>,
+    max_chars: usize,
+) -> Result<String, ToolError> {
+    let total_lines = output.text.lines().count();
+    if start_line > total_lines {
+        return Err(ToolError::InvalidParameters(format!(
+            "Line {} is past the end of {}, which has {} lines",
+            start_line, output.id, total_lines
+        )));
+    }
+    let end_line = end_line
+        .unwrap_or(total_lines)
+        .clamp(start_line, total_lines);
+
+    let mut text = String::new();
+    let mut last_line = start_line - 1;
+    for line in output
+        .text
+        .lines()
+        .skip(start_line - 1)
+        .take(end_line - start_line + 1)
+    {
+        if !text.is_empty() && text.len() + line.len() > max_chars {
+            break;
+        }
+        text.push_str(line);
+        text.push('\n');
+        last_line += 1;
+    }
+    if text.len() > max_chars {
+        text.truncate(byte_offset(&text, max_chars));
+    }
+
+    let mut header = format!(
+        "Lines {}-{} of {} of {}",
+        start_line, last_line, total_lines, output.id
+    );
+    if last_line < end_line {
+        header.push_str(" (cut short to fit, request fewer lines at a time)");
+    }
+    Ok(format!("{}:\n\n{}", header, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn store_with_output(text: &str) -> (ToolOutputStore, String) {
+        let mut store = ToolOutputStore::default();
+        store.start_session("session".to_string());
+        let id = store.store("developer__shell", text.to_string());
+        (store, id)
+    }
+
+    fn read_text(store: &ToolOutputStore, arguments: Value, limits: &ToolOutputLimits) -> String {
+        let contents = store.read(&arguments, limits).unwrap();
+        contents[0].as_text().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_page_through_output() {
+        let (store, id) = store_with_output(&"abcdefghij".repeat(250));
+        let limits = ToolOutputLimits::new(4_000);
+        assert_eq!(limits.page_chars, 1_000);
+
+        let first = read_text(&store, json!({"output_id": id}), &limits);
+        assert!(first.starts_with("Page 1 of 3 of output_1 from developer__shell"));
+        assert!(first.contains("characters 1-1000 of 2500"));
+
+        let last = read_text(&store, json!({"output_id": id, "page": 3}), &limits);
+        assert!(last.contains("characters 2001-2500 of 2500"));
+        assert!(store
+            .read(&json!({"output_id": id, "page": 4}), &limits)
+            .is_err());
+    }
+
+    #[test]
+    fn test_grep_and_slice_output() {
+        let text = (1..=100)
+            .map(|i| {
+                if i % 10 == 0 {
+                    format!("line {} ERROR failed", i)
+                } else {
+                    format!("line {} ok", i)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (store, id) = store_with_output(&text);
+        let limits = ToolOutputLimits::new(4_000);
+
+        let grep = read_text(
+            &store,
+            json!({"output_id": id, "action": "grep", "pattern": "error"}),
+            &limits,
+        );
+        assert!(grep.starts_with("No lines"));
+
+        let grep = read_text(
+            &store,
+            json!({"output_id": id, "action": "grep", "pattern": "(?i)error"}),
+            &limits,
+        );
+        assert!(grep.starts_with("10 lines of output_1 match"));
+        assert!(grep.contains("\n20: line 20 ERROR failed"));
+
+        let slice = read_text(
+            &store,
+            json!({"output_id": id, "action": "slice", "start_line": 98}),
+            &limits,
+        );
+        assert_eq!(
+            slice,
+            "Lines 98-100 of 100 of output_1:\n\nline 98 ok\nline 99 ok\nline 100 ERROR failed\n"
+        );
+    }
+
+    #[test]
+    fn test_outputs_are_dropped_with_their_session() {
+        let (mut store, id) = store_with_output("some output");
+        let limits = ToolOutputLimits::new(4_000);
+
+        store.start_session("session".to_string());
+        assert!(store.read(&json!({"output_id": id}), &limits).is_ok());
+
+        store.start_session("other".to_string());
+        assert!(matches!(
+            store.read(&json!({"output_id": id}), &limits),
+            Err(ToolError::InvalidParameters(_))
+        ));
+    }
+}
@@ -4,13 +4,13 @@ use tracing;
 
 use crate::agents::extension_manager::ExtensionManager;
 use crate::agents::platform_tools;
-use crate::agents::router_tool_selector::{RouterToolSelectionStrategy, RouterToolSelector};
+use crate::agents::router_tool_selector::RouterToolSelector;
 
-/// Manages tool indexing operations for the router when vector routing is enabled
+/// Manages tool indexing operations for the router when a tool selection strategy is enabled
 pub struct ToolRouterIndexManager;
 
 impl ToolRouterIndexManager {
-    /// Updates the vector index for tools when extensions are added or removed
+    /// Updates the router's index for tools when extensions are added or removed
     pub async fn update_extension_tools(
         selector: &Arc<Box<dyn RouterToolSelector>>,
         extension_manager: &ExtensionManager,
@@ -91,16 +91,12 @@ impl ToolRouterIndexManager {
             .await
             .map_err(|e| anyhow!("Failed to index platform tools: {}", e))?;
 
-        tracing::info!("Indexed platform tools for vector search");
+        tracing::info!("Indexed platform tools for tool routing");
         Ok(())
     }
 
-    /// Helper to check if vector tool router is enabled
-    pub fn vector_tool_router_enabled(selector: &Option<Arc<Box<dyn RouterToolSelector>>>) -> bool {
-        if let Some(selector) = selector {
-            selector.selector_type() == RouterToolSelectionStrategy::Vector
-        } else {
-            false
-        }
+    /// Helper to check if the tool router is enabled, whichever strategy it uses
+    pub fn tool_router_enabled(selector: &Option<Arc<Box<dyn RouterToolSelector>>>) -> bool {
+        selector.is_some()
     }
 }
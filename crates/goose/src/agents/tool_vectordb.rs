@@ -23,10 +23,16 @@ pub struct ToolRecord {
 pub struct ToolVectorDB {
     connection: Arc<RwLock<Connection>>,
     table_name: String,
+    /// Length of the embedding vectors stored in the table
+    dimension: i32,
 }
 
 impl ToolVectorDB {
-    pub async fn new(table_name: Option<String>) -> Result<Self> {
+    /// Open the tools table, creating it for vectors of `dimension` values. A table left
+    /// over from a model with a different dimension is recreated, since its vectors
+    /// can't be compared with the new model's.
+    pub async fn new(table_name: Option<String>, dimension: usize) -> Result<Self> {
+        let dimension = i32::try_from(dimension).context("Embedding dimension is too large")?;
         let db_path = Self::get_db_path()?;
 
         // Ensure the directory exists
@@ -44,6 +50,7 @@ impl ToolVectorDB {
         let tool_db = Self {
             connection: Arc::new(RwLock::new(connection)),
             table_name: table_name.unwrap_or_else(|| "tools".to_string()),
+            dimension,
         };
 
         // Initialize the table if it doesn't exist
@@ -60,7 +67,56 @@ impl ToolVectorDB {
         Ok(data_dir.join("goose").join("tool_db"))
     }
 
+    fn table_schema(&self) -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("tool_name", DataType::Utf8, false),
+            Field::new("description", DataType::Utf8, false),
+            Field::new("schema", DataType::Utf8, false),
+            Field::new(
+                "vector",
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    self.dimension,
+                ),
+                false,
+            ),
+        ]))
+    }
+
+    /// Drop the table if its vectors have a different length from the current model's
+    async fn drop_table_with_other_dimension(&self) -> Result<()> {
+        let connection = self.connection.write().await;
+        let table = match connection.open_table(&self.table_name).execute().await {
+            Ok(table) => table,
+            Err(_) => return Ok(()),
+        };
+
+        let schema = table
+            .schema()
+            .await
+            .context("Failed to read tools table schema")?;
+        let table_dimension = match schema.field_with_name("vector").map(|f| f.data_type()) {
+            Ok(DataType::FixedSizeList(_, size)) => Some(*size),
+            _ => None,
+        };
+        if table_dimension != Some(self.dimension) {
+            tracing::info!(
+                "Recreating tools table '{}' for {} dimensional embeddings",
+                self.table_name,
+                self.dimension
+            );
+            connection
+                .drop_table(&self.table_name)
+                .await
+                .context("Failed to drop tools table")?;
+        }
+
+        Ok(())
+    }
+
     async fn init_table(&self) -> Result<()> {
+        self.drop_table_with_other_dimension().await?;
+
         let connection = self.connection.read().await;
 
         // Check if table exists
@@ -72,19 +128,7 @@ impl ToolVectorDB {
 
         if !table_names.contains(&self.table_name) {
             // Create the table schema
-            let schema = Arc::new(Schema::new(vec![
-                Field::new("tool_name", DataType::Utf8, false),
-                Field::new("description", DataType::Utf8, false),
-                Field::new("schema", DataType::Utf8, false),
-                Field::new(
-                    "vector",
-                    DataType::FixedSizeList(
-                        Arc::new(Field::new("item", DataType::Float32, true)),
-                        1536, // OpenAI embedding dimension
-                    ),
-                    false,
-                ),
-            ]));
+            let schema = self.table_schema();
 
             // Create empty table
             let tool_names = StringArray::from(vec![] as Vec<&str>);
@@ -93,7 +137,7 @@ impl ToolVectorDB {
 
             // Create empty fixed size list array for vectors
             let mut vectors_builder =
-                FixedSizeListBuilder::new(arrow::array::Float32Builder::new(), 1536);
+                FixedSizeListBuilder::new(arrow::array::Float32Builder::new(), self.dimension);
             let vectors = vectors_builder.finish();
 
             let batch = arrow::record_batch::RecordBatch::try_new(
@@ -129,6 +173,16 @@ impl ToolVectorDB {
         Ok(())
     }
 
+    /// Remove the table and everything indexed in it, for tables that are only used once
+    pub async fn drop_table(&self) -> Result<()> {
+        self.connection
+            .write()
+            .await
+            .drop_table(&self.table_name)
+            .await
+            .with_context(|| format!("Failed to drop tools table '{}'", self.table_name))
+    }
+
     #[cfg(test)]
     pub async fn clear_tools(&self) -> Result<()> {
         let connection = self.connection.write().await;
@@ -159,6 +213,17 @@ impl ToolVectorDB {
         if tools.is_empty() {
             return Ok(());
         }
+        if let Some(tool) = tools
+            .iter()
+            .find(|t| t.vector.len() != self.dimension as usize)
+        {
+            return Err(anyhow::anyhow!(
+                "Embedding for tool '{}' has {} values, but the tools table holds {}",
+                tool.tool_name,
+                tool.vector.len(),
+                self.dimension
+            ));
+        }
 
         let tool_names: Vec<&str> = tools.iter().map(|t| t.tool_name.as_str()).collect();
         let descriptions: Vec<&str> = tools.iter().map(|t| t.description.as_str()).collect();
@@ -169,26 +234,14 @@ impl ToolVectorDB {
             .map(|t| Some(t.vector.iter().map(|&v| Some(v)).collect()))
             .collect();
 
-        let schema = Arc::new(Schema::new(vec![
-            Field::new("tool_name", DataType::Utf8, false),
-            Field::new("description", DataType::Utf8, false),
-            Field::new("schema", DataType::Utf8, false),
-            Field::new(
-                "vector",
-                DataType::FixedSizeList(
-                    Arc::new(Field::new("item", DataType::Float32, true)),
-                    1536,
-                ),
-                false,
-            ),
-        ]));
+        let schema = self.table_schema();
 
         let tool_names_array = StringArray::from(tool_names);
         let descriptions_array = StringArray::from(descriptions);
         let schemas_array = StringArray::from(schemas);
         // Build vectors array
         let mut vectors_builder =
-            FixedSizeListBuilder::new(arrow::array::Float32Builder::new(), 1536);
+            FixedSizeListBuilder::new(arrow::array::Float32Builder::new(), self.dimension);
         for vector_opt in vectors_data {
             if let Some(vector) = vector_opt {
                 let values = vectors_builder.values();
@@ -333,7 +386,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_tool_vectordb_creation() {
-        let db = ToolVectorDB::new(Some("test_tools_vectordb_creation".to_string()))
+        let db = ToolVectorDB::new(Some("test_tools_vectordb_creation".to_string()), 1536)
             .await
             .unwrap();
         db.clear_tools().await.unwrap();
@@ -343,7 +396,7 @@ mod tests {
     #[tokio::test]
     async fn test_tool_vectordb_operations() -> Result<()> {
         // Create a new database instance with a unique table name
-        let db = ToolVectorDB::new(Some("test_tool_vectordb_operations".to_string())).await?;
+        let db = ToolVectorDB::new(Some("test_tool_vectordb_operations".to_string()), 1536).await?;
 
         // Clear any existing tools
         db.clear_tools().await?;
@@ -390,7 +443,7 @@ mod tests {
     #[tokio::test]
     async fn test_empty_db() -> Result<()> {
         // Create a new database instance with a unique table name
-        let db = ToolVectorDB::new(Some("test_empty_db".to_string())).await?;
+        let db = ToolVectorDB::new(Some("test_empty_db".to_string()), 1536).await?;
 
         // Clear any existing tools
         db.clear_tools().await?;
@@ -408,7 +461,7 @@ mod tests {
     #[tokio::test]
     async fn test_tool_deletion() -> Result<()> {
         // Create a new database instance with a unique table name
-        let db = ToolVectorDB::new(Some("test_tool_deletion".to_string())).await?;
+        let db = ToolVectorDB::new(Some("test_tool_deletion".to_string()), 1536).await?;
 
         // Clear any existing tools
         db.clear_tools().await?;
@@ -437,4 +490,36 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_table_follows_embedding_dimension() -> Result<()> {
+        let table_name = "test_table_follows_embedding_dimension".to_string();
+        let db = ToolVectorDB::new(Some(table_name.clone()), 1536).await?;
+        db.clear_tools().await?;
+
+        // A model with a different dimension gets a fresh table with its own vector length
+        let db = ToolVectorDB::new(Some(table_name), 768).await?;
+        db.index_tools(vec![ToolRecord {
+            tool_name: "test_tool".to_string(),
+            description: "A test tool".to_string(),
+            schema: "{}".to_string(),
+            vector: vec![0.1; 768],
+        }])
+        .await?;
+        let results = db.search_tools(vec![0.1; 768], 1).await?;
+        assert_eq!(results.len(), 1);
+
+        // Vectors of the wrong length are rejected rather than written
+        let wrong_length = db
+            .index_tools(vec![ToolRecord {
+                tool_name: "other_tool".to_string(),
+                description: "Another test tool".to_string(),
+                schema: "{}".to_string(),
+                vector: vec![0.1; 1536],
+            }])
+            .await;
+        assert!(wrong_length.is_err());
+
+        Ok(())
+    }
 }
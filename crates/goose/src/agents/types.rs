@@ -1,4 +1,6 @@
+use crate::config::Config;
 use crate::session;
+use chrono::{DateTime, Utc};
 use mcp_core::{Content, Tool, ToolResult};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -8,6 +10,20 @@ use tokio::sync::{mpsc, Mutex};
 /// Type alias for the tool result channel receiver
 pub type ToolResultReceiver = Arc<Mutex<mpsc::Receiver<(String, ToolResult<Vec<Content>>)>>>;
 
+/// A GOOSE_MODE the agent runs in instead of the configured one, shared with the parts of
+/// the agent that follow the mode
+pub(crate) type GooseModeOverride = Arc<std::sync::RwLock<Option<String>>>;
+
+/// The agent's own mode if it was given one, otherwise GOOSE_MODE
+pub(crate) fn current_goose_mode(mode_override: &GooseModeOverride) -> String {
+    match mode_override.read().unwrap().as_ref() {
+        Some(mode) => mode.clone(),
+        None => Config::global()
+            .get_param("GOOSE_MODE")
+            .unwrap_or("auto".to_string()),
+    }
+}
+
 /// A frontend tool that will be executed by the frontend rather than an extension
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrontendTool {
@@ -24,4 +40,168 @@ pub struct SessionConfig {
     pub working_dir: PathBuf,
     /// ID of the schedule that triggered this session, if any
     pub schedule_id: Option<String>, // NEW
+    /// Maximum number of provider turns the agent may take for a single reply
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+    /// Maximum number of tokens the session may accumulate across all turns
+    #[serde(default)]
+    pub max_tokens: Option<i32>,
+    /// Wall-clock time after which the agent stops taking new turns
+    #[serde(default)]
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+impl SessionConfig {
+    /// Returns the final message to show when one of the run limits has been reached
+    ///
+    /// `turns` is the number of provider turns taken so far in this reply and
+    /// `accumulated_tokens` is the session's accumulated total token count.
+    pub fn limit_reached(&self, turns: u32, accumulated_tokens: Option<i32>) -> Option<String> {
+        if let Some(max_turns) = self.max_turns {
+            if turns >= max_turns {
+                return Some(format!(
+                    "I've reached the maximum of {} turns for this run, so I'm stopping here. \
+                    You can continue the session to pick up where I left off.",
+                    max_turns
+                ));
+            }
+        }
+
+        if let (Some(max_tokens), Some(used)) = (self.max_tokens, accumulated_tokens) {
+            if used >= max_tokens {
+                return Some(format!(
+                    "This session has used {} tokens, reaching its budget of {}, so I'm stopping here.",
+                    used, max_tokens
+                ));
+            }
+        }
+
+        if let Some(deadline) = self.deadline {
+            if Utc::now() >= deadline {
+                return Some(format!(
+                    "I've reached the time limit for this run ({}), so I'm stopping here.",
+                    deadline.to_rfc3339()
+                ));
+            }
+        }
+
+        None
+    }
+
+    /// What is left of the run's limits once `accumulated_tokens` have been used
+    pub fn remaining_budget(&self, accumulated_tokens: Option<i32>) -> RunBudget {
+        RunBudget {
+            deadline: self.deadline,
+            remaining_tokens: self.max_tokens.map(|max_tokens| {
+                max_tokens
+                    .saturating_sub(accumulated_tokens.unwrap_or(0))
+                    .max(0)
+            }),
+        }
+    }
+}
+
+/// The limits a run hands down to the sub-recipes it runs, so they can't outlast it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunBudget {
+    /// Wall-clock time after which no new turns are taken
+    pub deadline: Option<DateTime<Utc>>,
+    /// Tokens left before the run reaches its budget
+    pub remaining_tokens: Option<i32>,
+}
+
+impl RunBudget {
+    /// The tighter of a sub-run's own token limit and the tokens left in this budget
+    pub fn max_tokens(&self, max_tokens: Option<i32>) -> Option<i32> {
+        match (max_tokens, self.remaining_tokens) {
+            (Some(own), Some(remaining)) => Some(own.min(remaining)),
+            (own, remaining) => own.or(remaining),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn config() -> SessionConfig {
+        SessionConfig {
+            id: session::Identifier::Name("test".to_string()),
+            working_dir: PathBuf::from("."),
+            schedule_id: None,
+            max_turns: None,
+            max_tokens: None,
+            deadline: None,
+        }
+    }
+
+    #[test]
+    fn test_remaining_budget() {
+        let deadline = Utc::now() + Duration::hours(1);
+        let config = SessionConfig {
+            max_tokens: Some(1000),
+            deadline: Some(deadline),
+            ..config()
+        };
+        let budget = config.remaining_budget(Some(400));
+        assert_eq!(budget.remaining_tokens, Some(600));
+        assert_eq!(budget.deadline, Some(deadline));
+        assert_eq!(config.remaining_budget(None).remaining_tokens, Some(1000));
+        assert_eq!(
+            config.remaining_budget(Some(1200)).remaining_tokens,
+            Some(0)
+        );
+        assert_eq!(config().remaining_budget(Some(400)), RunBudget::default());
+
+        // A sub-run keeps the tighter of its own limit and the budget
+        assert_eq!(budget.max_tokens(Some(100)), Some(100));
+        assert_eq!(budget.max_tokens(Some(5000)), Some(600));
+        assert_eq!(budget.max_tokens(None), Some(600));
+        assert_eq!(RunBudget::default().max_tokens(None), None);
+    }
+
+    #[test]
+    fn test_no_limits_never_stops() {
+        assert!(config().limit_reached(1000, Some(i32::MAX)).is_none());
+    }
+
+    #[test]
+    fn test_max_turns() {
+        let config = SessionConfig {
+            max_turns: Some(3),
+            ..config()
+        };
+        assert!(config.limit_reached(2, None).is_none());
+        let message = config.limit_reached(3, None).unwrap();
+        assert!(message.contains("maximum of 3 turns"));
+    }
+
+    #[test]
+    fn test_max_tokens() {
+        let config = SessionConfig {
+            max_tokens: Some(1000),
+            ..config()
+        };
+        assert!(config.limit_reached(0, None).is_none());
+        assert!(config.limit_reached(0, Some(999)).is_none());
+        let message = config.limit_reached(0, Some(1200)).unwrap();
+        assert!(message.contains("1200 tokens"));
+        assert!(message.contains("budget of 1000"));
+    }
+
+    #[test]
+    fn test_deadline() {
+        let future = SessionConfig {
+            deadline: Some(Utc::now() + Duration::hours(1)),
+            ..config()
+        };
+        assert!(future.limit_reached(0, None).is_none());
+
+        let past = SessionConfig {
+            deadline: Some(Utc::now() - Duration::seconds(1)),
+            ..config()
+        };
+        assert!(past.limit_reached(0, None).unwrap().contains("time limit"));
+    }
 }
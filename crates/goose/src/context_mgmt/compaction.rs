@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Fraction of the model's context limit above which the agent compacts proactively
+const DEFAULT_COMPACTION_THRESHOLD: f32 = 0.8;
+
+/// What the agent should do when the conversation no longer fits in the model's context window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompactionPolicy {
+    /// Summarize older messages with the current provider and retry the turn
+    Summarize,
+    /// Drop the oldest messages and retry the turn
+    Truncate,
+    /// Stop the turn and let the caller decide (the historical behaviour)
+    Abort,
+}
+
+impl CompactionPolicy {
+    /// Reads the policy from `GOOSE_AUTO_COMPACT`, defaulting to `Abort`
+    pub fn from_config() -> Self {
+        let policy: String = Config::global()
+            .get_param("GOOSE_AUTO_COMPACT")
+            .unwrap_or_else(|_| "abort".to_string());
+        Self::parse(&policy)
+    }
+
+    fn parse(policy: &str) -> Self {
+        match policy.to_lowercase().as_str() {
+            "summarize" => CompactionPolicy::Summarize,
+            "truncate" => CompactionPolicy::Truncate,
+            _ => CompactionPolicy::Abort,
+        }
+    }
+}
+
+impl std::fmt::Display for CompactionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompactionPolicy::Summarize => write!(f, "summarize"),
+            CompactionPolicy::Truncate => write!(f, "truncate"),
+            CompactionPolicy::Abort => write!(f, "abort"),
+        }
+    }
+}
+
+/// Returns the token count above which the agent compacts before calling the provider.
+///
+/// The threshold is read from `GOOSE_AUTO_COMPACT_THRESHOLD` as a fraction of `context_limit`.
+/// Values outside of `(0, 1]` fall back to the default.
+pub fn compaction_threshold(context_limit: usize) -> usize {
+    let fraction: f32 = Config::global()
+        .get_param("GOOSE_AUTO_COMPACT_THRESHOLD")
+        .ok()
+        .filter(|f: &f32| *f > 0.0 && *f <= 1.0)
+        .unwrap_or(DEFAULT_COMPACTION_THRESHOLD);
+    (context_limit as f32 * fraction) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!(
+            CompactionPolicy::parse("summarize"),
+            CompactionPolicy::Summarize
+        );
+        assert_eq!(
+            CompactionPolicy::parse("Truncate"),
+            CompactionPolicy::Truncate
+        );
+        assert_eq!(CompactionPolicy::parse("abort"), CompactionPolicy::Abort);
+        assert_eq!(CompactionPolicy::parse("unknown"), CompactionPolicy::Abort);
+    }
+
+    #[test]
+    fn test_policy_round_trips_through_display() {
+        for policy in [
+            CompactionPolicy::Summarize,
+            CompactionPolicy::Truncate,
+            CompactionPolicy::Abort,
+        ] {
+            assert_eq!(CompactionPolicy::parse(&policy.to_string()), policy);
+        }
+    }
+}
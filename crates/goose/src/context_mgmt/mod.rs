@@ -1,5 +1,7 @@
 mod common;
+mod compaction;
 pub mod summarize;
 pub mod truncate;
 
 pub use common::*;
+pub use compaction::*;
@@ -1,8 +1,10 @@
+use crate::config::Config;
+use crate::providers::base::Usage;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-const DEFAULT_CONTEXT_LIMIT: usize = 128_000;
+pub const DEFAULT_CONTEXT_LIMIT: usize = 128_000;
 
 // Tokenizer names, used to infer from model name
 pub const GPT_4O_TOKENIZER: &str = "Xenova--gpt-4o";
@@ -34,6 +36,61 @@ static MODEL_SPECIFIC_LIMITS: Lazy<HashMap<&'static str, usize>> = Lazy::new(||
     map
 });
 
+// Prices in US dollars per million (input, output) tokens, matched against the model name
+static MODEL_SPECIFIC_PRICING: Lazy<HashMap<&'static str, ModelPricing>> = Lazy::new(|| {
+    let mut map = HashMap::new();
+    // OpenAI models, https://openai.com/api/pricing/
+    map.insert("gpt-4o", ModelPricing::new(2.50, 10.00));
+    map.insert("gpt-4o-mini", ModelPricing::new(0.15, 0.60));
+    map.insert("gpt-4-turbo", ModelPricing::new(10.00, 30.00));
+    map.insert("gpt-4.1", ModelPricing::new(2.00, 8.00));
+    map.insert("gpt-4.1-mini", ModelPricing::new(0.40, 1.60));
+    map.insert("gpt-4.1-nano", ModelPricing::new(0.10, 0.40));
+    map.insert("o1", ModelPricing::new(15.00, 60.00));
+    map.insert("o3", ModelPricing::new(2.00, 8.00));
+    map.insert("o3-mini", ModelPricing::new(1.10, 4.40));
+    map.insert("o4-mini", ModelPricing::new(1.10, 4.40));
+
+    // Anthropic models, https://www.anthropic.com/pricing#api
+    map.insert("claude-3-haiku", ModelPricing::new(0.25, 1.25));
+    map.insert("claude-3-5-haiku", ModelPricing::new(0.80, 4.00));
+    map.insert("claude-3-5-sonnet", ModelPricing::new(3.00, 15.00));
+    map.insert("claude-3-7-sonnet", ModelPricing::new(3.00, 15.00));
+    map.insert("claude-sonnet-4", ModelPricing::new(3.00, 15.00));
+    map.insert("claude-3-opus", ModelPricing::new(15.00, 75.00));
+    map.insert("claude-opus-4", ModelPricing::new(15.00, 75.00));
+
+    // Google models, https://ai.google.dev/gemini-api/docs/pricing
+    map.insert("gemini-2.0-flash", ModelPricing::new(0.10, 0.40));
+    map.insert("gemini-2.5-flash", ModelPricing::new(0.30, 2.50));
+    map.insert("gemini-2.5-pro", ModelPricing::new(1.25, 10.00));
+
+    // Prices of input tokens read from the prompt cache, where the provider discounts them
+    for (pattern, cached_input_cost) in [
+        ("gpt-4o", 1.25),
+        ("gpt-4o-mini", 0.075),
+        ("gpt-4.1", 0.50),
+        ("gpt-4.1-mini", 0.10),
+        ("gpt-4.1-nano", 0.025),
+        ("o1", 7.50),
+        ("o3", 0.50),
+        ("o3-mini", 0.55),
+        ("o4-mini", 0.275),
+        ("claude-3-haiku", 0.03),
+        ("claude-3-5-haiku", 0.08),
+        ("claude-3-5-sonnet", 0.30),
+        ("claude-3-7-sonnet", 0.30),
+        ("claude-sonnet-4", 0.30),
+        ("claude-3-opus", 1.50),
+        ("claude-opus-4", 1.50),
+    ] {
+        if let Some(pricing) = map.get_mut(pattern) {
+            *pricing = pricing.with_cached_input_cost(cached_input_cost);
+        }
+    }
+    map
+});
+
 /// Configuration for model-specific settings and limits
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
@@ -62,6 +119,65 @@ pub struct ModelLimitConfig {
     pub context_limit: usize,
 }
 
+/// Price of a model in US dollars per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input_cost_per_million: f64,
+    pub output_cost_per_million: f64,
+    /// Price of input tokens read from the prompt cache, if the model has a separate rate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input_cost_per_million: Option<f64>,
+}
+
+impl ModelPricing {
+    pub fn new(input_cost_per_million: f64, output_cost_per_million: f64) -> Self {
+        Self {
+            input_cost_per_million,
+            output_cost_per_million,
+            cached_input_cost_per_million: None,
+        }
+    }
+
+    /// Sets the price of input tokens read from the prompt cache
+    pub fn with_cached_input_cost(mut self, cached_input_cost_per_million: f64) -> Self {
+        self.cached_input_cost_per_million = Some(cached_input_cost_per_million);
+        self
+    }
+
+    /// Compute the cost in US dollars of a single provider usage
+    ///
+    /// Cached input tokens are priced at the cached rate when there is one, and as regular
+    /// input otherwise. Returns None if the usage reports neither input nor output tokens.
+    pub fn cost(&self, usage: &Usage) -> Option<f64> {
+        if usage.input_tokens.is_none() && usage.output_tokens.is_none() {
+            return None;
+        }
+        let input_tokens = usage.input_tokens.unwrap_or(0).max(0);
+        let cached = usage
+            .cached_input_tokens
+            .unwrap_or(0)
+            .clamp(0, input_tokens) as f64;
+        let input = input_tokens as f64 - cached;
+        let output = usage.output_tokens.unwrap_or(0) as f64;
+        let cached_rate = self
+            .cached_input_cost_per_million
+            .unwrap_or(self.input_cost_per_million);
+        Some(
+            (input * self.input_cost_per_million
+                + cached * cached_rate
+                + output * self.output_cost_per_million)
+                / 1_000_000.0,
+        )
+    }
+}
+
+/// Struct to represent model pattern matches and their pricing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPricingConfig {
+    pub pattern: String,
+    pub pricing: ModelPricing,
+}
+
 impl ModelConfig {
     /// Create a new ModelConfig with the specified model name
     ///
@@ -124,6 +240,51 @@ impl ModelConfig {
             .collect()
     }
 
+    /// Get the pricing for a model name
+    ///
+    /// Pricing is looked up with the following precedence:
+    /// 1. Patterns from `GOOSE_MODEL_PRICING` in the config file or environment
+    /// 2. Built-in model-specific pricing
+    ///
+    /// Within each source the longest pattern contained in the model name wins,
+    /// so "gpt-4o-mini" is not priced as "gpt-4o".
+    pub fn get_model_pricing(model_name: &str) -> Option<ModelPricing> {
+        let overrides: HashMap<String, ModelPricing> = Config::global()
+            .get_param("GOOSE_MODEL_PRICING")
+            .unwrap_or_default();
+
+        find_pricing(
+            model_name,
+            overrides
+                .iter()
+                .map(|(pattern, pricing)| (pattern.as_str(), *pricing)),
+        )
+        .or_else(|| {
+            find_pricing(
+                model_name,
+                MODEL_SPECIFIC_PRICING
+                    .iter()
+                    .map(|(&pattern, &pricing)| (pattern, pricing)),
+            )
+        })
+    }
+
+    /// Get all built-in model pattern matches and their pricing
+    pub fn get_all_model_pricing() -> Vec<ModelPricingConfig> {
+        MODEL_SPECIFIC_PRICING
+            .iter()
+            .map(|(&pattern, &pricing)| ModelPricingConfig {
+                pattern: pattern.to_string(),
+                pricing,
+            })
+            .collect()
+    }
+
+    /// Get the pricing for the current model, if known
+    pub fn pricing(&self) -> Option<ModelPricing> {
+        Self::get_model_pricing(&self.model_name)
+    }
+
     /// Set an explicit context limit
     pub fn with_context_limit(mut self, limit: Option<usize>) -> Self {
         // Default is None and therefore DEFAULT_CONTEXT_LIMIT, only set
@@ -171,6 +332,17 @@ impl ModelConfig {
     }
 }
 
+/// Find the pricing of the longest pattern contained in the model name
+fn find_pricing<'a>(
+    model_name: &str,
+    patterns: impl Iterator<Item = (&'a str, ModelPricing)>,
+) -> Option<ModelPricing> {
+    patterns
+        .filter(|(pattern, _)| model_name.contains(pattern))
+        .max_by_key(|(pattern, _)| pattern.len())
+        .map(|(_, pricing)| pricing)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -255,4 +427,56 @@ mod tests {
         assert!(gpt4_limit.is_some());
         assert_eq!(gpt4_limit.unwrap().context_limit, 128_000);
     }
+
+    #[test]
+    fn test_find_pricing_prefers_longest_pattern() {
+        let pricing = |name: &str| {
+            find_pricing(
+                name,
+                MODEL_SPECIFIC_PRICING
+                    .iter()
+                    .map(|(&pattern, &pricing)| (pattern, pricing)),
+            )
+        };
+
+        assert_eq!(pricing("gpt-4o"), Some(ModelPricing::new(2.50, 10.00)));
+        assert_eq!(
+            pricing("gpt-4o-mini-2024-07-18"),
+            Some(ModelPricing::new(0.15, 0.60))
+        );
+        assert_eq!(
+            pricing("claude-3-5-haiku-latest"),
+            Some(ModelPricing::new(0.80, 4.00))
+        );
+        assert_eq!(pricing("unknown-model"), None);
+    }
+
+    #[test]
+    fn test_find_pricing_with_overrides() {
+        let overrides = [("my-model", ModelPricing::new(1.0, 2.0))];
+        let found = find_pricing("my-model-large", overrides.iter().copied());
+        assert_eq!(found, Some(ModelPricing::new(1.0, 2.0)));
+    }
+
+    #[test]
+    fn test_model_pricing_cost() {
+        let pricing = ModelPricing::new(3.00, 15.00);
+
+        let usage = Usage::new(Some(1_000_000), Some(100_000), Some(1_100_000));
+        let cost = pricing.cost(&usage).unwrap();
+        assert!((cost - 4.5).abs() < 1e-9);
+
+        let usage = Usage::new(Some(2_000), None, None);
+        let cost = pricing.cost(&usage).unwrap();
+        assert!((cost - 0.006).abs() < 1e-9);
+
+        assert_eq!(pricing.cost(&Usage::default()), None);
+
+        // Cache reads are regular input until the pricing has a rate for them
+        let usage = Usage::new(Some(1_000_000), Some(0), Some(1_000_000))
+            .with_cached_input_tokens(Some(800_000));
+        assert!((pricing.cost(&usage).unwrap() - 3.0).abs() < 1e-9);
+        let cost = pricing.with_cached_input_cost(0.30).cost(&usage).unwrap();
+        assert!((cost - (0.6 + 0.24)).abs() < 1e-9);
+    }
 }
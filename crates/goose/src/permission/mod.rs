@@ -3,5 +3,7 @@ pub mod permission_judge;
 pub mod permission_store;
 
 pub use permission_confirmation::{Permission, PermissionConfirmation};
-pub use permission_judge::detect_read_only_tools;
+pub use permission_judge::{
+    decide_tool_permission, detect_read_only_tools, ToolPermissionDecision,
+};
 pub use permission_store::ToolPermissionStore;
@@ -1,5 +1,5 @@
 use crate::agents::platform_tools::PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME;
-use crate::config::permission::PermissionLevel;
+use crate::config::permission::{PermissionLevel, PermissionRule};
 use crate::config::PermissionManager;
 use crate::message::{Message, MessageContent, ToolRequest};
 use crate::providers::base::Provider;
@@ -152,6 +152,89 @@ pub async fn detect_read_only_tools(
     }
 }
 
+/// Why a tool call gets the permission it does, following the order the checks are made in
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolPermissionDecision {
+    /// Chat mode runs no tools
+    ChatMode,
+    /// An argument-aware rule matched, `index` is its position in the rule list
+    Rule { index: usize, rule: PermissionRule },
+    /// Auto mode runs every tool without asking
+    AutoMode,
+    /// The permission set for the tool
+    UserPermission(PermissionLevel),
+    /// What smart approve decided for the tool earlier
+    SmartApprove(PermissionLevel),
+    /// Smart approve runs tools annotated as read-only without asking
+    ReadOnlyAnnotation,
+    /// Smart approve asks the model whether the call is read-only
+    DetectReadOnly,
+    /// The mode asks before running the tool
+    Ask,
+}
+
+impl ToolPermissionDecision {
+    /// The permission the call gets, or `None` when it isn't run or the model still has to decide
+    pub fn level(&self) -> Option<PermissionLevel> {
+        match self {
+            ToolPermissionDecision::ChatMode | ToolPermissionDecision::DetectReadOnly => None,
+            ToolPermissionDecision::Rule { rule, .. } => Some(rule.permission.clone()),
+            ToolPermissionDecision::UserPermission(level)
+            | ToolPermissionDecision::SmartApprove(level) => Some(level.clone()),
+            ToolPermissionDecision::AutoMode | ToolPermissionDecision::ReadOnlyAnnotation => {
+                Some(PermissionLevel::AlwaysAllow)
+            }
+            ToolPermissionDecision::Ask => Some(PermissionLevel::AskBefore),
+        }
+    }
+}
+
+/// Decides the permission of a single tool call in `mode`, without asking the model.
+///
+/// Rules are checked first since they are the most specific, then auto mode, the permission
+/// set for the tool, and finally the mode's own fallback.
+pub fn decide_tool_permission(
+    tool_name: &str,
+    arguments: &Value,
+    mode: &str,
+    tools_with_readonly_annotation: &HashSet<String>,
+    tools_without_annotation: &HashSet<String>,
+    permission_manager: &PermissionManager,
+) -> ToolPermissionDecision {
+    if mode == "chat" {
+        return ToolPermissionDecision::ChatMode;
+    }
+
+    if let Some((index, rule)) = permission_manager.find_rule(tool_name, arguments) {
+        return ToolPermissionDecision::Rule {
+            index,
+            rule: rule.clone(),
+        };
+    }
+
+    if mode == "auto" {
+        return ToolPermissionDecision::AutoMode;
+    }
+
+    if let Some(level) = permission_manager.get_user_permission(tool_name) {
+        return ToolPermissionDecision::UserPermission(level);
+    }
+
+    if mode != "smart_approve" {
+        return ToolPermissionDecision::Ask;
+    }
+
+    if let Some(level) = permission_manager.get_smart_approve_permission(tool_name) {
+        ToolPermissionDecision::SmartApprove(level)
+    } else if tools_with_readonly_annotation.contains(tool_name) {
+        ToolPermissionDecision::ReadOnlyAnnotation
+    } else if tools_without_annotation.contains(tool_name) {
+        ToolPermissionDecision::DetectReadOnly
+    } else {
+        ToolPermissionDecision::Ask
+    }
+}
+
 // Define return structure
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PermissionCheckResult {
@@ -176,59 +259,32 @@ pub async fn check_tool_permissions(
 
     for request in candidate_requests {
         if let Ok(tool_call) = request.tool_call.clone() {
-            if mode == "chat" {
+            let decision = decide_tool_permission(
+                &tool_call.name,
+                &tool_call.arguments,
+                mode,
+                &tools_with_readonly_annotation,
+                &tools_without_annotation,
+                permission_manager,
+            );
+            if decision == ToolPermissionDecision::ChatMode {
                 continue;
-            } else if mode == "auto" {
-                approved.push(request.clone());
-            } else {
-                if tool_call.name == PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME {
-                    extension_request_ids.push(request.id.clone());
-                }
-
-                // 1. Check user-defined permission
-                if let Some(level) = permission_manager.get_user_permission(&tool_call.name) {
-                    match level {
-                        PermissionLevel::AlwaysAllow => approved.push(request.clone()),
-                        PermissionLevel::AskBefore => needs_approval.push(request.clone()),
-                        PermissionLevel::NeverAllow => denied.push(request.clone()),
-                    }
-                    continue;
-                }
+            }
 
-                // 2. Fallback based on mode
-                match mode {
-                    "approve" => {
-                        needs_approval.push(request.clone());
-                    }
-                    "smart_approve" => {
-                        if let Some(level) =
-                            permission_manager.get_smart_approve_permission(&tool_call.name)
-                        {
-                            match level {
-                                PermissionLevel::AlwaysAllow => approved.push(request.clone()),
-                                PermissionLevel::AskBefore => needs_approval.push(request.clone()),
-                                PermissionLevel::NeverAllow => denied.push(request.clone()),
-                            }
-                            continue;
-                        }
+            if mode != "auto" && tool_call.name == PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME {
+                extension_request_ids.push(request.id.clone());
+            }
 
-                        if tools_with_readonly_annotation.contains(&tool_call.name) {
-                            approved.push(request.clone());
-                        } else if tools_without_annotation.contains(&tool_call.name) {
-                            llm_detect_candidates.push(request.clone());
-                        } else {
-                            needs_approval.push(request.clone());
-                        }
-                    }
-                    _ => {
-                        needs_approval.push(request.clone());
-                    }
-                }
+            match decision.level() {
+                Some(PermissionLevel::AlwaysAllow) => approved.push(request.clone()),
+                Some(PermissionLevel::AskBefore) => needs_approval.push(request.clone()),
+                Some(PermissionLevel::NeverAllow) => denied.push(request.clone()),
+                None => llm_detect_candidates.push(request.clone()),
             }
         }
     }
 
-    // 3. LLM detect
+    // Ask the model about the calls smart approve couldn't decide
     if !llm_detect_candidates.is_empty() && mode == "smart_approve" {
         let detected_readonly_tools =
             detect_read_only_tools(provider, llm_detect_candidates.iter().collect()).await;
@@ -264,6 +320,7 @@ pub async fn check_tool_permissions(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::permission::{ArgumentMatcher, PermissionRule};
     use crate::message::{Message, MessageContent, ToolRequest};
     use crate::model::ModelConfig;
     use crate::providers::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
@@ -515,4 +572,140 @@ mod tests {
         assert_eq!(result.needs_approval.len(), 0); // data_fetcher should need approval
         assert_eq!(result.denied.len(), 0); // No tool should be denied in this test
     }
+
+    #[test]
+    fn test_decide_tool_permission_order() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut permission_manager = PermissionManager::new(temp_file.path());
+        let readonly = HashSet::from(["file_reader".to_string()]);
+        let unannotated = HashSet::from(["file_writer".to_string()]);
+        let decide = |tool: &str, mode: &str, manager: &PermissionManager| {
+            decide_tool_permission(tool, &json!({}), mode, &readonly, &unannotated, manager)
+        };
+
+        assert_eq!(
+            decide("file_writer", "chat", &permission_manager),
+            ToolPermissionDecision::ChatMode
+        );
+        assert_eq!(
+            decide("file_writer", "auto", &permission_manager),
+            ToolPermissionDecision::AutoMode
+        );
+        assert_eq!(
+            decide("file_reader", "smart_approve", &permission_manager),
+            ToolPermissionDecision::ReadOnlyAnnotation
+        );
+        assert_eq!(
+            decide("file_writer", "smart_approve", &permission_manager),
+            ToolPermissionDecision::DetectReadOnly
+        );
+        assert_eq!(
+            decide("unknown_tool", "smart_approve", &permission_manager),
+            ToolPermissionDecision::Ask
+        );
+        assert_eq!(
+            decide("file_reader", "approve", &permission_manager),
+            ToolPermissionDecision::Ask
+        );
+
+        // The tool's own permission comes before the annotations
+        permission_manager.update_user_permission("file_reader", PermissionLevel::NeverAllow);
+        let decision = decide("file_reader", "smart_approve", &permission_manager);
+        assert_eq!(
+            decision,
+            ToolPermissionDecision::UserPermission(PermissionLevel::NeverAllow)
+        );
+        assert_eq!(decision.level(), Some(PermissionLevel::NeverAllow));
+
+        // and rules before everything but chat mode
+        permission_manager.add_rule(PermissionRule {
+            tool: "file_reader".to_string(),
+            args: Default::default(),
+            path: None,
+            permission: PermissionLevel::AlwaysAllow,
+        });
+        let decision = decide("file_reader", "auto", &permission_manager);
+        assert!(matches!(
+            decision,
+            ToolPermissionDecision::Rule { index: 0, .. }
+        ));
+        assert_eq!(decision.level(), Some(PermissionLevel::AlwaysAllow));
+    }
+
+    #[tokio::test]
+    async fn test_check_tool_permissions_rules() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut permission_manager = PermissionManager::new(temp_file.path());
+        let provider = create_mock_provider();
+
+        permission_manager.update_user_permission("developer__shell", PermissionLevel::AskBefore);
+        permission_manager.add_rule(PermissionRule {
+            tool: "developer__shell".to_string(),
+            args: [(
+                "command".to_string(),
+                ArgumentMatcher::Glob("cargo test*".to_string()),
+            )]
+            .into_iter()
+            .collect(),
+            path: None,
+            permission: PermissionLevel::AlwaysAllow,
+        });
+        permission_manager.add_rule(PermissionRule {
+            tool: "developer__shell".to_string(),
+            args: [(
+                "command".to_string(),
+                ArgumentMatcher::Regex {
+                    regex: "^rm ".to_string(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            path: None,
+            permission: PermissionLevel::NeverAllow,
+        });
+
+        let shell_request = |id: &str, command: &str| ToolRequest {
+            id: id.to_string(),
+            tool_call: ToolResult::Ok(ToolCall {
+                name: "developer__shell".to_string(),
+                arguments: serde_json::json!({ "command": command }),
+            }),
+        };
+        let candidate_requests = vec![
+            shell_request("tests", "cargo test"),
+            shell_request("remove", "rm -rf target"),
+            shell_request("other", "ls"),
+        ];
+
+        let (result, _) = check_tool_permissions(
+            &candidate_requests,
+            "approve",
+            HashSet::new(),
+            HashSet::new(),
+            &mut permission_manager,
+            provider.clone(),
+        )
+        .await;
+        assert_eq!(result.approved.len(), 1);
+        assert_eq!(result.approved[0].id, "tests");
+        assert_eq!(result.denied.len(), 1);
+        assert_eq!(result.denied[0].id, "remove");
+        // No rule matched, so the tool-level permission applies
+        assert_eq!(result.needs_approval.len(), 1);
+        assert_eq!(result.needs_approval[0].id, "other");
+
+        // Deny rules hold even in auto mode
+        let (result, _) = check_tool_permissions(
+            &candidate_requests,
+            "auto",
+            HashSet::new(),
+            HashSet::new(),
+            &mut permission_manager,
+            provider,
+        )
+        .await;
+        assert_eq!(result.approved.len(), 2);
+        assert_eq!(result.denied.len(), 1);
+        assert_eq!(result.denied[0].id, "remove");
+    }
 }
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use futures::stream::BoxStream;
 use serde::{Deserialize, Serialize};
 
 use super::errors::ProviderError;
@@ -130,6 +131,9 @@ pub struct Usage {
     pub input_tokens: Option<i32>,
     pub output_tokens: Option<i32>,
     pub total_tokens: Option<i32>,
+    /// Input tokens read from the provider's prompt cache, already included in `input_tokens`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input_tokens: Option<i32>,
 }
 
 impl Usage {
@@ -142,10 +146,46 @@ impl Usage {
             input_tokens,
             output_tokens,
             total_tokens,
+            cached_input_tokens: None,
         }
     }
+
+    /// Sets how many of the input tokens were read from the prompt cache
+    pub fn with_cached_input_tokens(mut self, cached_input_tokens: Option<i32>) -> Self {
+        self.cached_input_tokens = cached_input_tokens;
+        self
+    }
+}
+
+/// An incremental piece of an assistant message produced while a completion is streaming
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MessageDelta {
+    /// A fragment of assistant text
+    Text { text: String },
+    /// A fragment of the model's reasoning
+    Thinking { thinking: String },
+    /// A fragment of a tool call; the name is only present on the first fragment for an index
+    #[serde(rename_all = "camelCase")]
+    ToolCall {
+        index: usize,
+        id: Option<String>,
+        name: Option<String>,
+        arguments: String,
+    },
 }
 
+/// An item of a streamed completion
+#[derive(Debug, Clone)]
+pub enum CompletionChunk {
+    /// A partial update which UIs can render incrementally
+    Delta(MessageDelta),
+    /// The fully assembled response, always the last item of a successful stream
+    Done(Message, ProviderUsage),
+}
+
+pub type CompletionStream = BoxStream<'static, Result<CompletionChunk, ProviderError>>;
+
 use async_trait::async_trait;
 
 /// Trait for LeadWorkerProvider-specific functionality
@@ -162,6 +202,16 @@ pub trait Provider: Send + Sync {
     where
         Self: Sized;
 
+    /// Short name of the provider for telemetry, such as `openai`, taken from the module
+    /// that implements it
+    fn telemetry_name(&self) -> String {
+        let type_name = std::any::type_name::<Self>();
+        let name = type_name
+            .strip_prefix("goose::providers::")
+            .unwrap_or(type_name);
+        name.split("::").next().unwrap_or(name).to_string()
+    }
+
     /// Generate the next message using the configured model and other parameters
     ///
     /// # Arguments
@@ -182,6 +232,28 @@ pub trait Provider: Send + Sync {
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError>;
 
+    /// Generate the next message as a stream of deltas followed by the complete message
+    ///
+    /// The default implementation wraps `complete` and yields a single `CompletionChunk::Done`,
+    /// so providers only need to override this when their API supports incremental output.
+    /// Errors raised before the first chunk must follow the same rules as `complete`.
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<CompletionStream, ProviderError> {
+        let (message, usage) = self.complete(system, messages, tools).await?;
+        Ok(Box::pin(futures::stream::once(async move {
+            Ok(CompletionChunk::Done(message, usage))
+        })))
+    }
+
+    /// Check if this provider streams completions incrementally
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
     /// Get the model config from the provider
     fn get_model_config(&self) -> ModelConfig;
 
@@ -293,6 +365,51 @@ mod tests {
         assert_eq!(*model_info.get("unknown-model").unwrap(), 128_000);
     }
 
+    #[tokio::test]
+    async fn test_default_stream_wraps_complete() -> Result<()> {
+        use futures::StreamExt;
+
+        struct FixedProvider;
+
+        #[async_trait]
+        impl Provider for FixedProvider {
+            fn metadata() -> ProviderMetadata {
+                ProviderMetadata::empty()
+            }
+
+            async fn complete(
+                &self,
+                _system: &str,
+                _messages: &[Message],
+                _tools: &[Tool],
+            ) -> Result<(Message, ProviderUsage), ProviderError> {
+                Ok((
+                    Message::assistant().with_text("hello"),
+                    ProviderUsage::new("fixed".to_string(), Usage::default()),
+                ))
+            }
+
+            fn get_model_config(&self) -> ModelConfig {
+                ModelConfig::new("fixed".to_string())
+            }
+        }
+
+        let provider = FixedProvider;
+        assert!(!provider.supports_streaming());
+
+        let chunks: Vec<_> = provider.stream("", &[], &[]).await?.collect().await;
+        assert_eq!(chunks.len(), 1);
+        match &chunks[0] {
+            Ok(CompletionChunk::Done(message, usage)) => {
+                assert_eq!(message.as_concat_text(), "hello");
+                assert_eq!(usage.model, "fixed");
+            }
+            other => panic!("Expected a single Done chunk, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_model_info_creation() {
         // Test direct ModelInfo creation
@@ -1,5 +1,4 @@
 use std::collections::HashMap;
-use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -14,6 +13,7 @@ use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
 use super::errors::ProviderError;
 use crate::message::Message;
 use crate::model::ModelConfig;
+use crate::providers::retry::RetryConfig;
 use crate::providers::utils::emit_debug_trace;
 
 // Import the migrated helper functions from providers/formats/bedrock.rs
@@ -30,11 +30,19 @@ pub const BEDROCK_KNOWN_MODELS: &[&str] = &[
     "anthropic.claude-3-5-sonnet-20241022-v2:0",
 ];
 
+// Bedrock throttles aggressively, so back off for longer than the shared defaults
+const DEFAULT_MAX_RETRIES: usize = 10;
+const DEFAULT_INITIAL_RETRY_INTERVAL_MS: u64 = 20_000; // 20 seconds
+const DEFAULT_MAX_RETRY_INTERVAL_MS: u64 = 120_000; // 120 seconds (2 minutes)
+const DEFAULT_BACKOFF_MULTIPLIER: f64 = 2.0;
+
 #[derive(Debug, serde::Serialize)]
 pub struct BedrockProvider {
     #[serde(skip)]
     client: Client,
     model: ModelConfig,
+    #[serde(skip)]
+    retry_config: RetryConfig,
 }
 
 impl BedrockProvider {
@@ -66,7 +74,21 @@ impl BedrockProvider {
         )?;
         let client = Client::new(&sdk_config);
 
-        Ok(Self { client, model })
+        let retry_config = RetryConfig::from_config_with_prefix(
+            Some("BEDROCK"),
+            RetryConfig {
+                max_retries: DEFAULT_MAX_RETRIES,
+                initial_interval_ms: DEFAULT_INITIAL_RETRY_INTERVAL_MS,
+                backoff_multiplier: DEFAULT_BACKOFF_MULTIPLIER,
+                max_interval_ms: DEFAULT_MAX_RETRY_INTERVAL_MS,
+            },
+        );
+
+        Ok(Self {
+            client,
+            model,
+            retry_config,
+        })
     }
 }
 
@@ -123,17 +145,9 @@ impl Provider for BedrockProvider {
             request = request.tool_config(to_bedrock_tool_config(tools)?);
         }
 
-        // Retry configuration
-        const MAX_RETRIES: u32 = 10;
-        const INITIAL_BACKOFF_MS: u64 = 20_000; // 20 seconds
-        const MAX_BACKOFF_MS: u64 = 120_000; // 120 seconds (2 minutes)
-
         let mut attempts = 0;
-        let mut backoff_ms = INITIAL_BACKOFF_MS;
 
         loop {
-            attempts += 1;
-
             match request.clone().send().await {
                 Ok(response) => {
                     // Successful response, process it and return
@@ -171,32 +185,33 @@ impl Provider for BedrockProvider {
                 Err(err) => {
                     match err.into_service_error() {
                         ConverseError::ThrottlingException(throttle_err) => {
-                            if attempts > MAX_RETRIES {
+                            let max_retries = self.retry_config.max_retries;
+                            if attempts >= max_retries {
                                 // We've exhausted our retries
                                 tracing::error!(
-                                    "Failed after {MAX_RETRIES} retries: {:?}",
+                                    "Failed after {max_retries} retries: {:?}",
                                     throttle_err
                                 );
                                 return Err(ProviderError::RateLimitExceeded(format!(
-                                    "Failed to call Bedrock after {MAX_RETRIES} retries: {:?}",
+                                    "Failed to call Bedrock after {max_retries} retries: {:?}",
                                     throttle_err
                                 )));
                             }
+                            attempts += 1;
+
+                            // Wait before retry with exponential backoff
+                            let delay = self.retry_config.delay_for_attempt(attempts);
 
                             // Log retry attempt
                             tracing::warn!(
-                                "Bedrock throttling error (attempt {}/{}), retrying in {} ms: {:?}",
+                                "Bedrock throttling error (attempt {}/{}), retrying in {:?}: {:?}",
                                 attempts,
-                                MAX_RETRIES,
-                                backoff_ms,
+                                max_retries,
+                                delay,
                                 throttle_err
                             );
 
-                            // Wait before retry with exponential backoff
-                            sleep(Duration::from_millis(backoff_ms)).await;
-
-                            // Calculate next backoff with exponential growth, capped at max
-                            backoff_ms = (backoff_ms * 2).min(MAX_BACKOFF_MS);
+                            sleep(delay).await;
 
                             // Continue to the next retry attempt
                             continue;
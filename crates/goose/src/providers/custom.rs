@@ -0,0 +1,572 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::base::{
+    CompletionChunk, CompletionStream, ConfigKey, ModelInfo, Provider, ProviderMetadata,
+    ProviderUsage, Usage,
+};
+use super::errors::ProviderError;
+use super::formats::openai::{
+    create_request, get_usage, response_to_message, response_to_streaming_message, with_streaming,
+};
+use super::retry::{send_with_retry, RetryConfig};
+use super::utils::{
+    emit_debug_trace, get_model, handle_response_openai_compat,
+    handle_stream_response_openai_compat, sse_data_stream, ImageFormat,
+};
+use crate::config::Config;
+use crate::message::{Message, MessageContent};
+use crate::model::ModelConfig;
+use mcp_core::{tool::Tool, Content};
+
+/// Config key holding the list of custom providers
+pub const CUSTOM_PROVIDERS_CONFIG_KEY: &str = "GOOSE_CUSTOM_PROVIDERS";
+
+const DEFAULT_BASE_PATH: &str = "v1/chat/completions";
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+const IMAGE_OMITTED: &str = "[image omitted, this model does not accept images]";
+
+/// How a custom provider sends its API key
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomProviderAuth {
+    /// `Authorization: Bearer <key>`, as OpenAI does
+    #[default]
+    Bearer,
+    /// The bare key in the named header, such as `api-key` or `x-api-key`
+    Header(String),
+    /// No key at all, typical for servers on localhost
+    #[serde(rename = "none")]
+    NoAuth,
+}
+
+/// A model served by a custom provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomModelConfig {
+    pub name: String,
+    /// Context window in tokens, guessed from the model name when not set
+    #[serde(default)]
+    pub context_limit: Option<usize>,
+}
+
+/// An OpenAI-compatible server declared in the config file, such as vLLM, LiteLLM or
+/// LM Studio, so it can be used without a provider written for it:
+///
+/// ```yaml
+/// GOOSE_CUSTOM_PROVIDERS:
+///   - name: lmstudio
+///     display_name: LM Studio
+///     base_url: http://localhost:1234
+///     auth: none
+///     models:
+///       - name: qwen2.5-coder-14b-instruct
+///         context_limit: 32768
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustomProviderConfig {
+    /// Identifier used as GOOSE_PROVIDER
+    pub name: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Scheme and host of the server, e.g. http://localhost:8000
+    pub base_url: String,
+    /// Chat completions endpoint, relative to the base URL
+    #[serde(default = "default_base_path")]
+    pub base_path: String,
+    #[serde(default)]
+    pub auth: CustomProviderAuth,
+    /// Config key or environment variable holding the API key, `<NAME>_API_KEY` by default
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+    /// Extra headers sent with every request
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Models offered in `goose configure`; the first one is the default
+    #[serde(default)]
+    pub models: Vec<CustomModelConfig>,
+    /// Whether the models can call tools; when false no tools are sent
+    #[serde(default = "default_true")]
+    pub supports_tools: bool,
+    /// Whether the models accept images; when false images are replaced with a note
+    #[serde(default = "default_true")]
+    pub supports_images: bool,
+    /// Whether the server can stream responses
+    #[serde(default = "default_true")]
+    pub supports_streaming: bool,
+    /// Request timeout in seconds
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+fn default_base_path() -> String {
+    DEFAULT_BASE_PATH.to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl CustomProviderConfig {
+    /// Prefix of the provider's config keys: its name in upper case, e.g. LM_STUDIO
+    fn key_prefix(&self) -> String {
+        self.name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    pub fn api_key_env(&self) -> String {
+        self.api_key_env
+            .clone()
+            .unwrap_or_else(|| format!("{}_API_KEY", self.key_prefix()))
+    }
+
+    /// Config key that overrides the declared base URL
+    pub fn host_key(&self) -> String {
+        format!("{}_HOST", self.key_prefix())
+    }
+
+    pub fn default_model(&self) -> String {
+        self.models
+            .first()
+            .map(|model| model.name.clone())
+            .unwrap_or_default()
+    }
+
+    fn context_limit(&self, model_name: &str) -> Option<usize> {
+        self.models
+            .iter()
+            .find(|model| model.name == model_name)
+            .and_then(|model| model.context_limit)
+    }
+
+    pub fn metadata(&self) -> ProviderMetadata {
+        let mut config_keys = vec![];
+        if self.auth != CustomProviderAuth::NoAuth {
+            config_keys.push(ConfigKey::new(&self.api_key_env(), true, true, None));
+        }
+        config_keys.push(ConfigKey::new(
+            &self.host_key(),
+            false,
+            false,
+            Some(&self.base_url),
+        ));
+
+        ProviderMetadata {
+            name: self.name.clone(),
+            display_name: self
+                .display_name
+                .clone()
+                .unwrap_or_else(|| self.name.clone()),
+            description: self
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("OpenAI-compatible server at {}", self.base_url)),
+            default_model: self.default_model(),
+            known_models: self
+                .models
+                .iter()
+                .map(|model| ModelInfo {
+                    name: model.name.clone(),
+                    context_limit: model
+                        .context_limit
+                        .unwrap_or_else(|| ModelConfig::new(model.name.clone()).context_limit()),
+                })
+                .collect(),
+            model_doc_link: self.base_url.clone(),
+            config_keys,
+        }
+    }
+}
+
+/// The custom providers declared in the config, skipping any that are invalid
+pub fn custom_providers() -> Vec<CustomProviderConfig> {
+    match Config::global().get_param::<Vec<CustomProviderConfig>>(CUSTOM_PROVIDERS_CONFIG_KEY) {
+        Ok(providers) => providers
+            .into_iter()
+            .filter(|provider| {
+                let valid = !provider.name.is_empty() && !provider.base_url.is_empty();
+                if !valid {
+                    tracing::warn!("Ignoring custom provider without a name or base_url");
+                }
+                valid
+            })
+            .collect(),
+        Err(crate::config::ConfigError::NotFound(_)) => vec![],
+        Err(e) => {
+            tracing::warn!("Failed to read {}: {}", CUSTOM_PROVIDERS_CONFIG_KEY, e);
+            vec![]
+        }
+    }
+}
+
+pub fn find_custom_provider(name: &str) -> Option<CustomProviderConfig> {
+    custom_providers()
+        .into_iter()
+        .find(|provider| provider.name == name)
+}
+
+/// A provider for a server declared in `GOOSE_CUSTOM_PROVIDERS`, speaking the OpenAI
+/// chat completions format
+#[derive(Debug, serde::Serialize)]
+pub struct CustomProvider {
+    #[serde(skip)]
+    client: Client,
+    #[serde(skip)]
+    config: CustomProviderConfig,
+    host: String,
+    #[serde(skip)]
+    api_key: Option<String>,
+    model: ModelConfig,
+    #[serde(skip)]
+    retry_config: RetryConfig,
+}
+
+impl CustomProvider {
+    pub fn from_config(config: CustomProviderConfig, mut model: ModelConfig) -> Result<Self> {
+        let global = Config::global();
+        let api_key = match config.auth {
+            CustomProviderAuth::NoAuth => None,
+            _ => Some(global.get_secret::<String>(&config.api_key_env())?),
+        };
+        let host: String = global
+            .get_param(&config.host_key())
+            .unwrap_or_else(|_| config.base_url.clone());
+
+        // The declared context limit only applies when nothing more specific is set
+        if model.context_limit.is_none() {
+            model = model.with_context_limit(config.context_limit(&model.model_name));
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(
+                config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS),
+            ))
+            .build()?;
+
+        Ok(Self {
+            client,
+            config,
+            host,
+            api_key,
+            model,
+            retry_config: RetryConfig::from_config(),
+        })
+    }
+
+    fn add_headers(&self, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(api_key) = &self.api_key {
+            request = match &self.config.auth {
+                CustomProviderAuth::Bearer => request.bearer_auth(api_key),
+                CustomProviderAuth::Header(name) => request.header(name, api_key),
+                CustomProviderAuth::NoAuth => request,
+            };
+        }
+        for (key, value) in &self.config.headers {
+            request = request.header(key, value);
+        }
+        request
+    }
+
+    fn url(&self, path: &str) -> Result<url::Url, ProviderError> {
+        let base_url = url::Url::parse(&self.host)
+            .map_err(|e| ProviderError::RequestFailed(format!("Invalid base URL: {e}")))?;
+        base_url.join(path).map_err(|e| {
+            ProviderError::RequestFailed(format!("Failed to construct endpoint URL: {e}"))
+        })
+    }
+
+    /// The chat completions request, leaving out what the models can't take
+    fn create_request(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<Value, ProviderError> {
+        let tools = if self.config.supports_tools {
+            tools
+        } else {
+            &[]
+        };
+        let request = if self.config.supports_images {
+            create_request(&self.model, system, messages, tools, &ImageFormat::OpenAi)
+        } else {
+            let messages = without_images(messages);
+            create_request(&self.model, system, &messages, tools, &ImageFormat::OpenAi)
+        };
+        Ok(request?)
+    }
+
+    async fn send(&self, payload: &Value) -> Result<Response, ProviderError> {
+        let url = self.url(&self.config.base_path)?;
+
+        send_with_retry(&self.retry_config, || {
+            self.add_headers(self.client.post(url.clone()))
+                .json(payload)
+                .send()
+        })
+        .await
+    }
+}
+
+/// Replace images, including those in tool results, with a note for the model
+fn without_images(messages: &[Message]) -> Vec<Message> {
+    messages
+        .iter()
+        .map(|message| {
+            let mut message = message.clone();
+            for content in message.content.iter_mut() {
+                match content {
+                    MessageContent::Image(_) => *content = MessageContent::text(IMAGE_OMITTED),
+                    MessageContent::ToolResponse(response) => {
+                        if let Ok(contents) = response.tool_result.as_mut() {
+                            for content in contents.iter_mut() {
+                                if matches!(content, Content::Image(_)) {
+                                    *content = Content::text(IMAGE_OMITTED);
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            message
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Provider for CustomProvider {
+    /// Custom providers describe themselves through [`CustomProviderConfig::metadata`]
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            "custom",
+            "Custom",
+            "An OpenAI-compatible server declared in the config file",
+            "",
+            vec![],
+            "",
+            vec![],
+        )
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model.clone()
+    }
+
+    #[tracing::instrument(
+        skip(self, system, messages, tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn complete(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let payload = self.create_request(system, messages, tools)?;
+
+        let response = self.send(&payload).await?;
+        let response = handle_response_openai_compat(response).await?;
+
+        let message = response_to_message(response.clone())?;
+        let usage = match get_usage(&response) {
+            Ok(usage) => usage,
+            Err(ProviderError::UsageError(e)) => {
+                tracing::debug!("Failed to get usage data: {}", e);
+                Usage::default()
+            }
+            Err(e) => return Err(e),
+        };
+        let model = get_model(&response);
+        emit_debug_trace(&self.model, &payload, &response, &usage);
+        Ok((message, ProviderUsage::new(model, usage)))
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<CompletionStream, ProviderError> {
+        if !self.config.supports_streaming {
+            let (message, usage) = self.complete(system, messages, tools).await?;
+            return Ok(Box::pin(futures::stream::once(async move {
+                Ok(CompletionChunk::Done(message, usage))
+            })));
+        }
+
+        let payload = with_streaming(self.create_request(system, messages, tools)?);
+
+        let response = self.send(&payload).await?;
+        let response = handle_stream_response_openai_compat(response).await?;
+
+        Ok(Box::pin(response_to_streaming_message(sse_data_stream(
+            response.bytes_stream(),
+        ))))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.config.supports_streaming
+    }
+
+    /// The declared models, or those the server lists at `v1/models` if none are declared
+    async fn fetch_supported_models_async(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        if !self.config.models.is_empty() {
+            return Ok(Some(
+                self.config
+                    .models
+                    .iter()
+                    .map(|model| model.name.clone())
+                    .collect(),
+            ));
+        }
+
+        let url = self.url("v1/models")?;
+        let response = self.add_headers(self.client.get(url)).send().await?;
+        let json: Value = response.json().await?;
+        let Some(data) = json.get("data").and_then(|v| v.as_array()) else {
+            return Ok(None);
+        };
+        let mut models: Vec<String> = data
+            .iter()
+            .filter_map(|m| m.get("id").and_then(|v| v.as_str()).map(str::to_string))
+            .collect();
+        models.sort();
+        Ok(Some(models))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const CONFIG: &str = r#"
+- name: lm-studio
+  display_name: LM Studio
+  base_url: http://localhost:1234
+  auth: none
+  supports_images: false
+  models:
+    - name: qwen2.5-coder
+      context_limit: 32768
+    - name: llama3.2
+- name: litellm
+  base_url: https://litellm.internal
+  auth:
+    header: x-api-key
+  supports_tools: false
+"#;
+
+    fn configs() -> Vec<CustomProviderConfig> {
+        serde_yaml::from_str(CONFIG).unwrap()
+    }
+
+    fn provider(config: CustomProviderConfig, host: &str) -> CustomProvider {
+        let model = ModelConfig::new(config.default_model());
+        CustomProvider {
+            client: Client::new(),
+            host: host.to_string(),
+            api_key: (config.auth != CustomProviderAuth::NoAuth).then(|| "secret".to_string()),
+            model,
+            config,
+            retry_config: RetryConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_custom_providers() {
+        let configs = configs();
+        assert_eq!(configs[0].auth, CustomProviderAuth::NoAuth);
+        assert_eq!(configs[0].base_path, "v1/chat/completions");
+        assert!(configs[0].supports_tools);
+        assert_eq!(
+            configs[1].auth,
+            CustomProviderAuth::Header("x-api-key".to_string())
+        );
+        assert_eq!(configs[1].api_key_env(), "LITELLM_API_KEY");
+        assert_eq!(configs[0].host_key(), "LM_STUDIO_HOST");
+    }
+
+    #[test]
+    fn test_custom_provider_metadata() {
+        let metadata = configs()[0].metadata();
+        assert_eq!(metadata.name, "lm-studio");
+        assert_eq!(metadata.display_name, "LM Studio");
+        assert_eq!(metadata.default_model, "qwen2.5-coder");
+        assert_eq!(metadata.known_models[0].context_limit, 32768);
+        assert_eq!(metadata.known_models.len(), 2);
+        // Without auth there is no key to ask for, only the optional host override
+        assert!(metadata.config_keys.iter().all(|key| !key.required));
+
+        let metadata = configs()[1].metadata();
+        assert_eq!(metadata.config_keys[0].name, "LITELLM_API_KEY");
+        assert!(metadata.config_keys[0].required && metadata.config_keys[0].secret);
+    }
+
+    #[test]
+    fn test_request_leaves_out_unsupported_features() {
+        let tool = Tool::new("shell", "Run a command", json!({"type": "object"}), None);
+        let messages = vec![Message::user()
+            .with_text("What is in this picture?")
+            .with_image("aGVsbG8=", "image/png")];
+
+        let lm_studio = provider(configs()[0].clone(), "http://localhost:1234");
+        let request = lm_studio
+            .create_request("system", &messages, &[tool.clone()])
+            .unwrap();
+        assert_eq!(request["tools"].as_array().unwrap().len(), 1);
+        assert!(request.to_string().contains(IMAGE_OMITTED));
+        assert!(!request.to_string().contains("aGVsbG8="));
+
+        let litellm = provider(configs()[1].clone(), "https://litellm.internal");
+        let request = litellm
+            .create_request("system", &messages, &[tool])
+            .unwrap();
+        assert!(request.get("tools").is_none());
+        assert!(request.to_string().contains("aGVsbG8="));
+    }
+
+    #[tokio::test]
+    async fn test_complete_sends_configured_auth_header() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("x-api-key", "secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{
+                    "message": {"role": "assistant", "content": "Hello from LiteLLM"},
+                    "finish_reason": "stop"
+                }],
+                "model": "gpt-4o",
+                "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let litellm = provider(configs()[1].clone(), &server.uri());
+        let (message, usage) = litellm
+            .complete("system", &[Message::user().with_text("Hi")], &[])
+            .await
+            .unwrap();
+        assert_eq!(message.as_concat_text(), "Hello from LiteLLM");
+        assert_eq!(usage.usage.total_tokens, Some(15));
+    }
+}
@@ -1,6 +1,21 @@
-use anyhow::Result;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::base::Provider;
+use crate::config::Config;
+use crate::model::ModelConfig;
+
+/// Embedding model used when `GOOSE_EMBEDDING_MODEL` is not set
+pub const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+/// Embedding endpoint of an OpenAI-compatible server, relative to its host
+pub const DEFAULT_EMBEDDING_PATH: &str = "v1/embeddings";
+/// `GOOSE_EMBEDDING_PROVIDER` value for an OpenAI-compatible server that is not a chat provider
+pub const OPENAI_COMPATIBLE_EMBEDDING_PROVIDER: &str = "openai_compatible";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingRequest {
@@ -19,6 +34,214 @@ pub struct EmbeddingData {
 }
 
 #[async_trait]
-pub trait EmbeddingCapable {
+pub trait EmbeddingCapable: Send + Sync {
     async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>>;
 }
+
+/// The embedding model from `GOOSE_EMBEDDING_MODEL`, or `default` when it is not set
+pub fn embedding_model(default: &str) -> String {
+    Config::global()
+        .get_param("GOOSE_EMBEDDING_MODEL")
+        .unwrap_or_else(|_| default.to_string())
+}
+
+/// Embeds with any OpenAI-compatible `/v1/embeddings` endpoint, such as a local LM Studio,
+/// vLLM or text-embeddings-inference server, independently of the chat provider
+pub struct OpenAiCompatibleEmbedder {
+    client: Client,
+    url: Url,
+    api_key: Option<String>,
+    model: String,
+}
+
+impl OpenAiCompatibleEmbedder {
+    pub fn new(host: &str, path: &str, api_key: Option<String>, model: String) -> Result<Self> {
+        let base_url = Url::parse(host).context("Invalid GOOSE_EMBEDDING_HOST")?;
+        let url = base_url
+            .join(path)
+            .context("Failed to construct embeddings URL")?;
+
+        Ok(Self {
+            client: Client::new(),
+            url,
+            api_key,
+            model,
+        })
+    }
+
+    /// Configured by `GOOSE_EMBEDDING_HOST`, `GOOSE_EMBEDDING_PATH`, `GOOSE_EMBEDDING_API_KEY`
+    /// and `GOOSE_EMBEDDING_MODEL`
+    pub fn from_config() -> Result<Self> {
+        let config = Config::global();
+        let host: String = config.get_param("GOOSE_EMBEDDING_HOST").context(
+            "GOOSE_EMBEDDING_HOST must be set to use an OpenAI-compatible embedding endpoint",
+        )?;
+        let path: String = config
+            .get_param("GOOSE_EMBEDDING_PATH")
+            .unwrap_or_else(|_| DEFAULT_EMBEDDING_PATH.to_string());
+        let api_key: Option<String> = config.get_secret("GOOSE_EMBEDDING_API_KEY").ok();
+
+        Self::new(
+            &host,
+            &path,
+            api_key,
+            embedding_model(DEFAULT_EMBEDDING_MODEL),
+        )
+    }
+}
+
+#[async_trait]
+impl EmbeddingCapable for OpenAiCompatibleEmbedder {
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let request = EmbeddingRequest {
+            input: texts,
+            model: self.model.clone(),
+        };
+
+        let mut req = self.client.post(self.url.clone()).json(&request);
+        if let Some(api_key) = &self.api_key {
+            req = req.bearer_auth(api_key);
+        }
+
+        let response = req
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send embedding request: {e}"))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!("Embedding API error: {}", error_text));
+        }
+
+        let embedding_response: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to parse embedding response: {e}"))?;
+
+        Ok(embedding_response
+            .data
+            .into_iter()
+            .map(|d| d.embedding)
+            .collect())
+    }
+}
+
+/// Embeds with a chat provider's own embedding support
+pub struct ProviderEmbedder {
+    provider: Arc<dyn Provider>,
+}
+
+impl ProviderEmbedder {
+    pub fn new(provider: Arc<dyn Provider>) -> Self {
+        Self { provider }
+    }
+}
+
+#[async_trait]
+impl EmbeddingCapable for ProviderEmbedder {
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        if !self.provider.supports_embeddings() {
+            return Err(anyhow::anyhow!(
+                "Embedding provider does not support embeddings"
+            ));
+        }
+
+        Ok(self.provider.create_embeddings(texts).await?)
+    }
+}
+
+/// The embedder selected by `GOOSE_EMBEDDING_PROVIDER`, falling back to the chat provider
+///
+/// The provider can be any provider with embedding support, such as `openai` or `ollama`,
+/// or `openai_compatible` for an embedding-only server. `GOOSE_EMBEDDING_MODEL_PROVIDER` is
+/// still read for configurations from before `GOOSE_EMBEDDING_PROVIDER` existed.
+pub fn create_embedder(chat_provider: Arc<dyn Provider>) -> Result<Arc<dyn EmbeddingCapable>> {
+    let config = Config::global();
+    let provider_name: Option<String> = config
+        .get_param("GOOSE_EMBEDDING_PROVIDER")
+        .or_else(|_| config.get_param("GOOSE_EMBEDDING_MODEL_PROVIDER"))
+        .ok();
+
+    match provider_name.as_deref() {
+        None => Ok(Arc::new(ProviderEmbedder::new(chat_provider))),
+        Some(OPENAI_COMPATIBLE_EMBEDDING_PROVIDER) => {
+            Ok(Arc::new(OpenAiCompatibleEmbedder::from_config()?))
+        }
+        Some(name) => {
+            let model_config = ModelConfig::new(embedding_model(DEFAULT_EMBEDDING_MODEL));
+            let provider = super::create(name, model_config).context(format!(
+                "Failed to create {} provider for embeddings. Make sure the provider is configured, or set GOOSE_EMBEDDING_PROVIDER to openai_compatible with GOOSE_EMBEDDING_HOST to use an embedding endpoint directly.",
+                name
+            ))?;
+            Ok(Arc::new(ProviderEmbedder::new(provider)))
+        }
+    }
+}
+
+/// The length of the vectors the embedder produces
+///
+/// Taken from `GOOSE_EMBEDDING_DIMENSIONS` when set, otherwise discovered by embedding a
+/// short probe text, so vector indexes can be created to match whichever model is in use.
+pub async fn embedding_dimension(embedder: &dyn EmbeddingCapable) -> Result<usize> {
+    if let Ok(dimension) = Config::global().get_param::<usize>("GOOSE_EMBEDDING_DIMENSIONS") {
+        if dimension > 0 {
+            return Ok(dimension);
+        }
+    }
+
+    let embeddings = embedder
+        .create_embeddings(vec!["embedding dimension probe".to_string()])
+        .await
+        .context("Failed to discover the embedding dimension")?;
+    match embeddings.first() {
+        Some(embedding) if !embedding.is_empty() => Ok(embedding.len()),
+        _ => Err(anyhow::anyhow!(
+            "The embedding model returned no embedding for the dimension probe"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedEmbedder(usize);
+
+    #[async_trait]
+    impl EmbeddingCapable for FixedEmbedder {
+        async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| vec![0.5; self.0]).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embedding_dimension_is_discovered() {
+        temp_env::async_with_vars([("GOOSE_EMBEDDING_DIMENSIONS", None::<&str>)], async {
+            assert_eq!(embedding_dimension(&FixedEmbedder(768)).await.unwrap(), 768);
+            assert!(embedding_dimension(&FixedEmbedder(0)).await.is_err());
+        })
+        .await;
+
+        // A configured dimension is used without asking the model
+        temp_env::async_with_vars([("GOOSE_EMBEDDING_DIMENSIONS", Some("384"))], async {
+            assert_eq!(embedding_dimension(&FixedEmbedder(0)).await.unwrap(), 384);
+        })
+        .await;
+    }
+
+    #[test]
+    fn test_openai_compatible_embedder_url() {
+        let embedder = OpenAiCompatibleEmbedder::new(
+            "http://localhost:1234",
+            DEFAULT_EMBEDDING_PATH,
+            None,
+            "nomic-embed-text".to_string(),
+        )
+        .unwrap();
+        assert_eq!(embedder.url.as_str(), "http://localhost:1234/v1/embeddings");
+    }
+}
@@ -5,6 +5,7 @@ use super::{
     azure::AzureProvider,
     base::{Provider, ProviderMetadata},
     bedrock::BedrockProvider,
+    custom::{custom_providers, find_custom_provider, CustomProvider},
     databricks::DatabricksProvider,
     gcpvertexai::GcpVertexAIProvider,
     githubcopilot::GithubCopilotProvider,
@@ -35,7 +36,23 @@ fn default_fallback_turns() -> usize {
     2
 }
 
+/// The built-in providers followed by the custom providers declared in the config
 pub fn providers() -> Vec<ProviderMetadata> {
+    let mut providers = builtin_providers();
+    for custom in custom_providers() {
+        if providers.iter().any(|p| p.name == custom.name) {
+            tracing::warn!(
+                "Ignoring custom provider '{}', which has the name of a built-in provider",
+                custom.name
+            );
+            continue;
+        }
+        providers.push(custom.metadata());
+    }
+    providers
+}
+
+fn builtin_providers() -> Vec<ProviderMetadata> {
     vec![
         AnthropicProvider::metadata(),
         AzureProvider::metadata(),
@@ -53,14 +70,37 @@ pub fn providers() -> Vec<ProviderMetadata> {
     ]
 }
 
+/// Lead/worker settings that take precedence over GOOSE_LEAD_PROVIDER, GOOSE_LEAD_MODEL
+/// and GOOSE_LEAD_TURNS, such as those of a recipe
+#[derive(Debug, Clone, Default)]
+pub struct LeadWorkerSettings {
+    pub lead_provider: Option<String>,
+    pub lead_model: Option<String>,
+    pub lead_turns: Option<usize>,
+}
+
 pub fn create(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
+    create_with_lead_worker(name, model, &LeadWorkerSettings::default())
+}
+
+/// Create a provider, using a lead model for the first turns when the settings or the
+/// environment name one
+pub fn create_with_lead_worker(
+    name: &str,
+    model: ModelConfig,
+    lead_worker: &LeadWorkerSettings,
+) -> Result<Arc<dyn Provider>> {
     let config = crate::config::Config::global();
 
     // Check for lead model environment variables
-    if let Ok(lead_model_name) = config.get_param::<String>("GOOSE_LEAD_MODEL") {
+    let lead_model_name = match &lead_worker.lead_model {
+        Some(lead_model_name) => Some(lead_model_name.clone()),
+        None => config.get_param::<String>("GOOSE_LEAD_MODEL").ok(),
+    };
+    if let Some(lead_model_name) = lead_model_name {
         tracing::info!("Creating lead/worker provider from environment variables");
 
-        return create_lead_worker_from_env(name, &model, &lead_model_name);
+        return create_lead_worker_from_env(name, &model, &lead_model_name, lead_worker);
     }
 
     // Default: create regular provider
@@ -72,18 +112,24 @@ fn create_lead_worker_from_env(
     default_provider_name: &str,
     default_model: &ModelConfig,
     lead_model_name: &str,
+    lead_worker: &LeadWorkerSettings,
 ) -> Result<Arc<dyn Provider>> {
     let config = crate::config::Config::global();
 
     // Get lead provider (optional, defaults to main provider)
-    let lead_provider_name = config
-        .get_param::<String>("GOOSE_LEAD_PROVIDER")
-        .unwrap_or_else(|_| default_provider_name.to_string());
+    let lead_provider_name = match &lead_worker.lead_provider {
+        Some(lead_provider_name) => lead_provider_name.clone(),
+        None => config
+            .get_param::<String>("GOOSE_LEAD_PROVIDER")
+            .unwrap_or_else(|_| default_provider_name.to_string()),
+    };
 
     // Get configuration parameters with defaults
-    let lead_turns = config
-        .get_param::<usize>("GOOSE_LEAD_TURNS")
-        .unwrap_or(default_lead_turns());
+    let lead_turns = lead_worker.lead_turns.unwrap_or_else(|| {
+        config
+            .get_param::<usize>("GOOSE_LEAD_TURNS")
+            .unwrap_or(default_lead_turns())
+    });
     let failure_threshold = config
         .get_param::<usize>("GOOSE_LEAD_FAILURE_THRESHOLD")
         .unwrap_or(default_failure_threshold());
@@ -125,7 +171,10 @@ fn create_provider(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>>
         "venice" => Ok(Arc::new(VeniceProvider::from_env(model)?)),
         "snowflake" => Ok(Arc::new(SnowflakeProvider::from_env(model)?)),
         "github_copilot" => Ok(Arc::new(GithubCopilotProvider::from_env(model)?)),
-        _ => Err(anyhow::anyhow!("Unknown provider: {}", name)),
+        _ => match find_custom_provider(name) {
+            Some(config) => Ok(Arc::new(CustomProvider::from_config(config, model)?)),
+            None => Err(anyhow::anyhow!("Unknown provider: {}", name)),
+        },
     }
 }
 
@@ -290,6 +339,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_lead_worker_settings_take_precedence() {
+        temp_env::with_vars(
+            [
+                ("GOOSE_LEAD_MODEL", Some("env-lead-model")),
+                ("GOOSE_LEAD_PROVIDER", Some("env-lead-provider")),
+            ],
+            || {
+                // The lead provider is created first, so its name in the error shows which
+                // lead settings were used
+                let lead_worker = LeadWorkerSettings {
+                    lead_provider: Some("nonexistent".to_string()),
+                    lead_model: Some("lead-model".to_string()),
+                    lead_turns: Some(2),
+                };
+                let error = create_with_lead_worker(
+                    "openai",
+                    ModelConfig::new("gpt-4o-mini".to_string()),
+                    &lead_worker,
+                )
+                .err()
+                .expect("An unknown lead provider should fail");
+                assert!(error.to_string().contains("Unknown provider: nonexistent"));
+
+                // Without settings of their own the environment still applies
+                let error = create("openai", ModelConfig::new("gpt-4o-mini".to_string()))
+                    .err()
+                    .expect("An unknown lead provider should fail");
+                assert!(error
+                    .to_string()
+                    .contains("Unknown provider: env-lead-provider"));
+            },
+        );
+    }
+
     #[test]
     fn test_create_regular_provider_without_lead_config() {
         // Save current env vars
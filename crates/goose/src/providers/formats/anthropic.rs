@@ -1,13 +1,15 @@
 use crate::message::{Message, MessageContent};
 use crate::model::ModelConfig;
-use crate::providers::base::Usage;
+use crate::providers::base::{CompletionChunk, MessageDelta, ProviderUsage, Usage};
 use crate::providers::errors::ProviderError;
+use crate::providers::utils::get_model;
 use anyhow::{anyhow, Result};
+use futures::{Stream, StreamExt};
 use mcp_core::content::Content;
 use mcp_core::role::Role;
 use mcp_core::tool::{Tool, ToolCall};
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 /// Convert internal Message format to Anthropic's API message specification
 pub fn format_messages(messages: &[Message]) -> Vec<Value> {
@@ -263,7 +265,14 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
 
         let total_tokens = output_tokens.map(|o| total_input_tokens as i32 + o);
 
-        Ok(Usage::new(input_tokens, output_tokens, total_tokens))
+        // Cache reads are priced lower than other input tokens
+        let cached_input_tokens = usage
+            .get("cache_read_input_tokens")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as i32);
+
+        Ok(Usage::new(input_tokens, output_tokens, total_tokens)
+            .with_cached_input_tokens(cached_input_tokens))
     } else {
         tracing::debug!(
             "Failed to get usage data: {}",
@@ -352,6 +361,153 @@ pub fn create_request(
     Ok(payload)
 }
 
+/// Convert the `data:` payloads of an Anthropic streaming response into message deltas,
+/// followed by the complete message once `message_stop` arrives
+///
+/// https://docs.anthropic.com/en/api/messages-streaming
+pub fn response_to_streaming_message<S>(
+    data: S,
+) -> impl Stream<Item = Result<CompletionChunk, ProviderError>>
+where
+    S: Stream<Item = Result<String, ProviderError>> + Send + 'static,
+{
+    async_stream::try_stream! {
+        let mut data = Box::pin(data);
+        // Rebuild the non-streaming response shape so the existing parsers can be reused
+        let mut response = json!({ "content": [] });
+        let mut partial_inputs: HashMap<usize, String> = HashMap::new();
+
+        while let Some(payload) = data.next().await {
+            let payload = payload?;
+            let event: Value = match serde_json::from_str(&payload) {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::debug!("Skipping unparseable stream payload: {} | {}", e, payload);
+                    continue;
+                }
+            };
+            let index = event.get("index").and_then(|i| i.as_u64()).unwrap_or(0) as usize;
+
+            match event.get("type").and_then(|t| t.as_str()) {
+                Some("message_start") => {
+                    if let Some(message) = event.get("message") {
+                        response = message.clone();
+                        response["content"] = json!([]);
+                    }
+                }
+                Some("content_block_start") => {
+                    let mut block = event.get("content_block").cloned().unwrap_or(json!({}));
+                    match block.get("type").and_then(|t| t.as_str()) {
+                        Some("tool_use") => {
+                            partial_inputs.insert(index, String::new());
+                            yield CompletionChunk::Delta(MessageDelta::ToolCall {
+                                index,
+                                id: block.get("id").and_then(|i| i.as_str()).map(String::from),
+                                name: block.get("name").and_then(|n| n.as_str()).map(String::from),
+                                arguments: String::new(),
+                            });
+                        }
+                        Some("thinking") => {
+                            if block.get("signature").is_none() {
+                                block["signature"] = json!("");
+                            }
+                        }
+                        _ => {}
+                    }
+                    if let Some(content) = response["content"].as_array_mut() {
+                        while content.len() <= index {
+                            content.push(json!({}));
+                        }
+                        content[index] = block;
+                    }
+                }
+                Some("content_block_delta") => {
+                    let delta = event.get("delta").cloned().unwrap_or(json!({}));
+                    let Some(block) = response["content"].get_mut(index) else {
+                        continue;
+                    };
+                    match delta.get("type").and_then(|t| t.as_str()) {
+                        Some("text_delta") => {
+                            let text = delta.get("text").and_then(|t| t.as_str()).unwrap_or_default();
+                            append_str(block, "text", text);
+                            yield CompletionChunk::Delta(MessageDelta::Text { text: text.to_string() });
+                        }
+                        Some("thinking_delta") => {
+                            let thinking = delta.get("thinking").and_then(|t| t.as_str()).unwrap_or_default();
+                            append_str(block, "thinking", thinking);
+                            yield CompletionChunk::Delta(MessageDelta::Thinking { thinking: thinking.to_string() });
+                        }
+                        Some("signature_delta") => {
+                            let signature = delta.get("signature").and_then(|s| s.as_str()).unwrap_or_default();
+                            append_str(block, "signature", signature);
+                        }
+                        Some("input_json_delta") => {
+                            let partial = delta.get("partial_json").and_then(|p| p.as_str()).unwrap_or_default();
+                            partial_inputs.entry(index).or_default().push_str(partial);
+                            yield CompletionChunk::Delta(MessageDelta::ToolCall {
+                                index,
+                                id: None,
+                                name: None,
+                                arguments: partial.to_string(),
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+                Some("content_block_stop") => {
+                    if let Some(partial) = partial_inputs.remove(&index) {
+                        let input = if partial.trim().is_empty() {
+                            json!({})
+                        } else {
+                            serde_json::from_str(&partial).map_err(|e| {
+                                ProviderError::RequestFailed(format!("Invalid tool input in stream: {}", e))
+                            })?
+                        };
+                        if let Some(block) = response["content"].get_mut(index) {
+                            block["input"] = input;
+                        }
+                    }
+                }
+                Some("message_delta") => {
+                    if let Some(usage) = event.get("usage").and_then(|u| u.as_object()) {
+                        if !response["usage"].is_object() {
+                            response["usage"] = json!({});
+                        }
+                        for (key, value) in usage {
+                            response["usage"][key] = value.clone();
+                        }
+                    }
+                    if let Some(stop_reason) = event.pointer("/delta/stop_reason") {
+                        response["stop_reason"] = stop_reason.clone();
+                    }
+                }
+                Some("message_stop") => break,
+                Some("error") => {
+                    let error_type = event.pointer("/error/type").and_then(|t| t.as_str()).unwrap_or_default();
+                    let error_msg = event.pointer("/error/message").and_then(|m| m.as_str()).unwrap_or("Unknown error").to_string();
+                    let error = match error_type {
+                        "overloaded_error" | "api_error" => ProviderError::ServerError(error_msg),
+                        "rate_limit_error" => ProviderError::RateLimitExceeded(error_msg),
+                        _ => ProviderError::RequestFailed(error_msg),
+                    };
+                    Err::<(), _>(error)?;
+                }
+                _ => {}
+            }
+        }
+
+        let message = response_to_message(response.clone())
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))?;
+        let usage = get_usage(&response).map_err(|e| ProviderError::UsageError(e.to_string()))?;
+        yield CompletionChunk::Done(message, ProviderUsage::new(get_model(&response), usage));
+    }
+}
+
+fn append_str(block: &mut Value, key: &str, fragment: &str) {
+    let existing = block.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+    block[key] = json!(format!("{}{}", existing, fragment));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -631,4 +787,77 @@ mod tests {
         // Return the test result
         result
     }
+
+    #[tokio::test]
+    async fn test_response_to_streaming_message() -> Result<()> {
+        let payloads = vec![
+            r#"{"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","content":[],"model":"claude-3-7-sonnet-20250219","usage":{"input_tokens":20,"cache_read_input_tokens":5,"output_tokens":1}}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Need the weather."}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"sig"}}"#,
+            r#"{"type":"content_block_stop","index":0}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"ping"}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"Checking "}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"text_delta","text":"now."}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+            r#"{"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_1","name":"get_weather","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"city\": "}}"#,
+            r#"{"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"\"Paris\"}"}}"#,
+            r#"{"type":"content_block_stop","index":2}"#,
+            r#"{"type":"message_delta","delta":{"stop_reason":"tool_use"},"usage":{"output_tokens":30}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+        let data = futures::stream::iter(payloads.into_iter().map(|p| Ok(p.to_string())));
+
+        let chunks: Vec<CompletionChunk> = response_to_streaming_message(data)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+
+        assert!(matches!(
+            &chunks[0],
+            CompletionChunk::Delta(MessageDelta::Thinking { thinking }) if thinking == "Need the weather."
+        ));
+        assert!(matches!(
+            &chunks[1],
+            CompletionChunk::Delta(MessageDelta::Text { text }) if text == "Checking "
+        ));
+
+        match chunks.last() {
+            Some(CompletionChunk::Done(message, usage)) => {
+                assert_eq!(message.content.len(), 3);
+                let thinking = message.content[0].as_thinking().unwrap();
+                assert_eq!(thinking.signature, "sig");
+                assert_eq!(message.content[1].as_text(), Some("Checking now."));
+                let tool_call = message.content[2]
+                    .as_tool_request()
+                    .unwrap()
+                    .tool_call
+                    .as_ref()
+                    .unwrap();
+                assert_eq!(tool_call.name, "get_weather");
+                assert_eq!(tool_call.arguments, json!({"city": "Paris"}));
+
+                assert_eq!(usage.model, "claude-3-7-sonnet-20250219");
+                assert_eq!(usage.usage.input_tokens, Some(25));
+                assert_eq!(usage.usage.output_tokens, Some(30));
+            }
+            _ => panic!("Expected the stream to end with a complete message"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_response_to_streaming_message_overloaded() {
+        let data = futures::stream::iter(vec![Ok(
+            r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#
+                .to_string(),
+        )]);
+
+        let chunks: Vec<_> = response_to_streaming_message(data).collect().await;
+        assert!(matches!(chunks[0], Err(ProviderError::ServerError(_))));
+    }
 }
@@ -1,12 +1,14 @@
 use crate::message::{Message, MessageContent};
 use crate::model::ModelConfig;
-use crate::providers::base::Usage;
+use crate::providers::base::{CompletionChunk, MessageDelta, ProviderUsage, Usage};
 use crate::providers::errors::ProviderError;
 use crate::providers::utils::{
-    convert_image, detect_image_path, is_valid_function_name, load_image_file,
+    convert_image, detect_image_path, get_model, is_valid_function_name, load_image_file,
     sanitize_function_name, ImageFormat,
 };
+use crate::providers::utils_universal_openai_stream::{OAIStreamChunk, OAIStreamCollector};
 use anyhow::{anyhow, Error};
+use futures::{Stream, StreamExt};
 use mcp_core::ToolError;
 use mcp_core::{Content, Role, Tool, ToolCall};
 use serde_json::{json, Value};
@@ -305,7 +307,14 @@ pub fn get_usage(data: &Value) -> Result<Usage, ProviderError> {
             _ => None,
         });
 
-    Ok(Usage::new(input_tokens, output_tokens, total_tokens))
+    let cached_input_tokens = usage
+        .get("prompt_tokens_details")
+        .and_then(|details| details.get("cached_tokens"))
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32);
+
+    Ok(Usage::new(input_tokens, output_tokens, total_tokens)
+        .with_cached_input_tokens(cached_input_tokens))
 }
 
 /// Validates and fixes tool schemas to ensure they have proper parameter structure.
@@ -450,6 +459,81 @@ pub fn create_request(
     Ok(payload)
 }
 
+/// Enable streaming on a request created by `create_request`, asking for usage in the final chunk
+pub fn with_streaming(mut payload: Value) -> Value {
+    if let Some(obj) = payload.as_object_mut() {
+        obj.insert("stream".to_string(), json!(true));
+        obj.insert(
+            "stream_options".to_string(),
+            json!({ "include_usage": true }),
+        );
+    }
+    payload
+}
+
+/// Convert the `data:` payloads of an OpenAI streaming response into message deltas,
+/// followed by the complete message once the stream finishes
+pub fn response_to_streaming_message<S>(
+    data: S,
+) -> impl Stream<Item = Result<CompletionChunk, ProviderError>>
+where
+    S: Stream<Item = Result<String, ProviderError>> + Send + 'static,
+{
+    async_stream::try_stream! {
+        let mut data = Box::pin(data);
+        let mut collector = OAIStreamCollector::new();
+
+        while let Some(payload) = data.next().await {
+            let payload = payload?;
+            let value: Value = match serde_json::from_str(&payload) {
+                Ok(value) => value,
+                Err(e) => {
+                    tracing::debug!("Skipping unparseable stream payload: {} | {}", e, payload);
+                    continue;
+                }
+            };
+            if let Some(error) = value.get("error") {
+                Err::<(), _>(ProviderError::RequestFailed(format!("Stream error: {}", error)))?;
+            }
+            let chunk: OAIStreamChunk = match serde_json::from_value(value) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    tracing::debug!("Skipping unexpected stream chunk: {} | {}", e, payload);
+                    continue;
+                }
+            };
+
+            for choice in &chunk.choices {
+                if let Some(text) = choice.delta.content.as_ref().filter(|t| !t.is_empty()) {
+                    yield CompletionChunk::Delta(MessageDelta::Text { text: text.clone() });
+                }
+                for tool_call in &choice.delta.tool_calls {
+                    yield CompletionChunk::Delta(MessageDelta::ToolCall {
+                        index: tool_call.index,
+                        id: tool_call.id.clone(),
+                        name: tool_call.function.name.clone(),
+                        arguments: tool_call.function.arguments.clone(),
+                    });
+                }
+            }
+            collector.add_chunk(&chunk);
+        }
+
+        let response = serde_json::to_value(collector.build_response())
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))?;
+        let message = response_to_message(response.clone())
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))?;
+        let usage = match get_usage(&response) {
+            Ok(usage) => usage,
+            Err(e) => {
+                tracing::debug!("Failed to get usage data: {}", e);
+                Usage::default()
+            }
+        };
+        yield CompletionChunk::Done(message, ProviderUsage::new(get_model(&response), usage));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -945,4 +1029,62 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_response_to_streaming_message() -> anyhow::Result<()> {
+        let payloads = vec![
+            r#"{"id":"1","model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":"Let me "}}]}"#,
+            r#"{"id":"1","model":"gpt-4o","choices":[{"index":0,"delta":{"content":"check."}}]}"#,
+            r#"{"id":"1","model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"get_weather","arguments":""}}]}}]}"#,
+            r#"{"id":"1","model":"gpt-4o","choices":[{"index":0,"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"city\":\"Paris\"}"}}]}}]}"#,
+            r#"{"id":"1","model":"gpt-4o","choices":[{"index":0,"delta":{},"finish_reason":"tool_calls"}],"usage":{"prompt_tokens":10,"completion_tokens":5,"total_tokens":15}}"#,
+        ];
+        let data = futures::stream::iter(payloads.into_iter().map(|p| Ok(p.to_string())));
+
+        let chunks: Vec<CompletionChunk> = response_to_streaming_message(data)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_, _>>()?;
+
+        assert_eq!(
+            chunks[0..2]
+                .iter()
+                .map(|c| match c {
+                    CompletionChunk::Delta(MessageDelta::Text { text }) => text.as_str(),
+                    _ => panic!("Expected text delta"),
+                })
+                .collect::<String>(),
+            "Let me check."
+        );
+        assert!(matches!(
+            &chunks[2],
+            CompletionChunk::Delta(MessageDelta::ToolCall { index: 0, name: Some(name), .. }) if name == "get_weather"
+        ));
+
+        match chunks.last() {
+            Some(CompletionChunk::Done(message, usage)) => {
+                assert_eq!(message.as_concat_text(), "Let me check.");
+                let request = message.content[1].as_tool_request().unwrap();
+                let tool_call = request.tool_call.as_ref().unwrap();
+                assert_eq!(tool_call.name, "get_weather");
+                assert_eq!(tool_call.arguments, json!({"city": "Paris"}));
+                assert_eq!(usage.model, "gpt-4o");
+                assert_eq!(usage.usage.total_tokens, Some(15));
+            }
+            _ => panic!("Expected the stream to end with a complete message"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_response_to_streaming_message_error() {
+        let data = futures::stream::iter(vec![Ok(
+            r#"{"error":{"message":"overloaded","type":"server_error"}}"#.to_string(),
+        )]);
+
+        let chunks: Vec<_> = response_to_streaming_message(data).collect().await;
+        assert!(matches!(chunks[0], Err(ProviderError::RequestFailed(_))));
+    }
 }
@@ -14,6 +14,7 @@ use std::time::Duration;
 use super::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::formats::openai::{create_request, get_usage, response_to_message};
+use super::retry::{send_with_retry, RetryConfig};
 use super::utils::{emit_debug_trace, get_model, handle_response_openai_compat, ImageFormat};
 
 use crate::config::{Config, ConfigError};
@@ -112,6 +113,8 @@ pub struct GithubCopilotProvider {
     #[serde(skip)]
     mu: tokio::sync::Mutex<RefCell<Option<CopilotState>>>,
     model: ModelConfig,
+    #[serde(skip)]
+    retry_config: RetryConfig,
 }
 
 impl Default for GithubCopilotProvider {
@@ -133,6 +136,7 @@ impl GithubCopilotProvider {
             cache,
             mu,
             model,
+            retry_config: RetryConfig::from_config(),
         })
     }
 
@@ -153,14 +157,15 @@ impl GithubCopilotProvider {
         let (endpoint, token) = self.get_api_info().await?;
         let url = url::Url::parse(&format!("{}/chat/completions", endpoint))
             .map_err(|e| ProviderError::RequestFailed(format!("Invalid base URL: {e}")))?;
-        let response = self
-            .client
-            .post(url)
-            .headers(self.get_github_headers())
-            .header("Authorization", format!("Bearer {}", token))
-            .json(&payload)
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry_config, || {
+            self.client
+                .post(url.clone())
+                .headers(self.get_github_headers())
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&payload)
+                .send()
+        })
+        .await?;
         if stream_only_model {
             let mut collector = OAIStreamCollector::new();
             let mut stream = response.bytes_stream();
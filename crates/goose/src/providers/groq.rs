@@ -3,6 +3,7 @@ use crate::message::Message;
 use crate::model::ModelConfig;
 use crate::providers::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
 use crate::providers::formats::openai::{create_request, get_usage, response_to_message};
+use crate::providers::retry::{send_with_retry, RetryConfig};
 use crate::providers::utils::get_model;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -25,6 +26,8 @@ pub struct GroqProvider {
     host: String,
     api_key: String,
     model: ModelConfig,
+    #[serde(skip)]
+    retry_config: RetryConfig,
 }
 
 impl Default for GroqProvider {
@@ -51,6 +54,7 @@ impl GroqProvider {
             host,
             api_key,
             model,
+            retry_config: RetryConfig::from_config(),
         })
     }
 
@@ -61,13 +65,14 @@ impl GroqProvider {
             ProviderError::RequestFailed(format!("Failed to construct endpoint URL: {e}"))
         })?;
 
-        let response = self
-            .client
-            .post(url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&payload)
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry_config, || {
+            self.client
+                .post(url.clone())
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .json(&payload)
+                .send()
+        })
+        .await?;
 
         let status = response.status();
         let payload: Option<Value> = response.json().await.ok();
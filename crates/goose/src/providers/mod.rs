@@ -3,6 +3,7 @@ pub mod azure;
 pub mod azureauth;
 pub mod base;
 pub mod bedrock;
+pub mod custom;
 pub mod databricks;
 pub mod embedding;
 pub mod errors;
@@ -18,6 +19,8 @@ pub mod oauth;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
+pub mod replay;
+pub mod retry;
 pub mod snowflake;
 pub mod toolshim;
 pub mod utils;
@@ -1,13 +1,20 @@
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{CompletionStream, ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::embedding::{embedding_model, EmbeddingCapable};
 use super::errors::ProviderError;
-use super::utils::{get_model, handle_response_openai_compat};
+use super::retry::{send_with_retry, RetryConfig};
+use super::utils::{
+    get_model, handle_response_openai_compat, handle_stream_response_openai_compat, sse_data_stream,
+};
 use crate::message::Message;
 use crate::model::ModelConfig;
-use crate::providers::formats::openai::{create_request, get_usage, response_to_message};
+use crate::providers::formats::openai::{
+    create_request, get_usage, response_to_message, response_to_streaming_message, with_streaming,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use mcp_core::tool::Tool;
-use reqwest::Client;
+use reqwest::{Client, Response};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::time::Duration;
 use url::Url;
@@ -18,6 +25,19 @@ pub const OLLAMA_DEFAULT_MODEL: &str = "qwen2.5";
 // Ollama can run many models, we only provide the default
 pub const OLLAMA_KNOWN_MODELS: &[&str] = &[OLLAMA_DEFAULT_MODEL];
 pub const OLLAMA_DOC_URL: &str = "https://ollama.com/library";
+pub const OLLAMA_DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+/// Request body of Ollama's native `/api/embed` endpoint
+#[derive(Debug, Serialize)]
+struct OllamaEmbedRequest {
+    model: String,
+    input: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
 
 #[derive(serde::Serialize)]
 pub struct OllamaProvider {
@@ -25,6 +45,8 @@ pub struct OllamaProvider {
     client: Client,
     host: String,
     model: ModelConfig,
+    #[serde(skip)]
+    retry_config: RetryConfig,
 }
 
 impl Default for OllamaProvider {
@@ -49,6 +71,7 @@ impl OllamaProvider {
             client,
             host,
             model,
+            retry_config: RetryConfig::from_config(),
         })
     }
 
@@ -80,7 +103,7 @@ impl OllamaProvider {
         Ok(base_url)
     }
 
-    async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+    async fn send(&self, payload: &Value) -> Result<Response, ProviderError> {
         // TODO: remove this later when the UI handles provider config refresh
         let base_url = self.get_base_url()?;
 
@@ -88,12 +111,55 @@ impl OllamaProvider {
             ProviderError::RequestFailed(format!("Failed to construct endpoint URL: {e}"))
         })?;
 
-        let response = self.client.post(url).json(&payload).send().await?;
+        send_with_retry(&self.retry_config, || {
+            self.client.post(url.clone()).json(payload).send()
+        })
+        .await
+    }
 
+    async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+        let response = self.send(&payload).await?;
         handle_response_openai_compat(response).await
     }
 }
 
+#[async_trait]
+impl EmbeddingCapable for OllamaProvider {
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // The chat model usually can't embed, so use a dedicated embedding model
+        let request = OllamaEmbedRequest {
+            model: embedding_model(OLLAMA_DEFAULT_EMBEDDING_MODEL),
+            input: texts,
+        };
+
+        let url = self
+            .get_base_url()?
+            .join("api/embed")
+            .map_err(|e| anyhow::anyhow!("Failed to construct embeddings URL: {e}"))?;
+
+        let response = send_with_retry(&self.retry_config, || {
+            self.client.post(url.clone()).json(&request).send()
+        })
+        .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!("Ollama embedding error: {}", error_text));
+        }
+
+        let embed_response: OllamaEmbedResponse = response
+            .json()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to parse embedding response: {e}"))?;
+
+        Ok(embed_response.embeddings)
+    }
+}
+
 #[async_trait]
 impl Provider for OllamaProvider {
     fn metadata() -> ProviderMetadata {
@@ -150,4 +216,88 @@ impl Provider for OllamaProvider {
         super::utils::emit_debug_trace(&self.model, &payload, &response, &usage);
         Ok((message, ProviderUsage::new(model, usage)))
     }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<CompletionStream, ProviderError> {
+        let payload = with_streaming(create_request(
+            &self.model,
+            system,
+            messages,
+            tools,
+            &super::utils::ImageFormat::OpenAi,
+        )?);
+
+        let response = self.send(&payload).await?;
+        let response = handle_stream_response_openai_compat(response).await?;
+
+        Ok(Box::pin(response_to_streaming_message(sse_data_stream(
+            response.bytes_stream(),
+        ))))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    fn supports_embeddings(&self) -> bool {
+        true
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        EmbeddingCapable::create_embeddings(self, texts)
+            .await
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_embeddings_use_the_native_embed_endpoint() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/embed"))
+            .and(body_json(json!({
+                "model": OLLAMA_DEFAULT_EMBEDDING_MODEL,
+                "input": ["first", "second"]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "model": OLLAMA_DEFAULT_EMBEDDING_MODEL,
+                "embeddings": [[0.1, 0.2, 0.3], [0.4, 0.5, 0.6]]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = OllamaProvider {
+            client: Client::new(),
+            host: server.uri(),
+            model: ModelConfig::new(OLLAMA_DEFAULT_MODEL.to_string()),
+            retry_config: RetryConfig::default(),
+        };
+        let embeddings = temp_env::async_with_vars(
+            [("GOOSE_EMBEDDING_MODEL", None::<&str>)],
+            EmbeddingCapable::create_embeddings(
+                &provider,
+                vec!["first".to_string(), "second".to_string()],
+            ),
+        )
+        .await
+        .unwrap();
+        assert_eq!(embeddings, vec![vec![0.1, 0.2, 0.3], vec![0.4, 0.5, 0.6]]);
+
+        assert!(EmbeddingCapable::create_embeddings(&provider, vec![])
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
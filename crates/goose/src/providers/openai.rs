@@ -1,15 +1,23 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, Response};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::time::Duration;
 
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
-use super::embedding::{EmbeddingCapable, EmbeddingRequest, EmbeddingResponse};
+use super::base::{CompletionStream, ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::embedding::{
+    embedding_model, EmbeddingCapable, EmbeddingRequest, EmbeddingResponse, DEFAULT_EMBEDDING_MODEL,
+};
 use super::errors::ProviderError;
-use super::formats::openai::{create_request, get_usage, response_to_message};
-use super::utils::{emit_debug_trace, get_model, handle_response_openai_compat, ImageFormat};
+use super::formats::openai::{
+    create_request, get_usage, response_to_message, response_to_streaming_message, with_streaming,
+};
+use super::retry::{send_with_retry, RetryConfig};
+use super::utils::{
+    emit_debug_trace, get_model, handle_response_openai_compat,
+    handle_stream_response_openai_compat, sse_data_stream, ImageFormat,
+};
 use crate::message::Message;
 use crate::model::ModelConfig;
 use mcp_core::tool::Tool;
@@ -38,6 +46,8 @@ pub struct OpenAiProvider {
     project: Option<String>,
     model: ModelConfig,
     custom_headers: Option<HashMap<String, String>>,
+    #[serde(skip)]
+    retry_config: RetryConfig,
 }
 
 impl Default for OpenAiProvider {
@@ -78,6 +88,7 @@ impl OpenAiProvider {
             project,
             model,
             custom_headers,
+            retry_config: RetryConfig::from_config(),
         })
     }
 
@@ -103,22 +114,26 @@ impl OpenAiProvider {
         request
     }
 
-    async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+    async fn send(&self, payload: &Value) -> Result<Response, ProviderError> {
         let base_url = url::Url::parse(&self.host)
             .map_err(|e| ProviderError::RequestFailed(format!("Invalid base URL: {e}")))?;
         let url = base_url.join(&self.base_path).map_err(|e| {
             ProviderError::RequestFailed(format!("Failed to construct endpoint URL: {e}"))
         })?;
 
-        let request = self
-            .client
-            .post(url)
-            .header("Authorization", format!("Bearer {}", self.api_key));
-
-        let request = self.add_headers(request);
+        send_with_retry(&self.retry_config, || {
+            let request = self
+                .client
+                .post(url.clone())
+                .header("Authorization", format!("Bearer {}", self.api_key));
 
-        let response = request.json(&payload).send().await?;
+            self.add_headers(request).json(payload).send()
+        })
+        .await
+    }
 
+    async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+        let response = self.send(&payload).await?;
         handle_response_openai_compat(response).await
     }
 }
@@ -179,6 +194,32 @@ impl Provider for OpenAiProvider {
         Ok((message, ProviderUsage::new(model, usage)))
     }
 
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<CompletionStream, ProviderError> {
+        let payload = with_streaming(create_request(
+            &self.model,
+            system,
+            messages,
+            tools,
+            &ImageFormat::OpenAi,
+        )?);
+
+        let response = self.send(&payload).await?;
+        let response = handle_stream_response_openai_compat(response).await?;
+
+        Ok(Box::pin(response_to_streaming_message(sse_data_stream(
+            response.bytes_stream(),
+        ))))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
     /// Fetch supported models from OpenAI; returns Err on any failure, Ok(None) if no data
     async fn fetch_supported_models_async(&self) -> Result<Option<Vec<String>>, ProviderError> {
         // List available models via OpenAI API
@@ -248,13 +289,9 @@ impl EmbeddingCapable for OpenAiProvider {
             return Ok(vec![]);
         }
 
-        // Get embedding model from env var or use default
-        let embedding_model = std::env::var("GOOSE_EMBEDDING_MODEL")
-            .unwrap_or_else(|_| "text-embedding-3-small".to_string());
-
         let request = EmbeddingRequest {
             input: texts,
-            model: embedding_model,
+            model: embedding_model(DEFAULT_EMBEDDING_MODEL),
         };
 
         // Construct embeddings endpoint URL
@@ -6,6 +6,7 @@ use std::time::Duration;
 
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
+use super::retry::{send_with_retry, RetryConfig};
 use super::utils::{
     emit_debug_trace, get_model, handle_response_google_compat, handle_response_openai_compat,
     is_google_model,
@@ -35,6 +36,8 @@ pub struct OpenRouterProvider {
     host: String,
     api_key: String,
     model: ModelConfig,
+    #[serde(skip)]
+    retry_config: RetryConfig,
 }
 
 impl Default for OpenRouterProvider {
@@ -61,6 +64,7 @@ impl OpenRouterProvider {
             host,
             api_key,
             model,
+            retry_config: RetryConfig::from_config(),
         })
     }
 
@@ -71,16 +75,17 @@ impl OpenRouterProvider {
             ProviderError::RequestFailed(format!("Failed to construct endpoint URL: {e}"))
         })?;
 
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("HTTP-Referer", "https://block.github.io/goose")
-            .header("X-Title", "Goose")
-            .json(&payload)
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry_config, || {
+            self.client
+                .post(url.clone())
+                .header("Content-Type", "application/json")
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("HTTP-Referer", "https://block.github.io/goose")
+                .header("X-Title", "Goose")
+                .json(&payload)
+                .send()
+        })
+        .await?;
 
         // Handle Google-compatible model responses differently
         if is_google_model(&payload) {
@@ -0,0 +1,179 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use mcp_core::tool::{Tool, ToolCall};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::errors::ProviderError;
+use crate::message::Message;
+use crate::model::ModelConfig;
+
+pub const REPLAY_PROVIDER_NAME: &str = "replay";
+
+/// One scripted assistant response: some text, tool calls, or both
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ReplayToolCall>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayToolCall {
+    /// The prefixed tool name, such as `developer__shell`
+    pub name: String,
+    #[serde(default = "empty_arguments")]
+    pub arguments: Value,
+}
+
+fn empty_arguments() -> Value {
+    Value::Object(Default::default())
+}
+
+impl ReplayResponse {
+    /// The response as an assistant message; tool requests get ids from the response's index
+    pub fn to_message(&self, index: usize) -> Message {
+        let mut message = Message::assistant();
+        if let Some(text) = &self.text {
+            message = message.with_text(text);
+        }
+        for (call_index, call) in self.tool_calls.iter().enumerate() {
+            message = message.with_tool_request(
+                format!("replay_{}_{}", index, call_index),
+                Ok(ToolCall::new(&call.name, call.arguments.clone())),
+            );
+        }
+        message
+    }
+}
+
+#[derive(Debug, Default)]
+struct ReplayState {
+    next: usize,
+    /// Set once the agent asked for a response after the last one
+    exhausted: bool,
+}
+
+/// A provider that answers with scripted responses in order, whatever it's asked, so recipes
+/// can be tested without a model. Asking for more responses than the script has is an error.
+pub struct ReplayProvider {
+    model_config: ModelConfig,
+    responses: Vec<ReplayResponse>,
+    state: Mutex<ReplayState>,
+}
+
+impl ReplayProvider {
+    pub fn new(responses: Vec<ReplayResponse>) -> Self {
+        Self {
+            model_config: ModelConfig::new(REPLAY_PROVIDER_NAME.to_string()),
+            responses,
+            state: Mutex::new(ReplayState::default()),
+        }
+    }
+
+    /// Reports a smaller context window, so tests can exercise compaction with short conversations
+    pub fn with_context_limit(mut self, limit: usize) -> Self {
+        self.model_config = self.model_config.with_context_limit(Some(limit));
+        self
+    }
+
+    /// How many scripted responses haven't been used yet
+    pub fn remaining(&self) -> usize {
+        self.responses.len() - self.state.lock().unwrap().next
+    }
+
+    /// Whether the agent asked for more responses than the script has
+    pub fn exhausted(&self) -> bool {
+        self.state.lock().unwrap().exhausted
+    }
+}
+
+#[async_trait]
+impl Provider for ReplayProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            REPLAY_PROVIDER_NAME,
+            "Replay",
+            "Replays scripted responses, for testing recipes",
+            REPLAY_PROVIDER_NAME,
+            vec![REPLAY_PROVIDER_NAME],
+            "",
+            vec![],
+        )
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model_config.clone()
+    }
+
+    async fn complete(
+        &self,
+        _system: &str,
+        _messages: &[Message],
+        _tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let mut state = self.state.lock().unwrap();
+        let index = state.next;
+        let response = match self.responses.get(index) {
+            Some(response) => response,
+            None => {
+                state.exhausted = true;
+                return Err(ProviderError::ExecutionError(format!(
+                    "The replay script has {} responses and the agent asked for another",
+                    self.responses.len()
+                )));
+            }
+        };
+        state.next += 1;
+        Ok((
+            response.to_message(index),
+            ProviderUsage::new(REPLAY_PROVIDER_NAME.to_string(), Usage::default()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::MessageContent;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_responses_are_replayed_in_order() {
+        let responses: Vec<ReplayResponse> = serde_yaml::from_str(
+            r#"
+- text: Listing files
+  tool_calls:
+    - name: developer__shell
+      arguments: {command: ls}
+- text: Done
+"#,
+        )
+        .unwrap();
+        let provider = ReplayProvider::new(responses);
+        assert_eq!(provider.remaining(), 2);
+
+        let (message, _) = provider.complete("", &[], &[]).await.unwrap();
+        assert_eq!(message.as_concat_text(), "Listing files");
+        match &message.content[1] {
+            MessageContent::ToolRequest(request) => {
+                assert_eq!(request.id, "replay_0_0");
+                let call = request.tool_call.as_ref().unwrap();
+                assert_eq!(call.name, "developer__shell");
+                assert_eq!(call.arguments, json!({"command": "ls"}));
+            }
+            other => panic!("Expected a tool request, got {:?}", other),
+        }
+
+        let (message, _) = provider.complete("", &[], &[]).await.unwrap();
+        assert_eq!(message.as_concat_text(), "Done");
+        assert_eq!(provider.remaining(), 0);
+        assert!(!provider.exhausted());
+
+        assert!(provider.complete("", &[], &[]).await.is_err());
+        assert!(provider.exhausted());
+    }
+}
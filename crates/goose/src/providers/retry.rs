@@ -0,0 +1,553 @@
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::{Response, StatusCode};
+use serde_json::Value;
+use tokio::time::sleep;
+
+use super::errors::ProviderError;
+use crate::config::Config;
+
+/// Default maximum number of retries after the first attempt
+pub const DEFAULT_MAX_RETRIES: usize = 3;
+/// Default initial interval for retry (in milliseconds)
+pub const DEFAULT_INITIAL_RETRY_INTERVAL_MS: u64 = 1000;
+/// Default retry backoff multiplier
+pub const DEFAULT_BACKOFF_MULTIPLIER: f64 = 2.0;
+/// Default maximum interval for retry (in milliseconds)
+pub const DEFAULT_MAX_RETRY_INTERVAL_MS: u64 = 30_000;
+
+/// Upper bound on how long we are willing to wait when the server asks us to back off
+const MAX_SERVER_RETRY_DELAY: Duration = Duration::from_secs(15 * 60);
+
+/// Headers that carry a server-provided hint about when to retry, in order of preference
+const RETRY_HEADERS: &[&str] = &[
+    "retry-after-ms",
+    "retry-after",
+    "x-ratelimit-reset",
+    "x-ratelimit-reset-requests",
+    "x-ratelimit-reset-tokens",
+];
+
+/// Retry configuration shared by the HTTP based providers
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryConfig {
+    /// Maximum number of retry attempts
+    pub max_retries: usize,
+    /// Initial interval between retries in milliseconds
+    pub initial_interval_ms: u64,
+    /// Multiplier for backoff (exponential)
+    pub backoff_multiplier: f64,
+    /// Maximum interval between retries in milliseconds
+    pub max_interval_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            initial_interval_ms: DEFAULT_INITIAL_RETRY_INTERVAL_MS,
+            backoff_multiplier: DEFAULT_BACKOFF_MULTIPLIER,
+            max_interval_ms: DEFAULT_MAX_RETRY_INTERVAL_MS,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Loads retry configuration from `GOOSE_PROVIDER_*` settings or uses defaults.
+    pub fn from_config() -> Self {
+        Self::from_config_with_prefix(None, Self::default())
+    }
+
+    /// Loads retry configuration for a provider with its own settings and defaults.
+    ///
+    /// Provider specific keys such as `DATABRICKS_MAX_RETRIES` take precedence over the
+    /// shared `GOOSE_PROVIDER_MAX_RETRIES`, which takes precedence over `defaults`.
+    pub fn from_config_with_prefix(prefix: Option<&str>, defaults: RetryConfig) -> Self {
+        let config = Config::global();
+        let read = |name: &str| {
+            prefix
+                .and_then(|prefix| read_number(config, &format!("{prefix}_{name}")))
+                .or_else(|| read_number(config, &format!("GOOSE_PROVIDER_{name}")))
+        };
+
+        Self {
+            max_retries: read("MAX_RETRIES").unwrap_or(defaults.max_retries),
+            initial_interval_ms: read("INITIAL_RETRY_INTERVAL_MS")
+                .unwrap_or(defaults.initial_interval_ms),
+            backoff_multiplier: read("BACKOFF_MULTIPLIER").unwrap_or(defaults.backoff_multiplier),
+            max_interval_ms: read("MAX_RETRY_INTERVAL_MS").unwrap_or(defaults.max_interval_ms),
+        }
+    }
+
+    /// Calculate the delay for a specific retry attempt (with jitter)
+    pub fn delay_for_attempt(&self, attempt: usize) -> Duration {
+        if attempt == 0 {
+            return Duration::from_millis(0);
+        }
+
+        // Calculate exponential backoff
+        let exponent = (attempt - 1) as i32;
+        let base_delay_ms =
+            (self.initial_interval_ms as f64 * self.backoff_multiplier.powi(exponent)) as u64;
+
+        // Apply max limit
+        let capped_delay_ms = std::cmp::min(base_delay_ms, self.max_interval_ms);
+
+        // Add jitter (+/-20% randomness) to avoid thundering herd problem
+        let jitter_factor = 0.8 + (rand::random::<f64>() * 0.4); // Between 0.8 and 1.2
+        let jittered_delay_ms = (capped_delay_ms as f64 * jitter_factor) as u64;
+
+        Duration::from_millis(jittered_delay_ms)
+    }
+
+    /// The delay before the next attempt, preferring what the server asked for in its headers
+    pub fn delay_for_response(&self, attempt: usize, headers: &HeaderMap) -> Duration {
+        retry_after(headers).unwrap_or_else(|| self.delay_for_attempt(attempt))
+    }
+}
+
+/// Accepts both numbers and strings, since env values are parsed as JSON and YAML values are typed
+fn read_number<T: FromStr>(config: &Config, key: &str) -> Option<T> {
+    match config.get_param::<Value>(key).ok()? {
+        Value::Number(n) => n.to_string().parse().ok(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Whether a response with this status is worth retrying
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status.as_u16(),
+        // 529 is used by Anthropic when the API is overloaded
+        429 | 500 | 502 | 503 | 504 | 529
+    )
+}
+
+/// Whether a provider error is transient and worth retrying
+pub fn is_retryable_error(error: &ProviderError) -> bool {
+    matches!(
+        error,
+        ProviderError::RateLimitExceeded(_) | ProviderError::ServerError(_)
+    )
+}
+
+/// Parses the server's hint about when to retry from `Retry-After` or `x-ratelimit-reset` style headers.
+/// Delays are capped at the longest we are willing to wait.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    RETRY_HEADERS.iter().find_map(|name| {
+        let value = headers.get(*name)?.to_str().ok()?.trim();
+        let delay = if *name == "retry-after-ms" {
+            value
+                .parse::<f64>()
+                .ok()
+                .and_then(|ms| seconds_delay(ms / 1000.0))
+        } else {
+            parse_retry_value(value)
+        };
+        delay.map(|delay| delay.min(MAX_SERVER_RETRY_DELAY))
+    })
+}
+
+/// A delay of some seconds, capped at the longest we wait for a server. Out of range values,
+/// such as infinity, are capped too rather than overflowing.
+fn seconds_delay(seconds: f64) -> Option<Duration> {
+    if seconds.is_nan() {
+        return None;
+    }
+    let delay = Duration::try_from_secs_f64(seconds.max(0.0)).unwrap_or(MAX_SERVER_RETRY_DELAY);
+    Some(delay.min(MAX_SERVER_RETRY_DELAY))
+}
+
+/// Parses a retry hint in any of the formats providers use:
+/// seconds (`"30"`, `"1.5"`), a unix timestamp, a duration (`"6m0s"`, `"250ms"`),
+/// an RFC 3339 timestamp or an HTTP date.
+fn parse_retry_value(value: &str) -> Option<Duration> {
+    if let Ok(number) = value.parse::<f64>() {
+        // Anything this large is an absolute unix timestamp rather than a number of seconds
+        if number > 1_000_000_000.0 {
+            // Past the dates chrono can represent, so it's as far off as a delay can be
+            return Some(
+                DateTime::<Utc>::from_timestamp(number as i64, 0)
+                    .map(duration_until)
+                    .unwrap_or(MAX_SERVER_RETRY_DELAY),
+            );
+        }
+        return seconds_delay(number);
+    }
+
+    if let Some(duration) = parse_duration(value) {
+        return Some(duration);
+    }
+
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_rfc2822(value))
+        .ok()
+        .map(|until| duration_until(until.with_timezone(&Utc)))
+}
+
+fn duration_until(until: DateTime<Utc>) -> Duration {
+    (until - Utc::now()).to_std().unwrap_or(Duration::ZERO)
+}
+
+/// Parses durations such as `"1h2m3s"`, `"6m0s"`, `"1.5s"` or `"250ms"`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let (number, tail) = rest.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let unit_len = tail
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        total += match unit {
+            "ms" => number / 1000.0,
+            "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return None,
+        };
+        rest = tail;
+    }
+    if total > 0.0 || value.starts_with('0') {
+        seconds_delay(total)
+    } else {
+        None
+    }
+}
+
+/// Sends a request, retrying on rate limits, transient server errors, timeouts and connection failures.
+///
+/// `send` is called once per attempt. Once the retries are exhausted the last response is
+/// returned as is, so each provider keeps mapping status codes to `ProviderError` the way it
+/// always has.
+pub async fn send_with_retry<F, Fut>(
+    config: &RetryConfig,
+    mut send: F,
+) -> Result<Response, ProviderError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Response, reqwest::Error>>,
+{
+    send_with_retry_and_auth(config, || async { Ok(String::new()) }, |_| send()).await
+}
+
+/// Like [`send_with_retry`], for providers whose tokens expire: `auth` is awaited before
+/// every attempt and its auth header passed to `send`, so a retry after a long backoff
+/// doesn't reuse a token that has expired or been refreshed in the meantime
+pub async fn send_with_retry_and_auth<A, AFut, F, Fut>(
+    config: &RetryConfig,
+    mut auth: A,
+    mut send: F,
+) -> Result<Response, ProviderError>
+where
+    A: FnMut() -> AFut,
+    AFut: Future<Output = Result<String, ProviderError>>,
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<Response, reqwest::Error>>,
+{
+    let mut attempts = 0;
+    loop {
+        let auth_header = auth().await?;
+        let response = match send(auth_header).await {
+            Ok(response) => response,
+            Err(e) if (e.is_connect() || e.is_timeout()) && attempts < config.max_retries => {
+                attempts += 1;
+                let delay = config.delay_for_attempt(attempts);
+                tracing::warn!(
+                    "Request failed (attempt {}/{}): {}. Retrying in {:?}",
+                    attempts,
+                    config.max_retries,
+                    e,
+                    delay
+                );
+                sleep(delay).await;
+                continue;
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let status = response.status();
+        if !is_retryable_status(status) || attempts >= config.max_retries {
+            return Ok(response);
+        }
+
+        attempts += 1;
+        let delay = config.delay_for_response(attempts, response.headers());
+        tracing::warn!(
+            "Request failed with status {} (attempt {}/{}). Retrying in {:?}",
+            status,
+            attempts,
+            config.max_retries,
+            delay
+        );
+        sleep(delay).await;
+    }
+}
+
+/// Runs an operation, retrying when it fails with a rate limit or server error.
+///
+/// This is for providers that don't expose the raw HTTP response, such as SDK based ones.
+pub async fn retry_on_error<T, F, Fut>(
+    config: &RetryConfig,
+    mut operation: F,
+) -> Result<T, ProviderError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ProviderError>>,
+{
+    let mut attempts = 0;
+    loop {
+        match operation().await {
+            Err(e) if is_retryable_error(&e) && attempts < config.max_retries => {
+                attempts += 1;
+                let delay = config.delay_for_attempt(attempts);
+                tracing::warn!(
+                    "{} (attempt {}/{}). Retrying in {:?}",
+                    e,
+                    attempts,
+                    config.max_retries,
+                    delay
+                );
+                sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn fast_config(max_retries: usize) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_interval_ms: 1,
+            backoff_multiplier: 2.0,
+            max_interval_ms: 5,
+        }
+    }
+
+    fn headers(name: &'static str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_delay_for_attempt_is_capped_and_jittered() {
+        let config = RetryConfig {
+            max_retries: 5,
+            initial_interval_ms: 1000,
+            backoff_multiplier: 2.0,
+            max_interval_ms: 3000,
+        };
+        assert_eq!(config.delay_for_attempt(0), Duration::ZERO);
+
+        let first = config.delay_for_attempt(1).as_millis();
+        assert!((800..=1200).contains(&first));
+
+        let capped = config.delay_for_attempt(10).as_millis();
+        assert!((2400..=3600).contains(&capped));
+    }
+
+    #[test]
+    fn test_retry_after_formats() {
+        assert_eq!(
+            retry_after(&headers("retry-after", "7")),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            retry_after(&headers("retry-after-ms", "250")),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            retry_after(&headers("x-ratelimit-reset-requests", "1m30s")),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            retry_after(&headers("x-ratelimit-reset-tokens", "20ms")),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(retry_after(&headers("retry-after", "soon")), None);
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+
+        // Dates in the past mean we can retry right away
+        assert_eq!(
+            retry_after(&headers("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO)
+        );
+
+        let reset = (Utc::now() + chrono::Duration::seconds(120)).timestamp();
+        let delay = retry_after(&headers("x-ratelimit-reset", &reset.to_string())).unwrap();
+        assert!(delay <= Duration::from_secs(120) && delay >= Duration::from_secs(110));
+    }
+
+    #[test]
+    fn test_retry_after_out_of_range_values_are_capped() {
+        for (name, value) in [
+            ("retry-after", "1e30"),
+            ("retry-after", "inf"),
+            ("retry-after-ms", "1e300"),
+            ("x-ratelimit-reset", "99999999999999999999"),
+            ("x-ratelimit-reset-tokens", "1000000000000000000000000000h"),
+        ] {
+            assert_eq!(
+                retry_after(&headers(name, value)),
+                Some(MAX_SERVER_RETRY_DELAY),
+                "{}: {}",
+                name,
+                value
+            );
+        }
+        assert_eq!(retry_after(&headers("retry-after", "NaN")), None);
+        assert_eq!(
+            retry_after(&headers("retry-after", "-5")),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::from_u16(529).unwrap()));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::UNAUTHORIZED));
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_recovers_from_rate_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let url = format!("{}/v1/chat/completions", server.uri());
+        let response = send_with_retry(&fast_config(3), || client.post(&url).send())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_and_auth_gets_a_header_per_attempt() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("authorization", "Bearer token-1"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(header("authorization", "Bearer token-2"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let tokens = AtomicUsize::new(0);
+        let response = send_with_retry_and_auth(
+            &fast_config(2),
+            || async {
+                Ok(format!(
+                    "Bearer token-{}",
+                    tokens.fetch_add(1, Ordering::SeqCst) + 1
+                ))
+            },
+            |auth_header| {
+                client
+                    .post(server.uri())
+                    .header("Authorization", auth_header)
+                    .send()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(tokens.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_returns_last_response_when_exhausted() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let response = send_with_retry(&fast_config(2), || client.post(server.uri()).send())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_does_not_retry_client_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = reqwest::Client::new();
+        let response = send_with_retry(&fast_config(3), || client.post(server.uri()).send())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_error() {
+        let calls = AtomicUsize::new(0);
+        let result = retry_on_error(&fast_config(3), || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(ProviderError::RateLimitExceeded("slow down".to_string()))
+            } else {
+                Ok("done")
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let calls = AtomicUsize::new(0);
+        let result: Result<(), _> = retry_on_error(&fast_config(3), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(ProviderError::Authentication("bad key".to_string()))
+        })
+        .await;
+        assert!(matches!(result, Err(ProviderError::Authentication(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
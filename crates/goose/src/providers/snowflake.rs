@@ -8,6 +8,7 @@ use std::time::Duration;
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
 use super::errors::ProviderError;
 use super::formats::snowflake::{create_request, get_usage, response_to_message};
+use super::retry::{send_with_retry, RetryConfig};
 use super::utils::{get_model, ImageFormat};
 use crate::config::ConfigError;
 use crate::message::Message;
@@ -40,6 +41,8 @@ pub struct SnowflakeProvider {
     auth: SnowflakeAuth,
     model: ModelConfig,
     image_format: ImageFormat,
+    #[serde(skip)]
+    retry_config: RetryConfig,
 }
 
 impl Default for SnowflakeProvider {
@@ -98,6 +101,7 @@ impl SnowflakeProvider {
             auth: SnowflakeAuth::token(api_key),
             model,
             image_format: ImageFormat::OpenAi,
+            retry_config: RetryConfig::from_config(),
         })
     }
 
@@ -123,14 +127,15 @@ impl SnowflakeProvider {
         })?;
 
         let auth_header = self.ensure_auth_header().await?;
-        let response = self
-            .client
-            .post(url)
-            .header("Authorization", auth_header)
-            .header("User-Agent", "Goose")
-            .json(&payload)
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry_config, || {
+            self.client
+                .post(url.clone())
+                .header("Authorization", auth_header.clone())
+                .header("User-Agent", "Goose")
+                .json(&payload)
+                .send()
+        })
+        .await?;
 
         let status = response.status();
 
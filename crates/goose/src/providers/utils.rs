@@ -3,6 +3,7 @@ use super::errors::GoogleErrorCode;
 use crate::model::ModelConfig;
 use anyhow::Result;
 use base64::Engine;
+use futures::{Stream, StreamExt};
 use regex::Regex;
 use reqwest::{Response, StatusCode};
 use serde::{Deserialize, Serialize};
@@ -89,6 +90,60 @@ pub async fn handle_response_openai_compat(response: Response) -> Result<Value,
     }
 }
 
+/// Check a streaming response from OpenAI compatible endpoints before consuming its body.
+/// Successful responses are returned untouched, error statuses are mapped the same way as
+/// `handle_response_openai_compat`.
+pub async fn handle_stream_response_openai_compat(
+    response: Response,
+) -> Result<Response, ProviderError> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    match handle_response_openai_compat(response).await {
+        Err(e) => Err(e),
+        Ok(_) => Err(ProviderError::RequestFailed(format!(
+            "Request failed with status: {}",
+            status
+        ))),
+    }
+}
+
+/// Split a server-sent events byte stream into the payloads of its `data:` lines.
+///
+/// Network chunks can end in the middle of a line (or a UTF-8 sequence), so bytes are buffered
+/// until a full line is available. The stream ends at the OpenAI style `[DONE]` sentinel or when
+/// the underlying body ends.
+pub fn sse_data_stream<S, B, E>(bytes: S) -> impl Stream<Item = Result<String, ProviderError>>
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]> + Send,
+    E: std::fmt::Display + Send,
+{
+    async_stream::try_stream! {
+        let mut bytes = Box::pin(bytes);
+        let mut buffer: Vec<u8> = Vec::new();
+        'read: while let Some(chunk) = bytes.next().await {
+            let chunk = chunk.map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+            buffer.extend_from_slice(chunk.as_ref());
+
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line);
+                if let Some(data) = line.trim_end().strip_prefix("data:") {
+                    let data = data.trim_start();
+                    if data == "[DONE]" {
+                        break 'read;
+                    }
+                    if !data.is_empty() {
+                        yield data.to_string();
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Check if the model is a Google model based on the "model" field in the payload.
 ///
 /// ### Arguments
@@ -430,6 +485,22 @@ mod tests {
             .contains("Unsupported image format"));
     }
 
+    #[tokio::test]
+    async fn test_sse_data_stream_handles_split_lines() {
+        let chunks: Vec<Result<Vec<u8>, std::io::Error>> = vec![
+            Ok(b"event: message\ndata: {\"a\":".to_vec()),
+            Ok(b"1}\n\ndata: {\"b\":2}\n".to_vec()),
+            Ok(b": keep-alive\ndata: [DONE]\ndata: {\"c\":3}\n".to_vec()),
+        ];
+
+        let payloads: Vec<String> = sse_data_stream(futures::stream::iter(chunks))
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(payloads, vec![r#"{"a":1}"#, r#"{"b":2}"#]);
+    }
+
     #[test]
     fn test_sanitize_function_name() {
         assert_eq!(sanitize_function_name("hello-world"), "hello-world");
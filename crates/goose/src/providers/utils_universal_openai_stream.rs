@@ -136,6 +136,29 @@ impl OAIStreamCollector {
     }
 
     pub fn add_chunk(&mut self, chunk: &OAIStreamChunk) {
+        // Top level fields are repeated on every chunk, usage only arrives on the last one
+        if let Some(id) = chunk.id.as_ref().filter(|id| !id.is_empty()) {
+            self.id = Some(id.clone());
+        }
+        if chunk.object.is_some() {
+            self.object = chunk.object.clone();
+        }
+        if chunk.created.is_some_and(|created| created > 0) {
+            self.created = chunk.created;
+        }
+        if chunk.model.is_some() {
+            self.model = chunk.model.clone();
+        }
+        if chunk.system_fingerprint.is_some() {
+            self.system_fingerprint = chunk.system_fingerprint.clone();
+        }
+        if chunk.prompt_filter_results.is_some() {
+            self.prompt_filter_results = chunk.prompt_filter_results.clone();
+        }
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage.clone();
+        }
+
         for ch in chunk.choices.iter() {
             // Always ensure choice exists, even if all fields are absent!
             let idx = ch.index;
@@ -298,6 +321,8 @@ data: [DONE]
         assert_eq!(tc.function.name.as_deref(), Some("get_weather"));
         assert_eq!(tc.function.arguments, r#"{"location":"San Francisco"}"#);
         assert_eq!(choice.finish_reason, "tool_calls");
+        assert_eq!(resp.model, "gpt-4o-2024-11-20");
+        assert_eq!(resp.usage.and_then(|u| u.total_tokens), Some(89));
     }
 
     const TEXT_STREAM: &str = r#"
@@ -8,6 +8,7 @@ use std::time::Duration;
 
 use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
+use super::retry::{send_with_retry, RetryConfig};
 use crate::message::{Message, MessageContent};
 use crate::model::ModelConfig;
 use mcp_core::{tool::Tool, Role, ToolCall, ToolResult};
@@ -77,6 +78,8 @@ pub struct VeniceProvider {
     models_path: String,
     api_key: String,
     model: ModelConfig,
+    #[serde(skip)]
+    retry_config: RetryConfig,
 }
 
 impl Default for VeniceProvider {
@@ -114,6 +117,7 @@ impl VeniceProvider {
             models_path,
             api_key,
             model,
+            retry_config: RetryConfig::from_config(),
         };
 
         Ok(instance)
@@ -126,24 +130,30 @@ impl VeniceProvider {
             .join(path)
             .map_err(|e| ProviderError::RequestFailed(format!("Failed to construct URL: {e}")))?;
         // Choose GET for models endpoint, POST otherwise
-        let method = if path.contains("models") {
+        let is_models = path.contains("models");
+        if is_models {
             tracing::debug!("Using GET method for models endpoint");
-            self.client.get(url.clone())
         } else {
             tracing::debug!("Using POST method for completions endpoint");
-            self.client.post(url.clone())
-        };
+        }
 
         // Log the request details
         tracing::debug!("Venice request URL: {}", url);
         tracing::debug!("Venice request body: {}", body);
 
-        let response = method
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .body(body.to_string())
-            .send()
-            .await?;
+        let response = send_with_retry(&self.retry_config, || {
+            let method = if is_models {
+                self.client.get(url.clone())
+            } else {
+                self.client.post(url.clone())
+            };
+            method
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .header("Content-Type", "application/json")
+                .body(body.to_string())
+                .send()
+        })
+        .await?;
 
         let status = response.status();
         tracing::debug!("Venice response status: {}", status);
@@ -554,6 +564,7 @@ impl Provider for VeniceProvider {
             input_tokens: usage_data["prompt_tokens"].as_i64().map(|v| v as i32),
             output_tokens: usage_data["completion_tokens"].as_i64().map(|v| v as i32),
             total_tokens: usage_data["total_tokens"].as_i64().map(|v| v as i32),
+            cached_input_tokens: None,
         };
 
         Ok((
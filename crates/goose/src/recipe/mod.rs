@@ -1,12 +1,35 @@
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::Arc;
 
 use crate::agents::extension::ExtensionConfig;
+use crate::config::Config;
+use crate::model::ModelConfig;
+use crate::providers::base::Provider;
+use crate::providers::factory::{self, LeadWorkerSettings};
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
+pub mod parameters;
+pub mod sub_recipe;
+
+/// Parameter every recipe can use for the directory its file is in
+pub const RECIPE_DIR_PARAM: &str = "recipe_dir";
+
 fn default_version() -> String {
     "1.0.0".to_string()
 }
 
+/// Parse a recipe from the contents of a JSON or YAML file
+pub fn parse_recipe_content(content: &str) -> Result<Recipe> {
+    if serde_json::from_str::<serde_json::Value>(content).is_ok() {
+        Ok(serde_json::from_str(content)?)
+    } else {
+        serde_yaml::from_str(content)
+            .map_err(|e| anyhow!("Recipe is neither valid JSON nor YAML: {}", e))
+    }
+}
+
 /// A Recipe represents a personalized, user-generated agent configuration that defines
 /// specific behaviors and capabilities within the Goose system.
 ///
@@ -25,6 +48,11 @@ fn default_version() -> String {
 /// * `activities` - Activity labels that appear when loading the Recipe
 /// * `author` - Information about the Recipe's creator and metadata
 /// * `parameters` - Additional parameters for the Recipe
+/// * `max_turns` - Maximum number of agent turns before the run stops
+/// * `max_tokens` - Maximum number of accumulated tokens before the run stops
+/// * `response` - JSON Schema the final output of a run must match
+/// * `sub_recipes` - Other recipes the agent can run as tools
+/// * `settings` - The provider, model and mode the Recipe was designed for
 ///
 /// # Example
 ///
@@ -51,6 +79,11 @@ fn default_version() -> String {
 ///     activities: None,
 ///     author: None,
 ///     parameters: None,
+///     max_turns: None,
+///     max_tokens: None,
+///     response: None,
+///     sub_recipes: None,
+///     settings: None,
 /// };
 ///
 #[derive(Serialize, Deserialize, Debug)]
@@ -85,6 +118,150 @@ pub struct Recipe {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parameters: Option<Vec<RecipeParameter>>, // any additional parameters for the recipe
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_turns: Option<u32>, // the maximum number of agent turns for a run
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<i32>, // the maximum number of accumulated tokens for a run
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Response>, // the schema of the structured final output
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_recipes: Option<Vec<SubRecipe>>, // recipes the agent can run as tools
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Settings>, // the provider, model and mode to run with
+}
+
+/// The structured result a run must finish with
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<serde_json::Value>, // JSON Schema the final output is validated against
+}
+
+/// Another recipe the agent can run as a tool, in an agent of its own
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubRecipe {
+    pub name: String, // name of the tool that runs the sub-recipe
+
+    pub path: String, // the recipe file, relative to the directory of this recipe
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<HashMap<String, String>>, // fixed values for the sub-recipe's parameters
+}
+
+/// The provider, model and mode a recipe was designed for; anything left out comes from
+/// the configuration, as it does for any other session
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goose_provider: Option<String>, // instead of GOOSE_PROVIDER
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goose_model: Option<String>, // instead of GOOSE_MODEL
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>, // instead of GOOSE_TEMPERATURE
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toolshim: Option<bool>, // instead of GOOSE_TOOLSHIM
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toolshim_model: Option<String>, // instead of GOOSE_TOOLSHIM_OLLAMA_MODEL
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lead_provider: Option<String>, // instead of GOOSE_LEAD_PROVIDER
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lead_model: Option<String>, // instead of GOOSE_LEAD_MODEL
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lead_turns: Option<usize>, // instead of GOOSE_LEAD_TURNS
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goose_mode: Option<String>, // instead of GOOSE_MODE
+}
+
+const GOOSE_MODES: [&str; 4] = ["auto", "approve", "smart_approve", "chat"];
+
+impl Settings {
+    /// The provider to run with, or the configured GOOSE_PROVIDER
+    pub fn provider_name(&self) -> Result<String> {
+        match &self.goose_provider {
+            Some(provider) => Ok(provider.clone()),
+            None => Config::global()
+                .get_param("GOOSE_PROVIDER")
+                .map_err(|_| anyhow!("No provider configured. Run 'goose configure' first")),
+        }
+    }
+
+    /// The model to run with. A provider chosen without a model uses that provider's
+    /// default model, since the configured GOOSE_MODEL belongs to another provider.
+    pub fn model_name(&self) -> Result<String> {
+        if let Some(model) = &self.goose_model {
+            return Ok(model.clone());
+        }
+        if let Some(provider) = &self.goose_provider {
+            return factory::providers()
+                .into_iter()
+                .find(|metadata| &metadata.name == provider)
+                .map(|metadata| metadata.default_model)
+                .ok_or_else(|| anyhow!("Unknown provider: {}", provider));
+        }
+        Config::global()
+            .get_param("GOOSE_MODEL")
+            .map_err(|_| anyhow!("No model configured. Run 'goose configure' first"))
+    }
+
+    /// Whether the settings choose or configure a provider or model, rather than only
+    /// setting the mode of the run
+    pub fn configures_provider(&self) -> bool {
+        self.goose_provider.is_some()
+            || self.goose_model.is_some()
+            || self.temperature.is_some()
+            || self.toolshim.is_some()
+            || self.toolshim_model.is_some()
+            || self.lead_provider.is_some()
+            || self.lead_model.is_some()
+            || self.lead_turns.is_some()
+    }
+
+    /// The settings' GOOSE_MODE, checked to be one goose knows
+    pub fn goose_mode(&self) -> Result<Option<String>> {
+        match &self.goose_mode {
+            Some(mode) if !GOOSE_MODES.contains(&mode.as_str()) => Err(anyhow!(
+                "Invalid goose_mode '{}', expected one of: {}",
+                mode,
+                GOOSE_MODES.join(", ")
+            )),
+            mode => Ok(mode.clone()),
+        }
+    }
+
+    /// Create the provider these settings describe
+    pub fn create_provider(&self) -> Result<Arc<dyn Provider>> {
+        let provider_name = self.provider_name()?;
+        let mut model_config = ModelConfig::new(self.model_name()?);
+        if let Some(temperature) = self.temperature {
+            model_config = model_config.with_temperature(Some(temperature));
+        }
+        if let Some(toolshim) = self.toolshim {
+            model_config = model_config.with_toolshim(toolshim);
+        }
+        if let Some(toolshim_model) = &self.toolshim_model {
+            model_config = model_config.with_toolshim_model(Some(toolshim_model.clone()));
+        }
+
+        let lead_worker = LeadWorkerSettings {
+            lead_provider: self.lead_provider.clone(),
+            lead_model: self.lead_model.clone(),
+            lead_turns: self.lead_turns,
+        };
+        factory::create_with_lead_worker(&provider_name, model_config, &lead_worker)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -96,7 +273,7 @@ pub struct Author {
     pub metadata: Option<String>, // any additional metadata for the author
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum RecipeParameterRequirement {
     Required,
@@ -114,7 +291,7 @@ impl fmt::Display for RecipeParameterRequirement {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum RecipeParameterInputType {
     String,
@@ -122,6 +299,8 @@ pub enum RecipeParameterInputType {
     Boolean,
     Date,
     File,
+    Select,
+    Secret,
 }
 
 impl fmt::Display for RecipeParameterInputType {
@@ -134,7 +313,7 @@ impl fmt::Display for RecipeParameterInputType {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RecipeParameter {
     pub key: String,
     pub input_type: RecipeParameterInputType,
@@ -142,6 +321,8 @@ pub struct RecipeParameter {
     pub description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>, // the values a select parameter can take
 }
 
 /// Builder for creating Recipe instances
@@ -159,6 +340,11 @@ pub struct RecipeBuilder {
     activities: Option<Vec<String>>,
     author: Option<Author>,
     parameters: Option<Vec<RecipeParameter>>,
+    max_turns: Option<u32>,
+    max_tokens: Option<i32>,
+    response: Option<Response>,
+    sub_recipes: Option<Vec<SubRecipe>>,
+    settings: Option<Settings>,
 }
 
 impl Recipe {
@@ -188,6 +374,11 @@ impl Recipe {
             activities: None,
             author: None,
             parameters: None,
+            max_turns: None,
+            max_tokens: None,
+            response: None,
+            sub_recipes: None,
+            settings: None,
         }
     }
 }
@@ -252,6 +443,36 @@ impl RecipeBuilder {
         self
     }
 
+    /// Sets the maximum number of agent turns for the Recipe
+    pub fn max_turns(mut self, max_turns: u32) -> Self {
+        self.max_turns = Some(max_turns);
+        self
+    }
+
+    /// Sets the maximum number of accumulated tokens for the Recipe
+    pub fn max_tokens(mut self, max_tokens: i32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Sets the JSON Schema the final output of a run must match
+    pub fn response(mut self, response: Response) -> Self {
+        self.response = Some(response);
+        self
+    }
+
+    /// Sets the recipes the agent can run as tools
+    pub fn sub_recipes(mut self, sub_recipes: Vec<SubRecipe>) -> Self {
+        self.sub_recipes = Some(sub_recipes);
+        self
+    }
+
+    /// Sets the provider, model and mode the Recipe runs with
+    pub fn settings(mut self, settings: Settings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
     /// Builds the Recipe instance
     ///
     /// Returns an error if any required fields are missing
@@ -274,6 +495,11 @@ impl RecipeBuilder {
             activities: self.activities,
             author: self.author,
             parameters: self.parameters,
+            max_turns: self.max_turns,
+            max_tokens: self.max_tokens,
+            response: self.response,
+            sub_recipes: self.sub_recipes,
+            settings: self.settings,
         })
     }
 }
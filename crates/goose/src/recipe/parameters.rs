@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate};
+
+use super::{RecipeParameter, RecipeParameterInputType};
+use crate::config::Config;
+
+/// Largest file a file parameter inlines into the recipe
+const MAX_FILE_PARAMETER_BYTES: u64 = 1024 * 1024;
+
+/// Check the parameter definitions themselves: select parameters need options, with their
+/// default among them, and secrets come from the keyring rather than a default
+pub fn validate_parameter_definitions(parameters: &[RecipeParameter]) -> Result<()> {
+    for param in parameters {
+        match (&param.input_type, &param.options) {
+            (RecipeParameterInputType::Select, options) => {
+                if options.as_deref().unwrap_or_default().is_empty() {
+                    return Err(anyhow!(
+                        "Select parameter '{}' needs a list of options",
+                        param.key
+                    ));
+                }
+            }
+            (_, Some(_)) => {
+                return Err(anyhow!(
+                    "Parameter '{}' has options, which only select parameters can have",
+                    param.key
+                ));
+            }
+            (_, None) => {}
+        }
+
+        match (&param.input_type, &param.default) {
+            (RecipeParameterInputType::Secret, Some(_)) => {
+                return Err(anyhow!(
+                    "Secret parameter '{}' can't have a default, its value comes from the keyring",
+                    param.key
+                ));
+            }
+            // Files are read when the recipe runs, so only their path can be checked then
+            (RecipeParameterInputType::File, _) | (_, None) => {}
+            (_, Some(default)) => {
+                parameter_value(param, default)
+                    .map_err(|e| anyhow!("Invalid default value: {}", e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check a value given for a parameter against its input type, returning the value the
+/// recipe is rendered with: numbers and booleans normalized, and files replaced by their
+/// contents. Errors never include the value of a secret.
+pub fn parameter_value(param: &RecipeParameter, value: &str) -> Result<String> {
+    match param.input_type {
+        RecipeParameterInputType::String => Ok(value.to_string()),
+        RecipeParameterInputType::Secret => {
+            if value.is_empty() {
+                return Err(anyhow!("Secret parameter '{}' is empty", param.key));
+            }
+            Ok(value.to_string())
+        }
+        RecipeParameterInputType::Number => match value.trim().parse::<f64>() {
+            Ok(number) if number.is_finite() => Ok(value.trim().to_string()),
+            _ => Err(anyhow!(
+                "Parameter '{}' must be a number, got '{}'",
+                param.key,
+                value
+            )),
+        },
+        RecipeParameterInputType::Boolean => match parse_boolean(value) {
+            Some(flag) => Ok(flag.to_string()),
+            None => Err(anyhow!(
+                "Parameter '{}' must be true or false, got '{}'",
+                param.key,
+                value
+            )),
+        },
+        RecipeParameterInputType::Date => {
+            let value = value.trim();
+            if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+                || DateTime::parse_from_rfc3339(value).is_ok()
+            {
+                Ok(value.to_string())
+            } else {
+                Err(anyhow!(
+                    "Parameter '{}' must be an ISO 8601 date such as 2025-06-30, got '{}'",
+                    param.key,
+                    value
+                ))
+            }
+        }
+        RecipeParameterInputType::File => read_file_parameter(param, value),
+        RecipeParameterInputType::Select => {
+            let options = param.options.as_deref().unwrap_or_default();
+            if options.iter().any(|option| option == value) {
+                Ok(value.to_string())
+            } else {
+                Err(anyhow!(
+                    "Parameter '{}' must be one of {}, got '{}'",
+                    param.key,
+                    options.join(", "),
+                    value
+                ))
+            }
+        }
+    }
+}
+
+/// Check the values of a recipe's parameters, returning all of them as the recipe is rendered
+/// with; values without a parameter, such as built-in ones, are kept as they are
+pub fn render_parameter_values(
+    parameters: &[RecipeParameter],
+    values: &HashMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let mut rendered = values.clone();
+    let mut errors = Vec::new();
+    for param in parameters {
+        if let Some(value) = values.get(&param.key) {
+            match parameter_value(param, value) {
+                Ok(value) => {
+                    rendered.insert(param.key.clone(), value);
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(rendered)
+    } else {
+        Err(anyhow!(
+            "Invalid parameter values:\n- {}",
+            errors.join("\n- ")
+        ))
+    }
+}
+
+/// The value of a secret parameter, given as a `RECIPE_SECRET_<KEY>` environment variable or
+/// kept in the keyring under `recipe_secret.<key>`. The prefix keeps a recipe from reading
+/// secrets that aren't meant for recipes, such as provider API keys.
+pub fn secret_value(param: &RecipeParameter) -> Option<String> {
+    let env_var = format!("RECIPE_SECRET_{}", param.key.to_uppercase());
+    if let Ok(value) = std::env::var(env_var) {
+        return Some(value);
+    }
+    Config::global()
+        .get_secret::<String>(&format!("recipe_secret.{}", param.key))
+        .ok()
+}
+
+/// Read true/false, yes/no, y/n, on/off and 1/0 in any case
+pub fn parse_boolean(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "yes" | "y" | "on" | "1" => Some(true),
+        "false" | "no" | "n" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn read_file_parameter(param: &RecipeParameter, path: &str) -> Result<String> {
+    let metadata = std::fs::metadata(path).map_err(|e| {
+        anyhow!(
+            "Parameter '{}' must be an existing file, {}: {}",
+            param.key,
+            path,
+            e
+        )
+    })?;
+    if !metadata.is_file() {
+        return Err(anyhow!(
+            "Parameter '{}' must be a file, {} is not one",
+            param.key,
+            path
+        ));
+    }
+    if metadata.len() > MAX_FILE_PARAMETER_BYTES {
+        return Err(anyhow!(
+            "Parameter '{}' is a file of {} bytes, larger than the limit of {} bytes",
+            param.key,
+            metadata.len(),
+            MAX_FILE_PARAMETER_BYTES
+        ));
+    }
+    std::fs::read_to_string(path).map_err(|e| {
+        anyhow!(
+            "Failed to read file parameter '{}' from {}: {}",
+            param.key,
+            path,
+            e
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::RecipeParameterRequirement;
+
+    fn param(input_type: RecipeParameterInputType) -> RecipeParameter {
+        RecipeParameter {
+            key: "value".to_string(),
+            input_type,
+            requirement: RecipeParameterRequirement::Required,
+            description: "a value".to_string(),
+            default: None,
+            options: None,
+        }
+    }
+
+    #[test]
+    fn test_values_are_checked_against_their_type() {
+        let number = param(RecipeParameterInputType::Number);
+        assert_eq!(parameter_value(&number, " 42 ").unwrap(), "42");
+        assert_eq!(parameter_value(&number, "-1.5").unwrap(), "-1.5");
+        assert!(parameter_value(&number, "abc").is_err());
+        assert!(parameter_value(&number, "NaN").is_err());
+
+        let boolean = param(RecipeParameterInputType::Boolean);
+        assert_eq!(parameter_value(&boolean, "Yes").unwrap(), "true");
+        assert_eq!(parameter_value(&boolean, "0").unwrap(), "false");
+        assert!(parameter_value(&boolean, "maybe").is_err());
+
+        let date = param(RecipeParameterInputType::Date);
+        assert!(parameter_value(&date, "2025-06-30").is_ok());
+        assert!(parameter_value(&date, "2025-06-30T12:00:00Z").is_ok());
+        assert!(parameter_value(&date, "2025-02-30").is_err());
+        assert!(parameter_value(&date, "30/06/2025").is_err());
+
+        let select = RecipeParameter {
+            options: Some(vec!["low".to_string(), "high".to_string()]),
+            ..param(RecipeParameterInputType::Select)
+        };
+        assert_eq!(parameter_value(&select, "high").unwrap(), "high");
+        let err = parameter_value(&select, "medium").unwrap_err();
+        assert!(err.to_string().contains("one of low, high"));
+    }
+
+    #[test]
+    fn test_file_parameters_inline_their_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.md");
+        std::fs::write(&path, "# Notes\n").unwrap();
+
+        let file = param(RecipeParameterInputType::File);
+        assert_eq!(
+            parameter_value(&file, path.to_str().unwrap()).unwrap(),
+            "# Notes\n"
+        );
+        assert!(parameter_value(&file, dir.path().to_str().unwrap()).is_err());
+        let missing = dir.path().join("missing.md");
+        assert!(parameter_value(&file, missing.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_errors_are_collected_and_secrets_not_shown() {
+        let parameters = vec![
+            RecipeParameter {
+                key: "count".to_string(),
+                ..param(RecipeParameterInputType::Number)
+            },
+            RecipeParameter {
+                key: "token".to_string(),
+                ..param(RecipeParameterInputType::Secret)
+            },
+            RecipeParameter {
+                key: "dry_run".to_string(),
+                ..param(RecipeParameterInputType::Boolean)
+            },
+        ];
+        let values = HashMap::from([
+            ("count".to_string(), "abc".to_string()),
+            ("token".to_string(), "hunter2".to_string()),
+            ("dry_run".to_string(), "maybe".to_string()),
+            ("recipe_dir".to_string(), "/tmp".to_string()),
+        ]);
+        let err = render_parameter_values(&parameters, &values)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'count'"));
+        assert!(err.contains("'dry_run'"));
+        assert!(!err.contains("hunter2"));
+
+        let values = HashMap::from([
+            ("count".to_string(), "3".to_string()),
+            ("dry_run".to_string(), "no".to_string()),
+        ]);
+        let rendered = render_parameter_values(&parameters, &values).unwrap();
+        assert_eq!(rendered["dry_run"], "false");
+    }
+
+    #[test]
+    fn test_parameter_definitions() {
+        let select = param(RecipeParameterInputType::Select);
+        assert!(validate_parameter_definitions(&[select.clone()]).is_err());
+
+        let select = RecipeParameter {
+            options: Some(vec!["a".to_string(), "b".to_string()]),
+            default: Some("c".to_string()),
+            ..select
+        };
+        assert!(validate_parameter_definitions(&[select.clone()]).is_err());
+        let select = RecipeParameter {
+            default: Some("b".to_string()),
+            ..select
+        };
+        assert!(validate_parameter_definitions(&[select]).is_ok());
+
+        let string = RecipeParameter {
+            options: Some(vec!["a".to_string()]),
+            ..param(RecipeParameterInputType::String)
+        };
+        assert!(validate_parameter_definitions(&[string]).is_err());
+
+        let secret = RecipeParameter {
+            default: Some("hunter2".to_string()),
+            ..param(RecipeParameterInputType::Secret)
+        };
+        assert!(validate_parameter_definitions(&[secret]).is_err());
+    }
+
+    #[test]
+    fn test_secrets_are_read_under_their_prefix() {
+        let secret = RecipeParameter {
+            key: "github_token".to_string(),
+            ..param(RecipeParameterInputType::Secret)
+        };
+        temp_env::with_vars(
+            [
+                ("GITHUB_TOKEN", Some("not-for-recipes")),
+                ("RECIPE_SECRET_GITHUB_TOKEN", Some("hunter2")),
+            ],
+            || assert_eq!(secret_value(&secret).as_deref(), Some("hunter2")),
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use minijinja::{Environment, UndefinedBehavior};
+
+use super::parameters::{parameter_value, secret_value, validate_parameter_definitions};
+use super::{
+    parse_recipe_content, Recipe, RecipeParameter, RecipeParameterInputType,
+    RecipeParameterRequirement, SubRecipe, RECIPE_DIR_PARAM,
+};
+use crate::config::Config;
+
+/// Sub-recipes run as tools named with this prefix and the sub-recipe's name
+pub const SUB_RECIPE_TOOL_PREFIX: &str = "subrecipe__";
+const DEFAULT_MAX_SUB_RECIPE_DEPTH: usize = 3;
+
+/// How deeply sub-recipes may run other sub-recipes, from `GOOSE_SUB_RECIPE_MAX_DEPTH`
+pub fn max_sub_recipe_depth() -> usize {
+    Config::global()
+        .get_param("GOOSE_SUB_RECIPE_MAX_DEPTH")
+        .unwrap_or(DEFAULT_MAX_SUB_RECIPE_DEPTH)
+}
+
+/// Parse a recipe file before its parameters have values, to read its title, description,
+/// parameters and sub-recipes. Template expressions render as empty text, so a YAML value
+/// like `prompt: {{ task }}` that only parses once rendered can still be read.
+fn parse_recipe_template(content: &str, recipe_dir: &Path) -> Result<Recipe> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Chainable);
+    let params = HashMap::from([(RECIPE_DIR_PARAM, recipe_dir.to_string_lossy().to_string())]);
+    let rendered = env
+        .render_str(content, &params)
+        .map_err(|e| anyhow!("Invalid template syntax: {}", e))?;
+    parse_recipe_content(&rendered)
+}
+
+/// Make the paths of sub-recipes absolute, relative to the directory of their recipe
+pub fn resolve_sub_recipe_paths(sub_recipes: Vec<SubRecipe>, recipe_dir: &Path) -> Vec<SubRecipe> {
+    sub_recipes
+        .into_iter()
+        .map(|sub_recipe| SubRecipe {
+            path: recipe_dir
+                .join(&sub_recipe.path)
+                .to_string_lossy()
+                .to_string(),
+            ..sub_recipe
+        })
+        .collect()
+}
+
+/// A sub-recipe's file, read but not yet rendered with its parameter values
+#[derive(Debug, Clone)]
+pub struct LoadedSubRecipe {
+    pub sub_recipe: SubRecipe,
+    pub title: String,
+    pub description: String,
+    /// The parameters the agent provides when running it, those without a fixed value
+    /// that aren't secrets
+    pub parameters: Vec<RecipeParameter>,
+    definitions: Vec<RecipeParameter>,
+    content: String,
+    dir: PathBuf,
+}
+
+impl LoadedSubRecipe {
+    /// Read the recipe file of a sub-recipe whose path has been resolved
+    pub fn load(sub_recipe: &SubRecipe) -> Result<Self> {
+        if !is_valid_tool_name(&sub_recipe.name) {
+            return Err(anyhow!(
+                "Sub-recipe name '{}' may only contain letters, numbers, '_' and '-'",
+                sub_recipe.name
+            ));
+        }
+
+        let path = PathBuf::from(&sub_recipe.path);
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            anyhow!(
+                "Failed to read sub-recipe '{}' from {}: {}",
+                sub_recipe.name,
+                path.display(),
+                e
+            )
+        })?;
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let recipe = parse_recipe_template(&content, &dir)
+            .map_err(|e| anyhow!("Invalid sub-recipe '{}': {}", sub_recipe.name, e))?;
+
+        let definitions = recipe.parameters.unwrap_or_default();
+        validate_parameter_definitions(&definitions)
+            .map_err(|e| anyhow!("Invalid sub-recipe '{}': {}", sub_recipe.name, e))?;
+
+        let values = sub_recipe.values.clone().unwrap_or_default();
+        let declared: HashSet<&str> = definitions.iter().map(|param| param.key.as_str()).collect();
+        if let Some(unknown) = values.keys().find(|key| !declared.contains(key.as_str())) {
+            return Err(anyhow!(
+                "Sub-recipe '{}' has a value for '{}', which is not one of its parameters",
+                sub_recipe.name,
+                unknown
+            ));
+        }
+
+        // Secrets come from the keyring, the agent never sees them
+        let parameters = definitions
+            .iter()
+            .filter(|param| {
+                !values.contains_key(&param.key)
+                    && param.input_type != RecipeParameterInputType::Secret
+            })
+            .cloned()
+            .collect();
+
+        Ok(Self {
+            sub_recipe: sub_recipe.clone(),
+            title: recipe.title,
+            description: recipe.description,
+            parameters,
+            definitions,
+            content,
+            dir,
+        })
+    }
+
+    pub fn tool_name(&self) -> String {
+        format!("{}{}", SUB_RECIPE_TOOL_PREFIX, self.sub_recipe.name)
+    }
+
+    /// Render the recipe with its fixed values, secrets from the keyring, the arguments the
+    /// agent provided and the parameters' defaults, checked against their input types, and
+    /// resolve the paths of its own sub-recipes
+    pub fn render(&self, arguments: &HashMap<String, String>) -> Result<Recipe> {
+        let fixed = self.sub_recipe.values.clone().unwrap_or_default();
+        let mut params = HashMap::new();
+        for param in &self.definitions {
+            let value = match fixed.get(&param.key) {
+                Some(value) => value.clone(),
+                None if param.input_type == RecipeParameterInputType::Secret => secret_value(param)
+                    .ok_or_else(|| anyhow!("The secret '{}' is not set", param.key))?,
+                None => match (arguments.get(&param.key), &param.default) {
+                    (Some(value), _) => value.clone(),
+                    (None, Some(default)) => default.clone(),
+                    (None, None) => {
+                        return Err(anyhow!("Missing required parameter '{}'", param.key));
+                    }
+                },
+            };
+            params.insert(param.key.clone(), parameter_value(param, &value)?);
+        }
+        params.insert(
+            RECIPE_DIR_PARAM.to_string(),
+            self.dir.to_string_lossy().to_string(),
+        );
+
+        let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        let rendered = env.render_str(&self.content, &params).map_err(|e| {
+            anyhow!(
+                "Failed to render sub-recipe '{}': {}",
+                self.sub_recipe.name,
+                e
+            )
+        })?;
+
+        let mut recipe = parse_recipe_content(&rendered)?;
+        recipe.sub_recipes = recipe
+            .sub_recipes
+            .map(|sub_recipes| resolve_sub_recipe_paths(sub_recipes, &self.dir));
+        Ok(recipe)
+    }
+}
+
+fn is_valid_tool_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Check that every sub-recipe of a recipe, and theirs in turn, can be loaded, that names
+/// are unique, and that they don't run each other in a cycle or nest deeper than allowed
+pub fn validate_sub_recipes(recipe: &Recipe, recipe_dir: &Path) -> Result<()> {
+    let sub_recipes = match &recipe.sub_recipes {
+        Some(sub_recipes) => resolve_sub_recipe_paths(sub_recipes.clone(), recipe_dir),
+        None => return Ok(()),
+    };
+    validate_nested(&sub_recipes, &mut Vec::new(), max_sub_recipe_depth())
+}
+
+fn validate_nested(
+    sub_recipes: &[SubRecipe],
+    ancestors: &mut Vec<PathBuf>,
+    max_depth: usize,
+) -> Result<()> {
+    if ancestors.len() >= max_depth {
+        return Err(anyhow!(
+            "Sub-recipes are nested deeper than the limit of {}",
+            max_depth
+        ));
+    }
+
+    let mut names = HashSet::new();
+    for sub_recipe in sub_recipes {
+        if !names.insert(sub_recipe.name.as_str()) {
+            return Err(anyhow!("Duplicate sub-recipe name '{}'", sub_recipe.name));
+        }
+
+        let loaded = LoadedSubRecipe::load(sub_recipe)?;
+        let path = std::fs::canonicalize(&sub_recipe.path)?;
+        if ancestors.contains(&path) {
+            return Err(anyhow!(
+                "Sub-recipe '{}' runs {} again, which would never finish",
+                sub_recipe.name,
+                path.display()
+            ));
+        }
+
+        // Nested sub-recipes are checked as written, their parameters aren't known yet
+        let raw = parse_recipe_template(&loaded.content, &loaded.dir)?;
+        if let Some(nested) = raw.sub_recipes {
+            let nested = resolve_sub_recipe_paths(nested, &loaded.dir);
+            ancestors.push(path);
+            validate_nested(&nested, ancestors, max_depth)?;
+            ancestors.pop();
+        }
+    }
+    Ok(())
+}
+
+/// Whether the agent has to provide a value for the parameter
+pub fn is_required(param: &RecipeParameter) -> bool {
+    param.default.is_none() && param.requirement != RecipeParameterRequirement::Optional
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, content: &str) {
+        std::fs::write(dir.path().join(name), content).unwrap();
+    }
+
+    fn parent_with(sub_recipes: &str) -> Recipe {
+        parse_recipe_content(&format!(
+            "title: Parent\ndescription: Runs others\nprompt: go\nsub_recipes:\n{}",
+            sub_recipes
+        ))
+        .unwrap()
+    }
+
+    const CHILD: &str = r#"
+title: Fix
+description: Fix an issue
+prompt: Fix {{ issue }} in {{ repo }}
+parameters:
+  - key: issue
+    input_type: string
+    requirement: required
+    description: the issue to fix
+  - key: repo
+    input_type: string
+    requirement: required
+    description: the repository
+"#;
+
+    #[test]
+    fn test_load_and_render_with_fixed_values() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir, "fix.yaml", CHILD);
+        let parent =
+            parent_with("  - name: fix\n    path: fix.yaml\n    values:\n      repo: goose\n");
+        let sub_recipes = resolve_sub_recipe_paths(parent.sub_recipes.unwrap(), dir.path());
+
+        let loaded = LoadedSubRecipe::load(&sub_recipes[0]).unwrap();
+        assert_eq!(loaded.tool_name(), "subrecipe__fix");
+        let keys: Vec<_> = loaded.parameters.iter().map(|p| p.key.as_str()).collect();
+        assert_eq!(keys, vec!["issue"]);
+
+        let arguments = HashMap::from([("issue".to_string(), "#42".to_string())]);
+        let recipe = loaded.render(&arguments).unwrap();
+        assert_eq!(recipe.prompt.as_deref(), Some("Fix #42 in goose"));
+        assert!(loaded.render(&HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_unquoted_template_values_load() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir,
+            "review.yaml",
+            r#"
+title: Review
+description: Review a change
+prompt: {{ change }}
+parameters:
+  - key: change
+    input_type: string
+    requirement: required
+    description: the change to review
+"#,
+        );
+        let parent = parent_with("  - name: review\n    path: review.yaml\n");
+        validate_sub_recipes(&parent, dir.path()).unwrap();
+
+        let sub_recipes = resolve_sub_recipe_paths(parent.sub_recipes.unwrap(), dir.path());
+        let loaded = LoadedSubRecipe::load(&sub_recipes[0]).unwrap();
+        assert_eq!(loaded.parameters[0].key, "change");
+        let arguments = HashMap::from([("change".to_string(), "PR 7".to_string())]);
+        assert_eq!(
+            loaded.render(&arguments).unwrap().prompt.as_deref(),
+            Some("PR 7")
+        );
+    }
+
+    #[test]
+    fn test_values_must_be_parameters() {
+        let dir = tempfile::tempdir().unwrap();
+        write(&dir, "fix.yaml", CHILD);
+        let parent =
+            parent_with("  - name: fix\n    path: fix.yaml\n    values:\n      branch: main\n");
+        let err = validate_sub_recipes(&parent, dir.path()).unwrap_err();
+        assert!(err.to_string().contains("'branch'"));
+    }
+
+    #[test]
+    fn test_cycles_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            &dir,
+            "a.yaml",
+            "title: A\ndescription: a\nprompt: a\nsub_recipes:\n  - name: b\n    path: b.yaml\n",
+        );
+        write(
+            &dir,
+            "b.yaml",
+            "title: B\ndescription: b\nprompt: b\nsub_recipes:\n  - name: a\n    path: a.yaml\n",
+        );
+        let parent = parent_with("  - name: a\n    path: a.yaml\n");
+        let err = validate_sub_recipes(&parent, dir.path()).unwrap_err();
+        assert!(err.to_string().contains("never finish"));
+
+        let parent = parent_with("  - name: fix\n    path: missing.yaml\n");
+        assert!(validate_sub_recipes(&parent, dir.path()).is_err());
+    }
+}
@@ -13,10 +13,9 @@ use tokio_cron_scheduler::{job::JobId, Job, JobScheduler as TokioJobScheduler};
 
 use crate::agents::AgentEvent;
 use crate::agents::{Agent, SessionConfig};
-use crate::config::{self, Config};
+use crate::config;
 use crate::message::Message;
 use crate::providers::base::Provider as GooseProvider; // Alias to avoid conflict in test section
-use crate::providers::create;
 use crate::recipe::Recipe;
 use crate::session;
 use crate::session::storage::SessionMetadata;
@@ -571,28 +570,15 @@ impl Scheduler {
         limit: usize,
     ) -> Result<Vec<(String, SessionMetadata)>, SchedulerError> {
         // Changed return type
-        let all_session_files = session::storage::list_sessions()
+        let all_sessions = session::session_store()
+            .list_session_info()
             .map_err(|e| SchedulerError::StorageError(io::Error::other(e)))?;
 
-        let mut schedule_sessions: Vec<(String, SessionMetadata)> = Vec::new();
-
-        for (session_name, session_path) in all_session_files {
-            match session::storage::read_metadata(&session_path) {
-                Ok(metadata) => {
-                    // metadata is not mutable here, and SessionMetadata is original
-                    if metadata.schedule_id.as_deref() == Some(sched_id) {
-                        schedule_sessions.push((session_name, metadata)); // Keep the tuple
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to read metadata for session file {}: {}. Skipping.",
-                        session_path.display(),
-                        e
-                    );
-                }
-            }
-        }
+        let mut schedule_sessions: Vec<(String, SessionMetadata)> = all_sessions
+            .into_iter()
+            .filter(|info| info.metadata.schedule_id.as_deref() == Some(sched_id))
+            .map(|info| (info.id, info.metadata))
+            .collect();
 
         schedule_sessions.sort_by(|a, b| b.0.cmp(&a.0)); // Sort by session_name (timestamp string)
 
@@ -1019,33 +1005,11 @@ async fn run_scheduled_job_internal(
     if let Some(provider) = provider_override {
         agent_provider = provider;
     } else {
-        let global_config = Config::global();
-        let provider_name: String = match global_config.get_param("GOOSE_PROVIDER") {
-            Ok(name) => name,
-            Err(_) => return Err(JobExecutionError {
-                job_id: job.id.clone(),
-                error:
-                    "GOOSE_PROVIDER not configured globally. Run 'goose configure' or set env var."
-                        .to_string(),
-            }),
-        };
-        let model_name: String =
-            match global_config.get_param("GOOSE_MODEL") {
-                Ok(name) => name,
-                Err(_) => return Err(JobExecutionError {
-                    job_id: job.id.clone(),
-                    error:
-                        "GOOSE_MODEL not configured globally. Run 'goose configure' or set env var."
-                            .to_string(),
-                }),
-            };
-        let model_config = crate::model::ModelConfig::new(model_name.clone());
-        agent_provider = create(&provider_name, model_config).map_err(|e| JobExecutionError {
+        // The recipe's settings, falling back to GOOSE_PROVIDER and GOOSE_MODEL
+        let settings = recipe.settings.clone().unwrap_or_default();
+        agent_provider = settings.create_provider().map_err(|e| JobExecutionError {
             job_id: job.id.clone(),
-            error: format!(
-                "Failed to create provider instance '{}': {}",
-                provider_name, e
-            ),
+            error: format!("Failed to create provider instance: {}", e),
         })?;
     }
 
@@ -1057,6 +1021,16 @@ async fn run_scheduled_job_internal(
     }
     tracing::info!("Agent configured with provider for job '{}'", job.id);
 
+    if let Some(settings) = &recipe.settings {
+        let goose_mode = settings.goose_mode().map_err(|e| JobExecutionError {
+            job_id: job.id.clone(),
+            error: e.to_string(),
+        })?;
+        if goose_mode.is_some() {
+            agent.set_goose_mode(goose_mode).await;
+        }
+    }
+
     let session_id_for_return = session::generate_session_id();
 
     // Update the job with the session ID if we have access to the jobs arc
@@ -1089,6 +1063,9 @@ async fn run_scheduled_job_internal(
             id: crate::session::storage::Identifier::Name(session_id_for_return.clone()),
             working_dir: current_dir.clone(),
             schedule_id: Some(job.id.clone()),
+            max_turns: recipe.max_turns,
+            max_tokens: recipe.max_tokens,
+            deadline: None,
         };
 
         match agent
@@ -1112,6 +1089,20 @@ async fn run_scheduled_job_internal(
                         Ok(AgentEvent::McpNotification(_)) => {
                             // Handle notifications if needed
                         }
+                        Ok(AgentEvent::MessageDelta(_)) => {
+                            // Scheduled jobs only record complete messages
+                        }
+                        Ok(AgentEvent::ContextCompacted { messages, .. }) => {
+                            all_session_messages = messages;
+                        }
+                        Ok(AgentEvent::ExtensionRestarted { name, reason }) => {
+                            tracing::warn!(
+                                "[Job {}] Extension {} was restarted because {}",
+                                job.id,
+                                name,
+                                reason
+                            );
+                        }
                         Err(e) => {
                             tracing::error!(
                                 "[Job {}] Error receiving message from agent: {}",
@@ -1148,6 +1139,8 @@ async fn run_scheduled_job_internal(
                             working_dir: current_dir.clone(),
                             description: String::new(),
                             schedule_id: Some(job.id.clone()),
+                            parent_session_id: None,
+                            fork_point: None,
                             message_count: all_session_messages.len(),
                             total_tokens: None,
                             input_tokens: None,
@@ -1155,6 +1148,9 @@ async fn run_scheduled_job_internal(
                             accumulated_total_tokens: None,
                             accumulated_input_tokens: None,
                             accumulated_output_tokens: None,
+                            cost: None,
+                            accumulated_cost: None,
+                            accumulated_cost_by_model: None,
                         };
                         if let Err(e_fb) = crate::session::storage::save_messages_with_metadata(
                             &session_file_path,
@@ -1298,6 +1294,11 @@ mod tests {
             activities: None,
             author: None,
             parameters: None,
+            max_turns: None,
+            max_tokens: None,
+            response: None,
+            sub_recipes: None,
+            settings: None,
         };
         let mut recipe_file = File::create(&recipe_filename)?;
         writeln!(
@@ -0,0 +1,258 @@
+use crate::message::Message;
+use crate::session::info::{format_modified, SessionInfo};
+use crate::session::storage::{ensure_session_dir, SessionMetadata};
+use crate::session::store::{role_name, searchable_text, SessionSearchHit, SessionStore};
+use anyhow::Result;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Characters of context kept on each side of a search match
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Stores each session as a JSONL file in the session directory, with the metadata on
+/// the first line and one message per line after it
+pub struct JsonlSessionStore;
+
+impl JsonlSessionStore {
+    fn session_files(&self) -> Result<Vec<(String, PathBuf)>> {
+        let session_dir = ensure_session_dir()?;
+        let entries = fs::read_dir(&session_dir)?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let path = entry.path();
+
+                if path.extension().is_some_and(|ext| ext == "jsonl") {
+                    let name = path.file_stem()?.to_string_lossy().to_string();
+                    Some((name, path))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(entries)
+    }
+}
+
+fn modified_time(path: &Path) -> SystemTime {
+    path.metadata()
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+impl SessionStore for JsonlSessionStore {
+    /// Creates the file if it doesn't exist, reads and deserializes all messages if it does.
+    /// The first line of the file is expected to be metadata, and the rest are messages.
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(session_file)?;
+
+        let reader = io::BufReader::new(file);
+        let mut lines = reader.lines();
+        let mut messages = Vec::new();
+
+        // Read the first line as metadata or create default if empty/missing
+        if let Some(line) = lines.next() {
+            let line = line?;
+            // Try to parse as metadata, but if it fails, treat it as a message
+            if let Ok(_metadata) = serde_json::from_str::<SessionMetadata>(&line) {
+                // Metadata successfully parsed, continue with the rest of the lines as messages
+            } else {
+                // This is not metadata, it's a message
+                messages.push(serde_json::from_str::<Message>(&line)?);
+            }
+        }
+
+        // Read the rest of the lines as messages
+        for line in lines {
+            messages.push(serde_json::from_str::<Message>(&line?)?);
+        }
+
+        Ok(messages)
+    }
+
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata> {
+        if !session_file.exists() {
+            return Ok(SessionMetadata::default());
+        }
+
+        let file = fs::File::open(session_file)?;
+        let mut reader = io::BufReader::new(file);
+        let mut first_line = String::new();
+
+        // Read just the first line
+        if reader.read_line(&mut first_line)? > 0 {
+            // Try to parse as metadata
+            match serde_json::from_str::<SessionMetadata>(&first_line) {
+                Ok(metadata) => Ok(metadata),
+                Err(_) => {
+                    // If the first line isn't metadata, return default
+                    Ok(SessionMetadata::default())
+                }
+            }
+        } else {
+            // Empty file, return default
+            Ok(SessionMetadata::default())
+        }
+    }
+
+    fn save_messages_with_metadata(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()> {
+        let file = File::create(session_file).expect("The path specified does not exist");
+        let mut writer = io::BufWriter::new(file);
+
+        // Write metadata as the first line
+        serde_json::to_writer(&mut writer, &metadata)?;
+        writeln!(writer)?;
+
+        // Write all messages
+        for message in messages {
+            serde_json::to_writer(&mut writer, &message)?;
+            writeln!(writer)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn session_exists(&self, session_file: &Path) -> bool {
+        session_file.exists()
+    }
+
+    fn remove_session(&self, session_file: &Path) -> Result<()> {
+        fs::remove_file(session_file)?;
+        Ok(())
+    }
+
+    fn list_sessions(&self) -> Result<Vec<(String, PathBuf)>> {
+        self.session_files()
+    }
+
+    fn list_session_info(&self) -> Result<Vec<SessionInfo>> {
+        self.session_files()?
+            .into_iter()
+            .map(|(id, path)| {
+                let modified = path
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .map(format_modified)
+                    .unwrap_or_else(|_| "Unknown".to_string());
+                let metadata = self.read_metadata(&path)?;
+
+                Ok(SessionInfo {
+                    id,
+                    path: path.to_string_lossy().to_string(),
+                    modified,
+                    metadata,
+                })
+            })
+            .collect()
+    }
+
+    fn get_most_recent_session(&self) -> Result<PathBuf> {
+        self.session_files()?
+            .into_iter()
+            .map(|(_, path)| path)
+            .max_by_key(|path| modified_time(path))
+            .ok_or_else(|| anyhow::anyhow!("No session files found"))
+    }
+
+    /// Scans every session file, newest first, for messages containing all query words
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SessionSearchHit>> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut sessions = self.session_files()?;
+        sessions.sort_by_key(|(_, path)| std::cmp::Reverse(modified_time(path)));
+
+        let mut hits = Vec::new();
+        for (session_id, path) in sessions {
+            let messages = match self.read_messages(&path) {
+                Ok(messages) => messages,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable session {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+
+            for (message_index, message) in messages.iter().enumerate() {
+                let text = searchable_text(message);
+                let lowercase = text.to_lowercase();
+                if !terms.iter().all(|term| lowercase.contains(term)) {
+                    continue;
+                }
+
+                let position = lowercase.find(&terms[0]).unwrap_or(0);
+                hits.push(SessionSearchHit {
+                    session_id: session_id.clone(),
+                    path: path.to_string_lossy().to_string(),
+                    message_index,
+                    role: role_name(message).to_string(),
+                    snippet: snippet(&text, &lowercase, position),
+                });
+                if hits.len() >= limit {
+                    return Ok(hits);
+                }
+            }
+        }
+
+        Ok(hits)
+    }
+}
+
+/// The text around `position` in `lowercase`, mapped back onto the original text
+fn snippet(text: &str, lowercase: &str, position: usize) -> String {
+    // Lowercasing can change byte lengths, so work in characters
+    let match_char = lowercase[..position].chars().count();
+    let chars: Vec<char> = text.chars().collect();
+    let match_char = match_char.min(chars.len());
+    let start = match_char.saturating_sub(SNIPPET_CONTEXT);
+    let end = (match_char + SNIPPET_CONTEXT).min(chars.len());
+
+    let mut snippet: String = chars[start..end].iter().collect();
+    snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_trims_around_match() {
+        let text = format!("{} needle {}", "a ".repeat(100), "b ".repeat(100));
+        let lowercase = text.to_lowercase();
+        let position = lowercase.find("needle").unwrap();
+
+        let snippet = snippet(&text, &lowercase, position);
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+        assert!(snippet.chars().count() <= 2 * SNIPPET_CONTEXT + 2);
+
+        assert_eq!(snippet_for("Short Needle"), "Short Needle");
+    }
+
+    fn snippet_for(text: &str) -> String {
+        let lowercase = text.to_lowercase();
+        snippet(text, &lowercase, lowercase.find("needle").unwrap())
+    }
+}
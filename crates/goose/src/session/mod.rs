@@ -1,11 +1,16 @@
 pub mod info;
+mod jsonl_store;
+pub mod sqlite_store;
 pub mod storage;
+pub mod store;
 
 // Re-export common session types and functions
 pub use storage::{
-    ensure_session_dir, generate_description, generate_session_id, get_most_recent_session,
-    get_path, list_sessions, persist_messages, read_messages, read_metadata, update_metadata,
-    Identifier, SessionMetadata,
+    check_fork_point, ensure_session_dir, fork_session, generate_description, generate_session_id,
+    get_most_recent_session, get_path, is_unsaved_session, list_sessions, persist_messages,
+    read_messages, read_metadata, remove_session, search_sessions, session_exists,
+    unsaved_session_file, update_metadata, Identifier, SessionMetadata,
 };
+pub use store::{session_store, SessionSearchHit, SessionStore};
 
 pub use info::{get_session_info, SessionInfo};
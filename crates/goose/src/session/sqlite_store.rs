@@ -0,0 +1,458 @@
+use crate::message::Message;
+use crate::session::info::{format_modified, SessionInfo};
+use crate::session::jsonl_store::JsonlSessionStore;
+use crate::session::storage::SessionMetadata;
+use crate::session::store::{role_name, searchable_text, SessionSearchHit, SessionStore};
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Name of the database file in the session directory
+pub const SESSION_DATABASE: &str = "sessions.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    path TEXT PRIMARY KEY,
+    id TEXT NOT NULL,
+    metadata TEXT NOT NULL,
+    modified INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_modified ON sessions (modified);
+CREATE TABLE IF NOT EXISTS messages (
+    session_path TEXT NOT NULL REFERENCES sessions (path) ON DELETE CASCADE,
+    idx INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    PRIMARY KEY (session_path, idx)
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS message_search USING fts5 (
+    session_path UNINDEXED,
+    idx UNINDEXED,
+    role UNINDEXED,
+    content,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+CREATE TABLE IF NOT EXISTS store_info (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// Set in `store_info` once the JSONL sessions in the session directory have been imported
+const JSONL_MIGRATED_KEY: &str = "jsonl_migrated";
+
+/// Stores sessions in a SQLite database with a full-text index over message text, tool
+/// calls and tool results
+pub struct SqliteSessionStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteSessionStore {
+    pub fn open(database: &Path) -> Result<Self> {
+        let conn = Connection::open(database)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Open the database in the session directory, importing the JSONL sessions there the
+    /// first time it is opened
+    pub fn open_and_migrate(session_dir: &Path) -> Result<Self> {
+        let store = Self::open(&session_dir.join(SESSION_DATABASE))?;
+        let migrated = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT value FROM store_info WHERE key = ?1",
+                [JSONL_MIGRATED_KEY],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .is_some();
+
+        if !migrated {
+            let imported = store.migrate_jsonl_sessions(session_dir)?;
+            tracing::info!(
+                "Imported {} JSONL sessions into {}",
+                imported,
+                SESSION_DATABASE
+            );
+            store.conn.lock().unwrap().execute(
+                "INSERT OR REPLACE INTO store_info (key, value) VALUES (?1, ?2)",
+                params![JSONL_MIGRATED_KEY, chrono::Utc::now().to_rfc3339()],
+            )?;
+        }
+        Ok(store)
+    }
+
+    /// Import every JSONL session file in `dir` that is not in the database yet, keeping
+    /// its modification time. The files are left in place. Returns how many were imported.
+    pub fn migrate_jsonl_sessions(&self, dir: &Path) -> Result<usize> {
+        let jsonl = JsonlSessionStore;
+        let mut imported = 0;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "jsonl") || self.session_exists(&path) {
+                continue;
+            }
+
+            let (metadata, messages) = match jsonl
+                .read_metadata(&path)
+                .and_then(|metadata| Ok((metadata, jsonl.read_messages(&path)?)))
+            {
+                Ok(session) => session,
+                Err(e) => {
+                    tracing::warn!("Skipping unreadable session {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let modified = path
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or_else(|_| SystemTime::now());
+            self.write_session(&path, &metadata, &messages, modified)?;
+            imported += 1;
+        }
+        Ok(imported)
+    }
+
+    fn write_session(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+        modified: SystemTime,
+    ) -> Result<()> {
+        let path = path_key(session_file);
+        let id = session_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+        let modified = modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO sessions (path, id, metadata, modified) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (path) DO UPDATE SET metadata = ?3, modified = ?4",
+            params![path, id, serde_json::to_string(metadata)?, modified],
+        )?;
+        tx.execute("DELETE FROM messages WHERE session_path = ?1", [&path])?;
+        tx.execute(
+            "DELETE FROM message_search WHERE session_path = ?1",
+            [&path],
+        )?;
+        {
+            let mut insert_message = tx
+                .prepare("INSERT INTO messages (session_path, idx, message) VALUES (?1, ?2, ?3)")?;
+            let mut insert_search = tx.prepare(
+                "INSERT INTO message_search (session_path, idx, role, content)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (idx, message) in messages.iter().enumerate() {
+                insert_message.execute(params![path, idx, serde_json::to_string(message)?])?;
+                let text = searchable_text(message);
+                if !text.is_empty() {
+                    insert_search.execute(params![path, idx, role_name(message), text])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Sessions are keyed by the path of the JSONL file they would be stored in
+fn path_key(session_file: &Path) -> String {
+    session_file.to_string_lossy().to_string()
+}
+
+fn modified_from_millis(millis: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64)
+}
+
+/// Quote each word of a user query so FTS5 operators in it are matched literally.
+/// Adjacent phrases are combined with AND.
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl SessionStore for SqliteSessionStore {
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement =
+            conn.prepare("SELECT message FROM messages WHERE session_path = ?1 ORDER BY idx")?;
+        let rows = statement.query_map([path_key(session_file)], |row| row.get::<_, String>(0))?;
+
+        let mut messages = Vec::new();
+        for row in rows {
+            messages.push(serde_json::from_str(&row?)?);
+        }
+        Ok(messages)
+    }
+
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata> {
+        let metadata = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT metadata FROM sessions WHERE path = ?1",
+                [path_key(session_file)],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+
+        match metadata {
+            Some(metadata) => Ok(serde_json::from_str(&metadata)?),
+            None => Ok(SessionMetadata::default()),
+        }
+    }
+
+    fn save_messages_with_metadata(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()> {
+        self.write_session(session_file, metadata, messages, SystemTime::now())
+    }
+
+    fn session_exists(&self, session_file: &Path) -> bool {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT 1 FROM sessions WHERE path = ?1",
+                [path_key(session_file)],
+                |_| Ok(()),
+            )
+            .optional()
+            .is_ok_and(|row| row.is_some())
+    }
+
+    fn remove_session(&self, session_file: &Path) -> Result<()> {
+        let path = path_key(session_file);
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM message_search WHERE session_path = ?1",
+            [&path],
+        )?;
+        let removed = tx.execute("DELETE FROM sessions WHERE path = ?1", [&path])?;
+        tx.commit()?;
+
+        if removed == 0 {
+            return Err(anyhow::anyhow!("Session {} not found", path));
+        }
+        Ok(())
+    }
+
+    fn list_sessions(&self) -> Result<Vec<(String, PathBuf)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT id, path FROM sessions")?;
+        let sessions = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    PathBuf::from(row.get::<_, String>(1)?),
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sessions)
+    }
+
+    fn list_session_info(&self) -> Result<Vec<SessionInfo>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare("SELECT id, path, metadata, modified FROM sessions")?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            let (id, path, metadata, modified) = row?;
+            sessions.push(SessionInfo {
+                id,
+                path,
+                modified: format_modified(modified_from_millis(modified)),
+                metadata: serde_json::from_str(&metadata)?,
+            });
+        }
+        Ok(sessions)
+    }
+
+    fn get_most_recent_session(&self) -> Result<PathBuf> {
+        self.conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT path FROM sessions ORDER BY modified DESC LIMIT 1",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow::anyhow!("No session files found"))
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SessionSearchHit>> {
+        let query = fts_query(query);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT sessions.id, message_search.session_path, message_search.idx,
+                    message_search.role,
+                    snippet(message_search, 3, '', '', '…', 24)
+             FROM message_search
+             JOIN sessions ON sessions.path = message_search.session_path
+             WHERE message_search MATCH ?1
+             ORDER BY rank, sessions.modified DESC
+             LIMIT ?2",
+        )?;
+        let hits = statement
+            .query_map(params![query, limit as i64], |row| {
+                Ok(SessionSearchHit {
+                    session_id: row.get(0)?,
+                    path: row.get(1)?,
+                    message_index: row.get::<_, i64>(2)? as usize,
+                    role: row.get(3)?,
+                    snippet: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::tool::ToolCall;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn messages() -> Vec<Message> {
+        vec![
+            Message::user().with_text("Why does the build fail on Windows?"),
+            Message::assistant().with_tool_request(
+                "1",
+                Ok(ToolCall::new(
+                    "developer__shell",
+                    json!({"command": "cargo build --target x86_64-pc-windows-msvc"}),
+                )),
+            ),
+            Message::assistant().with_text("The linker is missing from the PATH."),
+        ]
+    }
+
+    #[test]
+    fn test_save_read_and_remove_session() -> Result<()> {
+        let dir = tempdir()?;
+        let store = SqliteSessionStore::open(&dir.path().join(SESSION_DATABASE))?;
+        let session_file = dir.path().join("build.jsonl");
+        assert!(!store.session_exists(&session_file));
+        assert!(store.read_messages(&session_file)?.is_empty());
+
+        let mut metadata = SessionMetadata::default();
+        metadata.description = "Windows build".to_string();
+        store.save_messages_with_metadata(&session_file, &metadata, &messages())?;
+        assert!(store.session_exists(&session_file));
+        assert_eq!(store.read_messages(&session_file)?, messages());
+        assert_eq!(
+            store.read_metadata(&session_file)?.description,
+            "Windows build"
+        );
+
+        // Saving again replaces the messages rather than appending them
+        store.save_messages_with_metadata(&session_file, &metadata, &messages()[..1])?;
+        assert_eq!(store.read_messages(&session_file)?.len(), 1);
+        assert!(store.search("linker", 10)?.is_empty());
+
+        let info = store.list_session_info()?;
+        assert_eq!(info.len(), 1);
+        assert_eq!(info[0].id, "build");
+        assert_eq!(store.get_most_recent_session()?, session_file);
+
+        store.remove_session(&session_file)?;
+        assert!(!store.session_exists(&session_file));
+        assert!(store.list_sessions()?.is_empty());
+        assert!(store.search("windows", 10)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_matches_text_and_tool_calls() -> Result<()> {
+        let dir = tempdir()?;
+        let store = SqliteSessionStore::open(&dir.path().join(SESSION_DATABASE))?;
+        let session_file = dir.path().join("build.jsonl");
+        store.save_messages_with_metadata(
+            &session_file,
+            &SessionMetadata::default(),
+            &messages(),
+        )?;
+
+        let hits = store.search("linker PATH", 10)?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "build");
+        assert_eq!(hits[0].message_index, 2);
+        assert_eq!(hits[0].role, "assistant");
+        assert!(hits[0].snippet.contains("linker"));
+
+        let hits = store.search("developer__shell msvc", 10)?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].message_index, 1);
+
+        // FTS operators in the query are matched as plain words
+        assert!(store.search("windows OR \"linker", 10)?.is_empty());
+        assert!(store.search("   ", 10)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrates_jsonl_sessions_once() -> Result<()> {
+        let dir = tempdir()?;
+        let session_file = dir.path().join("old.jsonl");
+        JsonlSessionStore.save_messages_with_metadata(
+            &session_file,
+            &SessionMetadata::default(),
+            &messages(),
+        )?;
+
+        let store = SqliteSessionStore::open_and_migrate(dir.path())?;
+        assert_eq!(store.read_messages(&session_file)?, messages());
+        assert_eq!(store.search("linker", 10)?.len(), 1);
+        drop(store);
+
+        // Sessions written as JSONL after the migration are not imported on reopening
+        JsonlSessionStore.save_messages_with_metadata(
+            &dir.path().join("new.jsonl"),
+            &SessionMetadata::default(),
+            &messages(),
+        )?;
+        let store = SqliteSessionStore::open_and_migrate(dir.path())?;
+        assert_eq!(store.list_sessions()?.len(), 1);
+        Ok(())
+    }
+}
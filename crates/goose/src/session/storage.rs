@@ -1,11 +1,12 @@
-use crate::message::Message;
+use crate::message::{Message, MessageContent};
 use crate::providers::base::Provider;
+use crate::session::store::{session_store, SessionSearchHit, SessionStore};
 use anyhow::Result;
 use chrono::Local;
 use etcetera::{choose_app_strategy, AppStrategy, AppStrategyArgs};
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::io::{self, BufRead, Write};
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use utoipa::ToSchema;
@@ -27,6 +28,10 @@ pub struct SessionMetadata {
     pub description: String,
     /// ID of the schedule that triggered this session, if any
     pub schedule_id: Option<String>,
+    /// ID of the session this one was forked from, if any
+    pub parent_session_id: Option<String>,
+    /// Number of the parent session's messages this session was forked with
+    pub fork_point: Option<usize>,
     /// Number of messages in the session
     pub message_count: usize,
     /// The total number of tokens used in the session. Retrieved from the provider's last usage.
@@ -41,6 +46,12 @@ pub struct SessionMetadata {
     pub accumulated_input_tokens: Option<i32>,
     /// The number of output tokens used in the session. Accumulated across all messages.
     pub accumulated_output_tokens: Option<i32>,
+    /// The cost in US dollars of the provider's last usage, if the model's pricing is known.
+    pub cost: Option<f64>,
+    /// The cost in US dollars of the session. Accumulated across all messages with known pricing.
+    pub accumulated_cost: Option<f64>,
+    /// The accumulated cost in US dollars broken down by the model that produced the usage.
+    pub accumulated_cost_by_model: Option<HashMap<String, f64>>,
 }
 
 // Custom deserializer to handle old sessions without working_dir
@@ -54,12 +65,17 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             description: String,
             message_count: usize,
             schedule_id: Option<String>, // For backward compatibility
+            parent_session_id: Option<String>,
+            fork_point: Option<usize>,
             total_tokens: Option<i32>,
             input_tokens: Option<i32>,
             output_tokens: Option<i32>,
             accumulated_total_tokens: Option<i32>,
             accumulated_input_tokens: Option<i32>,
             accumulated_output_tokens: Option<i32>,
+            cost: Option<f64>,
+            accumulated_cost: Option<f64>,
+            accumulated_cost_by_model: Option<HashMap<String, f64>>,
             working_dir: Option<PathBuf>,
         }
 
@@ -75,12 +91,17 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             description: helper.description,
             message_count: helper.message_count,
             schedule_id: helper.schedule_id,
+            parent_session_id: helper.parent_session_id,
+            fork_point: helper.fork_point,
             total_tokens: helper.total_tokens,
             input_tokens: helper.input_tokens,
             output_tokens: helper.output_tokens,
             accumulated_total_tokens: helper.accumulated_total_tokens,
             accumulated_input_tokens: helper.accumulated_input_tokens,
             accumulated_output_tokens: helper.accumulated_output_tokens,
+            cost: helper.cost,
+            accumulated_cost: helper.accumulated_cost,
+            accumulated_cost_by_model: helper.accumulated_cost_by_model,
             working_dir,
         })
     }
@@ -99,6 +120,8 @@ impl SessionMetadata {
             working_dir,
             description: String::new(),
             schedule_id: None,
+            parent_session_id: None,
+            fork_point: None,
             message_count: 0,
             total_tokens: None,
             input_tokens: None,
@@ -106,6 +129,9 @@ impl SessionMetadata {
             accumulated_total_tokens: None,
             accumulated_input_tokens: None,
             accumulated_output_tokens: None,
+            cost: None,
+            accumulated_cost: None,
+            accumulated_cost_by_model: None,
         }
     }
 }
@@ -155,51 +181,44 @@ pub fn ensure_session_dir() -> Result<PathBuf> {
     Ok(data_dir)
 }
 
-/// Get the path to the most recently modified session file
-pub fn get_most_recent_session() -> Result<PathBuf> {
-    let session_dir = ensure_session_dir()?;
-    let mut entries = fs::read_dir(&session_dir)?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
-        .collect::<Vec<_>>();
-
-    if entries.is_empty() {
-        return Err(anyhow::anyhow!("No session files found"));
+/// The session file of a session run without saving it, such as `goose session --no-session`.
+/// Reading it finds no messages and writing it is a no-op, whichever store is configured.
+pub fn unsaved_session_file() -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from("NUL")
+    } else {
+        PathBuf::from("/dev/null")
     }
+}
 
-    // Sort by modification time, most recent first
-    entries.sort_by(|a, b| {
-        b.metadata()
-            .and_then(|m| m.modified())
-            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-            .cmp(
-                &a.metadata()
-                    .and_then(|m| m.modified())
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH),
-            )
-    });
-
-    Ok(entries[0].path())
+/// Whether a session file is the [`unsaved_session_file`]
+pub fn is_unsaved_session(session_file: &Path) -> bool {
+    session_file == unsaved_session_file()
+}
+
+/// Get the path to the most recently modified session file
+pub fn get_most_recent_session() -> Result<PathBuf> {
+    session_store().get_most_recent_session()
 }
 
 /// List all available session files
 pub fn list_sessions() -> Result<Vec<(String, PathBuf)>> {
-    let session_dir = ensure_session_dir()?;
-    let entries = fs::read_dir(&session_dir)?
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-
-            if path.extension().is_some_and(|ext| ext == "jsonl") {
-                let name = path.file_stem()?.to_string_lossy().to_string();
-                Some((name, path))
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
+    session_store().list_sessions()
+}
 
-    Ok(entries)
+/// Whether a session has been saved
+pub fn session_exists(session_file: &Path) -> bool {
+    !is_unsaved_session(session_file) && session_store().session_exists(session_file)
+}
+
+/// Delete a session and its messages
+pub fn remove_session(session_file: &Path) -> Result<()> {
+    session_store().remove_session(session_file)
+}
+
+/// Find messages across all sessions containing every word of the query
+pub fn search_sessions(query: &str, limit: usize) -> Result<Vec<SessionSearchHit>> {
+    session_store().search(query, limit)
 }
 
 /// Generate a session ID using timestamp format (yyyymmdd_hhmmss)
@@ -209,66 +228,23 @@ pub fn generate_session_id() -> String {
 
 /// Read messages from a session file
 ///
-/// Creates the file if it doesn't exist, reads and deserializes all messages if it does.
-/// The first line of the file is expected to be metadata, and the rest are messages.
+/// Reads from the configured session store. The JSONL store creates the file if it
+/// doesn't exist; the first line of the file is metadata, and the rest are messages.
 pub fn read_messages(session_file: &Path) -> Result<Vec<Message>> {
-    let file = fs::OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .truncate(false)
-        .open(session_file)?;
-
-    let reader = io::BufReader::new(file);
-    let mut lines = reader.lines();
-    let mut messages = Vec::new();
-
-    // Read the first line as metadata or create default if empty/missing
-    if let Some(line) = lines.next() {
-        let line = line?;
-        // Try to parse as metadata, but if it fails, treat it as a message
-        if let Ok(_metadata) = serde_json::from_str::<SessionMetadata>(&line) {
-            // Metadata successfully parsed, continue with the rest of the lines as messages
-        } else {
-            // This is not metadata, it's a message
-            messages.push(serde_json::from_str::<Message>(&line)?);
-        }
+    if is_unsaved_session(session_file) {
+        return Ok(Vec::new());
     }
-
-    // Read the rest of the lines as messages
-    for line in lines {
-        messages.push(serde_json::from_str::<Message>(&line?)?);
-    }
-
-    Ok(messages)
+    session_store().read_messages(session_file)
 }
 
 /// Read session metadata from a session file
 ///
 /// Returns default empty metadata if the file doesn't exist or has no metadata.
 pub fn read_metadata(session_file: &Path) -> Result<SessionMetadata> {
-    if !session_file.exists() {
+    if is_unsaved_session(session_file) {
         return Ok(SessionMetadata::default());
     }
-
-    let file = fs::File::open(session_file)?;
-    let mut reader = io::BufReader::new(file);
-    let mut first_line = String::new();
-
-    // Read just the first line
-    if reader.read_line(&mut first_line)? > 0 {
-        // Try to parse as metadata
-        match serde_json::from_str::<SessionMetadata>(&first_line) {
-            Ok(metadata) => Ok(metadata),
-            Err(_) => {
-                // If the first line isn't metadata, return default
-                Ok(SessionMetadata::default())
-            }
-        }
-    } else {
-        // Empty file, return default
-        Ok(SessionMetadata::default())
-    }
+    session_store().read_metadata(session_file)
 }
 
 /// Write messages to a session file with metadata
@@ -280,6 +256,10 @@ pub async fn persist_messages(
     messages: &[Message],
     provider: Option<Arc<dyn Provider>>,
 ) -> Result<()> {
+    if is_unsaved_session(session_file) {
+        return Ok(());
+    }
+
     // Count user messages
     let user_message_count = messages
         .iter()
@@ -309,21 +289,10 @@ pub fn save_messages_with_metadata(
     metadata: &SessionMetadata,
     messages: &[Message],
 ) -> Result<()> {
-    let file = File::create(session_file).expect("The path specified does not exist");
-    let mut writer = io::BufWriter::new(file);
-
-    // Write metadata as the first line
-    serde_json::to_writer(&mut writer, &metadata)?;
-    writeln!(writer)?;
-
-    // Write all messages
-    for message in messages {
-        serde_json::to_writer(&mut writer, &message)?;
-        writeln!(writer)?;
+    if is_unsaved_session(session_file) {
+        return Ok(());
     }
-
-    writer.flush()?;
-    Ok(())
+    session_store().save_messages_with_metadata(session_file, metadata, messages)
 }
 
 /// Generate a description for the session using the provider
@@ -376,6 +345,110 @@ pub async fn generate_description(
     save_messages_with_metadata(session_file, &metadata, messages)
 }
 
+/// Whether the first `at_message` messages make a session on their own: within the session
+/// and without tool requests whose responses would be cut off
+pub fn check_fork_point(messages: &[Message], at_message: usize) -> Result<()> {
+    if at_message > messages.len() {
+        return Err(anyhow::anyhow!(
+            "Cannot fork at message {}, the session has {} messages",
+            at_message,
+            messages.len()
+        ));
+    }
+
+    let answered = |end: usize| {
+        let mut pending = HashSet::new();
+        for content in messages[..end].iter().flat_map(|m| &m.content) {
+            match content {
+                MessageContent::ToolRequest(request) => {
+                    pending.insert(&request.id);
+                }
+                MessageContent::ToolResponse(response) => {
+                    pending.remove(&response.id);
+                }
+                _ => {}
+            }
+        }
+        pending.is_empty()
+    };
+    if !answered(at_message) {
+        let boundary = (0..at_message)
+            .rev()
+            .find(|&end| answered(end))
+            .unwrap_or(0);
+        return Err(anyhow::anyhow!(
+            "Cannot fork at message {}, it would separate a tool request from its response. \
+             The closest earlier fork point is message {}",
+            at_message,
+            boundary
+        ));
+    }
+    Ok(())
+}
+
+/// Fork a session into a new session next to it, keeping the first `at_message` messages
+/// or all of them when not given. A fork point that would cut a tool request off from its
+/// response is rejected, see [`check_fork_point`].
+///
+/// The fork starts with the parent's working directory and description and records the
+/// parent and fork point in its metadata. Usage and cost start over. Returns the new
+/// session's id.
+pub fn fork_session(parent_file: &Path, at_message: Option<usize>) -> Result<String> {
+    fork_session_in(session_store(), parent_file, at_message)
+}
+
+fn fork_session_in(
+    store: &dyn SessionStore,
+    parent_file: &Path,
+    at_message: Option<usize>,
+) -> Result<String> {
+    if !store.session_exists(parent_file) {
+        return Err(anyhow::anyhow!(
+            "Session file not found (expected path: {})",
+            parent_file.display()
+        ));
+    }
+    let parent_id = parent_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Invalid session path: {}", parent_file.display()))?;
+
+    let parent_metadata = store.read_metadata(parent_file)?;
+    let mut messages = store.read_messages(parent_file)?;
+    let fork_point = match at_message {
+        Some(at_message) => {
+            check_fork_point(&messages, at_message)?;
+            at_message
+        }
+        None => messages.len(),
+    };
+    messages.truncate(fork_point);
+
+    let session_dir = parent_file.parent().unwrap_or_else(|| Path::new("."));
+    let base = generate_session_id();
+    let mut fork_id = base.clone();
+    let mut suffix = 1;
+    while fork_id == parent_id
+        || store.session_exists(&session_dir.join(format!("{}.jsonl", fork_id)))
+    {
+        suffix += 1;
+        fork_id = format!("{}_{}", base, suffix);
+    }
+
+    let mut metadata = SessionMetadata::new(parent_metadata.working_dir);
+    metadata.description = parent_metadata.description;
+    metadata.parent_session_id = Some(parent_id);
+    metadata.fork_point = Some(fork_point);
+    metadata.message_count = messages.len();
+    store.save_messages_with_metadata(
+        &session_dir.join(format!("{}.jsonl", fork_id)),
+        &metadata,
+        &messages,
+    )?;
+
+    Ok(fork_id)
+}
+
 /// Update only the metadata in a session file, preserving all messages
 pub async fn update_metadata(session_file: &Path, metadata: &SessionMetadata) -> Result<()> {
     // Read all messages from the file
@@ -388,7 +461,11 @@ pub async fn update_metadata(session_file: &Path, metadata: &SessionMetadata) ->
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::message::MessageContent;
+    use crate::session::jsonl_store::JsonlSessionStore;
+    use crate::session::sqlite_store::{SqliteSessionStore, SESSION_DATABASE};
+    use mcp_core::tool::ToolCall;
+    use mcp_core::Content;
+    use serde_json::json;
     use tempfile::tempdir;
 
     #[tokio::test]
@@ -615,4 +692,206 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_metadata_cost_round_trip() -> Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("cost.jsonl");
+
+        let mut metadata = SessionMetadata::default();
+        metadata.cost = Some(0.25);
+        metadata.accumulated_cost = Some(1.5);
+        metadata.accumulated_cost_by_model = Some(HashMap::from([
+            ("lead-model".to_string(), 1.25),
+            ("worker-model".to_string(), 0.25),
+        ]));
+        save_messages_with_metadata(&file_path, &metadata, &[])?;
+
+        let read_metadata = read_metadata(&file_path)?;
+        assert_eq!(read_metadata.cost, Some(0.25));
+        assert_eq!(read_metadata.accumulated_cost, Some(1.5));
+        assert_eq!(
+            read_metadata.accumulated_cost_by_model,
+            metadata.accumulated_cost_by_model
+        );
+
+        // Sessions written before cost tracking have no cost fields
+        fs::write(
+            &file_path,
+            r#"{"description":"old","message_count":0,"total_tokens":10}"#,
+        )?;
+        let read_metadata = read_metadata(&file_path)?;
+        assert_eq!(read_metadata.description, "old");
+        assert_eq!(read_metadata.accumulated_cost, None);
+        assert_eq!(read_metadata.accumulated_cost_by_model, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fork_session() -> Result<()> {
+        let dir = tempdir()?;
+        let parent_file = dir.path().join("parent.jsonl");
+
+        let mut metadata = SessionMetadata::default();
+        metadata.description = "Parent session".to_string();
+        metadata.accumulated_cost = Some(0.5);
+        let messages = vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("first reply"),
+            Message::user().with_text("second"),
+            Message::assistant().with_text("second reply"),
+        ];
+        save_messages_with_metadata(&parent_file, &metadata, &messages)?;
+
+        let fork_id = fork_session(&parent_file, Some(2))?;
+        let fork_file = dir.path().join(format!("{}.jsonl", fork_id));
+        let fork_messages = read_messages(&fork_file)?;
+        assert_eq!(fork_messages.len(), 2);
+        assert_eq!(fork_messages[1].as_concat_text(), "first reply");
+
+        let fork_metadata = read_metadata(&fork_file)?;
+        assert_eq!(fork_metadata.parent_session_id.as_deref(), Some("parent"));
+        assert_eq!(fork_metadata.fork_point, Some(2));
+        assert_eq!(fork_metadata.message_count, 2);
+        assert_eq!(fork_metadata.description, "Parent session");
+        assert_eq!(fork_metadata.accumulated_cost, None);
+
+        // Forking again in the same second picks a new id, and the parent is untouched
+        let full_fork_id = fork_session(&parent_file, None)?;
+        assert_ne!(full_fork_id, fork_id);
+        let full_fork_file = dir.path().join(format!("{}.jsonl", full_fork_id));
+        assert_eq!(read_messages(&full_fork_file)?.len(), 4);
+        assert_eq!(read_messages(&parent_file)?.len(), 4);
+        assert_eq!(read_metadata(&parent_file)?.parent_session_id, None);
+
+        assert!(fork_session(&parent_file, Some(5)).is_err());
+        assert!(fork_session(&dir.path().join("missing.jsonl"), None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fork_session_keeps_tool_calls_whole() -> Result<()> {
+        let dir = tempdir()?;
+        let parent_file = dir.path().join("parent.jsonl");
+
+        let messages = vec![
+            Message::user().with_text("list files"),
+            Message::assistant().with_tool_request(
+                "call_1",
+                Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+            ),
+            Message::user().with_tool_response("call_1", Ok(vec![Content::text("a.txt")])),
+            Message::assistant().with_text("There is one file"),
+        ];
+        save_messages_with_metadata(&parent_file, &SessionMetadata::default(), &messages)?;
+
+        let err = fork_session(&parent_file, Some(2)).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("closest earlier fork point is message 1"));
+
+        for at_message in [0, 1, 3, 4] {
+            assert!(check_fork_point(&messages, at_message).is_ok());
+        }
+        let fork_id = fork_session(&parent_file, Some(3))?;
+        let fork_messages = read_messages(&dir.path().join(format!("{}.jsonl", fork_id)))?;
+        assert_eq!(fork_messages.len(), 3);
+
+        Ok(())
+    }
+
+    /// Both stores, each keeping its sessions in `dir`
+    fn stores(dir: &Path) -> Result<Vec<Box<dyn SessionStore>>> {
+        Ok(vec![
+            Box::new(JsonlSessionStore),
+            Box::new(SqliteSessionStore::open(&dir.join(SESSION_DATABASE))?),
+        ])
+    }
+
+    #[test]
+    fn test_stores_read_write_messages() -> Result<()> {
+        let dir = tempdir()?;
+        for store in stores(dir.path())? {
+            let file_path = dir.path().join("test.jsonl");
+            let mut metadata = SessionMetadata::default();
+            metadata.description = "Stored session".to_string();
+            let messages = vec![
+                Message::user().with_text("Hello"),
+                Message::assistant().with_text("Hi there"),
+            ];
+            store.save_messages_with_metadata(&file_path, &metadata, &messages)?;
+
+            assert!(store.session_exists(&file_path));
+            assert_eq!(store.read_messages(&file_path)?, messages);
+            assert_eq!(
+                store.read_metadata(&file_path)?.description,
+                "Stored session"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_fork_session_in_each_store() -> Result<()> {
+        let dir = tempdir()?;
+        for store in stores(dir.path())? {
+            check_fork_session(store.as_ref(), &dir.path().join("parent.jsonl"))?;
+        }
+        Ok(())
+    }
+
+    fn check_fork_session(store: &dyn SessionStore, parent_file: &Path) -> Result<()> {
+        let dir = parent_file.parent().unwrap();
+
+        let mut metadata = SessionMetadata::default();
+        metadata.description = "Parent session".to_string();
+        metadata.accumulated_cost = Some(0.5);
+        let messages = vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("first reply"),
+            Message::user().with_text("second"),
+            Message::assistant().with_text("second reply"),
+        ];
+        store.save_messages_with_metadata(parent_file, &metadata, &messages)?;
+
+        let fork_id = fork_session_in(store, parent_file, Some(2))?;
+        let fork_file = dir.join(format!("{}.jsonl", fork_id));
+        let fork_messages = store.read_messages(&fork_file)?;
+        assert_eq!(fork_messages.len(), 2);
+        assert_eq!(fork_messages[1].as_concat_text(), "first reply");
+
+        let fork_metadata = store.read_metadata(&fork_file)?;
+        assert_eq!(fork_metadata.parent_session_id.as_deref(), Some("parent"));
+        assert_eq!(fork_metadata.fork_point, Some(2));
+        assert_eq!(fork_metadata.message_count, 2);
+        assert_eq!(fork_metadata.description, "Parent session");
+        assert_eq!(fork_metadata.accumulated_cost, None);
+
+        // Forking again in the same second picks a new id, and the parent is untouched
+        let full_fork_id = fork_session_in(store, parent_file, None)?;
+        assert_ne!(full_fork_id, fork_id);
+        let full_fork_file = dir.join(format!("{}.jsonl", full_fork_id));
+        assert_eq!(store.read_messages(&full_fork_file)?.len(), 4);
+        assert_eq!(store.read_messages(parent_file)?.len(), 4);
+        assert_eq!(store.read_metadata(parent_file)?.parent_session_id, None);
+
+        assert!(fork_session_in(store, parent_file, Some(5)).is_err());
+        assert!(fork_session_in(store, &dir.join("missing.jsonl"), None).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unsaved_session_is_never_stored() -> Result<()> {
+        let session_file = unsaved_session_file();
+        let messages = vec![Message::user().with_text("not saved")];
+        persist_messages(&session_file, &messages, None).await?;
+
+        assert!(!session_exists(&session_file));
+        assert!(read_messages(&session_file)?.is_empty());
+        assert_eq!(read_metadata(&session_file)?.message_count, 0);
+        Ok(())
+    }
 }
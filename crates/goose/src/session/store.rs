@@ -0,0 +1,155 @@
+use crate::config::Config;
+use crate::message::{Message, MessageContent};
+use crate::session::info::SessionInfo;
+use crate::session::jsonl_store::JsonlSessionStore;
+use crate::session::sqlite_store::SqliteSessionStore;
+use crate::session::storage::{ensure_session_dir, SessionMetadata};
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+/// Where sessions are kept: one JSONL file per session, or a single SQLite database
+///
+/// Sessions are addressed by the path of their JSONL file in either backend, so that
+/// `get_path` and everything built on it work the same whichever store is configured.
+pub trait SessionStore: Send + Sync {
+    /// All messages of a session, empty if the session does not exist
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>>;
+
+    /// The metadata of a session, or default metadata if it does not exist
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata>;
+
+    /// Replace the metadata and messages of a session, creating it if needed
+    fn save_messages_with_metadata(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()>;
+
+    fn session_exists(&self, session_file: &Path) -> bool;
+
+    fn remove_session(&self, session_file: &Path) -> Result<()>;
+
+    /// The id and path of every stored session
+    fn list_sessions(&self) -> Result<Vec<(String, PathBuf)>>;
+
+    /// Every stored session with its metadata and last modification time, unsorted
+    fn list_session_info(&self) -> Result<Vec<SessionInfo>>;
+
+    /// The path of the most recently modified session
+    fn get_most_recent_session(&self) -> Result<PathBuf>;
+
+    /// Messages matching every word of the query, best matches first
+    fn search(&self, query: &str, limit: usize) -> Result<Vec<SessionSearchHit>>;
+}
+
+/// A message matching a session search
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SessionSearchHit {
+    pub session_id: String,
+    pub path: String,
+    /// Position of the matching message in the session, counted from 0
+    pub message_index: usize,
+    /// Role of the message's author, user or assistant
+    pub role: String,
+    /// The matching part of the message text
+    pub snippet: String,
+}
+
+/// The text a message is found by: its text, thinking, tool calls with their arguments,
+/// and the text of tool results
+pub(crate) fn searchable_text(message: &Message) -> String {
+    message
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            MessageContent::Text(text) => Some(text.text.clone()),
+            MessageContent::Thinking(thinking) => Some(thinking.thinking.clone()),
+            MessageContent::ToolRequest(request) => Some(request.to_readable_string()),
+            MessageContent::ToolResponse(response) => {
+                response.tool_result.as_ref().ok().map(|contents| {
+                    contents
+                        .iter()
+                        .filter_map(|content| content.as_text())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+            }
+            _ => None,
+        })
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub(crate) fn role_name(message: &Message) -> &'static str {
+    match message.role {
+        mcp_core::role::Role::User => "user",
+        mcp_core::role::Role::Assistant => "assistant",
+    }
+}
+
+static SESSION_STORE: Lazy<Box<dyn SessionStore>> = Lazy::new(open_configured_store);
+
+/// The store selected by `GOOSE_SESSION_STORE`, `jsonl` (the default) or `sqlite`
+pub fn session_store() -> &'static dyn SessionStore {
+    SESSION_STORE.as_ref()
+}
+
+fn open_configured_store() -> Box<dyn SessionStore> {
+    let backend = Config::global()
+        .get_param::<String>("GOOSE_SESSION_STORE")
+        .unwrap_or_else(|_| "jsonl".to_string());
+
+    match backend.to_lowercase().as_str() {
+        "sqlite" => {
+            match ensure_session_dir().and_then(|dir| SqliteSessionStore::open_and_migrate(&dir)) {
+                Ok(store) => Box::new(store),
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to open the SQLite session store, using JSONL files: {}",
+                        e
+                    );
+                    Box::new(JsonlSessionStore)
+                }
+            }
+        }
+        "jsonl" => Box::new(JsonlSessionStore),
+        other => {
+            tracing::warn!("Unknown GOOSE_SESSION_STORE '{}', using JSONL files", other);
+            Box::new(JsonlSessionStore)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::tool::ToolCall;
+    use mcp_core::Content;
+    use serde_json::json;
+
+    #[test]
+    fn test_searchable_text_includes_tool_calls_and_results() {
+        let request = Message::assistant()
+            .with_text("Let me check")
+            .with_tool_request(
+                "1",
+                Ok(ToolCall::new(
+                    "developer__shell",
+                    json!({"command": "cargo test"}),
+                )),
+            );
+        let text = searchable_text(&request);
+        assert!(text.contains("Let me check"));
+        assert!(text.contains("developer__shell"));
+        assert!(text.contains("cargo test"));
+
+        let response =
+            Message::user().with_tool_response("1", Ok(vec![Content::text("test result: ok")]));
+        assert_eq!(searchable_text(&response), "test result: ok");
+    }
+}
@@ -302,12 +302,12 @@ where
 }
 
 #[derive(Debug)]
-struct JsonVisitor {
-    recorded_fields: serde_json::Map<String, Value>,
+pub(crate) struct JsonVisitor {
+    pub(crate) recorded_fields: serde_json::Map<String, Value>,
 }
 
 impl JsonVisitor {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             recorded_fields: serde_json::Map::new(),
         }
@@ -0,0 +1,779 @@
+use crate::config::Config;
+use crate::tracing::observation_layer::JsonVisitor;
+use opentelemetry_proto::tonic::collector::trace::v1::trace_service_client::TraceServiceClient;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue};
+use opentelemetry_proto::tonic::resource::v1::Resource;
+use opentelemetry_proto::tonic::trace::v1::{
+    span, status, ResourceSpans, ScopeSpans, Span, Status,
+};
+use prost::Message;
+use reqwest::Client;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tonic::metadata::{Ascii, MetadataKey, MetadataValue};
+use tonic::transport::Channel;
+use tracing::{span as tracing_span, Event, Id, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+use uuid::Uuid;
+
+const DEFAULT_SERVICE_NAME: &str = "goose";
+const BATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Span fields that hold prompts, responses or tool arguments, only exported when
+/// OTEL_INSTRUMENTATION_GENAI_CAPTURE_MESSAGE_CONTENT is set
+const CONTENT_FIELDS: &[&str] = &["input", "output", "user_message"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OtlpProtocol {
+    HttpProtobuf,
+    Grpc,
+}
+
+impl OtlpProtocol {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "http/protobuf" | "http" => Some(Self::HttpProtobuf),
+            "grpc" => Some(Self::Grpc),
+            _ => None,
+        }
+    }
+}
+
+/// Where and how to export spans, read from the standard OTEL_* keys
+#[derive(Debug, Clone)]
+pub struct OtlpConfig {
+    /// The traces endpoint, e.g. `http://localhost:4318/v1/traces` or `http://localhost:4317`
+    pub endpoint: String,
+    pub protocol: OtlpProtocol,
+    pub headers: Vec<(String, String)>,
+    pub service_name: String,
+    /// Export prompts, responses and tool arguments along with the spans
+    pub capture_content: bool,
+}
+
+impl OtlpConfig {
+    /// Reads OTEL_EXPORTER_OTLP_* from the environment or the config file.
+    /// Returns None unless an endpoint is set.
+    pub fn from_config() -> Option<Self> {
+        let config = Config::global();
+        let get = |key: &str| config.get_param::<String>(key).ok();
+
+        let protocol = get("OTEL_EXPORTER_OTLP_TRACES_PROTOCOL")
+            .or_else(|| get("OTEL_EXPORTER_OTLP_PROTOCOL"))
+            .map(|name| {
+                OtlpProtocol::from_name(&name).unwrap_or_else(|| {
+                    tracing::warn!("Unsupported OTLP protocol {}, using http/protobuf", name);
+                    OtlpProtocol::HttpProtobuf
+                })
+            })
+            .unwrap_or(OtlpProtocol::HttpProtobuf);
+
+        let endpoint = traces_endpoint(
+            get("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"),
+            get("OTEL_EXPORTER_OTLP_ENDPOINT"),
+            protocol,
+        )?;
+
+        let headers = get("OTEL_EXPORTER_OTLP_TRACES_HEADERS")
+            .or_else(|| get("OTEL_EXPORTER_OTLP_HEADERS"))
+            .map(|headers| parse_headers(&headers))
+            .unwrap_or_default();
+
+        Some(Self {
+            endpoint,
+            protocol,
+            headers,
+            service_name: get("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string()),
+            capture_content: config
+                .get_param::<bool>("OTEL_INSTRUMENTATION_GENAI_CAPTURE_MESSAGE_CONTENT")
+                .unwrap_or(false),
+        })
+    }
+}
+
+/// A signal-specific endpoint is used as is, while the base endpoint gets the
+/// `/v1/traces` path when exporting over HTTP
+fn traces_endpoint(
+    traces_endpoint: Option<String>,
+    endpoint: Option<String>,
+    protocol: OtlpProtocol,
+) -> Option<String> {
+    if let Some(traces_endpoint) = traces_endpoint.filter(|e| !e.is_empty()) {
+        return Some(traces_endpoint);
+    }
+    let endpoint = endpoint.filter(|e| !e.is_empty())?;
+    match protocol {
+        OtlpProtocol::HttpProtobuf => Some(format!("{}/v1/traces", endpoint.trim_end_matches('/'))),
+        OtlpProtocol::Grpc => Some(endpoint),
+    }
+}
+
+/// Parses `key1=value1,key2=value2`, skipping malformed entries
+fn parse_headers(headers: &str) -> Vec<(String, String)> {
+    headers
+        .split(',')
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=')?;
+            let key = key.trim();
+            (!key.is_empty()).then(|| (key.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Sends finished spans to an OTLP collector
+pub struct OtlpExporter {
+    config: OtlpConfig,
+    client: Client,
+    grpc_client: Option<TraceServiceClient<Channel>>,
+}
+
+impl OtlpExporter {
+    pub fn new(config: OtlpConfig) -> Self {
+        Self {
+            config,
+            client: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to create HTTP client"),
+            grpc_client: None,
+        }
+    }
+
+    pub fn spawn_sender(mut self, queue: Arc<Mutex<Vec<Span>>>) {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(BATCH_INTERVAL).await;
+                let spans = std::mem::take(&mut *queue.lock().unwrap());
+                if spans.is_empty() {
+                    continue;
+                }
+                if let Err(e) = self.export(spans).await {
+                    tracing::error!(
+                        error.msg = %e,
+                        "Failed to export spans to the OTLP collector"
+                    );
+                }
+            }
+        });
+    }
+
+    pub async fn export(
+        &mut self,
+        spans: Vec<Span>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let request = self.export_request(spans);
+        match self.config.protocol {
+            OtlpProtocol::HttpProtobuf => {
+                let mut builder = self
+                    .client
+                    .post(&self.config.endpoint)
+                    .header("Content-Type", "application/x-protobuf")
+                    .body(request.encode_to_vec());
+                for (key, value) in &self.config.headers {
+                    builder = builder.header(key, value);
+                }
+
+                let response = builder.send().await?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    let err_text = response.text().await.unwrap_or_default();
+                    return Err(format!("OTLP collector error: {}: {}", status, err_text).into());
+                }
+            }
+            OtlpProtocol::Grpc => {
+                // Channels are cheap to clone, and a failed export reconnects next time
+                let mut client = match &self.grpc_client {
+                    Some(client) => client.clone(),
+                    None => TraceServiceClient::connect(self.config.endpoint.clone()).await?,
+                };
+
+                let mut request = tonic::Request::new(request);
+                for (key, value) in &self.config.headers {
+                    if let (Ok(key), Ok(value)) = (
+                        MetadataKey::<Ascii>::from_bytes(key.to_lowercase().as_bytes()),
+                        value.parse::<MetadataValue<Ascii>>(),
+                    ) {
+                        request.metadata_mut().insert(key, value);
+                    }
+                }
+                client.export(request).await?;
+                self.grpc_client = Some(client);
+            }
+        }
+        Ok(())
+    }
+
+    fn export_request(&self, spans: Vec<Span>) -> ExportTraceServiceRequest {
+        ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                resource: Some(Resource {
+                    attributes: vec![string_attribute("service.name", &self.config.service_name)],
+                    ..Default::default()
+                }),
+                scope_spans: vec![ScopeSpans {
+                    scope: Some(InstrumentationScope {
+                        name: "goose".to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                        ..Default::default()
+                    }),
+                    spans,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        }
+    }
+}
+
+/// A span being recorded, kept in the span's registry extensions until it closes
+struct OtlpSpanData {
+    trace_id: Vec<u8>,
+    span_id: Vec<u8>,
+    parent_span_id: Vec<u8>,
+    name: String,
+    target: String,
+    start_time: SystemTime,
+    fields: serde_json::Map<String, Value>,
+    error: Option<String>,
+}
+
+/// Records goose's spans and queues them as OTLP spans when they close, named and
+/// attributed following the OpenTelemetry GenAI semantic conventions
+#[derive(Clone)]
+pub struct OtlpLayer {
+    queue: Arc<Mutex<Vec<Span>>>,
+    capture_content: bool,
+}
+
+impl OtlpLayer {
+    pub fn new(capture_content: bool) -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(Vec::new())),
+            capture_content,
+        }
+    }
+}
+
+impl<S> Layer<S> for OtlpLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &tracing_span::Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let metadata = attrs.metadata();
+        if !metadata.target().starts_with("goose::") {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        // Nest under the closest recorded ancestor, or start a new trace
+        let parent = span.scope().skip(1).find_map(|ancestor| {
+            ancestor
+                .extensions()
+                .get::<OtlpSpanData>()
+                .map(|data| (data.trace_id.clone(), data.span_id.clone()))
+        });
+        let (trace_id, parent_span_id) =
+            parent.unwrap_or_else(|| (Uuid::new_v4().as_bytes().to_vec(), Vec::new()));
+
+        let mut visitor = JsonVisitor::new();
+        attrs.record(&mut visitor);
+
+        span.extensions_mut().insert(OtlpSpanData {
+            trace_id,
+            span_id: Uuid::new_v4().as_bytes()[..8].to_vec(),
+            parent_span_id,
+            name: metadata.name().to_string(),
+            target: metadata.target().to_string(),
+            start_time: SystemTime::now(),
+            fields: visitor.recorded_fields,
+            error: None,
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &tracing_span::Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(data) = span.extensions_mut().get_mut::<OtlpSpanData>() {
+            let mut visitor = JsonVisitor::new();
+            values.record(&mut visitor);
+            data.fields.extend(visitor.recorded_fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        if let Some(data) = span.extensions_mut().get_mut::<OtlpSpanData>() {
+            let mut visitor = JsonVisitor::new();
+            event.record(&mut visitor);
+            let mut fields = visitor.recorded_fields;
+            let message = fields.remove("message");
+
+            if *event.metadata().level() == Level::ERROR {
+                data.error = Some(
+                    message
+                        .as_ref()
+                        .and_then(Value::as_str)
+                        .unwrap_or("error")
+                        .to_string(),
+                );
+            }
+            data.fields.extend(fields);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(data) = span.extensions_mut().remove::<OtlpSpanData>() else {
+            return;
+        };
+        let span = to_otlp_span(data, SystemTime::now(), self.capture_content);
+        self.queue.lock().unwrap().push(span);
+    }
+}
+
+fn to_otlp_span(data: OtlpSpanData, end_time: SystemTime, capture_content: bool) -> Span {
+    let (name, kind, mut attributes, mapped_fields) =
+        genai_attributes(&data.name, &data.target, &data.fields);
+
+    for (key, value) in &data.fields {
+        if mapped_fields.contains(&key.as_str())
+            || (!capture_content && CONTENT_FIELDS.contains(&key.as_str()))
+        {
+            continue;
+        }
+        attributes.push(KeyValue {
+            key: format!("goose.{}", key),
+            value: Some(to_any_value(value)),
+        });
+    }
+
+    Span {
+        trace_id: data.trace_id,
+        span_id: data.span_id,
+        parent_span_id: data.parent_span_id,
+        name,
+        kind: kind as i32,
+        start_time_unix_nano: unix_nanos(data.start_time),
+        end_time_unix_nano: unix_nanos(end_time),
+        attributes,
+        status: data.error.map(|message| Status {
+            message,
+            code: status::StatusCode::Error as i32,
+        }),
+        ..Default::default()
+    }
+}
+
+/// Names and attributes for agent replies, provider completions and tool calls.
+/// Also returns the fields the attributes were taken from, so they are not exported twice.
+fn genai_attributes(
+    name: &str,
+    target: &str,
+    fields: &serde_json::Map<String, Value>,
+) -> (String, span::SpanKind, Vec<KeyValue>, Vec<&'static str>) {
+    match name {
+        "reply" => (
+            "invoke_agent goose".to_string(),
+            span::SpanKind::Internal,
+            vec![
+                string_attribute("gen_ai.operation.name", "invoke_agent"),
+                string_attribute("gen_ai.agent.name", "goose"),
+            ],
+            Vec::new(),
+        ),
+        "complete" => {
+            let mut attributes = vec![string_attribute("gen_ai.operation.name", "chat")];
+            // Spans opened outside the provider's module, such as for streaming, name it
+            let system = fields.get("provider").and_then(Value::as_str).or_else(|| {
+                let system = target.strip_prefix("goose::providers::")?;
+                system.split("::").next()
+            });
+            if let Some(system) = system {
+                attributes.push(string_attribute("gen_ai.system", system));
+            }
+
+            // Providers record the model config as a JSON string
+            let model = fields
+                .get("model_config")
+                .and_then(Value::as_str)
+                .and_then(|config| serde_json::from_str::<Value>(config).ok())
+                .and_then(|config| config.get("model_name")?.as_str().map(str::to_string));
+            if let Some(model) = &model {
+                attributes.push(string_attribute("gen_ai.request.model", model));
+            }
+
+            for (field, attribute) in [
+                ("input_tokens", "gen_ai.usage.input_tokens"),
+                ("output_tokens", "gen_ai.usage.output_tokens"),
+            ] {
+                if let Some(tokens) = fields.get(field).and_then(token_count) {
+                    attributes.push(KeyValue {
+                        key: attribute.to_string(),
+                        value: Some(AnyValue {
+                            value: Some(any_value::Value::IntValue(tokens)),
+                        }),
+                    });
+                }
+            }
+
+            let name = match model {
+                Some(model) => format!("chat {}", model),
+                None => "chat".to_string(),
+            };
+            (
+                name,
+                span::SpanKind::Client,
+                attributes,
+                vec!["provider", "model_config", "input_tokens", "output_tokens"],
+            )
+        }
+        "dispatch_tool_call" => {
+            let tool_name = fields.get("tool_name").and_then(Value::as_str);
+            let mut attributes = vec![string_attribute("gen_ai.operation.name", "execute_tool")];
+            if let Some(tool_name) = tool_name {
+                attributes.push(string_attribute("gen_ai.tool.name", tool_name));
+            }
+            if let Some(call_id) = fields.get("tool_call_id").and_then(Value::as_str) {
+                attributes.push(string_attribute("gen_ai.tool.call.id", call_id));
+            }
+
+            let name = match tool_name {
+                Some(tool_name) => format!("execute_tool {}", tool_name),
+                None => "execute_tool".to_string(),
+            };
+            (
+                name,
+                span::SpanKind::Internal,
+                attributes,
+                vec!["tool_name", "tool_call_id"],
+            )
+        }
+        _ => (
+            name.to_string(),
+            span::SpanKind::Internal,
+            Vec::new(),
+            Vec::new(),
+        ),
+    }
+}
+
+/// Token counts arrive as numbers or, when recorded with `?`, as strings
+fn token_count(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn string_attribute(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+fn to_any_value(value: &Value) -> AnyValue {
+    let value = match value {
+        Value::Bool(b) => any_value::Value::BoolValue(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => any_value::Value::IntValue(i),
+            None => any_value::Value::DoubleValue(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => any_value::Value::StringValue(s.clone()),
+        other => any_value::Value::StringValue(other.to_string()),
+    };
+    AnyValue { value: Some(value) }
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+pub fn create_otlp_layer() -> Option<OtlpLayer> {
+    let config = OtlpConfig::from_config()?;
+    let layer = OtlpLayer::new(config.capture_content);
+
+    if !cfg!(test) {
+        OtlpExporter::new(config).spawn_sender(layer.queue.clone());
+    }
+
+    Some(layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::{
+        TraceService, TraceServiceServer,
+    };
+    use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceResponse;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Registry;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn test_config(endpoint: String, protocol: OtlpProtocol) -> OtlpConfig {
+        OtlpConfig {
+            endpoint,
+            protocol,
+            headers: vec![("x-api-key".to_string(), "secret".to_string())],
+            service_name: "goose-test".to_string(),
+            capture_content: false,
+        }
+    }
+
+    /// Records an agent reply with a provider completion and a tool call inside it
+    fn record_reply() -> Vec<Span> {
+        let layer = OtlpLayer::new(false);
+        let subscriber = Registry::default().with(layer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let reply = tracing::info_span!(
+                target: "goose::agents::agent",
+                "reply",
+                user_message = "list my files"
+            );
+            let _reply = reply.enter();
+
+            let complete = tracing::info_span!(target: "goose::providers::anthropic", "complete");
+            complete.in_scope(|| {
+                tracing::debug!(
+                    target: "goose::providers::utils",
+                    model_config = r#"{"model_name": "claude-sonnet-4"}"#,
+                    input = "the full request",
+                    input_tokens = ?120,
+                    output_tokens = 30,
+                );
+            });
+            drop(complete);
+
+            let tool = tracing::info_span!(
+                target: "goose::agents::agent",
+                "dispatch_tool_call",
+                tool_name = "developer__shell",
+                tool_call_id = "call_1",
+            );
+            tool.in_scope(|| {
+                tracing::error!(target: "goose::agents::agent", "command failed");
+            });
+        });
+        std::mem::take(&mut *layer.queue.lock().unwrap())
+    }
+
+    fn attribute<'a>(span: &'a Span, key: &str) -> Option<&'a any_value::Value> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key == key)
+            .and_then(|kv| kv.value.as_ref()?.value.as_ref())
+    }
+
+    fn string_value(value: Option<&any_value::Value>) -> Option<&str> {
+        match value {
+            Some(any_value::Value::StringValue(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_complete_span_takes_system_from_provider_field() {
+        let layer = OtlpLayer::new(false);
+        let subscriber = Registry::default().with(layer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let complete = tracing::info_span!(
+                target: "goose::agents::reply_parts",
+                "complete",
+                provider = "openai",
+                model_config = r#"{"model_name": "gpt-4o"}"#,
+            );
+            drop(complete);
+        });
+        let spans = std::mem::take(&mut *layer.queue.lock().unwrap());
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].name, "chat gpt-4o");
+        assert_eq!(
+            string_value(attribute(&spans[0], "gen_ai.system")),
+            Some("openai")
+        );
+        assert!(attribute(&spans[0], "goose.provider").is_none());
+    }
+
+    #[test]
+    fn test_spans_follow_genai_conventions() {
+        let spans = record_reply();
+        assert_eq!(spans.len(), 3);
+        let find = |name: &str| spans.iter().find(|s| s.name == name).unwrap();
+
+        let reply = find("invoke_agent goose");
+        let chat = find("chat claude-sonnet-4");
+        let tool = find("execute_tool developer__shell");
+
+        assert!(reply.parent_span_id.is_empty());
+        assert_eq!(chat.parent_span_id, reply.span_id);
+        assert_eq!(chat.trace_id, reply.trace_id);
+        assert_eq!(tool.parent_span_id, reply.span_id);
+
+        assert_eq!(chat.kind, span::SpanKind::Client as i32);
+        assert_eq!(
+            string_value(attribute(chat, "gen_ai.system")),
+            Some("anthropic")
+        );
+        assert_eq!(
+            string_value(attribute(chat, "gen_ai.request.model")),
+            Some("claude-sonnet-4")
+        );
+        assert_eq!(
+            attribute(chat, "gen_ai.usage.input_tokens"),
+            Some(&any_value::Value::IntValue(120))
+        );
+        assert_eq!(
+            attribute(chat, "gen_ai.usage.output_tokens"),
+            Some(&any_value::Value::IntValue(30))
+        );
+        assert!(chat.end_time_unix_nano >= chat.start_time_unix_nano);
+
+        // Prompts and responses are left out unless content capture is on
+        assert!(attribute(chat, "goose.input").is_none());
+        assert!(attribute(reply, "goose.user_message").is_none());
+
+        assert_eq!(
+            string_value(attribute(tool, "gen_ai.tool.call.id")),
+            Some("call_1")
+        );
+        assert_eq!(
+            tool.status.as_ref().map(|s| s.code),
+            Some(status::StatusCode::Error as i32)
+        );
+    }
+
+    #[test]
+    fn test_traces_endpoint_and_headers() {
+        assert_eq!(
+            traces_endpoint(
+                None,
+                Some("http://collector:4318/".to_string()),
+                OtlpProtocol::HttpProtobuf
+            ),
+            Some("http://collector:4318/v1/traces".to_string())
+        );
+        assert_eq!(
+            traces_endpoint(
+                Some("http://collector:4318/custom".to_string()),
+                Some("http://other:4318".to_string()),
+                OtlpProtocol::HttpProtobuf
+            ),
+            Some("http://collector:4318/custom".to_string())
+        );
+        assert_eq!(
+            traces_endpoint(
+                None,
+                Some("http://collector:4317".to_string()),
+                OtlpProtocol::Grpc
+            ),
+            Some("http://collector:4317".to_string())
+        );
+        assert_eq!(traces_endpoint(None, None, OtlpProtocol::Grpc), None);
+
+        assert_eq!(
+            parse_headers("x-api-key=secret, tenant = goose,malformed"),
+            vec![
+                ("x-api-key".to_string(), "secret".to_string()),
+                ("tenant".to_string(), "goose".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_http_protobuf() {
+        let collector = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/traces"))
+            .and(header("content-type", "application/x-protobuf"))
+            .and(header("x-api-key", "secret"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&collector)
+            .await;
+
+        let mut exporter = OtlpExporter::new(test_config(
+            format!("{}/v1/traces", collector.uri()),
+            OtlpProtocol::HttpProtobuf,
+        ));
+        exporter.export(record_reply()).await.unwrap();
+
+        let requests = collector.received_requests().await.unwrap();
+        let request = ExportTraceServiceRequest::decode(requests[0].body.as_slice()).unwrap();
+        let resource_spans = &request.resource_spans[0];
+        assert_eq!(
+            resource_spans.resource.as_ref().unwrap().attributes[0],
+            string_attribute("service.name", "goose-test")
+        );
+        assert_eq!(resource_spans.scope_spans[0].spans.len(), 3);
+    }
+
+    #[derive(Clone, Default)]
+    struct CollectorStub {
+        requests: Arc<Mutex<Vec<(Option<String>, ExportTraceServiceRequest)>>>,
+    }
+
+    #[tonic::async_trait]
+    impl TraceService for CollectorStub {
+        async fn export(
+            &self,
+            request: tonic::Request<ExportTraceServiceRequest>,
+        ) -> Result<tonic::Response<ExportTraceServiceResponse>, tonic::Status> {
+            let api_key = request
+                .metadata()
+                .get("x-api-key")
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            self.requests
+                .lock()
+                .unwrap()
+                .push((api_key, request.into_inner()));
+            Ok(tonic::Response::new(ExportTraceServiceResponse::default()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_grpc() {
+        let collector = CollectorStub::default();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(TraceServiceServer::new(collector.clone()))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let mut exporter = OtlpExporter::new(test_config(
+            format!("http://{}", address),
+            OtlpProtocol::Grpc,
+        ));
+        exporter.export(record_reply()).await.unwrap();
+        exporter.export(record_reply()).await.unwrap();
+
+        let requests = collector.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let (api_key, request) = &requests[0];
+        assert_eq!(api_key.as_deref(), Some("secret"));
+        assert_eq!(request.resource_spans[0].scope_spans[0].spans.len(), 3);
+    }
+}
@@ -1,19 +1,23 @@
 // src/lib.rs or tests/truncate_agent_tests.rs
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::Result;
 use futures::StreamExt;
 use goose::agents::{Agent, AgentEvent};
-use goose::message::Message;
+use goose::message::{Message, MessageContent};
 use goose::model::ModelConfig;
 use goose::providers::base::Provider;
+use goose::providers::replay::{ReplayProvider, ReplayResponse, ReplayToolCall};
 use goose::providers::{
     anthropic::AnthropicProvider, azure::AzureProvider, bedrock::BedrockProvider,
     databricks::DatabricksProvider, gcpvertexai::GcpVertexAIProvider, google::GoogleProvider,
     groq::GroqProvider, ollama::OllamaProvider, openai::OpenAiProvider,
     openrouter::OpenRouterProvider,
 };
+use goose::recipe::Settings;
+use mcp_core::Content;
 
 #[derive(Debug, PartialEq)]
 enum ProviderType {
@@ -136,6 +140,9 @@ async fn run_truncate_test(
             Ok(AgentEvent::McpNotification(n)) => {
                 println!("MCP Notification: {n:?}");
             }
+            Ok(AgentEvent::MessageDelta(_)) => {}
+            Ok(AgentEvent::ContextCompacted { .. }) => {}
+            Ok(AgentEvent::ExtensionRestarted { .. }) => {}
             Err(e) => {
                 println!("Error: {:?}", e);
                 return Err(e);
@@ -327,3 +334,148 @@ mod tests {
         .await
     }
 }
+
+#[tokio::test]
+async fn test_oversized_tool_output_can_be_read_back() -> Result<()> {
+    let tool_call = |name: &str, arguments: serde_json::Value| ReplayResponse {
+        text: None,
+        tool_calls: vec![ReplayToolCall {
+            name: name.to_string(),
+            arguments,
+        }],
+    };
+    let provider = Arc::new(ReplayProvider::new(vec![
+        tool_call(
+            "developer__shell",
+            serde_json::json!({"command": "cat build.log"}),
+        ),
+        tool_call(
+            "platform__read_tool_output",
+            serde_json::json!({"output_id": "output_1", "action": "slice", "start_line": 2}),
+        ),
+        ReplayResponse {
+            text: Some("The build log has 200000 lines".to_string()),
+            tool_calls: vec![],
+        },
+    ]));
+
+    let log = (1..=200_000)
+        .map(|line| format!("build step {}\n", line))
+        .collect::<String>();
+    let stubs = HashMap::from([("developer__shell".to_string(), Ok(vec![Content::text(log)]))]);
+    let agent = Agent::new().with_tool_stubs(stubs);
+    agent.update_provider(provider.clone()).await?;
+    agent.set_goose_mode(Some("auto".to_string())).await;
+    assert!(agent
+        .list_tools(None)
+        .await
+        .iter()
+        .any(|tool| tool.name == "platform__read_tool_output"));
+
+    let messages = vec![Message::user().with_text("How long is the build log?")];
+    let mut stream = agent.reply(&messages, None).await?;
+    let mut tool_outputs = Vec::new();
+    while let Some(event) = stream.next().await {
+        if let AgentEvent::Message(message) = event? {
+            for content in &message.content {
+                if let MessageContent::ToolResponse(response) = content {
+                    let contents = response.tool_result.as_ref().expect("tool call failed");
+                    tool_outputs.push(contents[0].as_text().unwrap_or_default().to_string());
+                }
+            }
+        }
+    }
+
+    assert_eq!(tool_outputs.len(), 2);
+    assert!(tool_outputs[0].contains("stored as `output_1`"));
+    assert!(tool_outputs[1].contains("build step 2\n"));
+    assert!(!tool_outputs[1].contains("build step 1\n"));
+    assert_eq!(provider.remaining(), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recipe_settings_configure_the_provider() -> Result<()> {
+    temp_env::async_with_vars(
+        [
+            ("GOOSE_PROVIDER", Some("ollama")),
+            ("GOOSE_MODEL", Some("llama3.2")),
+            ("GOOSE_LEAD_MODEL", None),
+        ],
+        async {
+            let agent = Agent::new();
+            agent
+                .update_provider(Arc::new(ReplayProvider::new(vec![])))
+                .await?;
+
+            // A mode alone keeps the provider the agent has
+            let settings = Settings {
+                goose_mode: Some("chat".to_string()),
+                ..Default::default()
+            };
+            agent.apply_recipe_settings(&settings).await?;
+            let model_config = agent.provider().await?.get_model_config();
+            assert_eq!(model_config.model_name, "replay");
+
+            // A temperature alone configures the provider, as the CLI does
+            let settings = Settings {
+                temperature: Some(0.2),
+                ..Default::default()
+            };
+            agent.apply_recipe_settings(&settings).await?;
+            let model_config = agent.provider().await?.get_model_config();
+            assert_eq!(model_config.model_name, "llama3.2");
+            assert_eq!(model_config.temperature, Some(0.2));
+            Ok(())
+        },
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_compaction_that_cannot_reach_the_threshold_runs_once() -> Result<()> {
+    // Any conversation is above this threshold, even with nothing left to compact
+    temp_env::async_with_vars(
+        [
+            ("GOOSE_AUTO_COMPACT", Some("truncate")),
+            ("GOOSE_AUTO_COMPACT_THRESHOLD", Some("0.001")),
+        ],
+        async {
+            let provider = Arc::new(ReplayProvider::new(vec![
+                ReplayResponse {
+                    text: None,
+                    tool_calls: vec![ReplayToolCall {
+                        name: "developer__shell".to_string(),
+                        arguments: serde_json::json!({"command": "ls"}),
+                    }],
+                },
+                ReplayResponse {
+                    text: Some("There is one file".to_string()),
+                    tool_calls: vec![],
+                },
+            ]));
+            let stubs = HashMap::from([(
+                "developer__shell".to_string(),
+                Ok(vec![Content::text("a.txt")]),
+            )]);
+            let agent = Agent::new().with_tool_stubs(stubs);
+            agent.update_provider(provider.clone()).await?;
+            agent.set_goose_mode(Some("auto".to_string())).await;
+
+            let messages = vec![Message::user().with_text("Which files are there?")];
+            let mut stream = agent.reply(&messages, None).await?;
+            let mut compactions = 0;
+            while let Some(event) = stream.next().await {
+                if let AgentEvent::ContextCompacted { .. } = event? {
+                    compactions += 1;
+                }
+            }
+
+            assert_eq!(compactions, 1);
+            assert_eq!(provider.remaining(), 0);
+            assert!(!provider.exhausted());
+            Ok(())
+        },
+    )
+    .await
+}
@@ -1,12 +1,14 @@
 use mcp_core::protocol::{
-    CallToolResult, GetPromptResult, Implementation, InitializeResult, JsonRpcError,
-    JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, ListPromptsResult,
-    ListResourcesResult, ListToolsResult, ReadResourceResult, ServerCapabilities, METHOD_NOT_FOUND,
+    CallToolResult, CreateMessageParams, CreateMessageResult, EmptyResult, ErrorData,
+    GetPromptResult, Implementation, InitializeResult, JsonRpcError, JsonRpcMessage,
+    JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, ListPromptsResult,
+    ListResourceTemplatesResult, ListResourcesResult, ListToolsResult, ReadResourceResult,
+    ServerCapabilities, INVALID_PARAMS, METHOD_NOT_FOUND,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::{
-    atomic::{AtomicU64, Ordering},
+    atomic::{AtomicBool, AtomicU64, Ordering},
     Arc,
 };
 use thiserror::Error;
@@ -68,7 +70,21 @@ pub struct ClientInfo {
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct ClientCapabilities {
-    // Add fields as needed. For now, empty capabilities are fine.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingCapability>,
+}
+
+/// Advertises that the client can handle `sampling/createMessage` requests
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct SamplingCapability {}
+
+/// Handles `sampling/createMessage` requests the server sends to the client
+#[async_trait::async_trait]
+pub trait SamplingHandler: Send + Sync {
+    async fn create_message(
+        &self,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, ErrorData>;
 }
 
 #[derive(Serialize, Deserialize)]
@@ -95,6 +111,16 @@ pub trait McpClientTrait: Send + Sync {
 
     async fn read_resource(&self, uri: &str) -> Result<ReadResourceResult, Error>;
 
+    async fn list_resource_templates(
+        &self,
+        next_cursor: Option<String>,
+    ) -> Result<ListResourceTemplatesResult, Error>;
+
+    /// Ask the server to send `notifications/resources/updated` when the resource changes
+    async fn subscribe_resource(&self, uri: &str) -> Result<(), Error>;
+
+    async fn unsubscribe_resource(&self, uri: &str) -> Result<(), Error>;
+
     async fn list_tools(&self, next_cursor: Option<String>) -> Result<ListToolsResult, Error>;
 
     async fn call_tool(&self, name: &str, arguments: Value) -> Result<CallToolResult, Error>;
@@ -104,6 +130,12 @@ pub trait McpClientTrait: Send + Sync {
     async fn get_prompt(&self, name: &str, arguments: Value) -> Result<GetPromptResult, Error>;
 
     async fn subscribe(&self) -> mpsc::Receiver<JsonRpcMessage>;
+
+    /// Check that the server is still answering requests
+    async fn ping(&self) -> Result<(), Error>;
+
+    /// Whether the transport to the server has closed, e.g. because its process exited
+    fn is_closed(&self) -> bool;
 }
 
 /// The MCP client is the interface for MCP operations.
@@ -116,6 +148,8 @@ where
     server_capabilities: Option<ServerCapabilities>,
     server_info: Option<Implementation>,
     notification_subscribers: Arc<Mutex<Vec<mpsc::Sender<JsonRpcMessage>>>>,
+    transport: T,
+    closed: Arc<AtomicBool>,
 }
 
 impl<T> McpClient<T>
@@ -123,11 +157,24 @@ where
     T: TransportHandle + Send + Sync + 'static,
 {
     pub async fn connect(transport: T, timeout: std::time::Duration) -> Result<Self, Error> {
+        Self::connect_with_sampling(transport, timeout, None).await
+    }
+
+    /// Connect like `connect`, answering the server's sampling requests with `sampling_handler`.
+    /// Without a handler, sampling requests are rejected as unknown methods.
+    pub async fn connect_with_sampling(
+        transport: T,
+        timeout: std::time::Duration,
+        sampling_handler: Option<Arc<dyn SamplingHandler>>,
+    ) -> Result<Self, Error> {
         let service = McpService::new(transport.clone());
         let service_ptr = service.clone();
+        let client_transport = transport.clone();
         let notification_subscribers =
             Arc::new(Mutex::new(Vec::<mpsc::Sender<JsonRpcMessage>>::new()));
         let subscribers_ptr = notification_subscribers.clone();
+        let closed = Arc::new(AtomicBool::new(false));
+        let closed_ptr = closed.clone();
 
         tokio::spawn(async move {
             loop {
@@ -135,9 +182,22 @@ where
                     Ok(message) => {
                         tracing::info!("Received message: {:?}", message);
                         match message {
-                            JsonRpcMessage::Response(JsonRpcResponse { id: Some(id), .. }) => {
+                            JsonRpcMessage::Response(JsonRpcResponse { id: Some(id), .. })
+                            | JsonRpcMessage::Error(JsonRpcError { id: Some(id), .. }) => {
                                 service_ptr.respond(&id.to_string(), Ok(message)).await;
                             }
+                            JsonRpcMessage::Request(request) => {
+                                // Answer in a separate task, the handler may take a while
+                                // and responses to our own requests must keep flowing
+                                let transport = transport.clone();
+                                let handler = sampling_handler.clone();
+                                tokio::spawn(async move {
+                                    let response = handle_server_request(handler, request).await;
+                                    if let Err(e) = transport.send(response).await {
+                                        tracing::error!("failed to answer server request: {:?}", e);
+                                    }
+                                });
+                            }
                             _ => {
                                 let mut subs = subscribers_ptr.lock().await;
                                 subs.retain(|sub| sub.try_send(message.clone()).is_ok());
@@ -146,6 +206,7 @@ where
                     }
                     Err(e) => {
                         tracing::error!("transport error: {:?}", e);
+                        closed_ptr.store(true, Ordering::SeqCst);
                         service_ptr.hangup().await;
                         subscribers_ptr.lock().await.clear();
                         break;
@@ -162,6 +223,8 @@ where
             server_capabilities: None,
             server_info: None,
             notification_subscribers,
+            transport: client_transport,
+            closed,
         })
     }
 
@@ -186,19 +249,29 @@ where
             params: Some(params),
         });
 
-        let response_msg = service
-            .call(request)
-            .await
-            .map_err(|e| Error::McpServerError {
-                server: self
-                    .server_info
-                    .as_ref()
-                    .map(|s| s.name.clone())
-                    .unwrap_or("".to_string()),
-                method: method.to_string(),
-                // we don't need include params because it can be really large
-                source: Box::<Error>::new(e.into()),
-            })?;
+        // If this future is dropped or times out before the response arrives, tell the
+        // server so it can stop working on the request
+        let mut cancel_guard = CancelOnDrop::new(self.transport.clone(), id);
+        let response_msg = service.call(request).await;
+        match &response_msg {
+            Ok(_) => cancel_guard.disarm(),
+            Err(e) if e.is::<tower::timeout::error::Elapsed>() => {
+                cancel_guard.reason = "Request timed out"
+            }
+            Err(_) => {}
+        }
+        drop(cancel_guard);
+
+        let response_msg = response_msg.map_err(|e| Error::McpServerError {
+            server: self
+                .server_info
+                .as_ref()
+                .map(|s| s.name.clone())
+                .unwrap_or("".to_string()),
+            method: method.to_string(),
+            // we don't need include params because it can be really large
+            source: Box::<Error>::new(e.into()),
+        })?;
 
         match response_msg {
             JsonRpcMessage::Response(JsonRpcResponse {
@@ -354,6 +427,69 @@ where
         self.send_request("resources/read", params).await
     }
 
+    async fn list_resource_templates(
+        &self,
+        next_cursor: Option<String>,
+    ) -> Result<ListResourceTemplatesResult, Error> {
+        if !self.completed_initialization() {
+            return Err(Error::NotInitialized);
+        }
+        // If resources is not supported, return an empty list
+        if self
+            .server_capabilities
+            .as_ref()
+            .unwrap()
+            .resources
+            .is_none()
+        {
+            return Ok(ListResourceTemplatesResult {
+                resource_templates: vec![],
+                next_cursor: None,
+            });
+        }
+
+        let payload = next_cursor
+            .map(|cursor| serde_json::json!({"cursor": cursor}))
+            .unwrap_or_else(|| serde_json::json!({}));
+
+        self.send_request("resources/templates/list", payload).await
+    }
+
+    async fn subscribe_resource(&self, uri: &str) -> Result<(), Error> {
+        if !self.completed_initialization() {
+            return Err(Error::NotInitialized);
+        }
+        // Subscriptions are only available when the server advertises them
+        let supports_subscribe = self
+            .server_capabilities
+            .as_ref()
+            .unwrap()
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false);
+        if !supports_subscribe {
+            return Err(Error::RpcError {
+                code: METHOD_NOT_FOUND,
+                message: "Server does not support resource subscriptions".to_string(),
+            });
+        }
+
+        let params = serde_json::json!({ "uri": uri });
+        let _: EmptyResult = self.send_request("resources/subscribe", params).await?;
+        Ok(())
+    }
+
+    async fn unsubscribe_resource(&self, uri: &str) -> Result<(), Error> {
+        if !self.completed_initialization() {
+            return Err(Error::NotInitialized);
+        }
+
+        let params = serde_json::json!({ "uri": uri });
+        let _: EmptyResult = self.send_request("resources/unsubscribe", params).await?;
+        Ok(())
+    }
+
     async fn list_tools(&self, next_cursor: Option<String>) -> Result<ListToolsResult, Error> {
         if !self.completed_initialization() {
             return Err(Error::NotInitialized);
@@ -435,4 +571,243 @@ where
         self.notification_subscribers.lock().await.push(tx);
         rx
     }
+
+    async fn ping(&self) -> Result<(), Error> {
+        self.send_request::<EmptyResult>("ping", json!({}))
+            .await
+            .map(|_| ())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+}
+
+/// Sends `notifications/cancelled` for a request unless disarmed before being dropped
+struct CancelOnDrop<T: TransportHandle> {
+    transport: T,
+    request_id: u64,
+    reason: &'static str,
+    armed: bool,
+}
+
+impl<T: TransportHandle> CancelOnDrop<T> {
+    fn new(transport: T, request_id: u64) -> Self {
+        Self {
+            transport,
+            request_id,
+            reason: "Request cancelled by the client",
+            armed: true,
+        }
+    }
+
+    fn disarm(&mut self) {
+        self.armed = false;
+    }
+}
+
+impl<T: TransportHandle> Drop for CancelOnDrop<T> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        // Drop can't await, hand the notification to the runtime if there still is one
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let transport = self.transport.clone();
+        let notification = cancelled_notification(self.request_id, self.reason);
+        runtime.spawn(async move {
+            if let Err(e) = transport.send(notification).await {
+                tracing::debug!("failed to send cancellation: {:?}", e);
+            }
+        });
+    }
+}
+
+fn cancelled_notification(request_id: u64, reason: &str) -> JsonRpcMessage {
+    JsonRpcMessage::Notification(JsonRpcNotification {
+        jsonrpc: "2.0".to_string(),
+        method: "notifications/cancelled".to_string(),
+        params: Some(json!({
+            "requestId": request_id,
+            "reason": reason,
+        })),
+    })
+}
+
+/// Build the reply to a request initiated by the server
+async fn handle_server_request(
+    sampling_handler: Option<Arc<dyn SamplingHandler>>,
+    request: JsonRpcRequest,
+) -> JsonRpcMessage {
+    let result = match (request.method.as_str(), sampling_handler) {
+        ("ping", _) => Ok(json!({})),
+        ("sampling/createMessage", Some(handler)) => {
+            match serde_json::from_value::<CreateMessageParams>(
+                request.params.unwrap_or(Value::Null),
+            ) {
+                Ok(params) => handler.create_message(params).await.and_then(|result| {
+                    serde_json::to_value(result).map_err(|e| ErrorData {
+                        code: mcp_core::protocol::INTERNAL_ERROR,
+                        message: e.to_string(),
+                        data: None,
+                    })
+                }),
+                Err(e) => Err(ErrorData {
+                    code: INVALID_PARAMS,
+                    message: format!("Invalid sampling parameters: {}", e),
+                    data: None,
+                }),
+            }
+        }
+        (method, _) => Err(ErrorData {
+            code: METHOD_NOT_FOUND,
+            message: format!("Method not found: {}", method),
+            data: None,
+        }),
+    };
+
+    match result {
+        Ok(result) => JsonRpcMessage::Response(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: Some(result),
+            error: None,
+        }),
+        Err(error) => JsonRpcMessage::Error(JsonRpcError {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            error,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::{Content, Role};
+
+    struct EchoSampler;
+
+    #[async_trait::async_trait]
+    impl SamplingHandler for EchoSampler {
+        async fn create_message(
+            &self,
+            params: CreateMessageParams,
+        ) -> Result<CreateMessageResult, ErrorData> {
+            Ok(CreateMessageResult {
+                role: Role::Assistant,
+                content: params.messages[0].content.clone(),
+                model: "echo".to_string(),
+                stop_reason: Some("endTurn".to_string()),
+            })
+        }
+    }
+
+    fn sampling_request() -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(7),
+            method: "sampling/createMessage".to_string(),
+            params: Some(json!({
+                "messages": [{"role": "user", "content": {"type": "text", "text": "hello"}}],
+                "maxTokens": 10
+            })),
+        }
+    }
+
+    /// A transport whose server is already gone
+    #[derive(Clone)]
+    struct ClosedTransport;
+
+    #[async_trait::async_trait]
+    impl TransportHandle for ClosedTransport {
+        async fn send(&self, _message: JsonRpcMessage) -> Result<(), crate::transport::Error> {
+            Err(crate::transport::Error::ChannelClosed)
+        }
+
+        async fn receive(&self) -> Result<JsonRpcMessage, crate::transport::Error> {
+            Err(crate::transport::Error::ChannelClosed)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_reports_closed_transport() {
+        let client = McpClient::connect(ClosedTransport, std::time::Duration::from_secs(1))
+            .await
+            .unwrap();
+
+        // The receive loop notices the closed transport on its first read
+        for _ in 0..100 {
+            if client.is_closed() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert!(client.is_closed());
+    }
+
+    #[test]
+    fn test_cancelled_notification() {
+        match cancelled_notification(4, "Request timed out") {
+            JsonRpcMessage::Notification(notification) => {
+                assert_eq!(notification.method, "notifications/cancelled");
+                assert_eq!(
+                    notification.params.unwrap(),
+                    json!({"requestId": 4, "reason": "Request timed out"})
+                );
+            }
+            other => panic!("Expected a notification, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sampling_capability_serialization() {
+        let empty = serde_json::to_value(ClientCapabilities::default()).unwrap();
+        assert_eq!(empty, json!({}));
+
+        let sampling = serde_json::to_value(ClientCapabilities {
+            sampling: Some(SamplingCapability {}),
+        })
+        .unwrap();
+        assert_eq!(sampling, json!({"sampling": {}}));
+    }
+
+    #[tokio::test]
+    async fn test_sampling_request_uses_handler() {
+        let handler: Arc<dyn SamplingHandler> = Arc::new(EchoSampler);
+        match handle_server_request(Some(handler), sampling_request()).await {
+            JsonRpcMessage::Response(response) => {
+                assert_eq!(response.id, Some(7));
+                let result: CreateMessageResult =
+                    serde_json::from_value(response.result.unwrap()).unwrap();
+                assert_eq!(result.content, Content::text("hello"));
+                assert_eq!(result.model, "echo");
+            }
+            other => panic!("Expected a response, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sampling_request_without_handler_is_rejected() {
+        match handle_server_request(None, sampling_request()).await {
+            JsonRpcMessage::Error(error) => {
+                assert_eq!(error.id, Some(7));
+                assert_eq!(error.error.code, METHOD_NOT_FOUND);
+            }
+            other => panic!("Expected an error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_invalid_sampling_params() {
+        let handler: Arc<dyn SamplingHandler> = Arc::new(EchoSampler);
+        let mut request = sampling_request();
+        request.params = Some(json!({"messages": "nope"}));
+        match handle_server_request(Some(handler), request).await {
+            JsonRpcMessage::Error(error) => assert_eq!(error.error.code, INVALID_PARAMS),
+            other => panic!("Expected an error, got {:?}", other),
+        }
+    }
 }
@@ -28,6 +28,9 @@ pub enum Error {
     #[error("SSE connection error: {0}")]
     SseConnection(String),
 
+    #[error("Streamable HTTP error: {0}")]
+    StreamableHttpError(String),
+
     #[error("HTTP error: {status} - {message}")]
     HttpError { status: u16, message: String },
 }
@@ -78,3 +81,6 @@ pub use stdio::StdioTransport;
 
 pub mod sse;
 pub use sse::SseTransport;
+
+pub mod streamable_http;
+pub use streamable_http::StreamableHttpTransport;
@@ -0,0 +1,839 @@
+use crate::transport::Error;
+use async_trait::async_trait;
+use futures::StreamExt;
+use mcp_core::protocol::{ErrorData, JsonRpcError, JsonRpcMessage, INTERNAL_ERROR};
+use reqwest::{Client as HttpClient, Method, RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::time::Duration;
+use tracing::{debug, warn};
+
+use super::{serialize_and_send, Transport, TransportHandle};
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+const EVENT_STREAM_MIME_TYPE: &str = "text/event-stream";
+const JSON_MIME_TYPE: &str = "application/json";
+
+// How many times a dropped event stream is resumed before giving up
+const MAX_RESUME_ATTEMPTS: u32 = 3;
+const RESUME_DELAY_MS: u64 = 500;
+
+/// A single event read from a `text/event-stream` body
+#[derive(Debug, Default, Clone, PartialEq)]
+struct SseEvent {
+    id: Option<String>,
+    event: Option<String>,
+    data: String,
+}
+
+/// Incremental parser for `text/event-stream` bodies, fed with raw chunks as they arrive
+#[derive(Debug, Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    current: SseEvent,
+    has_fields: bool,
+}
+
+impl SseParser {
+    /// Feed a chunk of the body and return any events it completed
+    fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(|c| c == '\n' || c == '\r');
+
+            // A blank line dispatches the event built so far
+            if line.is_empty() {
+                if self.has_fields {
+                    events.push(std::mem::take(&mut self.current));
+                    self.has_fields = false;
+                }
+                continue;
+            }
+
+            // Lines starting with a colon are comments, used by servers as keep-alives
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+
+            match field {
+                "id" => self.current.id = Some(value.to_string()),
+                "event" => self.current.event = Some(value.to_string()),
+                "data" => {
+                    if !self.current.data.is_empty() {
+                        self.current.data.push('\n');
+                    }
+                    self.current.data.push_str(value);
+                }
+                _ => continue,
+            }
+            self.has_fields = true;
+        }
+
+        events
+    }
+}
+
+/// State shared by the actor and the tasks it spawns to read event streams
+#[derive(Clone)]
+struct StreamableHttpConnection {
+    /// Sends messages (responses, server requests and notifications) back to the handle
+    sender: mpsc::Sender<JsonRpcMessage>,
+    /// The single MCP endpoint used for POST, GET and DELETE
+    mcp_server_url: String,
+    http_client: HttpClient,
+    /// Extra headers sent with every request, e.g. for authorization
+    headers: HashMap<String, String>,
+    /// The session id assigned by the server during initialization
+    session_id: Arc<RwLock<Option<String>>>,
+    /// Whether the standalone GET event stream has been started
+    listening: Arc<AtomicBool>,
+}
+
+impl StreamableHttpConnection {
+    async fn request(&self, method: Method) -> RequestBuilder {
+        let mut request = self.http_client.request(method, &self.mcp_server_url);
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
+        }
+        if let Some(session_id) = self.session_id.read().await.as_ref() {
+            request = request.header(SESSION_ID_HEADER, session_id);
+        }
+        request
+    }
+
+    /// POST a message to the server and forward whatever it sends back
+    async fn post_message(&self, message_str: String) -> Result<(), Error> {
+        let response = self
+            .request(Method::POST)
+            .await
+            .header("Content-Type", JSON_MIME_TYPE)
+            .header(
+                "Accept",
+                format!("{}, {}", JSON_MIME_TYPE, EVENT_STREAM_MIME_TYPE),
+            )
+            .body(message_str)
+            .send()
+            .await
+            .map_err(|e| Error::StreamableHttpError(e.to_string()))?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            let mut current = self.session_id.write().await;
+            if current.as_deref() != Some(session_id) {
+                debug!("Streamable HTTP session id: {}", session_id);
+                *current = Some(session_id.to_string());
+            }
+        }
+
+        let status = response.status();
+        if status == StatusCode::ACCEPTED {
+            // Notifications and responses are acknowledged without a body. The first one
+            // is our `initialized` notification, after which the server may start sending
+            // messages on its own, whether or not it issued a session id
+            if !self.listening.swap(true, Ordering::SeqCst) {
+                tokio::spawn(self.clone().listen_for_server_messages());
+            }
+            return Ok(());
+        }
+
+        if status == StatusCode::NOT_FOUND && self.session_id.read().await.is_some() {
+            // The server no longer knows our session, the client has to initialize again
+            *self.session_id.write().await = None;
+            return Err(Error::HttpError {
+                status: status.as_u16(),
+                message: "MCP session expired, the extension needs to be restarted".to_string(),
+            });
+        }
+
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(Error::HttpError {
+                status: status.as_u16(),
+                message: if message.is_empty() {
+                    status.to_string()
+                } else {
+                    message
+                },
+            });
+        }
+
+        let content_type = response
+            .headers()
+            .get("Content-Type")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        if content_type.starts_with(EVENT_STREAM_MIME_TYPE) {
+            // Read the stream in the background so that later messages, such as our
+            // replies to requests the server makes mid-stream, are not blocked on it
+            tokio::spawn(self.clone().consume_event_stream(response));
+            return Ok(());
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| Error::StreamableHttpError(e.to_string()))?;
+        if body.trim().is_empty() {
+            return Ok(());
+        }
+
+        let messages = if body.trim_start().starts_with('[') {
+            serde_json::from_str::<Vec<JsonRpcMessage>>(&body)?
+        } else {
+            vec![serde_json::from_str::<JsonRpcMessage>(&body)?]
+        };
+        for message in messages {
+            self.sender
+                .send(message)
+                .await
+                .map_err(|_| Error::ChannelClosed)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read events from a stream, returning how many messages were forwarded
+    async fn read_event_stream(
+        &self,
+        response: Response,
+        last_event_id: &mut Option<String>,
+    ) -> Result<usize, Error> {
+        let mut parser = SseParser::default();
+        let mut stream = response.bytes_stream();
+        let mut forwarded = 0;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| Error::StreamableHttpError(e.to_string()))?;
+            for event in parser.feed(&chunk) {
+                if event.id.is_some() {
+                    *last_event_id = event.id.clone();
+                }
+                if event.data.is_empty() || event.event.as_deref().is_some_and(|e| e != "message") {
+                    continue;
+                }
+                match serde_json::from_str::<JsonRpcMessage>(&event.data) {
+                    Ok(message) => {
+                        self.sender
+                            .send(message)
+                            .await
+                            .map_err(|_| Error::ChannelClosed)?;
+                        forwarded += 1;
+                    }
+                    Err(err) => warn!("Failed to parse streamable HTTP message: {err}"),
+                }
+            }
+        }
+
+        Ok(forwarded)
+    }
+
+    /// Open a GET event stream, resuming after `last_event_id` when given
+    async fn open_event_stream(&self, last_event_id: Option<&str>) -> Result<Response, Error> {
+        let mut request = self
+            .request(Method::GET)
+            .await
+            .header("Accept", EVENT_STREAM_MIME_TYPE);
+        if let Some(last_event_id) = last_event_id {
+            request = request.header(LAST_EVENT_ID_HEADER, last_event_id);
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| Error::StreamableHttpError(e.to_string()))
+    }
+
+    /// Consume the event stream returned for a POST, resuming it if the connection drops
+    async fn consume_event_stream(self, response: Response) {
+        let mut last_event_id = None;
+        let mut result = self.read_event_stream(response, &mut last_event_id).await;
+        let mut attempts = 0;
+
+        while let Err(e) = result {
+            if matches!(e, Error::ChannelClosed) {
+                return;
+            }
+            let Some(resume_from) = last_event_id.clone() else {
+                warn!("Streamable HTTP event stream dropped before it could be resumed: {e}");
+                return;
+            };
+            if attempts >= MAX_RESUME_ATTEMPTS {
+                warn!("Giving up resuming streamable HTTP event stream: {e}");
+                return;
+            }
+            attempts += 1;
+            tokio::time::sleep(Duration::from_millis(RESUME_DELAY_MS * attempts as u64)).await;
+
+            debug!(
+                "Resuming streamable HTTP event stream after {}",
+                resume_from
+            );
+            result = match self.open_event_stream(Some(&resume_from)).await {
+                Ok(response) if response.status().is_success() => {
+                    self.read_event_stream(response, &mut last_event_id).await
+                }
+                Ok(response) => Err(Error::HttpError {
+                    status: response.status().as_u16(),
+                    message: response.status().to_string(),
+                }),
+                Err(e) => Err(e),
+            };
+        }
+    }
+
+    /// Keep a GET event stream open for requests and notifications the server sends on its own
+    async fn listen_for_server_messages(self) {
+        let mut last_event_id: Option<String> = None;
+        let mut attempts = 0;
+
+        while !self.sender.is_closed() && attempts < MAX_RESUME_ATTEMPTS {
+            let response = match self.open_event_stream(last_event_id.as_deref()).await {
+                Ok(response) => response,
+                Err(e) => {
+                    warn!("Failed to open streamable HTTP event stream: {e}");
+                    attempts += 1;
+                    tokio::time::sleep(Duration::from_millis(RESUME_DELAY_MS * attempts as u64))
+                        .await;
+                    continue;
+                }
+            };
+
+            if response.status() == StatusCode::METHOD_NOT_ALLOWED {
+                debug!("Server does not offer a standalone streamable HTTP event stream");
+                return;
+            }
+            if !response.status().is_success() {
+                debug!(
+                    "Server rejected the standalone streamable HTTP event stream: {}",
+                    response.status()
+                );
+                return;
+            }
+
+            // Only failures count towards giving up: servers and proxies routinely close
+            // streams that have been idle, and the next one is opened as if nothing happened
+            attempts = 0;
+            match self.read_event_stream(response, &mut last_event_id).await {
+                Ok(_) => {}
+                Err(Error::ChannelClosed) => return,
+                Err(e) => {
+                    debug!("Streamable HTTP event stream dropped: {e}");
+                    attempts += 1;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(
+                RESUME_DELAY_MS * attempts.max(1) as u64,
+            ))
+            .await;
+        }
+    }
+}
+
+/// The streamable HTTP actor POSTs each outgoing message to the MCP endpoint and
+/// forwards the JSON or event-stream responses back to the handle.
+pub struct StreamableHttpActor {
+    /// Receives messages (requests/notifications/responses) from the handle
+    receiver: mpsc::Receiver<String>,
+    connection: StreamableHttpConnection,
+}
+
+impl StreamableHttpActor {
+    pub fn new(
+        receiver: mpsc::Receiver<String>,
+        sender: mpsc::Sender<JsonRpcMessage>,
+        mcp_server_url: String,
+        headers: HashMap<String, String>,
+        session_id: Arc<RwLock<Option<String>>>,
+    ) -> Self {
+        Self {
+            receiver,
+            connection: StreamableHttpConnection {
+                sender,
+                mcp_server_url,
+                http_client: HttpClient::new(),
+                headers,
+                session_id,
+                listening: Arc::new(AtomicBool::new(false)),
+            },
+        }
+    }
+
+    pub async fn run(mut self) {
+        while let Some(message_str) = self.receiver.recv().await {
+            // POST on a separate task so a slow response doesn't hold back the messages
+            // queued after it, such as cancellations, pings or replies to the server
+            tokio::spawn(Self::send_message(self.connection.clone(), message_str));
+        }
+
+        // Every handle is gone, so the session won't be used again
+        let connection = &self.connection;
+        if let Err(e) = end_session(
+            &connection.http_client,
+            &connection.mcp_server_url,
+            &connection.headers,
+            &connection.session_id,
+        )
+        .await
+        {
+            warn!("Failed to end the streamable HTTP session: {e}");
+        }
+
+        tracing::info!("StreamableHttpActor shut down.");
+    }
+
+    async fn send_message(connection: StreamableHttpConnection, message_str: String) {
+        // Remember the request id so a failed POST can still be answered
+        let request_id = match serde_json::from_str::<JsonRpcMessage>(&message_str) {
+            Ok(JsonRpcMessage::Request(request)) => request.id,
+            _ => None,
+        };
+
+        if let Err(e) = connection.post_message(message_str).await {
+            warn!("Streamable HTTP request failed: {e}");
+            if request_id.is_some() {
+                let error = JsonRpcMessage::Error(JsonRpcError {
+                    jsonrpc: "2.0".to_string(),
+                    id: request_id,
+                    error: ErrorData {
+                        code: INTERNAL_ERROR,
+                        message: e.to_string(),
+                        data: None,
+                    },
+                });
+                // The handle may already be gone, in which case nobody is waiting
+                let _ = connection.sender.send(error).await;
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StreamableHttpTransportHandle {
+    sender: mpsc::Sender<String>,
+    receiver: Arc<Mutex<mpsc::Receiver<JsonRpcMessage>>>,
+}
+
+#[async_trait::async_trait]
+impl TransportHandle for StreamableHttpTransportHandle {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), Error> {
+        serialize_and_send(&self.sender, message).await
+    }
+
+    async fn receive(&self) -> Result<JsonRpcMessage, Error> {
+        let mut receiver = self.receiver.lock().await;
+        receiver.recv().await.ok_or(Error::ChannelClosed)
+    }
+}
+
+/// Transport for the single-endpoint Streamable HTTP protocol from the 2025-03-26 MCP spec
+#[derive(Clone)]
+pub struct StreamableHttpTransport {
+    mcp_server_url: String,
+    env: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    session_id: Arc<RwLock<Option<String>>>,
+}
+
+impl StreamableHttpTransport {
+    pub fn new<S: Into<String>>(mcp_server_url: S, env: HashMap<String, String>) -> Self {
+        Self {
+            mcp_server_url: mcp_server_url.into(),
+            env,
+            headers: HashMap::new(),
+            session_id: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Set headers sent with every request, e.g. `Authorization`
+    pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// The session id assigned by the server, once initialized
+    pub async fn session_id(&self) -> Option<String> {
+        self.session_id.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl Transport for StreamableHttpTransport {
+    type Handle = StreamableHttpTransportHandle;
+
+    async fn start(&self) -> Result<Self::Handle, Error> {
+        // Set environment variables
+        for (key, value) in &self.env {
+            std::env::set_var(key, value);
+        }
+
+        let (tx, rx) = mpsc::channel(32);
+        let (otx, orx) = mpsc::channel(32);
+
+        let actor = StreamableHttpActor::new(
+            rx,
+            otx,
+            self.mcp_server_url.clone(),
+            self.headers.clone(),
+            Arc::clone(&self.session_id),
+        );
+        tokio::spawn(actor.run());
+
+        Ok(StreamableHttpTransportHandle {
+            sender: tx,
+            receiver: Arc::new(Mutex::new(orx)),
+        })
+    }
+
+    async fn close(&self) -> Result<(), Error> {
+        end_session(
+            &HttpClient::new(),
+            &self.mcp_server_url,
+            &self.headers,
+            &self.session_id,
+        )
+        .await
+    }
+}
+
+/// Tell the server we are done with the session, if one was established. The session id is
+/// cleared, so a session is only ended once.
+async fn end_session(
+    http_client: &HttpClient,
+    mcp_server_url: &str,
+    headers: &HashMap<String, String>,
+    session_id: &RwLock<Option<String>>,
+) -> Result<(), Error> {
+    let Some(session_id) = session_id.write().await.take() else {
+        return Ok(());
+    };
+
+    let mut request = http_client
+        .delete(mcp_server_url)
+        .header(SESSION_ID_HEADER, session_id);
+    for (key, value) in headers {
+        request = request.header(key, value);
+    }
+
+    match request.send().await {
+        // Servers that do not allow clients to end sessions answer 405
+        Ok(response)
+            if response.status().is_success()
+                || response.status() == StatusCode::METHOD_NOT_ALLOWED =>
+        {
+            Ok(())
+        }
+        Ok(response) => Err(Error::HttpError {
+            status: response.status().as_u16(),
+            message: response.status().to_string(),
+        }),
+        Err(e) => Err(Error::StreamableHttpError(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::State;
+    use axum::http::HeaderMap;
+    use axum::response::sse::{Event, Sse};
+    use axum::response::{IntoResponse, Response as AxumResponse};
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use serde_json::{json, Value};
+    use std::convert::Infallible;
+    use std::sync::atomic::AtomicUsize;
+    use tokio::sync::Notify;
+
+    /// A minimal streamable HTTP server that never issues a session id. A `slow` request is
+    /// only answered once a `ping` arrives, and the GET stream sends one log notification.
+    async fn start_server() -> String {
+        async fn handle_post(
+            State(pinged): State<Arc<Notify>>,
+            Json(body): Json<Value>,
+        ) -> AxumResponse {
+            let Some(id) = body.get("id").cloned() else {
+                return StatusCode::ACCEPTED.into_response();
+            };
+            match body["method"].as_str() {
+                Some("slow") => pinged.notified().await,
+                Some("ping") => pinged.notify_one(),
+                _ => {}
+            }
+            Json(json!({"jsonrpc": "2.0", "id": id, "result": {}})).into_response()
+        }
+
+        async fn handle_get(headers: HeaderMap) -> AxumResponse {
+            assert!(headers.get(SESSION_ID_HEADER).is_none());
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/message",
+                "params": {"level": "info", "data": "hello"}
+            });
+            let event = Event::default()
+                .event("message")
+                .data(notification.to_string());
+            let stream = futures::stream::once(async move { Ok::<_, Infallible>(event) })
+                .chain(futures::stream::pending());
+            Sse::new(stream).into_response()
+        }
+
+        let app = Router::new()
+            .route("/mcp", post(handle_post).get(handle_get))
+            .with_state(Arc::new(Notify::new()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}/mcp", addr)
+    }
+
+    async fn start_transport() -> StreamableHttpTransportHandle {
+        let url = start_server().await;
+        StreamableHttpTransport::new(url, HashMap::new())
+            .start()
+            .await
+            .unwrap()
+    }
+
+    fn message(value: Value) -> JsonRpcMessage {
+        serde_json::from_value(value).unwrap()
+    }
+
+    async fn receive(handle: &StreamableHttpTransportHandle) -> JsonRpcMessage {
+        tokio::time::timeout(Duration::from_secs(5), handle.receive())
+            .await
+            .expect("timed out waiting for a message from the server")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_slow_response_does_not_block_later_requests() {
+        let handle = start_transport().await;
+        handle
+            .send(message(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "slow"}),
+            ))
+            .await
+            .unwrap();
+        handle
+            .send(message(
+                json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}),
+            ))
+            .await
+            .unwrap();
+
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            match receive(&handle).await {
+                JsonRpcMessage::Response(response) => ids.push(response.id),
+                other => panic!("Expected a response, got {:?}", other),
+            }
+        }
+        // With serial POSTs the slow request would wait forever for the ping behind it
+        ids.sort();
+        assert_eq!(ids, vec![Some(1), Some(2)]);
+    }
+
+    #[tokio::test]
+    async fn test_server_messages_arrive_without_a_session_id() {
+        let handle = start_transport().await;
+        handle
+            .send(message(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}),
+            ))
+            .await
+            .unwrap();
+        assert!(matches!(
+            receive(&handle).await,
+            JsonRpcMessage::Response(_)
+        ));
+
+        handle
+            .send(message(
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            ))
+            .await
+            .unwrap();
+        match receive(&handle).await {
+            JsonRpcMessage::Notification(notification) => {
+                assert_eq!(notification.method, "notifications/message");
+            }
+            other => panic!("Expected a notification, got {:?}", other),
+        }
+    }
+
+    /// A server that issues a session id, closes its GET stream without events more times than
+    /// a dropped stream is resumed before sending a notification, and reports session DELETEs
+    async fn start_session_server() -> (String, mpsc::UnboundedReceiver<Option<String>>) {
+        struct SessionServer {
+            streams_opened: AtomicUsize,
+            deleted: mpsc::UnboundedSender<Option<String>>,
+        }
+
+        async fn handle_post(Json(body): Json<Value>) -> AxumResponse {
+            let Some(id) = body.get("id").cloned() else {
+                return StatusCode::ACCEPTED.into_response();
+            };
+            let mut response =
+                Json(json!({"jsonrpc": "2.0", "id": id, "result": {}})).into_response();
+            response
+                .headers_mut()
+                .insert(SESSION_ID_HEADER, "session-1".parse().unwrap());
+            response
+        }
+
+        async fn handle_get(State(server): State<Arc<SessionServer>>) -> AxumResponse {
+            let opened = server.streams_opened.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut events = Vec::new();
+            if opened > MAX_RESUME_ATTEMPTS as usize + 1 {
+                let notification = json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/message",
+                    "params": {"level": "info", "data": "still listening"}
+                });
+                events.push(Ok::<_, Infallible>(
+                    Event::default()
+                        .event("message")
+                        .data(notification.to_string()),
+                ));
+            }
+            Sse::new(futures::stream::iter(events)).into_response()
+        }
+
+        async fn handle_delete(
+            State(server): State<Arc<SessionServer>>,
+            headers: HeaderMap,
+        ) -> StatusCode {
+            let session_id = headers
+                .get(SESSION_ID_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let _ = server.deleted.send(session_id);
+            StatusCode::OK
+        }
+
+        let (deleted, deletes) = mpsc::unbounded_channel();
+        let server = Arc::new(SessionServer {
+            streams_opened: AtomicUsize::new(0),
+            deleted,
+        });
+        let app = Router::new()
+            .route(
+                "/mcp",
+                post(handle_post).get(handle_get).delete(handle_delete),
+            )
+            .with_state(server);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}/mcp", addr), deletes)
+    }
+
+    async fn initialize(handle: &StreamableHttpTransportHandle) {
+        handle
+            .send(message(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}),
+            ))
+            .await
+            .unwrap();
+        assert!(matches!(receive(handle).await, JsonRpcMessage::Response(_)));
+        handle
+            .send(message(
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            ))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_idle_event_streams_are_reopened() {
+        let (url, _deletes) = start_session_server().await;
+        let handle = StreamableHttpTransport::new(url, HashMap::new())
+            .start()
+            .await
+            .unwrap();
+        initialize(&handle).await;
+
+        match receive(&handle).await {
+            JsonRpcMessage::Notification(notification) => {
+                assert_eq!(notification.method, "notifications/message");
+            }
+            other => panic!("Expected a notification, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_ends_when_the_transport_is_dropped() {
+        let (url, mut deletes) = start_session_server().await;
+        let handle = StreamableHttpTransport::new(url, HashMap::new())
+            .start()
+            .await
+            .unwrap();
+        initialize(&handle).await;
+        drop(handle);
+
+        let session_id = tokio::time::timeout(Duration::from_secs(5), deletes.recv())
+            .await
+            .expect("timed out waiting for the session to end")
+            .unwrap();
+        assert_eq!(session_id.as_deref(), Some("session-1"));
+    }
+
+    #[test]
+    fn test_sse_parser_single_event() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b"id: 1\nevent: message\ndata: {\"a\":1}\n\n");
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                id: Some("1".to_string()),
+                event: Some("message".to_string()),
+                data: "{\"a\":1}".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_sse_parser_split_chunks_and_multiline_data() {
+        let mut parser = SseParser::default();
+        assert!(parser.feed(b"data: first\r\nda").is_empty());
+        assert!(parser.feed(b"ta: second\r\n").is_empty());
+        let events = parser.feed(b"\r\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "first\nsecond");
+        assert_eq!(events[0].id, None);
+    }
+
+    #[test]
+    fn test_sse_parser_ignores_comments_and_unknown_fields() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b": keep-alive\n\nretry: 1000\ndata: x\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "x");
+    }
+
+    #[test]
+    fn test_sse_parser_multibyte_split() {
+        let mut parser = SseParser::default();
+        let bytes = "data: 🦆\n\n".as_bytes();
+        let (first, second) = bytes.split_at(8);
+        assert!(parser.feed(first).is_empty());
+        let events = parser.feed(second);
+        assert_eq!(events[0].data, "🦆");
+    }
+}
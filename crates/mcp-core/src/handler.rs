@@ -7,6 +7,8 @@ use serde_json::Value;
 use thiserror::Error;
 use utoipa::ToSchema;
 
+use crate::resource::ResourceTemplate;
+
 #[non_exhaustive]
 #[derive(Error, Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub enum ToolError {
@@ -71,13 +73,13 @@ pub trait ToolHandler: Send + Sync + 'static {
 /// Trait for implementing MCP resources
 #[async_trait]
 pub trait ResourceTemplateHandler: Send + Sync + 'static {
-    /// The URL template for this resource
-    fn template() -> &'static str;
+    /// The URL template for this resource, as listed to clients
+    fn template(&self) -> ResourceTemplate;
 
     /// JSON schema describing the resource parameters
-    fn schema() -> Value;
+    fn schema(&self) -> Value;
 
-    /// Get the resource value
+    /// Get the resource value, `params` holds the template variables of the URI read
     async fn get(&self, params: Value) -> ToolResult<String>;
 }
 
@@ -6,7 +6,7 @@ pub use role::Role;
 pub mod tool;
 pub use tool::{Tool, ToolCall};
 pub mod resource;
-pub use resource::{Resource, ResourceContents};
+pub use resource::{Resource, ResourceContents, ResourceTemplate};
 pub mod protocol;
 pub use handler::{ToolError, ToolResult};
 pub mod prompt;
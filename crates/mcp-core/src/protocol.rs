@@ -4,6 +4,8 @@ use crate::{
     prompt::{Prompt, PromptMessage},
     resource::Resource,
     resource::ResourceContents,
+    resource::ResourceTemplate,
+    role::Role,
     tool::Tool,
 };
 use serde::{Deserialize, Serialize};
@@ -201,6 +203,14 @@ pub struct ListResourcesResult {
     pub next_cursor: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourceTemplatesResult {
+    pub resource_templates: Vec<ResourceTemplate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ReadResourceResult {
     pub contents: Vec<ResourceContents>,
@@ -237,6 +247,66 @@ pub struct GetPromptResult {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EmptyResult {}
 
+/// A message in a `sampling/createMessage` request or result
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SamplingMessage {
+    pub role: Role,
+    pub content: Content,
+}
+
+/// A hint the server gives about which model it would like the client to use
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ModelHint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// The server's preferences for model selection, each priority in the range 0..=1
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPreferences {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hints: Option<Vec<ModelHint>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_priority: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_priority: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intelligence_priority: Option<f64>,
+}
+
+/// Parameters of a `sampling/createMessage` request sent from a server to the client
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageParams {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<ModelPreferences>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    /// One of "none", "thisServer" or "allServers"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// The client's answer to a `sampling/createMessage` request
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: Role,
+    pub content: Content,
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -286,4 +356,48 @@ mod tests {
             _ => panic!("Expected Request"),
         }
     }
+
+    #[test]
+    fn test_create_message_params_deserialization() {
+        let params: CreateMessageParams = serde_json::from_value(json!({
+            "messages": [
+                {"role": "user", "content": {"type": "text", "text": "Summarize this"}}
+            ],
+            "systemPrompt": "Be brief",
+            "maxTokens": 100,
+            "modelPreferences": {"hints": [{"name": "claude"}], "speedPriority": 0.5}
+        }))
+        .unwrap();
+
+        assert_eq!(params.messages.len(), 1);
+        assert_eq!(params.messages[0].role, Role::User);
+        assert_eq!(params.system_prompt.as_deref(), Some("Be brief"));
+        assert_eq!(params.max_tokens, 100);
+        let preferences = params.model_preferences.unwrap();
+        assert_eq!(preferences.speed_priority, Some(0.5));
+        assert_eq!(
+            preferences.hints.unwrap()[0].name.as_deref(),
+            Some("claude")
+        );
+    }
+
+    #[test]
+    fn test_create_message_result_serialization() {
+        let result = CreateMessageResult {
+            role: Role::Assistant,
+            content: Content::text("Done"),
+            model: "gpt-4o".to_string(),
+            stop_reason: Some("endTurn".to_string()),
+        };
+
+        assert_eq!(
+            serde_json::to_value(result).unwrap(),
+            json!({
+                "role": "assistant",
+                "content": {"type": "text", "text": "Done"},
+                "model": "gpt-4o",
+                "stopReason": "endTurn"
+            })
+        );
+    }
 }
@@ -3,6 +3,7 @@ use crate::content::Annotations;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use url::Url;
 use utoipa::ToSchema;
 
@@ -43,6 +44,124 @@ pub enum ResourceContents {
     },
 }
 
+/// A parameterized resource, whose URIs are built from an RFC 6570 style template
+/// such as "file:///logs/{date}.log"
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    /// URI template with `{name}` placeholders, `{+name}` placeholders may also match '/'
+    pub uri_template: String,
+    /// Name of the kind of resource this template produces
+    pub name: String,
+    /// Optional description of the template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// MIME type of every resource matching the template, if they share one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<Annotations>,
+}
+
+enum TemplatePart<'a> {
+    Literal(&'a str),
+    Variable { name: &'a str, reserved: bool },
+}
+
+impl ResourceTemplate {
+    pub fn new<S: Into<String>, N: Into<String>>(uri_template: S, name: N) -> Self {
+        Self {
+            uri_template: uri_template.into(),
+            name: name.into(),
+            description: None,
+            mime_type: None,
+            annotations: None,
+        }
+    }
+
+    /// Sets the description of the template
+    pub fn with_description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the MIME type of resources matching the template
+    pub fn with_mime_type<S: Into<String>>(mut self, mime_type: S) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    fn parts(&self) -> Vec<TemplatePart<'_>> {
+        let mut parts = Vec::new();
+        let mut rest = self.uri_template.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            if start > 0 {
+                parts.push(TemplatePart::Literal(&rest[..start]));
+            }
+            let expression = &rest[start + 1..start + end];
+            parts.push(match expression.strip_prefix('+') {
+                Some(name) => TemplatePart::Variable {
+                    name,
+                    reserved: true,
+                },
+                None => TemplatePart::Variable {
+                    name: expression,
+                    reserved: false,
+                },
+            });
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(TemplatePart::Literal(rest));
+        }
+        parts
+    }
+
+    /// Extracts the template variables from a URI, or None if the URI doesn't match
+    pub fn match_uri(&self, uri: &str) -> Option<HashMap<String, String>> {
+        let parts = self.parts();
+        let mut values = HashMap::new();
+        let mut rest = uri;
+        for (i, part) in parts.iter().enumerate() {
+            match part {
+                TemplatePart::Literal(literal) => {
+                    rest = rest.strip_prefix(literal)?;
+                }
+                TemplatePart::Variable { name, reserved } => {
+                    // A value runs until the next literal, or to the end of the URI
+                    let end = match parts.get(i + 1) {
+                        Some(TemplatePart::Literal(next)) => rest.find(next)?,
+                        _ => rest.len(),
+                    };
+                    let value = &rest[..end];
+                    if value.is_empty() || (!reserved && value.contains('/')) {
+                        return None;
+                    }
+                    values.insert(name.to_string(), value.to_string());
+                    rest = &rest[end..];
+                }
+            }
+        }
+        rest.is_empty().then_some(values)
+    }
+
+    /// Builds a URI by substituting the given values, missing values expand to nothing
+    pub fn expand(&self, values: &HashMap<String, String>) -> String {
+        self.parts()
+            .into_iter()
+            .map(|part| match part {
+                TemplatePart::Literal(literal) => literal.to_string(),
+                TemplatePart::Variable { name, .. } => {
+                    values.get(name).cloned().unwrap_or_default()
+                }
+            })
+            .collect()
+    }
+}
+
 fn default_mime_type() -> String {
     "text".to_string()
 }
@@ -252,6 +371,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_resource_template_match_uri() {
+        let template = ResourceTemplate::new("file:///logs/{date}.log", "Daily log");
+        let values = template.match_uri("file:///logs/2025-01-01.log").unwrap();
+        assert_eq!(values.get("date").map(String::as_str), Some("2025-01-01"));
+
+        assert!(template.match_uri("file:///logs/2025/01.log").is_none());
+        assert!(template.match_uri("file:///logs/.log").is_none());
+        assert!(template.match_uri("file:///other/2025-01-01.log").is_none());
+        assert!(template
+            .match_uri("file:///logs/2025-01-01.log.bak")
+            .is_none());
+    }
+
+    #[test]
+    fn test_resource_template_reserved_and_expand() {
+        let template = ResourceTemplate::new("repo://{owner}/{name}/tree/{+path}", "Repo file");
+        let values = template
+            .match_uri("repo://block/goose/tree/crates/mcp-core/src/lib.rs")
+            .unwrap();
+        assert_eq!(values.get("owner").map(String::as_str), Some("block"));
+        assert_eq!(values.get("name").map(String::as_str), Some("goose"));
+        assert_eq!(
+            values.get("path").map(String::as_str),
+            Some("crates/mcp-core/src/lib.rs")
+        );
+        assert_eq!(
+            template.expand(&values),
+            "repo://block/goose/tree/crates/mcp-core/src/lib.rs"
+        );
+    }
+
+    #[test]
+    fn test_resource_template_serialization() {
+        let template = ResourceTemplate::new("file:///logs/{date}.log", "Daily log")
+            .with_mime_type("text/plain");
+        assert_eq!(
+            serde_json::to_value(&template).unwrap(),
+            serde_json::json!({
+                "uriTemplate": "file:///logs/{date}.log",
+                "name": "Daily log",
+                "mimeType": "text/plain"
+            })
+        );
+    }
+
     #[test]
     fn test_invalid_uri() {
         let result = Resource::new("not-a-uri", None, None);
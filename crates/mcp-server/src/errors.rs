@@ -102,3 +102,21 @@ impl From<mcp_core::handler::ResourceError> for RouterError {
         }
     }
 }
+
+#[derive(Error, Debug)]
+pub enum PeerError {
+    #[error("Client does not support {0}")]
+    Unsupported(String),
+
+    #[error("Connection to the client is closed")]
+    ChannelClosed,
+
+    #[error("RPC error: code={code}, message={message}")]
+    RpcError { code: i32, message: String },
+
+    #[error("JSON serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Unexpected response from client: {0}")]
+    UnexpectedResponse(String),
+}
@@ -1,10 +1,13 @@
 use std::{
+    collections::VecDeque,
     pin::Pin,
     task::{Context, Poll},
 };
 
 use futures::{Future, Stream};
-use mcp_core::protocol::{JsonRpcError, JsonRpcMessage, JsonRpcResponse};
+use mcp_core::protocol::{
+    JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse,
+};
 use pin_project::pin_project;
 use router::McpRequest;
 use tokio::{
@@ -14,7 +17,10 @@ use tokio::{
 use tower_service::Service;
 
 mod errors;
-pub use errors::{BoxError, RouterError, ServerError, TransportError};
+pub use errors::{BoxError, PeerError, RouterError, ServerError, TransportError};
+
+pub mod peer;
+pub use peer::Peer;
 
 pub mod router;
 pub use router::Router;
@@ -144,124 +150,159 @@ where
         use futures::StreamExt;
         let mut service = self.service;
 
+        // Messages sent to the client outside of a response: notifications from tools
+        // and requests issued through the peer
+        let (outgoing_tx, mut outgoing_rx) = mpsc::channel(256);
+        let peer = Peer::new(outgoing_tx.clone());
+
+        // Requests are processed one at a time, but the transport keeps being read while
+        // one is in flight so responses to requests sent through the peer can be delivered
+        let mut queued: VecDeque<JsonRpcRequest> = VecDeque::new();
+        let mut in_flight: Option<(Option<u64>, Pin<Box<S::Future>>)> = None;
+        let mut closed = false;
+
         tracing::info!("Server started");
-        while let Some(msg_result) = transport.next().await {
-            let _span = tracing::span!(tracing::Level::INFO, "message_processing").entered();
-            match msg_result {
-                Ok(msg) => {
-                    match msg {
-                        JsonRpcMessage::Request(request) => {
-                            // Serialize request for logging
-                            let id = request.id;
-                            let request_json = serde_json::to_string(&request)
-                                .unwrap_or_else(|_| "Failed to serialize request".to_string());
-
-                            tracing::info!(
-                                request_id = ?id,
-                                method = ?request.method,
-                                json = %request_json,
-                                "Received request"
-                            );
-
-                            // Process the request using our service
-                            let (notify_tx, mut notify_rx) = mpsc::channel(256);
-                            let mcp_request = McpRequest {
-                                request,
-                                notifier: notify_tx,
-                            };
+        loop {
+            if in_flight.is_none() {
+                match queued.pop_front() {
+                    Some(request) => {
+                        // Serialize request for logging
+                        let id = request.id;
+                        let request_json = serde_json::to_string(&request)
+                            .unwrap_or_else(|_| "Failed to serialize request".to_string());
 
-                            let transport_fut = tokio::spawn(async move {
-                                while let Some(notification) = notify_rx.recv().await {
-                                    if transport.write_message(notification).await.is_err() {
-                                        break;
-                                    }
-                                }
-                                transport
-                            });
+                        tracing::info!(
+                            request_id = ?id,
+                            method = ?request.method,
+                            json = %request_json,
+                            "Received request"
+                        );
 
-                            let response = match service.call(mcp_request).await {
-                                Ok(resp) => resp,
-                                Err(e) => {
-                                    let error_msg = e.into().to_string();
-                                    tracing::error!(error = %error_msg, "Request processing failed");
-                                    JsonRpcResponse {
-                                        jsonrpc: "2.0".to_string(),
-                                        id,
-                                        result: None,
-                                        error: Some(mcp_core::protocol::ErrorData {
-                                            code: mcp_core::protocol::INTERNAL_ERROR,
-                                            message: error_msg,
-                                            data: None,
-                                        }),
+                        let mcp_request = McpRequest {
+                            request,
+                            notifier: outgoing_tx.clone(),
+                            peer: peer.clone(),
+                        };
+                        in_flight = Some((id, Box::pin(service.call(mcp_request))));
+                    }
+                    None if closed => break,
+                    None => {}
+                }
+            }
+
+            tokio::select! {
+                msg_result = transport.next(), if !closed => {
+                    let Some(msg_result) = msg_result else {
+                        // EOF, finish the requests we already have before stopping
+                        closed = true;
+                        continue;
+                    };
+                    match msg_result {
+                        Ok(JsonRpcMessage::Request(request)) => queued.push_back(request),
+                        Ok(message @ (JsonRpcMessage::Response(_) | JsonRpcMessage::Error(_))) => {
+                            if !peer.handle_response(message).await {
+                                tracing::warn!("Received a response with no pending request");
+                            }
+                        }
+                        Ok(JsonRpcMessage::Notification(notification))
+                            if notification.method == "notifications/cancelled" =>
+                        {
+                            let Some(request_id) = cancelled_request_id(&notification) else {
+                                continue;
+                            };
+                            if in_flight.as_ref().is_some_and(|(id, _)| *id == Some(request_id)) {
+                                // Dropping the future aborts the tool call, and a cancelled
+                                // request gets no response
+                                tracing::info!(request_id, "Request cancelled by client");
+                                in_flight = None;
+                            } else {
+                                queued.retain(|request| request.id != Some(request_id));
+                            }
+                        }
+                        Ok(JsonRpcMessage::Notification(_) | JsonRpcMessage::Nil) => {
+                            // Ignore other notifications and nil messages for now
+                        }
+                        Err(e) => {
+                            // Convert transport error to JSON-RPC error response
+                            let error = match e {
+                                TransportError::Json(_) | TransportError::InvalidMessage(_) => {
+                                    mcp_core::protocol::ErrorData {
+                                        code: mcp_core::protocol::PARSE_ERROR,
+                                        message: e.to_string(),
+                                        data: None,
                                     }
                                 }
+                                TransportError::Protocol(_) => mcp_core::protocol::ErrorData {
+                                    code: mcp_core::protocol::INVALID_REQUEST,
+                                    message: e.to_string(),
+                                    data: None,
+                                },
+                                _ => mcp_core::protocol::ErrorData {
+                                    code: mcp_core::protocol::INTERNAL_ERROR,
+                                    message: e.to_string(),
+                                    data: None,
+                                },
                             };
 
-                            transport = match transport_fut.await {
-                                Ok(transport) => transport,
-                                Err(e) => {
-                                    tracing::error!(error = %e, "Failed to spawn transport task");
-                                    return Err(ServerError::Transport(TransportError::Io(
-                                        e.into(),
-                                    )));
-                                }
-                            };
+                            let error_response = JsonRpcMessage::Error(JsonRpcError {
+                                jsonrpc: "2.0".to_string(),
+                                id: None,
+                                error,
+                            });
 
-                            // Serialize response for logging
-                            let response_json = serde_json::to_string(&response)
-                                .unwrap_or_else(|_| "Failed to serialize response".to_string());
-
-                            tracing::info!(
-                                response_id = ?response.id,
-                                json = %response_json,
-                                "Sending response"
-                            );
-                            // Send the response back
-                            if let Err(e) = transport
-                                .write_message(JsonRpcMessage::Response(response))
-                                .await
-                            {
+                            if let Err(e) = transport.write_message(error_response).await {
                                 return Err(ServerError::Transport(TransportError::Io(e)));
                             }
                         }
-                        JsonRpcMessage::Response(_)
-                        | JsonRpcMessage::Notification(_)
-                        | JsonRpcMessage::Nil
-                        | JsonRpcMessage::Error(_) => {
-                            // Ignore responses, notifications and nil messages for now
-                            continue;
-                        }
                     }
                 }
-                Err(e) => {
-                    // Convert transport error to JSON-RPC error response
-                    let error = match e {
-                        TransportError::Json(_) | TransportError::InvalidMessage(_) => {
-                            mcp_core::protocol::ErrorData {
-                                code: mcp_core::protocol::PARSE_ERROR,
-                                message: e.to_string(),
-                                data: None,
+                Some(message) = outgoing_rx.recv() => {
+                    if let Err(e) = transport.write_message(message).await {
+                        return Err(ServerError::Transport(TransportError::Io(e)));
+                    }
+                }
+                result = async { in_flight.as_mut().unwrap().1.as_mut().await }, if in_flight.is_some() => {
+                    let (id, _) = in_flight.take().unwrap();
+
+                    // Notifications sent while handling the request go out before its response
+                    while let Ok(message) = outgoing_rx.try_recv() {
+                        if let Err(e) = transport.write_message(message).await {
+                            return Err(ServerError::Transport(TransportError::Io(e)));
+                        }
+                    }
+
+                    let response = match result {
+                        Ok(resp) => resp,
+                        Err(e) => {
+                            let error_msg = e.into().to_string();
+                            tracing::error!(error = %error_msg, "Request processing failed");
+                            JsonRpcResponse {
+                                jsonrpc: "2.0".to_string(),
+                                id,
+                                result: None,
+                                error: Some(mcp_core::protocol::ErrorData {
+                                    code: mcp_core::protocol::INTERNAL_ERROR,
+                                    message: error_msg,
+                                    data: None,
+                                }),
                             }
                         }
-                        TransportError::Protocol(_) => mcp_core::protocol::ErrorData {
-                            code: mcp_core::protocol::INVALID_REQUEST,
-                            message: e.to_string(),
-                            data: None,
-                        },
-                        _ => mcp_core::protocol::ErrorData {
-                            code: mcp_core::protocol::INTERNAL_ERROR,
-                            message: e.to_string(),
-                            data: None,
-                        },
                     };
 
-                    let error_response = JsonRpcMessage::Error(JsonRpcError {
-                        jsonrpc: "2.0".to_string(),
-                        id: None,
-                        error,
-                    });
+                    // Serialize response for logging
+                    let response_json = serde_json::to_string(&response)
+                        .unwrap_or_else(|_| "Failed to serialize response".to_string());
 
-                    if let Err(e) = transport.write_message(error_response).await {
+                    tracing::info!(
+                        response_id = ?response.id,
+                        json = %response_json,
+                        "Sending response"
+                    );
+                    // Send the response back
+                    if let Err(e) = transport
+                        .write_message(JsonRpcMessage::Response(response))
+                        .await
+                    {
                         return Err(ServerError::Transport(TransportError::Io(e)));
                     }
                 }
@@ -272,6 +313,11 @@ where
     }
 }
 
+/// The id of the request a `notifications/cancelled` notification refers to
+fn cancelled_request_id(notification: &JsonRpcNotification) -> Option<u64> {
+    notification.params.as_ref()?.get("requestId")?.as_u64()
+}
+
 // Define a specific service implementation that we need for any
 // Any router implements this
 pub trait BoundedService:
@@ -296,3 +342,112 @@ impl<T> BoundedService for T where
         + 'static
 {
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+    use tokio::sync::oneshot;
+
+    /// Sets its flag when the request future holding it is dropped
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Answers `fast` right away and never finishes `slow`
+    struct TestService {
+        slow_started: Option<oneshot::Sender<()>>,
+        slow_dropped: Arc<AtomicBool>,
+    }
+
+    impl Service<McpRequest> for TestService {
+        type Response = JsonRpcResponse;
+        type Error = BoxError;
+        type Future = Pin<Box<dyn Future<Output = Result<JsonRpcResponse, BoxError>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: McpRequest) -> Self::Future {
+            let id = req.request.id;
+            if req.request.method == "slow" {
+                let flag = DropFlag(self.slow_dropped.clone());
+                let started = self.slow_started.take();
+                return Box::pin(async move {
+                    let _flag = flag;
+                    if let Some(started) = started {
+                        let _ = started.send(());
+                    }
+                    std::future::pending().await
+                });
+            }
+            Box::pin(async move {
+                Ok(JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    result: Some(json!({})),
+                    error: None,
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_is_dropped_without_response() {
+        let (started_tx, started_rx) = oneshot::channel();
+        let slow_dropped = Arc::new(AtomicBool::new(false));
+        let service = TestService {
+            slow_started: Some(started_tx),
+            slow_dropped: slow_dropped.clone(),
+        };
+
+        let (mut client_writer, server_reader) = tokio::io::duplex(4096);
+        let (server_writer, client_reader) = tokio::io::duplex(4096);
+        let server = tokio::spawn(
+            Server::new(service).run(ByteTransport::new(server_reader, server_writer)),
+        );
+        let mut client_lines = BufReader::new(client_reader).lines();
+
+        let send = |message: serde_json::Value| format!("{}\n", message);
+        client_writer
+            .write_all(send(json!({"jsonrpc": "2.0", "id": 1, "method": "slow"})).as_bytes())
+            .await
+            .unwrap();
+        started_rx.await.unwrap();
+
+        client_writer
+            .write_all(
+                send(json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/cancelled",
+                    "params": {"requestId": 1, "reason": "user pressed stop"}
+                }))
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        client_writer
+            .write_all(send(json!({"jsonrpc": "2.0", "id": 2, "method": "fast"})).as_bytes())
+            .await
+            .unwrap();
+
+        // The next request is answered, and the cancelled one never is
+        let line = client_lines.next_line().await.unwrap().unwrap();
+        let response: JsonRpcResponse = serde_json::from_str(&line).unwrap();
+        assert_eq!(response.id, Some(2));
+        assert!(slow_dropped.load(Ordering::SeqCst));
+
+        drop(client_writer);
+        server.await.unwrap().unwrap();
+        assert!(client_lines.next_line().await.unwrap().is_none());
+    }
+}
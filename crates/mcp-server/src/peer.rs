@@ -0,0 +1,336 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use mcp_core::protocol::{
+    CreateMessageParams, CreateMessageResult, JsonRpcError, JsonRpcMessage, JsonRpcNotification,
+    JsonRpcRequest, JsonRpcResponse,
+};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+use crate::PeerError;
+
+/// A handle for sending requests from the server back to the connected client,
+/// such as `sampling/createMessage`. Cloning is cheap and all clones share state.
+#[derive(Clone)]
+pub struct Peer {
+    sender: mpsc::Sender<JsonRpcMessage>,
+    pending: Arc<Mutex<HashMap<u64, oneshot::Sender<JsonRpcMessage>>>>,
+    next_id: Arc<AtomicU64>,
+    client_capabilities: Arc<RwLock<Option<Value>>>,
+    subscriptions: Arc<RwLock<HashSet<String>>>,
+    progress_token: Option<Value>,
+}
+
+impl Peer {
+    pub fn new(sender: mpsc::Sender<JsonRpcMessage>) -> Self {
+        Self {
+            sender,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            client_capabilities: Arc::new(RwLock::new(None)),
+            subscriptions: Arc::new(RwLock::new(HashSet::new())),
+            progress_token: None,
+        }
+    }
+
+    /// A peer for a single request, reporting progress against the token the client sent
+    pub fn with_progress_token(mut self, token: Value) -> Self {
+        self.progress_token = Some(token);
+        self
+    }
+
+    /// The progress token of the request this peer was created for, if the client sent one
+    pub fn progress_token(&self) -> Option<&Value> {
+        self.progress_token.as_ref()
+    }
+
+    /// Send a `notifications/progress` for the current request. Does nothing when the
+    /// client did not ask for progress. `progress` must increase with every call.
+    pub fn notify_progress(&self, progress: f64, total: Option<f64>, message: Option<&str>) {
+        let Some(token) = &self.progress_token else {
+            return;
+        };
+        let mut params = json!({
+            "progressToken": token,
+            "progress": progress,
+        });
+        if let Some(total) = total {
+            params["total"] = json!(total);
+        }
+        if let Some(message) = message {
+            params["message"] = json!(message);
+        }
+        self.sender
+            .try_send(JsonRpcMessage::Notification(JsonRpcNotification {
+                jsonrpc: "2.0".to_string(),
+                method: "notifications/progress".to_string(),
+                params: Some(params),
+            }))
+            .ok();
+    }
+
+    /// Record the capabilities the client sent in its `initialize` request
+    pub fn set_client_capabilities(&self, capabilities: Value) {
+        *self.client_capabilities.write().unwrap() = Some(capabilities);
+    }
+
+    /// Whether the client advertised the sampling capability
+    pub fn supports_sampling(&self) -> bool {
+        self.client_capabilities
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|caps| caps.get("sampling"))
+            .is_some_and(|sampling| !sampling.is_null())
+    }
+
+    /// Track a resource the client subscribed to
+    pub fn add_subscription(&self, uri: &str) {
+        self.subscriptions.write().unwrap().insert(uri.to_string());
+    }
+
+    /// Stop tracking a resource the client unsubscribed from
+    pub fn remove_subscription(&self, uri: &str) {
+        self.subscriptions.write().unwrap().remove(uri);
+    }
+
+    /// Whether the client asked for updates to this resource
+    pub fn is_subscribed(&self, uri: &str) -> bool {
+        self.subscriptions.read().unwrap().contains(uri)
+    }
+
+    /// Tell the client a resource changed with `notifications/resources/updated`,
+    /// if it subscribed to the resource. Returns whether a notification was sent.
+    pub fn notify_resource_updated(&self, uri: &str) -> bool {
+        if !self.is_subscribed(uri) {
+            return false;
+        }
+        self.sender
+            .try_send(JsonRpcMessage::Notification(JsonRpcNotification {
+                jsonrpc: "2.0".to_string(),
+                method: "notifications/resources/updated".to_string(),
+                params: Some(json!({ "uri": uri })),
+            }))
+            .is_ok()
+    }
+
+    /// Ask the client to generate a message with its model
+    pub async fn create_message(
+        &self,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, PeerError> {
+        if !self.supports_sampling() {
+            return Err(PeerError::Unsupported("sampling".to_string()));
+        }
+        let result = self
+            .request("sampling/createMessage", serde_json::to_value(params)?)
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Send a request to the client and wait for its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, PeerError> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+
+        let request = JsonRpcMessage::Request(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id),
+            method: method.to_string(),
+            params: Some(params),
+        });
+        if self.sender.send(request).await.is_err() {
+            self.pending.lock().await.remove(&id);
+            return Err(PeerError::ChannelClosed);
+        }
+
+        match rx.await.map_err(|_| PeerError::ChannelClosed)? {
+            JsonRpcMessage::Response(JsonRpcResponse {
+                error: Some(error), ..
+            })
+            | JsonRpcMessage::Error(JsonRpcError { error, .. }) => Err(PeerError::RpcError {
+                code: error.code,
+                message: error.message,
+            }),
+            JsonRpcMessage::Response(JsonRpcResponse {
+                result: Some(result),
+                ..
+            }) => Ok(result),
+            _ => Err(PeerError::UnexpectedResponse("missing result".to_string())),
+        }
+    }
+
+    /// Route a response or error from the client to the request waiting on it.
+    /// Returns false if no request with a matching id is pending.
+    pub async fn handle_response(&self, message: JsonRpcMessage) -> bool {
+        let id = match &message {
+            JsonRpcMessage::Response(JsonRpcResponse { id: Some(id), .. })
+            | JsonRpcMessage::Error(JsonRpcError { id: Some(id), .. }) => *id,
+            _ => return false,
+        };
+        match self.pending.lock().await.remove(&id) {
+            Some(tx) => tx.send(message).is_ok(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::{
+        content::Content,
+        protocol::{ErrorData, SamplingMessage},
+        role::Role,
+    };
+
+    fn params() -> CreateMessageParams {
+        CreateMessageParams {
+            messages: vec![SamplingMessage {
+                role: Role::User,
+                content: Content::text("hello"),
+            }],
+            model_preferences: None,
+            system_prompt: None,
+            include_context: None,
+            temperature: None,
+            max_tokens: 10,
+            stop_sequences: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_notify_progress_uses_request_token() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let peer = Peer::new(tx);
+
+        // Without a token, progress is not reported
+        peer.notify_progress(1.0, None, None);
+        assert!(rx.try_recv().is_err());
+
+        peer.with_progress_token(json!("prog-3")).notify_progress(
+            50.0,
+            Some(100.0),
+            Some("halfway"),
+        );
+        match rx.try_recv().unwrap() {
+            JsonRpcMessage::Notification(notification) => {
+                assert_eq!(notification.method, "notifications/progress");
+                assert_eq!(
+                    notification.params.unwrap(),
+                    json!({
+                        "progressToken": "prog-3",
+                        "progress": 50.0,
+                        "total": 100.0,
+                        "message": "halfway"
+                    })
+                );
+            }
+            other => panic!("Expected a notification, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_notify_resource_updated_only_when_subscribed() {
+        let (tx, mut rx) = mpsc::channel(4);
+        let peer = Peer::new(tx);
+
+        assert!(!peer.notify_resource_updated("file:///notes.txt"));
+        assert!(rx.try_recv().is_err());
+
+        peer.add_subscription("file:///notes.txt");
+        assert!(peer.notify_resource_updated("file:///notes.txt"));
+        match rx.try_recv().unwrap() {
+            JsonRpcMessage::Notification(notification) => {
+                assert_eq!(notification.method, "notifications/resources/updated");
+                assert_eq!(
+                    notification.params.unwrap(),
+                    json!({"uri": "file:///notes.txt"})
+                );
+            }
+            other => panic!("Expected a notification, got {:?}", other),
+        }
+
+        peer.remove_subscription("file:///notes.txt");
+        assert!(!peer.notify_resource_updated("file:///notes.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_create_message_requires_sampling_capability() {
+        let (tx, _rx) = mpsc::channel(1);
+        let peer = Peer::new(tx);
+        peer.set_client_capabilities(json!({}));
+
+        let err = peer.create_message(params()).await.unwrap_err();
+        assert!(matches!(err, PeerError::Unsupported(_)));
+    }
+
+    #[tokio::test]
+    async fn test_create_message_round_trip() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let peer = Peer::new(tx);
+        peer.set_client_capabilities(json!({"sampling": {}}));
+
+        let client = peer.clone();
+        tokio::spawn(async move {
+            let Some(JsonRpcMessage::Request(request)) = rx.recv().await else {
+                panic!("expected a request");
+            };
+            assert_eq!(request.method, "sampling/createMessage");
+            client
+                .handle_response(JsonRpcMessage::Response(JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: request.id,
+                    result: Some(json!({
+                        "role": "assistant",
+                        "content": {"type": "text", "text": "hi"},
+                        "model": "test-model"
+                    })),
+                    error: None,
+                }))
+                .await;
+        });
+
+        let result = peer.create_message(params()).await.unwrap();
+        assert_eq!(result.model, "test-model");
+        assert_eq!(result.content, Content::text("hi"));
+    }
+
+    #[tokio::test]
+    async fn test_request_returns_client_error() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let peer = Peer::new(tx);
+
+        let client = peer.clone();
+        tokio::spawn(async move {
+            let Some(JsonRpcMessage::Request(request)) = rx.recv().await else {
+                panic!("expected a request");
+            };
+            client
+                .handle_response(JsonRpcMessage::Error(JsonRpcError {
+                    jsonrpc: "2.0".to_string(),
+                    id: request.id,
+                    error: ErrorData {
+                        code: -1,
+                        message: "User rejected sampling request".to_string(),
+                        data: None,
+                    },
+                }))
+                .await;
+        });
+
+        let err = peer
+            .request("sampling/createMessage", json!({}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PeerError::RpcError { code: -1, .. }));
+    }
+}
@@ -1,6 +1,7 @@
 use std::{
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
@@ -8,13 +9,13 @@ type PromptFuture = Pin<Box<dyn Future<Output = Result<String, PromptError>> + S
 
 use mcp_core::{
     content::Content,
-    handler::{PromptError, ResourceError, ToolError},
+    handler::{PromptError, ResourceError, ResourceTemplateHandler, ToolError},
     prompt::{Prompt, PromptMessage, PromptMessageRole},
     protocol::{
-        CallToolResult, GetPromptResult, Implementation, InitializeResult, JsonRpcMessage,
-        JsonRpcRequest, JsonRpcResponse, ListPromptsResult, ListResourcesResult, ListToolsResult,
-        PromptsCapability, ReadResourceResult, ResourcesCapability, ServerCapabilities,
-        ToolsCapability,
+        CallToolResult, EmptyResult, GetPromptResult, Implementation, InitializeResult,
+        JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, ListPromptsResult,
+        ListResourceTemplatesResult, ListResourcesResult, ListToolsResult, PromptsCapability,
+        ReadResourceResult, ResourcesCapability, ServerCapabilities, ToolsCapability,
     },
     ResourceContents,
 };
@@ -22,7 +23,7 @@ use serde_json::Value;
 use tokio::sync::mpsc;
 use tower_service::Service;
 
-use crate::{BoxError, RouterError};
+use crate::{BoxError, Peer, RouterError};
 
 /// Builder for configuring and constructing capabilities
 pub struct CapabilitiesBuilder {
@@ -94,11 +95,38 @@ pub trait Router: Send + Sync + 'static {
         arguments: Value,
         notifier: mpsc::Sender<JsonRpcMessage>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>>;
+    /// Like `call_tool`, but with a [`Peer`] for sending requests back to the client,
+    /// such as asking its model for a completion with `Peer::create_message`.
+    /// Routers that need the client override this; the default ignores the peer.
+    fn call_tool_with_peer(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        notifier: mpsc::Sender<JsonRpcMessage>,
+        _peer: Peer,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
+        self.call_tool(tool_name, arguments, notifier)
+    }
     fn list_resources(&self) -> Vec<mcp_core::resource::Resource>;
     fn read_resource(
         &self,
         uri: &str,
     ) -> Pin<Box<dyn Future<Output = Result<String, ResourceError>> + Send + 'static>>;
+    /// Resources that are read by URI but not listed. Reading a URI that matches one of
+    /// the templates goes to its handler instead of `read_resource`.
+    fn resource_templates(&self) -> Vec<Arc<dyn ResourceTemplateHandler>> {
+        Vec::new()
+    }
+    /// Called when the client subscribes to a resource. Once this succeeds the peer tracks
+    /// the subscription, so routers that watch for changes keep it and call
+    /// `Peer::notify_resource_updated` when the resource changes.
+    fn subscribe_resource(&self, _uri: &str, _peer: Peer) -> Result<(), ResourceError> {
+        Ok(())
+    }
+    /// Called when the client no longer wants updates for a resource
+    fn unsubscribe_resource(&self, _uri: &str) -> Result<(), ResourceError> {
+        Ok(())
+    }
     fn list_prompts(&self) -> Vec<Prompt>;
     fn get_prompt(&self, prompt_name: &str) -> PromptFuture;
 
@@ -162,6 +190,7 @@ pub trait Router: Send + Sync + 'static {
         &self,
         req: JsonRpcRequest,
         notifier: mpsc::Sender<JsonRpcMessage>,
+        peer: Peer,
     ) -> impl Future<Output = Result<JsonRpcResponse, RouterError>> + Send {
         async move {
             let params = req
@@ -175,7 +204,10 @@ pub trait Router: Send + Sync + 'static {
 
             let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
 
-            let result = match self.call_tool(name, arguments, notifier).await {
+            let result = match self
+                .call_tool_with_peer(name, arguments, notifier, peer)
+                .await
+            {
                 Ok(result) => CallToolResult {
                     content: result,
                     is_error: None,
@@ -231,7 +263,21 @@ pub trait Router: Send + Sync + 'static {
                 .and_then(Value::as_str)
                 .ok_or_else(|| RouterError::InvalidParams("Missing resource URI".into()))?;
 
-            let contents = self.read_resource(uri).await.map_err(RouterError::from)?;
+            let template = self.resource_templates().into_iter().find_map(|handler| {
+                let values = handler.template().match_uri(uri)?;
+                Some((handler, values))
+            });
+            let contents =
+                match template {
+                    Some((handler, values)) => handler
+                        .get(serde_json::json!(values))
+                        .await
+                        .map_err(|e| match e {
+                            ToolError::NotFound(msg) => RouterError::ResourceNotFound(msg),
+                            e => RouterError::Internal(e.to_string()),
+                        })?,
+                    None => self.read_resource(uri).await.map_err(RouterError::from)?,
+                };
 
             let result = ReadResourceResult {
                 contents: vec![ResourceContents::TextResourceContents {
@@ -251,6 +297,100 @@ pub trait Router: Send + Sync + 'static {
         }
     }
 
+    fn handle_resources_templates_list(
+        &self,
+        req: JsonRpcRequest,
+    ) -> impl Future<Output = Result<JsonRpcResponse, RouterError>> + Send {
+        async move {
+            let result = ListResourceTemplatesResult {
+                resource_templates: self
+                    .resource_templates()
+                    .iter()
+                    .map(|handler| handler.template())
+                    .collect(),
+                next_cursor: None,
+            };
+
+            let mut response = self.create_response(req.id);
+            response.result =
+                Some(serde_json::to_value(result).map_err(|e| {
+                    RouterError::Internal(format!("JSON serialization error: {}", e))
+                })?);
+
+            Ok(response)
+        }
+    }
+
+    fn handle_resources_subscribe(
+        &self,
+        req: JsonRpcRequest,
+        peer: Peer,
+    ) -> impl Future<Output = Result<JsonRpcResponse, RouterError>> + Send {
+        async move {
+            let supports_subscribe = self
+                .capabilities()
+                .resources
+                .and_then(|resources| resources.subscribe)
+                .unwrap_or(false);
+            if !supports_subscribe {
+                return Err(RouterError::MethodNotFound(
+                    "Resource subscriptions are not supported".into(),
+                ));
+            }
+
+            let params = req
+                .params
+                .ok_or_else(|| RouterError::InvalidParams("Missing parameters".into()))?;
+
+            let uri = params
+                .get("uri")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RouterError::InvalidParams("Missing resource URI".into()))?;
+
+            // Only track the subscription once the router accepted it, so a failed
+            // subscribe never produces update notifications
+            self.subscribe_resource(uri, peer.clone())
+                .map_err(RouterError::from)?;
+            peer.add_subscription(uri);
+
+            let mut response = self.create_response(req.id);
+            response.result =
+                Some(serde_json::to_value(EmptyResult {}).map_err(|e| {
+                    RouterError::Internal(format!("JSON serialization error: {}", e))
+                })?);
+
+            Ok(response)
+        }
+    }
+
+    fn handle_resources_unsubscribe(
+        &self,
+        req: JsonRpcRequest,
+        peer: Peer,
+    ) -> impl Future<Output = Result<JsonRpcResponse, RouterError>> + Send {
+        async move {
+            let params = req
+                .params
+                .ok_or_else(|| RouterError::InvalidParams("Missing parameters".into()))?;
+
+            let uri = params
+                .get("uri")
+                .and_then(Value::as_str)
+                .ok_or_else(|| RouterError::InvalidParams("Missing resource URI".into()))?;
+
+            peer.remove_subscription(uri);
+            self.unsubscribe_resource(uri).map_err(RouterError::from)?;
+
+            let mut response = self.create_response(req.id);
+            response.result =
+                Some(serde_json::to_value(EmptyResult {}).map_err(|e| {
+                    RouterError::Internal(format!("JSON serialization error: {}", e))
+                })?);
+
+            Ok(response)
+        }
+    }
+
     fn handle_prompts_list(
         &self,
         req: JsonRpcRequest,
@@ -400,6 +540,7 @@ pub struct RouterService<T>(pub T);
 pub struct McpRequest {
     pub request: JsonRpcRequest,
     pub notifier: mpsc::Sender<JsonRpcMessage>,
+    pub peer: Peer,
 }
 
 impl<T> Service<McpRequest> for RouterService<T>
@@ -419,11 +560,45 @@ where
 
         Box::pin(async move {
             let result = match req.request.method.as_str() {
-                "initialize" => this.handle_initialize(req.request).await,
+                "initialize" => {
+                    if let Some(capabilities) = req
+                        .request
+                        .params
+                        .as_ref()
+                        .and_then(|params| params.get("capabilities"))
+                    {
+                        req.peer.set_client_capabilities(capabilities.clone());
+                    }
+                    this.handle_initialize(req.request).await
+                }
                 "tools/list" => this.handle_tools_list(req.request).await,
-                "tools/call" => this.handle_tools_call(req.request, req.notifier).await,
+                "tools/call" => {
+                    // Progress for this call is reported against the token the client sent
+                    let peer = match req
+                        .request
+                        .params
+                        .as_ref()
+                        .and_then(|params| params.get("_meta"))
+                        .and_then(|meta| meta.get("progressToken"))
+                    {
+                        Some(token) => req.peer.with_progress_token(token.clone()),
+                        None => req.peer,
+                    };
+                    this.handle_tools_call(req.request, req.notifier, peer)
+                        .await
+                }
                 "resources/list" => this.handle_resources_list(req.request).await,
                 "resources/read" => this.handle_resources_read(req.request).await,
+                "resources/templates/list" => {
+                    this.handle_resources_templates_list(req.request).await
+                }
+                "resources/subscribe" => {
+                    this.handle_resources_subscribe(req.request, req.peer).await
+                }
+                "resources/unsubscribe" => {
+                    this.handle_resources_unsubscribe(req.request, req.peer)
+                        .await
+                }
                 "prompts/list" => this.handle_prompts_list(req.request).await,
                 "prompts/get" => this.handle_prompts_get(req.request).await,
                 _ => {
@@ -437,3 +612,245 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use mcp_core::handler::ToolResult;
+    use mcp_core::protocol::JsonRpcRequest;
+    use mcp_core::ResourceTemplate;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    struct LogTemplate;
+
+    #[async_trait]
+    impl ResourceTemplateHandler for LogTemplate {
+        fn template(&self) -> ResourceTemplate {
+            ResourceTemplate::new("file:///logs/{date}.log", "Daily log")
+        }
+
+        fn schema(&self) -> Value {
+            json!({"type": "object", "properties": {"date": {"type": "string"}}})
+        }
+
+        async fn get(&self, params: Value) -> ToolResult<String> {
+            Ok(format!(
+                "log of {}",
+                params["date"].as_str().unwrap_or_default()
+            ))
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct TestRouter {
+        supports_subscribe: bool,
+        subscribed: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Router for TestRouter {
+        fn name(&self) -> String {
+            "test".to_string()
+        }
+
+        fn instructions(&self) -> String {
+            String::new()
+        }
+
+        fn capabilities(&self) -> ServerCapabilities {
+            CapabilitiesBuilder::new()
+                .with_resources(self.supports_subscribe, false)
+                .build()
+        }
+
+        fn list_tools(&self) -> Vec<mcp_core::tool::Tool> {
+            Vec::new()
+        }
+
+        fn call_tool(
+            &self,
+            tool_name: &str,
+            _arguments: Value,
+            _notifier: mpsc::Sender<JsonRpcMessage>,
+        ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>>
+        {
+            let tool_name = tool_name.to_string();
+            Box::pin(async move { Err(ToolError::NotFound(tool_name)) })
+        }
+
+        fn list_resources(&self) -> Vec<mcp_core::resource::Resource> {
+            Vec::new()
+        }
+
+        fn read_resource(
+            &self,
+            uri: &str,
+        ) -> Pin<Box<dyn Future<Output = Result<String, ResourceError>> + Send + 'static>> {
+            let uri = uri.to_string();
+            Box::pin(async move { Err(ResourceError::NotFound(uri)) })
+        }
+
+        fn resource_templates(&self) -> Vec<Arc<dyn ResourceTemplateHandler>> {
+            vec![Arc::new(LogTemplate)]
+        }
+
+        fn subscribe_resource(&self, uri: &str, _peer: Peer) -> Result<(), ResourceError> {
+            if uri == "file:///locked.txt" {
+                return Err(ResourceError::ExecutionError("locked".to_string()));
+            }
+            self.subscribed.lock().unwrap().push(uri.to_string());
+            Ok(())
+        }
+
+        fn unsubscribe_resource(&self, uri: &str) -> Result<(), ResourceError> {
+            self.subscribed
+                .lock()
+                .unwrap()
+                .retain(|subscribed| subscribed != uri);
+            Ok(())
+        }
+
+        fn list_prompts(&self) -> Vec<Prompt> {
+            Vec::new()
+        }
+
+        fn get_prompt(&self, prompt_name: &str) -> PromptFuture {
+            let prompt_name = prompt_name.to_string();
+            Box::pin(async move { Err(PromptError::NotFound(prompt_name)) })
+        }
+    }
+
+    async fn call(
+        router: &TestRouter,
+        peer: &Peer,
+        method: &str,
+        params: Value,
+    ) -> Result<JsonRpcResponse, BoxError> {
+        let (notifier, _) = mpsc::channel(1);
+        RouterService(router.clone())
+            .call(McpRequest {
+                request: JsonRpcRequest {
+                    jsonrpc: "2.0".to_string(),
+                    id: Some(1),
+                    method: method.to_string(),
+                    params: Some(params),
+                },
+                notifier,
+                peer: peer.clone(),
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_resource_templates_are_listed_and_read() {
+        let router = TestRouter::default();
+        let (tx, _rx) = mpsc::channel(1);
+        let peer = Peer::new(tx);
+
+        let response = call(&router, &peer, "resources/templates/list", json!({}))
+            .await
+            .unwrap();
+        let result: ListResourceTemplatesResult =
+            serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(
+            result.resource_templates,
+            vec![ResourceTemplate::new(
+                "file:///logs/{date}.log",
+                "Daily log"
+            )]
+        );
+
+        let response = call(
+            &router,
+            &peer,
+            "resources/read",
+            json!({"uri": "file:///logs/2025-01-01.log"}),
+        )
+        .await
+        .unwrap();
+        let result: ReadResourceResult = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert_eq!(
+            result.contents,
+            vec![ResourceContents::TextResourceContents {
+                uri: "file:///logs/2025-01-01.log".to_string(),
+                mime_type: Some("text/plain".to_string()),
+                text: "log of 2025-01-01".to_string(),
+            }]
+        );
+
+        // URIs outside of the templates still go to read_resource
+        assert!(call(
+            &router,
+            &peer,
+            "resources/read",
+            json!({"uri": "file:///notes.txt"})
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resource_subscribe_and_unsubscribe() {
+        let router = TestRouter {
+            supports_subscribe: true,
+            ..Default::default()
+        };
+        let (tx, _rx) = mpsc::channel(1);
+        let peer = Peer::new(tx);
+
+        call(
+            &router,
+            &peer,
+            "resources/subscribe",
+            json!({"uri": "file:///notes.txt"}),
+        )
+        .await
+        .unwrap();
+        assert!(peer.is_subscribed("file:///notes.txt"));
+        assert_eq!(
+            *router.subscribed.lock().unwrap(),
+            vec!["file:///notes.txt"]
+        );
+
+        // A subscription the router refuses is not tracked
+        assert!(call(
+            &router,
+            &peer,
+            "resources/subscribe",
+            json!({"uri": "file:///locked.txt"})
+        )
+        .await
+        .is_err());
+        assert!(!peer.is_subscribed("file:///locked.txt"));
+
+        call(
+            &router,
+            &peer,
+            "resources/unsubscribe",
+            json!({"uri": "file:///notes.txt"}),
+        )
+        .await
+        .unwrap();
+        assert!(!peer.is_subscribed("file:///notes.txt"));
+        assert!(router.subscribed.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resource_subscribe_requires_capability() {
+        let router = TestRouter::default();
+        let (tx, _rx) = mpsc::channel(1);
+        let peer = Peer::new(tx);
+
+        assert!(call(
+            &router,
+            &peer,
+            "resources/subscribe",
+            json!({"uri": "file:///notes.txt"})
+        )
+        .await
+        .is_err());
+        assert!(!peer.is_subscribed("file:///notes.txt"));
+        assert!(router.subscribed.lock().unwrap().is_empty());
+    }
+}
@@ -1,5 +1,20 @@
 // This file is auto-generated by @hey-api/openapi-ts
 
+/**
+ * A session with its own agent, as listed by the server
+ */
+export type AgentSessionInfo = {
+    /**
+     * Seconds since a request last used the session
+     */
+    idle_seconds: number;
+    /**
+     * Model of the session's provider, if one is set
+     */
+    model?: string | null;
+    session_id: string;
+};
+
 export type Annotations = {
     audience?: Array<Role> | null;
     priority?: number | null;
@@ -68,6 +83,25 @@ export type CreateScheduleRequest = {
     recipe_source: string;
 };
 
+export type CreateSessionRequest = {
+    /**
+     * Model for the session's agent, defaults to the configured GOOSE_MODEL
+     */
+    model?: string | null;
+    /**
+     * Provider for the session's agent, defaults to the configured GOOSE_PROVIDER
+     */
+    provider?: string | null;
+    /**
+     * Id for the new session, made of letters, digits, `_` and `-`. Generated when not given
+     */
+    session_id?: string | null;
+};
+
+export type CreateSessionResponse = {
+    session_id: string;
+};
+
 export type EmbeddedResource = {
     annotations?: Annotations | null;
     resource: ResourceContents;
@@ -95,6 +129,27 @@ export type ExtensionConfig = {
     timeout?: number | null;
     type: 'sse';
     uri: string;
+} | {
+    /**
+     * Whether this extension is bundled with Goose
+     */
+    bundled?: boolean | null;
+    description?: string | null;
+    env_keys?: Array<string>;
+    envs?: Envs;
+    /**
+     * Headers sent with every request, values can reference env vars as `${NAME}`
+     */
+    headers?: {
+        [key: string]: string;
+    };
+    /**
+     * The name used to identify this extension
+     */
+    name: string;
+    timeout?: number | null;
+    type: 'streamable_http';
+    uri: string;
 } | {
     args: Array<string>;
     /**
@@ -159,6 +214,21 @@ export type ExtensionResponse = {
     extensions: Array<ExtensionEntry>;
 };
 
+export type ForkSessionRequest = {
+    /**
+     * Number of messages to keep in the fork, counted from the start. Defaults to all.
+     */
+    atMessage?: number | null;
+};
+
+export type ForkSessionResponse = {
+    metadata: SessionMetadata;
+    /**
+     * Unique identifier for the new session
+     */
+    sessionId: string;
+};
+
 export type FrontendToolRequest = {
     id: string;
     toolCall: {
@@ -324,6 +394,7 @@ export type ScheduledJob = {
 };
 
 export type SessionDisplayInfo = {
+    accumulatedCost?: number | null;
     accumulatedInputTokens?: number | null;
     accumulatedOutputTokens?: number | null;
     accumulatedTotalTokens?: number | null;
@@ -368,6 +439,16 @@ export type SessionListResponse = {
  * Metadata for a session, stored as the first line in the session file
  */
 export type SessionMetadata = {
+    /**
+     * The cost in US dollars of the session. Accumulated across all messages with known pricing.
+     */
+    accumulated_cost?: number | null;
+    /**
+     * The accumulated cost in US dollars broken down by the model that produced the usage.
+     */
+    accumulated_cost_by_model?: {
+        [key: string]: number;
+    } | null;
     /**
      * The number of input tokens used in the session. Accumulated across all messages.
      */
@@ -380,10 +461,18 @@ export type SessionMetadata = {
      * The total number of tokens used in the session. Accumulated across all messages (useful for tracking cost over an entire session).
      */
     accumulated_total_tokens?: number | null;
+    /**
+     * The cost in US dollars of the provider's last usage, if the model's pricing is known.
+     */
+    cost?: number | null;
     /**
      * A short description of the session, typically 3 words or less
      */
     description: string;
+    /**
+     * Number of the parent session's messages this session was forked with
+     */
+    fork_point?: number | null;
     /**
      * The number of input tokens used in the session. Retrieved from the provider's last usage.
      */
@@ -396,6 +485,10 @@ export type SessionMetadata = {
      * The number of output tokens used in the session. Retrieved from the provider's last usage.
      */
     output_tokens?: number | null;
+    /**
+     * ID of the session this one was forked from, if any
+     */
+    parent_session_id?: string | null;
     /**
      * ID of the schedule that triggered this session, if any
      */
@@ -410,6 +503,44 @@ export type SessionMetadata = {
     working_dir: string;
 };
 
+/**
+ * A message matching a session search
+ */
+export type SessionSearchHit = {
+    /**
+     * Position of the matching message in the session, counted from 0
+     */
+    message_index: number;
+    path: string;
+    /**
+     * Role of the message's author, user or assistant
+     */
+    role: string;
+    session_id: string;
+    /**
+     * The matching part of the message text
+     */
+    snippet: string;
+};
+
+export type SessionSearchQuery = {
+    /**
+     * Maximum number of matching messages to return
+     */
+    limit?: number;
+    /**
+     * Words to search for; messages must contain all of them
+     */
+    query: string;
+};
+
+export type SessionSearchResponse = {
+    /**
+     * Matching messages, best matches first
+     */
+    results: Array<SessionSearchHit>;
+};
+
 export type SessionsQuery = {
     limit?: number;
 };
@@ -555,6 +686,94 @@ export type UpsertPermissionsQuery = {
     tool_permissions: Array<ToolPermission>;
 };
 
+export type ListAgentSessionsData = {
+    body?: never;
+    path?: never;
+    query?: never;
+    url: '/agent/sessions';
+};
+
+export type ListAgentSessionsErrors = {
+    /**
+     * Unauthorized - invalid secret key
+     */
+    401: unknown;
+};
+
+export type ListAgentSessionsResponses = {
+    /**
+     * Sessions with their own agent
+     */
+    200: Array<AgentSessionInfo>;
+};
+
+export type ListAgentSessionsResponse = ListAgentSessionsResponses[keyof ListAgentSessionsResponses];
+
+export type CreateAgentSessionData = {
+    body: CreateSessionRequest;
+    path?: never;
+    query?: never;
+    url: '/agent/sessions';
+};
+
+export type CreateAgentSessionErrors = {
+    /**
+     * No model given or configured for the provider
+     */
+    400: unknown;
+    /**
+     * Unauthorized - invalid secret key
+     */
+    401: unknown;
+    /**
+     * A session with this id already exists
+     */
+    409: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type CreateAgentSessionResponses = {
+    /**
+     * Session created with its own agent
+     */
+    200: CreateSessionResponse;
+};
+
+export type CreateAgentSessionResponse = CreateAgentSessionResponses[keyof CreateAgentSessionResponses];
+
+export type CloseAgentSessionData = {
+    body?: never;
+    path: {
+        /**
+         * Session to close
+         */
+        session_id: string;
+    };
+    query?: never;
+    url: '/agent/sessions/{session_id}';
+};
+
+export type CloseAgentSessionErrors = {
+    /**
+     * Unauthorized - invalid secret key
+     */
+    401: unknown;
+    /**
+     * Session not found
+     */
+    404: unknown;
+};
+
+export type CloseAgentSessionResponses = {
+    /**
+     * Session closed and its extensions stopped
+     */
+    200: unknown;
+};
+
 export type GetToolsData = {
     body?: never;
     path?: never;
@@ -1221,6 +1440,42 @@ export type ListSessionsResponses = {
 
 export type ListSessionsResponse = ListSessionsResponses[keyof ListSessionsResponses];
 
+export type SearchSessionsData = {
+    body?: never;
+    path?: never;
+    query: {
+        /**
+         * Words to search for; messages must contain all of them
+         */
+        query: string;
+        /**
+         * Maximum number of matching messages to return
+         */
+        limit?: number;
+    };
+    url: '/sessions/search';
+};
+
+export type SearchSessionsErrors = {
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type SearchSessionsResponses = {
+    /**
+     * Matching messages retrieved successfully
+     */
+    200: SessionSearchResponse;
+};
+
+export type SearchSessionsResponse = SearchSessionsResponses[keyof SearchSessionsResponses];
+
 export type GetSessionHistoryData = {
     body?: never;
     path: {
@@ -1257,6 +1512,46 @@ export type GetSessionHistoryResponses = {
 
 export type GetSessionHistoryResponse = GetSessionHistoryResponses[keyof GetSessionHistoryResponses];
 
+export type ForkSessionData = {
+    body: ForkSessionRequest;
+    path: {
+        /**
+         * Unique identifier for the session to fork
+         */
+        session_id: string;
+    };
+    query?: never;
+    url: '/sessions/{session_id}/fork';
+};
+
+export type ForkSessionErrors = {
+    /**
+     * Fork point is past the end of the session
+     */
+    400: unknown;
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Session not found
+     */
+    404: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type ForkSessionResponses = {
+    /**
+     * Session forked successfully
+     */
+    200: ForkSessionResponse2;
+};
+
+export type ForkSessionResponse2 = ForkSessionResponses[keyof ForkSessionResponses];
+
 export type ClientOptions = {
     baseUrl: `${string}://${string}` | (string & {});
 };
\ No newline at end of file
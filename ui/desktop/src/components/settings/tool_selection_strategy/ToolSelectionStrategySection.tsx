@@ -18,6 +18,17 @@ export const all_tool_selection_strategies = [
     description:
       'Filter tools based on vector-based similarity. Recommended when many extensions are enabled.',
   },
+  {
+    key: 'bm25',
+    label: 'BM25',
+    description:
+      'Filter tools with keyword search over tool names and descriptions. Works without an embedding model.',
+  },
+  {
+    key: 'llm',
+    label: 'LLM',
+    description: 'Ask the current model to pick the relevant tools. Uses an extra model call per search.',
+  },
 ];
 
 export const ToolSelectionStrategySection = ({